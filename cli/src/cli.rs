@@ -0,0 +1,152 @@
+//! Command-line interface. With no subcommand the binary runs the engine,
+//! so `./pretty_rusty` keeps working in the container unchanged.
+
+use clap::{Args, Parser, Subcommand};
+use pretty_rusty::Asset;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(name = "pretty_rusty", version, about = "Kalshi crypto binary paper-trading engine")]
+pub struct Cli {
+    /// Config file (default: config.toml if present)
+    #[arg(long, global = true, env = "CONFIG_FILE", value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Profile to load from the config file
+    #[arg(long, global = true, env = "PROFILE")]
+    pub profile: Option<String>,
+
+    /// Directory holding the database
+    #[arg(long, global = true, env = "DATA_DIR", value_name = "DIR", default_value = "data")]
+    pub data_dir: PathBuf,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Start the engine, feeds and HTTP server (the default)
+    Run(RunArgs),
+    /// Replay recorded prices and quotes through the strategy
+    Backtest(BacktestArgs),
+    /// Per-model performance from the trade history
+    Report(ReportArgs),
+    /// Database maintenance
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
+    /// List the open markets of each asset's configured series
+    Markets,
+    /// Verify the Kalshi key and request signing against /portfolio/balance
+    CheckAuth,
+    /// Print a fresh API key and its config entry
+    GenKey {
+        /// read or operator
+        role: String,
+        label: String,
+    },
+    /// Print the config entry hash for an existing API key
+    HashKey { key: String },
+}
+
+#[derive(Debug, Default, Args)]
+#[group(multiple = false)]
+pub struct RunArgs {
+    /// Paper trading, whatever the profile's mode
+    #[arg(long)]
+    pub paper: bool,
+    /// Live trading, whatever the profile's mode
+    #[arg(long)]
+    pub live: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct BacktestArgs {
+    /// Asset to replay (default: the profile's first)
+    #[arg(long)]
+    pub asset: Option<Asset>,
+    /// Expiry horizon to replay, by name (default: the profile's first)
+    #[arg(long)]
+    pub horizon: Option<String>,
+    /// Replay from this time, inclusive (RFC 3339 or YYYY-MM-DD)
+    #[arg(long)]
+    pub from: Option<String>,
+    /// Replay up to this time, exclusive
+    #[arg(long)]
+    pub to: Option<String>,
+    /// Keep the backtest's trades in a new database at FILE
+    #[arg(long, value_name = "FILE")]
+    pub save: Option<PathBuf>,
+    /// Print the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// Only this model
+    #[arg(long)]
+    pub model: Option<String>,
+    /// Only this asset
+    #[arg(long)]
+    pub asset: Option<Asset>,
+    /// Trades closed at or after this time (RFC 3339 or YYYY-MM-DD)
+    #[arg(long)]
+    pub since: Option<String>,
+    /// Trades closed before this time
+    #[arg(long)]
+    pub until: Option<String>,
+    /// Print the full analytics report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbAction {
+    /// Create the database if needed and apply pending migrations
+    Migrate,
+    /// Checkpoint the WAL and compact the database file
+    Vacuum,
+    /// Write trades as CSV
+    Export {
+        /// Output file (default: stdout)
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+        #[arg(long)]
+        model: Option<String>,
+        /// Only this asset's trades (btc, eth or sol)
+        #[arg(long)]
+        asset: Option<Asset>,
+        /// Trades entered at or after this time
+        #[arg(long)]
+        since: Option<String>,
+        /// Trades entered before this time
+        #[arg(long)]
+        until: Option<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_no_subcommand_means_run() {
+        let cli = Cli::try_parse_from(["pretty_rusty"]).unwrap();
+        assert!(cli.command.is_none());
+
+        let cli = Cli::try_parse_from(["pretty_rusty", "run", "--paper", "--config", "x.toml"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Run(RunArgs { paper: true, live: false }))));
+        assert_eq!(cli.config, Some(PathBuf::from("x.toml")));
+
+        assert!(Cli::try_parse_from(["pretty_rusty", "run", "--paper", "--live"]).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! One-shot subcommands: everything the binary does besides `run`.
+//! Results go to stdout, logs to stderr, and errors to the caller, which
+//! exits non-zero.
+
+use crate::cli::{BacktestArgs, DbAction, ReportArgs};
+use pretty_rusty::asset::AssetConfig;
+use pretty_rusty::config::AppConfig;
+use pretty_rusty::db::{self, migrations, trades};
+use pretty_rusty::errors::{EngineError, EngineResult};
+use pretty_rusty::kalshi::client::KalshiClient;
+use pretty_rusty::kalshi::selector::{MarketSelector, SelectionContext};
+use pretty_rusty::kalshi::{auth::KalshiAuth, scanner};
+use pretty_rusty::metrics::Metrics;
+use pretty_rusty::models::{self, PricingModel};
+use pretty_rusty::paper::analytics::{self, AnalyticsReport};
+use pretty_rusty::paper::backtest;
+use pretty_rusty::paper::simulator::parse_time;
+use rusqlite::Connection;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Trades per page when exporting
+const EXPORT_PAGE: usize = 1000;
+
+pub fn backtest(cfg: &AppConfig, data_dir: &Path, args: &BacktestArgs) -> EngineResult<()> {
+    let history = db::DbPool::open(existing_db(data_dir)?);
+    let history = history.get()?;
+
+    let mut out = match &args.save {
+        Some(path) if path.exists() => {
+            return Err(EngineError::Config(format!("{} already exists; pick a new file", path.display())))
+        }
+        Some(path) => Connection::open(path)?,
+        None => Connection::open_in_memory()?,
+    };
+    migrations::migrate(&mut out)?;
+
+    let enabled = models::enabled(&cfg.models);
+    let models: Vec<&dyn PricingModel> = enabled.iter().map(|m| m.as_ref()).collect();
+    let asset = match args.asset {
+        Some(asset) => cfg
+            .asset(asset)
+            .ok_or_else(|| EngineError::Config(format!("{asset} is not traded in profile {}", cfg.profile)))?,
+        None => &cfg.assets[0],
+    };
+    let horizon = match &args.horizon {
+        Some(name) => cfg
+            .horizons
+            .iter()
+            .find(|h| &h.name == name)
+            .ok_or_else(|| EngineError::Config(format!("no horizon {name:?} in profile {}", cfg.profile)))?,
+        None => &cfg.horizons[0],
+    };
+    let replay = backtest::Replay {
+        asset,
+        horizon,
+        models: &models,
+        trading: &cfg.trading,
+        calendar: cfg.trading_calendar,
+        from: args.from.clone(),
+        to: args.to.clone(),
+    };
+    let stats = backtest::run(&history, &mut out, &replay)?;
+
+    let trades = trades::closed_trades(&out, None, None, None, None)?;
+    let report = analytics::compute_report(&trades, analytics::period_days(Some(&stats.start), Some(&stats.end)));
+    if args.json {
+        let json = serde_json::json!({ "profile": cfg.profile, "asset": asset.asset, "horizon": horizon.name, "replay": stats, "report": report });
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        println!("profile {} | {} {} | {} -> {}", cfg.profile, asset.asset, horizon.name, stats.start, stats.end);
+        println!(
+            "replayed {} prices (median gap {:.1}s), {} quotes, {} ticks; {} markets, {} settled; {} trades left open",
+            stats.prices,
+            stats.median_price_gap_secs,
+            stats.quotes,
+            stats.ticks,
+            stats.markets,
+            stats.markets_settled,
+            stats.open_trades
+        );
+        println!();
+        print_models(&report);
+    }
+    if let Some(path) = &args.save {
+        eprintln!("trades saved to {}", path.display());
+    }
+    Ok(())
+}
+
+pub fn report(data_dir: &Path, args: &ReportArgs) -> EngineResult<()> {
+    let pool = db::DbPool::open(existing_db(data_dir)?);
+    let conn = pool.get()?;
+    let trades = trades::closed_trades(&conn, args.model.as_deref(), args.asset, args.since.as_deref(), args.until.as_deref())?;
+    let report = analytics::compute_report(&trades, analytics::period_days(args.since.as_deref(), args.until.as_deref()));
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_models(&report);
+    }
+    Ok(())
+}
+
+fn print_models(report: &AnalyticsReport) {
+    if report.models.is_empty() {
+        println!("no closed trades");
+        return;
+    }
+    println!(
+        "{:<5} {:<16} {:>6} {:>5} {:>6} {:>6} {:>10} {:>8} {:>7} {:>7} {:>9}",
+        "asset", "model", "trades", "wins", "losses", "hit%", "pnl", "fees", "pf", "sharpe", "max_dd"
+    );
+    for m in &report.models {
+        let pf = m.profit_factor.map(|p| format!("{p:.2}")).unwrap_or_else(|| "-".into());
+        println!(
+            "{:<5} {:<16} {:>6} {:>5} {:>6} {:>6.1} {:>10.2} {:>8.2} {:>7} {:>7.2} {:>9.2}",
+            m.asset,
+            m.model,
+            m.trades,
+            m.wins,
+            m.losses,
+            m.hit_rate * 100.0,
+            m.total_pnl,
+            m.total_fees,
+            pf,
+            m.sharpe,
+            m.max_drawdown
+        );
+    }
+}
+
+pub fn db(data_dir: &Path, action: &DbAction) -> EngineResult<()> {
+    match action {
+        DbAction::Migrate => {
+            let (conn, _) = db::init_db(data_dir)?;
+            println!(
+                "{}: schema version {} (latest {})",
+                db::db_path(data_dir).display(),
+                migrations::current_version(&conn)?,
+                migrations::latest_version()
+            );
+        }
+        DbAction::Vacuum => {
+            let path = existing_db(data_dir)?;
+            let size = || std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let before = size();
+            // Not while the engine runs: VACUUM needs an exclusive lock and
+            // would stall the writer for as long as it takes
+            let conn = Connection::open(&path)?;
+            conn.busy_timeout(std::time::Duration::from_secs(5))?;
+            conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")?;
+            println!("{}: {} -> {} bytes", path.display(), before, size());
+        }
+        DbAction::Export { out, model, asset, since, until } => {
+            let pool = db::DbPool::open(existing_db(data_dir)?);
+            let conn = pool.get()?;
+            let filter = trades::TradeFilter {
+                model: model.clone(),
+                asset: *asset,
+                since: since.clone(),
+                until: until.clone(),
+                ..Default::default()
+            };
+            let mut w: Box<dyn Write> = match out {
+                Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path).map_err(|e| {
+                    EngineError::Config(format!("{}: {e}", path.display()))
+                })?)),
+                None => Box::new(std::io::stdout().lock()),
+            };
+            let write_err = |e: std::io::Error| EngineError::Database(format!("export: {e}"));
+
+            w.write_all(trades::CSV_HEADER.as_bytes()).map_err(write_err)?;
+            let (mut cursor, mut rows) = (None, 0usize);
+            loop {
+                let page = trades::query_trades(&conn, &filter, cursor.as_deref(), EXPORT_PAGE, false)?;
+                for t in &page.trades {
+                    w.write_all(trades::csv_row(t).as_bytes()).map_err(write_err)?;
+                }
+                rows += page.trades.len();
+                match page.next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+            w.flush().map_err(write_err)?;
+            if let Some(path) = out {
+                eprintln!("{rows} trades written to {}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn markets(cfg: &AppConfig) -> EngineResult<()> {
+    let client = kalshi_client(cfg)?;
+    let selector = cfg.selection.selector();
+    for (i, asset) in cfg.assets.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        print_ladder(asset, &client, selector.as_ref()).await?;
+    }
+    Ok(())
+}
+
+/// The asset's soonest-closing event, with the market the scanner would
+/// pick marked. There is no price feed here, so policies that price the
+/// ladder mark the nearest-ATM market.
+async fn print_ladder(asset: &AssetConfig, client: &KalshiClient, selector: &dyn MarketSelector) -> EngineResult<()> {
+    let series = asset.series.join(",");
+    let markets = scanner::open_markets(asset, client).await?;
+    let now = chrono::Utc::now();
+    let selected = scanner::find_best_market(markets.clone(), selector, &SelectionContext::at(now)).and_then(|m| m.ticker);
+
+    // The ladder is the event closing soonest; later events are summarized
+    let mut ladder: Vec<_> = markets
+        .iter()
+        .filter_map(|m| Some((parse_time(m.close_time.as_deref()?)?, m)))
+        .filter(|(close, _)| *close > now)
+        .collect();
+    let Some(first_close) = ladder.iter().map(|(close, _)| *close).min() else {
+        println!("no open {series} markets");
+        return Ok(());
+    };
+    let later = ladder.iter().filter(|(close, _)| *close != first_close).count();
+    ladder.retain(|(close, _)| *close == first_close);
+    ladder.sort_by(|a, b| a.1.strike_price().unwrap_or(0.0).total_cmp(&b.1.strike_price().unwrap_or(0.0)));
+
+    println!(
+        "{series} closing {} ({} min), * = {} pick",
+        first_close.to_rfc3339(),
+        (first_close - now).num_minutes(),
+        selector.name()
+    );
+    println!("  {:<28} {:>10} {:>7} {:>7} {:>7} {:>9}", "ticker", "strike", "yes_bid", "yes_ask", "last", "volume");
+    for (_, m) in &ladder {
+        let price = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".into());
+        let mark = if m.ticker.is_some() && m.ticker == selected { "*" } else { " " };
+        println!(
+            "{mark} {:<28} {:>10} {:>7} {:>7} {:>7} {:>9}",
+            m.ticker_str(),
+            m.strike_price().map(|s| format!("{s:.2}")).unwrap_or_else(|| "-".into()),
+            price(&m.yes_bid_dollars),
+            price(&m.yes_ask_dollars),
+            price(&m.last_price_dollars),
+            m.volume.map(|v| v.to_string()).unwrap_or_else(|| "-".into()),
+        );
+    }
+    if later > 0 {
+        println!("({later} markets in later events not shown)");
+    }
+    Ok(())
+}
+
+pub async fn check_auth(cfg: &AppConfig) -> EngineResult<()> {
+    let client = kalshi_client(cfg)?;
+    println!("key {} loaded, signing GET /portfolio/balance against {}", cfg.kalshi_api_key_id, cfg.kalshi_base_url);
+    match client.get_balance().await {
+        Ok(resp) => {
+            let dollars = |c: Option<i64>| c.map(|c| format!("${:.2}", c as f64 / 100.0)).unwrap_or_else(|| "-".into());
+            println!("ok: balance {}, portfolio value {}", dollars(resp.balance), dollars(resp.portfolio_value));
+            Ok(())
+        }
+        Err(EngineError::KalshiApi { status: status @ (401 | 403), body }) => Err(EngineError::Auth(format!(
+            "Kalshi rejected the signature ({status}): check KALSHI_API_KEY_ID matches the private key and the clock is in sync: {body}"
+        ))),
+        Err(e) => Err(e),
+    }
+}
+
+fn kalshi_client(cfg: &AppConfig) -> EngineResult<KalshiClient> {
+    cfg.require_credentials(false)?;
+    let auth = KalshiAuth::new(&cfg.kalshi_api_key_id, &cfg.kalshi_private_key_path)?;
+    Ok(KalshiClient::new(&cfg.kalshi_base_url, auth, Arc::new(Metrics::new())))
+}
+
+/// The database path, which must already exist; read commands never create one.
+fn existing_db(data_dir: &Path) -> EngineResult<std::path::PathBuf> {
+    let path = db::db_path(data_dir);
+    if path.exists() {
+        Ok(path)
+    } else {
+        Err(EngineError::Database(format!("no database at {} (wrong --data-dir?)", path.display())))
+    }
+}
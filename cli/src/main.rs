@@ -0,0 +1,371 @@
+mod cli;
+mod commands;
+
+use clap::Parser;
+use pretty_rusty::state::*;
+use pretty_rusty::{config, db, engine, errors, feeds, kalshi, server, supervisor};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+#[tokio::main]
+async fn main() {
+    // Structured logging
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
+
+    dotenvy::dotenv().ok();
+    let cli = cli::Cli::parse();
+
+    let result = match &cli.command {
+        None => run(&cli, &cli::RunArgs::default()).await,
+        Some(cli::Command::Run(args)) => run(&cli, args).await,
+        Some(cli::Command::Backtest(args)) => {
+            load_config(&cli).and_then(|cfg| commands::backtest(&cfg, &cli.data_dir, args))
+        }
+        Some(cli::Command::Report(args)) => commands::report(&cli.data_dir, args),
+        Some(cli::Command::Db { action }) => commands::db(&cli.data_dir, action),
+        Some(cli::Command::Markets) => match load_config(&cli) {
+            Ok(cfg) => commands::markets(&cfg).await,
+            Err(e) => Err(e),
+        },
+        Some(cli::Command::CheckAuth) => match load_config(&cli) {
+            Ok(cfg) => commands::check_auth(&cfg).await,
+            Err(e) => Err(e),
+        },
+        Some(cli::Command::GenKey { role, label }) => match role.parse::<server::auth::Role>() {
+            Ok(role) => {
+                let (raw, key) = server::auth::generate_key(role, label);
+                println!("key (give to client, shown once): {raw}");
+                println!("config entry (add to API_KEYS or API_KEYS_FILE): {}", key.to_config_line());
+                Ok(())
+            }
+            Err(e) => Err(e),
+        },
+        Some(cli::Command::HashKey { key }) => {
+            println!("{}", server::auth::hash_key(key));
+            Ok(())
+        }
+    };
+    if let Err(e) = result {
+        tracing::error!("{e}");
+        std::process::exit(1);
+    }
+}
+
+fn load_config(cli: &cli::Cli) -> errors::EngineResult<config::AppConfig> {
+    let cfg = config::AppConfig::load(cli.config.clone(), cli.profile.clone())?;
+    tracing::info!(profile = %cfg.profile, mode = %cfg.mode, file = ?cfg.config_file, models = ?cfg.models, "config loaded");
+    Ok(cfg)
+}
+
+/// Start the engine, feeds and HTTP server; returns after a clean shutdown.
+async fn run(cli: &cli::Cli, args: &cli::RunArgs) -> errors::EngineResult<()> {
+    tracing::info!("pretty_rusty engine starting");
+
+    let mut cfg = load_config(cli)?;
+    // --paper / --live override the profile's mode
+    if args.paper {
+        cfg.mode = config::Mode::Paper;
+    } else if args.live {
+        cfg.mode = config::Mode::Live;
+    }
+    match cfg.mode {
+        config::Mode::Paper => {}
+        // Only the paper simulator exists; refuse rather than pretend
+        config::Mode::Live => {
+            return Err(errors::EngineError::Config(format!(
+                "profile {:?} is in live mode, but live order routing is not implemented; run with --paper",
+                cfg.profile
+            )))
+        }
+        config::Mode::Backtest => {
+            return Err(errors::EngineError::Config(format!(
+                "profile {:?} is a backtest profile; use `pretty_rusty backtest --profile {}`",
+                cfg.profile, cfg.profile
+            )))
+        }
+    }
+    cfg.require_credentials(true)?;
+
+    // Init database
+    let (db_conn, db_pool) = db::init_db(&cli.data_dir)?;
+
+    // Create bounded channels
+    let (engine_tx, engine_rx) = mpsc::channel::<EngineEvent>(512);
+    let (db_tx, db_rx) = db::channel(1024);
+
+    // Create shared state
+    let app_state = AppState::new(cfg.clone(), db_pool.clone(), engine_tx.clone(), db_tx.clone());
+
+    // Init Kalshi auth
+    let kalshi_auth = kalshi::auth::KalshiAuth::new(&cfg.kalshi_api_key_id, &cfg.kalshi_private_key_path)?;
+
+    let kalshi_client = kalshi::client::KalshiClient::new(
+        &cfg.kalshi_base_url,
+        kalshi_auth,
+        app_state.metrics.clone(),
+    )
+    .with_breaker(kalshi::limits::BreakerConfig::default(), app_state.clock.clone());
+
+    // Size the rate limiter to the account's tier
+    if let Err(e) = kalshi_client.apply_account_limits().await {
+        tracing::warn!(
+            reads_per_sec = kalshi::limits::DEFAULT_READS_PER_SEC,
+            "could not read Kalshi account limits, using the default: {e}"
+        );
+    }
+
+    // Startup recovery: positions saved by a `persist` shutdown
+    let restored_positions = db::take_persisted_positions(&db_conn).unwrap_or_else(|e| {
+        tracing::error!("failed to load persisted positions: {e}");
+        Vec::new()
+    });
+
+    // ── Spawn tasks (owned by the supervisor) ──
+
+    let mut supervisor = supervisor::Supervisor::new(app_state.clone());
+
+    // 1. DB writer task (dedicated, owns the read-write connection)
+    let db_metrics = app_state.metrics.clone();
+    supervisor.spawn_critical("db_writer", async move {
+        db::run_db_writer(db_conn, db_rx, db_metrics).await;
+    });
+
+    for asset in cfg.assets.iter().map(|a| a.asset) {
+        // 2. Price feed task, one per asset
+        let crypto_key = cfg.crypto_api_key.clone();
+        let crypto_url = cfg.crypto_api_base_url.clone();
+        let feed_tx = engine_tx.clone();
+        let feed_metrics = app_state.metrics.clone();
+        supervisor.spawn_restartable(format!("{asset}_feed"), move || {
+            Box::pin(feeds::crypto_api::run_price_feed(
+                asset,
+                crypto_key.clone(),
+                crypto_url.clone(),
+                feed_tx.clone(),
+                feed_metrics.clone(),
+            ))
+        });
+
+        // 3. Kalshi market scanner task, one per asset. Seeded with markets
+        // that still have open trades (from before a restart, or before the
+        // scanner crashed); each keeps the ones in its own series.
+        let scanner_cfg = cfg.clone();
+        let scanner_client = kalshi_client.clone();
+        let scanner_tx = engine_tx.clone();
+        let scanner_db = db_pool.clone();
+        let scanner_clock = app_state.clock.clone();
+        let scanner_snapshots = app_state.snapshot_rx.clone();
+        supervisor.spawn_restartable(format!("{asset}_market_scanner"), move || {
+            let (cfg, client, tx, pool) = (scanner_cfg.clone(), scanner_client.clone(), scanner_tx.clone(), scanner_db.clone());
+            let (snapshots, clock) = (scanner_snapshots.clone(), scanner_clock.clone());
+            Box::pin(async move {
+                let pending = pool.read(db::get_unsettled_tickers).await.unwrap_or_else(|e| {
+                    tracing::error!("failed to load unsettled markets: {e}");
+                    Vec::new()
+                });
+                kalshi::scanner::run_market_scanner(cfg, asset, client, tx, pending, snapshots, clock).await
+            })
+        });
+    }
+
+    // 3b. Exchange status, hours and announcements
+    let exchange_client = kalshi_client.clone();
+    let exchange_tx = engine_tx.clone();
+    let exchange_clock = app_state.clock.clone();
+    supervisor.spawn_restartable("exchange_monitor", move || {
+        Box::pin(kalshi::exchange::run_exchange_monitor(
+            exchange_client.clone(),
+            exchange_tx.clone(),
+            exchange_clock.clone(),
+        ))
+    });
+
+    // 4. Tick generator (1-second interval)
+    let tick_tx = engine_tx.clone();
+    supervisor.spawn_restartable("tick", move || {
+        let tick_tx = tick_tx.clone();
+        Box::pin(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                if tick_tx.send(EngineEvent::Tick).await.is_err() {
+                    break;
+                }
+            }
+        })
+    });
+
+    // 4b. SIGHUP re-reads the config file, same as POST /api/control/reload-config
+    #[cfg(unix)]
+    {
+        let reload_state = app_state.clone();
+        supervisor.spawn_restartable("config_reload", move || {
+            let state = reload_state.clone();
+            Box::pin(async move {
+                let mut hup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+                    Ok(sig) => sig,
+                    Err(e) => {
+                        tracing::error!("SIGHUP handler error: {e}");
+                        return std::future::pending().await;
+                    }
+                };
+                while hup.recv().await.is_some() {
+                    if let Err(e) = server::control::apply_config_reload(&state, "sighup").await {
+                        tracing::error!("config reload failed, keeping current params: {e}");
+                    }
+                }
+            })
+        });
+    }
+
+    // 5. Engine task (core loop -- this is the hot path)
+    let engine_state = app_state.clone();
+    let engine_cfg = cfg.clone();
+    supervisor.spawn_critical("engine", async move {
+        engine::run_engine(engine_state, engine_cfg, engine_rx, restored_positions).await;
+    });
+
+    // 6. Axum HTTP + WS server
+    let server_state = app_state.clone();
+    let port = cfg.server_port;
+
+    if cfg.auth_disabled {
+        tracing::warn!("AUTH_DISABLED is set: read endpoints are open to anyone who can reach the server");
+    } else if cfg.api_keys.is_empty() {
+        tracing::warn!("no API keys configured: every endpoint except /api/health is closed");
+    }
+
+    let app = server::router(server_state, &cfg.cors_allowed_origins);
+
+    let addr = format!("0.0.0.0:{port}");
+    tracing::info!("server listening on {addr}");
+
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .map_err(|e| errors::EngineError::Config(format!("bind {addr}: {e}")))?;
+
+    let mut server_shutdown = app_state.shutdown_tx.subscribe();
+    let mut server_task = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .with_graceful_shutdown(async move {
+                let _ = server_shutdown.wait_for(|s| *s).await;
+            })
+            .await
+    });
+
+    tokio::select! {
+        _ = shutdown_signal() => {}
+        _ = supervisor.watch() => {}
+        res = &mut server_task => {
+            tracing::error!(result = ?res, "server exited unexpectedly");
+        }
+    }
+
+    shutdown(
+        &app_state,
+        supervisor,
+        server_task,
+        std::time::Duration::from_secs(cfg.shutdown_timeout_secs),
+    )
+    .await;
+    Ok(())
+}
+
+/// Resolves on ctrl-c or SIGTERM (what Railway sends on every deploy).
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("ctrl-c handler error: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("SIGTERM handler error: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::warn!("ctrl-c received"),
+        _ = terminate => tracing::warn!("SIGTERM received"),
+    }
+}
+
+/// Ordered shutdown, bounded as a whole by `timeout`:
+///   1. stop producers so nothing new reaches the engine
+///   2. engine applies the position policy, publishes a final snapshot, exits
+///   3. DB writer executes everything queued so far
+///   4. WebSocket clients get a close frame, HTTP server finishes in-flight requests
+async fn shutdown(
+    state: &Arc<AppState>,
+    mut supervisor: supervisor::Supervisor,
+    server: tokio::task::JoinHandle<std::io::Result<()>>,
+    timeout: std::time::Duration,
+) {
+    use tokio::time::timeout_at;
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    tracing::warn!(
+        policy = %state.config.shutdown_position_policy,
+        timeout_secs = timeout.as_secs(),
+        "shutting down"
+    );
+
+    supervisor.stop_restartable();
+
+    let engine = supervisor.take("engine");
+    match timeout_at(deadline, state.engine_tx.send(EngineEvent::Shutdown)).await {
+        Ok(Ok(())) => match engine {
+            Some(engine) => match timeout_at(deadline, engine).await {
+                Ok(_) => tracing::info!("engine stopped"),
+                Err(_) => tracing::error!("engine did not stop before the shutdown deadline"),
+            },
+            None => tracing::error!("engine task already gone"),
+        },
+        Ok(Err(_)) => tracing::error!("engine already stopped; position policy not applied"),
+        Err(_) => tracing::error!("engine channel full at shutdown; position policy not applied"),
+    }
+
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    let drained = timeout_at(deadline, async {
+        state.db_tx.send(DbCommand::Flush { reply: reply_tx }).await.is_ok() && reply_rx.await.is_ok()
+    })
+    .await;
+    match drained {
+        Ok(true) => tracing::info!("db writer drained"),
+        Ok(false) => tracing::error!("db writer stopped before draining"),
+        Err(_) => tracing::error!(
+            queued = state.db_tx.max_capacity() - state.db_tx.capacity(),
+            "db writer did not drain before the shutdown deadline"
+        ),
+    }
+    if let Some(db_writer) = supervisor.take("db_writer") {
+        db_writer.abort();
+    }
+
+    state.shutdown_tx.send_replace(true);
+    match timeout_at(deadline, server).await {
+        Ok(_) => tracing::info!("http server stopped"),
+        Err(_) => tracing::warn!("http server did not stop before the shutdown deadline"),
+    }
+
+    tracing::info!("shutdown complete");
+}
+
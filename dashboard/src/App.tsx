@@ -6,7 +6,8 @@ import { TradesFeed } from './components/TradesFeed';
 import { PnLChart } from './components/PnLChart';
 import { RiskPanel } from './components/RiskPanel';
 import { MarketState } from './components/MarketState';
-import type { WsMessage, ModelState, TradeRow, EngineSnapshot } from './types';
+import { HorizonPnl } from './components/HorizonPnl';
+import type { WsMessage, ModelState, TradeRow, EngineSnapshot, Asset, MarketDepth } from './types';
 
 const MODEL_COLORS: Record<string, string> = {
   'Black-Scholes': '#3b82f6',
@@ -15,12 +16,15 @@ const MODEL_COLORS: Record<string, string> = {
 };
 
 interface MarketInfo {
+  horizon: string;
   ticker: string;
   strike: number | null;
   ttl_seconds: number;
   yes_bid: string | null;
   yes_ask: string | null;
   status: string;
+  depth: MarketDepth;
+  entry_gate: string | null;
 }
 
 interface PnlPoint {
@@ -29,14 +33,31 @@ interface PnlPoint {
   pnl: number;
 }
 
+// Each model runs once per asset
+const modelKey = (asset: Asset, model: string) => `${asset}/${model}`;
+// ... and trades one market per expiry horizon
+const marketKey = (asset: Asset, horizon: string) => `${asset}/${horizon}`;
+
+// Snapshots carry realized P/L and open positions; updates carry the sum
+function pnlByHorizon(m: ModelState): Record<string, number> {
+  const pnl = { ...m.realized_by_horizon };
+  for (const p of m.open_positions ?? []) {
+    pnl[p.horizon] = (pnl[p.horizon] ?? 0) + p.unrealized;
+  }
+  return pnl;
+}
+
 export default function App() {
-  const [btcPrice, setBtcPrice] = useState(0);
-  const [btcTimestamp, setBtcTimestamp] = useState('');
+  const [prices, setPrices] = useState<Partial<Record<Asset, { price: number; timestamp: string }>>>({});
+  const [asset, setAsset] = useState<Asset>('btc');
   const [engineState, setEngineState] = useState('connecting');
-  const [market, setMarket] = useState<MarketInfo | null>(null);
+  const [kalshiApi, setKalshiApi] = useState<{ breaker: string; last_error: string | null }>({ breaker: 'closed', last_error: null });
+  const [exchange, setExchange] = useState<{ phase: string; reason: string; announcement: string | null }>({ phase: 'open', reason: '', announcement: null });
+  const [horizons, setHorizons] = useState<Partial<Record<Asset, string[]>>>({});
+  const [markets, setMarkets] = useState<Record<string, MarketInfo>>({});
   const [models, setModels] = useState<Record<string, Partial<ModelState>>>({});
   const [trades, setTrades] = useState<TradeRow[]>([]);
-  const [pnlData, setPnlData] = useState<PnlPoint[]>([]);
+  const [pnlData, setPnlData] = useState<Partial<Record<Asset, PnlPoint[]>>>({});
 
   const tradesRef = useRef(trades);
   tradesRef.current = trades;
@@ -46,26 +67,38 @@ export default function App() {
     if (snap.engine_state) {
       setEngineState(snap.engine_state.toLowerCase?.() ?? snap.engine_state);
     }
-    if (snap.btc_price > 0) {
-      setBtcPrice(snap.btc_price);
-      setBtcTimestamp(snap.btc_timestamp);
+    if (snap.kalshi_api) {
+      setKalshiApi(snap.kalshi_api);
     }
-    if (snap.active_market) {
-      setMarket({
-        ticker: snap.active_market.ticker,
-        strike: snap.active_market.strike,
-        ttl_seconds: 0,
-        yes_bid: snap.active_market.yes_bid,
-        yes_ask: snap.active_market.yes_ask,
-        status: snap.active_market.status,
-      });
+    if (snap.exchange) {
+      const latest = snap.exchange.announcements[snap.exchange.announcements.length - 1];
+      setExchange({ phase: snap.exchange.phase, reason: snap.exchange.reason, announcement: latest?.message ?? null });
     }
-    if (snap.models && snap.models.length > 0) {
-      const next: Record<string, Partial<ModelState>> = {};
-      for (const m of snap.models) {
-        next[m.name] = m;
+    if (snap.assets && snap.assets.length > 0) {
+      const nextModels: Record<string, Partial<ModelState>> = {};
+      for (const a of snap.assets) {
+        if (a.price > 0) {
+          setPrices((prev) => ({ ...prev, [a.asset]: { price: a.price, timestamp: a.price_timestamp } }));
+        }
+        setHorizons((prev) => ({ ...prev, [a.asset]: a.horizons.map((h) => h.name) }));
+        for (const h of a.horizons) {
+          const m = h.active_market;
+          if (!m) continue;
+          setMarkets((prev) => ({
+            ...prev,
+            [marketKey(a.asset, h.name)]: {
+              horizon: h.name, ticker: m.ticker, strike: m.strike, ttl_seconds: 0, yes_bid: m.yes_bid, yes_ask: m.yes_ask, status: m.status,
+              depth: m.depth, entry_gate: h.entry_gate,
+            },
+          }));
+        }
+        for (const m of a.models) {
+          nextModels[modelKey(a.asset, m.name)] = { ...m, pnl_by_horizon: pnlByHorizon(m) };
+        }
       }
-      setModels(next);
+      setModels(nextModels);
+      // Keep the selection if the engine still trades it
+      setAsset((prev) => (snap.assets.some((a) => a.asset === prev) ? prev : snap.assets[0].asset));
     }
   }, []);
 
@@ -78,31 +111,36 @@ export default function App() {
 
     const msg = raw as WsMessage;
     switch (msg.type) {
-      case 'btc_price':
-        setBtcPrice(msg.price);
-        setBtcTimestamp(msg.timestamp);
+      case 'price':
+        setPrices((prev) => ({ ...prev, [msg.asset]: { price: msg.price, timestamp: msg.timestamp } }));
         break;
 
       case 'market_state':
-        setMarket({
-          ticker: msg.ticker,
-          strike: msg.strike,
-          ttl_seconds: msg.ttl_seconds,
-          yes_bid: msg.yes_bid,
-          yes_ask: msg.yes_ask,
-          status: msg.status,
-        });
+        setMarkets((prev) => ({
+          ...prev,
+          [marketKey(msg.asset, msg.horizon)]: {
+            horizon: msg.horizon,
+            ticker: msg.ticker,
+            strike: msg.strike,
+            ttl_seconds: msg.ttl_seconds,
+            yes_bid: msg.yes_bid,
+            yes_ask: msg.yes_ask,
+            status: msg.status,
+            depth: msg.depth,
+            entry_gate: msg.entry_gate,
+          },
+        }));
         break;
 
-      case 'model_update':
+      case 'model_update': {
+        const key = modelKey(msg.asset, msg.model);
         setModels((prev) => ({
           ...prev,
-          [msg.model]: {
-            ...prev[msg.model],
+          [key]: {
+            ...prev[key],
             name: msg.model,
-            probability: msg.probability,
-            ev: msg.ev,
-            kelly_size: msg.kelly_size,
+            asset: msg.asset,
+            signals: msg.signals,
             cumulative_pnl: msg.cumulative_pnl,
             unrealized_pnl: msg.unrealized_pnl,
             total_pnl: msg.total_pnl,
@@ -114,19 +152,24 @@ export default function App() {
             daily_pnl: msg.daily_pnl,
             current_exposure: msg.current_exposure,
             open_position_count: msg.open_position_count,
+            pnl_by_horizon: msg.pnl_by_horizon,
+            paused: msg.paused,
+            last_risk_decision: msg.last_risk_decision,
           },
         }));
         setPnlData((prev) => {
-          const next = [...prev, { time: new Date().toISOString(), model: msg.model, pnl: msg.total_pnl }];
-          return next.length > 3000 ? next.slice(-3000) : next;
+          const series = [...(prev[msg.asset] ?? []), { time: new Date().toISOString(), model: msg.model, pnl: msg.total_pnl }];
+          return { ...prev, [msg.asset]: series.length > 3000 ? series.slice(-3000) : series };
         });
         break;
+      }
 
       case 'new_trade':
         setTrades((prev) => {
           const trade: TradeRow = {
             id: crypto.randomUUID(),
             model_name: msg.model,
+            asset: msg.asset,
             market_ticker: '',
             side: msg.side,
             action: msg.action,
@@ -150,7 +193,7 @@ export default function App() {
         setTrades((prev) => {
           // Mark the matching open trade with exit info
           const updated = prev.map((t) =>
-            (t.model_name === msg.model && !t.outcome)
+            (t.model_name === msg.model && t.asset === msg.asset && !t.outcome)
               ? { ...t, outcome: `exit:${msg.reason}`, pnl: msg.pnl, settle_time: msg.timestamp }
               : t
           );
@@ -161,18 +204,19 @@ export default function App() {
       case 'trade_settled':
         setTrades((prev) =>
           prev.map((t) =>
-            t.id === msg.trade_id || (t.model_name === msg.model && !t.outcome)
+            t.id === msg.trade_id || (t.model_name === msg.model && t.asset === msg.asset && !t.outcome)
               ? { ...t, outcome: msg.outcome, pnl: msg.pnl, settle_time: msg.timestamp }
               : t
           )
         );
         break;
 
-      case 'metrics_update':
+      case 'metrics_update': {
+        const key = modelKey(msg.asset, msg.model);
         setModels((prev) => ({
           ...prev,
-          [msg.model]: {
-            ...prev[msg.model],
+          [key]: {
+            ...prev[key],
             sharpe: msg.sharpe,
             max_drawdown: msg.max_drawdown,
             total_trades: msg.total_trades,
@@ -182,10 +226,33 @@ export default function App() {
           },
         }));
         break;
+      }
 
       case 'engine_state':
         setEngineState(msg.state);
         break;
+
+      case 'kalshi_api':
+        setKalshiApi({ breaker: msg.breaker, last_error: msg.last_error });
+        break;
+
+      case 'exchange_status':
+        setExchange((prev) => ({ ...prev, phase: msg.phase, reason: msg.reason }));
+        break;
+
+      case 'announcement':
+        setExchange((prev) => ({ ...prev, announcement: msg.message }));
+        break;
+
+      case 'day_rollover':
+        setModels((prev) => {
+          const next = { ...prev };
+          for (const name of Object.keys(next)) {
+            next[name] = { ...next[name], daily_pnl: 0 };
+          }
+          return next;
+        });
+        break;
     }
   }, [handleSnapshot]);
 
@@ -194,9 +261,7 @@ export default function App() {
   const modelList: ModelState[] = ['Black-Scholes', 'Jump-Diffusion', 'Student-t'].map(
     (name) => ({
       name,
-      probability: 0,
-      ev: 0,
-      kelly_size: 0,
+      signals: {},
       cumulative_pnl: 0,
       unrealized_pnl: 0,
       total_pnl: 0,
@@ -211,29 +276,35 @@ export default function App() {
       beta_alpha: 1,
       beta_beta: 1,
       open_position_count: 0,
-      ...models[name],
+      ...models[modelKey(asset, name)],
     })
   );
 
   return (
     <div className="min-h-screen" style={{ background: 'var(--bg-primary)' }}>
       <Header
-        btcPrice={btcPrice}
-        btcTimestamp={btcTimestamp}
+        prices={prices}
+        asset={asset}
+        onSelectAsset={setAsset}
         engineState={engineState}
+        kalshiApi={kalshiApi}
+        exchange={exchange}
         connected={connected}
       />
 
       <main className="max-w-[1600px] mx-auto px-4 py-4 space-y-4">
-        <MarketState market={market} />
+        {(horizons[asset] ?? ['hourly']).map((h) => (
+          <MarketState key={h} asset={asset} horizon={h} market={markets[marketKey(asset, h)] ?? null} />
+        ))}
         <ModelGrid models={modelList} colors={MODEL_COLORS} />
 
         <div className="grid grid-cols-1 lg:grid-cols-3 gap-4">
           <div className="lg:col-span-2">
-            <PnLChart data={pnlData} colors={MODEL_COLORS} />
+            <PnLChart data={pnlData[asset] ?? []} colors={MODEL_COLORS} />
           </div>
-          <div>
+          <div className="space-y-4">
             <RiskPanel models={modelList} />
+            <HorizonPnl horizons={horizons[asset] ?? ['hourly']} models={modelList} />
           </div>
         </div>
 
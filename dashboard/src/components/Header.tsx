@@ -1,7 +1,12 @@
+import type { Asset } from '../types';
+
 interface HeaderProps {
-  btcPrice: number;
-  btcTimestamp: string;
+  prices: Partial<Record<string, { price: number; timestamp: string }>>;
+  asset: string;
+  onSelectAsset: (asset: Asset) => void;
   engineState: string;
+  kalshiApi: { breaker: string; last_error: string | null };
+  exchange: { phase: string; reason: string; announcement: string | null };
   connected: boolean;
 }
 
@@ -9,11 +14,15 @@ const stateColors: Record<string, string> = {
   connecting: '#f59e0b',
   syncing: '#3b82f6',
   trading: '#10b981',
+  paused: '#f59e0b',
+  exchange_closed: '#6b7280',
+  maintenance: '#f59e0b',
   halted: '#ef4444',
 };
 
-export function Header({ btcPrice, btcTimestamp, engineState, connected }: HeaderProps) {
-  const time = btcTimestamp ? new Date(btcTimestamp).toLocaleTimeString() : '--:--:--';
+export function Header({ prices, asset, onSelectAsset, engineState, kalshiApi, exchange, connected }: HeaderProps) {
+  const timestamp = prices[asset]?.timestamp;
+  const time = timestamp ? new Date(timestamp).toLocaleTimeString() : '--:--:--';
 
   return (
     <header
@@ -24,14 +33,24 @@ export function Header({ btcPrice, btcTimestamp, engineState, connected }: Heade
         <h1 className="text-lg font-bold tracking-tight" style={{ color: 'var(--text-primary)' }}>
           pretty_rusty
         </h1>
-        <div className="flex items-center gap-2">
-          <span className="text-xs uppercase tracking-wider" style={{ color: 'var(--text-secondary)' }}>
-            BTC
-          </span>
-          <span className="text-xl font-bold tabular-nums" style={{ color: btcPrice > 0 ? '#10b981' : 'var(--text-secondary)' }}>
-            ${btcPrice > 0 ? btcPrice.toLocaleString(undefined, { minimumFractionDigits: 2, maximumFractionDigits: 2 }) : '---'}
-          </span>
-        </div>
+        {(Object.keys(prices).length > 0 ? Object.keys(prices) : [asset]).map((a) => {
+          const price = prices[a]?.price ?? 0;
+          return (
+            <button
+              key={a}
+              className="flex items-center gap-2"
+              style={{ opacity: a === asset ? 1 : 0.6 }}
+              onClick={() => onSelectAsset(a as Asset)}
+            >
+              <span className="text-xs uppercase tracking-wider" style={{ color: 'var(--text-secondary)' }}>
+                {a}
+              </span>
+              <span className="text-xl font-bold tabular-nums" style={{ color: price > 0 ? '#10b981' : 'var(--text-secondary)' }}>
+                ${price > 0 ? price.toLocaleString(undefined, { minimumFractionDigits: 2, maximumFractionDigits: 2 }) : '---'}
+              </span>
+            </button>
+          );
+        })}
       </div>
 
       <div className="flex items-center gap-6">
@@ -45,10 +64,34 @@ export function Header({ btcPrice, btcTimestamp, engineState, connected }: Heade
             style={{ background: stateColors[engineState] || '#6b7280' }}
           />
           <span className="text-xs uppercase tracking-wider" style={{ color: 'var(--text-secondary)' }}>
-            {engineState}
+            {engineState.replace('_', ' ')}
           </span>
         </div>
 
+        {exchange.phase !== 'open' && (
+          <span className="text-xs uppercase tracking-wider" style={{ color: '#f59e0b' }} title={exchange.reason}>
+            Exchange {exchange.phase}
+          </span>
+        )}
+
+        {exchange.announcement && (
+          <span className="text-xs max-w-xs truncate" style={{ color: 'var(--text-secondary)' }} title={exchange.announcement}>
+            {exchange.announcement}
+          </span>
+        )}
+
+        {kalshiApi.breaker !== 'closed' && (
+          <div className="flex items-center gap-2" title={kalshiApi.last_error ?? undefined}>
+            <div
+              className="w-2 h-2 rounded-full"
+              style={{ background: kalshiApi.breaker === 'open' ? '#ef4444' : '#f59e0b' }}
+            />
+            <span className="text-xs uppercase tracking-wider" style={{ color: 'var(--text-secondary)' }}>
+              Kalshi API {kalshiApi.breaker === 'open' ? 'down' : 'recovering'}
+            </span>
+          </div>
+        )}
+
         <div className="flex items-center gap-2">
           <div
             className="w-2 h-2 rounded-full"
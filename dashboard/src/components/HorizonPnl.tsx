@@ -0,0 +1,39 @@
+import type { ModelState } from '../types';
+
+interface HorizonPnlProps {
+  horizons: string[];
+  models: ModelState[];
+}
+
+function formatPnl(pnl: number): string {
+  return `${pnl >= 0 ? '+' : '-'}$${Math.abs(pnl).toFixed(2)}`;
+}
+
+export function HorizonPnl({ horizons, models }: HorizonPnlProps) {
+  return (
+    <div
+      className="rounded-lg border p-4"
+      style={{ background: 'var(--bg-card)', borderColor: 'var(--border)' }}
+    >
+      <h3 className="text-sm font-bold mb-4" style={{ color: 'var(--text-primary)' }}>
+        P/L by Horizon
+      </h3>
+
+      <div className="space-y-2 text-xs">
+        {horizons.map((h) => {
+          const pnl = models.reduce((s, m) => s + (m.pnl_by_horizon?.[h] ?? 0), 0);
+          return (
+            <div key={h} className="flex items-center justify-between">
+              <span className="uppercase tracking-wider" style={{ color: 'var(--text-secondary)' }}>
+                {h}
+              </span>
+              <span className="font-bold tabular-nums" style={{ color: pnl >= 0 ? '#10b981' : '#ef4444' }}>
+                {formatPnl(pnl)}
+              </span>
+            </div>
+          );
+        })}
+      </div>
+    </div>
+  );
+}
@@ -1,3 +1,5 @@
+import type { MarketDepth } from '../types';
+
 interface MarketInfo {
   ticker: string;
   strike: number | null;
@@ -5,12 +7,21 @@ interface MarketInfo {
   yes_bid: string | null;
   yes_ask: string | null;
   status: string;
+  depth: MarketDepth;
+  // Liquidity gate holding back entries, e.g. 'wide_spread'
+  entry_gate: string | null;
 }
 
 interface MarketStateProps {
+  asset: string;
+  horizon: string;
   market: MarketInfo | null;
 }
 
+function formatCount(n: number | null): string {
+  return n === null ? '--' : Math.round(n).toLocaleString();
+}
+
 function formatTTL(seconds: number): string {
   if (seconds <= 0) return '00:00';
   const m = Math.floor(seconds / 60);
@@ -18,7 +29,7 @@ function formatTTL(seconds: number): string {
   return `${m.toString().padStart(2, '0')}:${s.toString().padStart(2, '0')}`;
 }
 
-export function MarketState({ market }: MarketStateProps) {
+export function MarketState({ asset, horizon, market }: MarketStateProps) {
   if (!market) {
     return (
       <div
@@ -26,12 +37,14 @@ export function MarketState({ market }: MarketStateProps) {
         style={{ background: 'var(--bg-card)', borderColor: 'var(--border)' }}
       >
         <span style={{ color: 'var(--text-secondary)' }}>
-          Scanning for active BTC 15-min market...
+          Scanning for active {asset.toUpperCase()} {horizon} market...
         </span>
       </div>
     );
   }
 
+  const { yes_bid_size, no_bid_size } = market.depth;
+  const topOfBook = yes_bid_size === null || no_bid_size === null ? null : Math.min(yes_bid_size, no_bid_size);
   const ttlColor = market.ttl_seconds < 60 ? '#ef4444' : market.ttl_seconds < 300 ? '#f59e0b' : '#10b981';
 
   return (
@@ -42,7 +55,7 @@ export function MarketState({ market }: MarketStateProps) {
       <div className="flex items-center gap-6">
         <div>
           <div className="text-xs uppercase tracking-wider" style={{ color: 'var(--text-secondary)' }}>
-            Market
+            {horizon} Market
           </div>
           <div className="text-sm font-bold" style={{ color: 'var(--text-primary)' }}>
             {market.ticker}
@@ -68,6 +81,24 @@ export function MarketState({ market }: MarketStateProps) {
             {market.status}
           </div>
         </div>
+
+        <div>
+          <div className="text-xs uppercase tracking-wider" style={{ color: 'var(--text-secondary)' }}>
+            Entries
+          </div>
+          <div className="text-sm font-bold uppercase" style={{ color: market.entry_gate ? '#f59e0b' : '#10b981' }}>
+            {market.entry_gate ? market.entry_gate.replace(/_/g, ' ') : 'open'}
+          </div>
+        </div>
+
+        <div>
+          <div className="text-xs uppercase tracking-wider" style={{ color: 'var(--text-secondary)' }}>
+            OI / Book / Trades
+          </div>
+          <div className="text-sm font-bold tabular-nums" style={{ color: 'var(--text-primary)' }}>
+            {formatCount(market.depth.open_interest)} / {formatCount(topOfBook)} / {formatCount(market.depth.recent_trades)}
+          </div>
+        </div>
       </div>
 
       <div className="flex items-center gap-6">
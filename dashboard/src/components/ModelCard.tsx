@@ -44,6 +44,11 @@ export function ModelCard({ model, color }: ModelCardProps) {
               {model.open_position_count} open
             </span>
           )}
+          {model.paused && (
+            <span className="text-xs px-1.5 py-0.5 rounded ml-1" style={{ background: '#f59e0b20', color: '#f59e0b' }}>
+              paused
+            </span>
+          )}
         </div>
         <div className="text-right">
           <span
@@ -87,11 +92,18 @@ export function ModelCard({ model, color }: ModelCardProps) {
         </div>
       )}
 
+      {/* Latest signal per horizon */}
+      {Object.entries(model.signals ?? {}).map(([horizon, s]) => (
+        <div key={horizon} className="grid grid-cols-4 gap-x-2 mb-2 text-xs tabular-nums">
+          <span className="uppercase tracking-wider" style={{ color: 'var(--text-secondary)' }}>{horizon}</span>
+          <span title="Probability">{(s.probability * 100).toFixed(1)}%</span>
+          <span title="EV" style={{ color: s.ev > 0 ? '#10b981' : '#ef4444' }}>{fmtPnl(s.ev)}</span>
+          <span title="Kelly size">{fmt(s.kelly_size, 1)} cts</span>
+        </div>
+      ))}
+
       {/* Metrics grid */}
       <div className="grid grid-cols-2 gap-y-3 gap-x-4 text-xs">
-        <Metric label="Probability" value={`${(model.probability * 100).toFixed(1)}%`} />
-        <Metric label="EV" value={fmtPnl(model.ev)} valueColor={model.ev > 0 ? '#10b981' : '#ef4444'} />
-        <Metric label="Kelly Size" value={`${fmt(model.kelly_size, 1)} cts`} />
         <Metric label="Sharpe" value={fmt(model.sharpe, 2)} />
         <Metric label="Win Rate" value={`${winRate}%`} />
         <Metric label="Max DD" value={`-$${fmt(model.max_drawdown, 2)}`} valueColor="#ef4444" />
@@ -100,6 +112,16 @@ export function ModelCard({ model, color }: ModelCardProps) {
         <Metric label="Daily P/L" value={fmtPnl(model.daily_pnl)} valueColor={model.daily_pnl >= 0 ? '#10b981' : '#ef4444'} />
         <Metric label="Exposure" value={`$${fmt(model.current_exposure, 2)}`} />
       </div>
+
+      {/* Last order cut by risk limits */}
+      {model.last_risk_decision && (
+        <div className="mt-3 text-xs" style={{ color: '#f59e0b' }} title={model.last_risk_decision.timestamp}>
+          {model.last_risk_decision.action === 'scale_in' ? 'Scale-in' : 'Entry'} {model.last_risk_decision.outcome}
+          {model.last_risk_decision.outcome === 'scaled' &&
+            ` ${fmt(model.last_risk_decision.requested_contracts, 0)} → ${fmt(model.last_risk_decision.allowed_contracts, 0)} cts`}
+          : {model.last_risk_decision.reason}
+        </div>
+      )}
     </div>
   );
 }
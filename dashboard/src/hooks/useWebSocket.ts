@@ -1,5 +1,20 @@
 import { useEffect, useRef, useCallback, useState } from 'react';
 
+const API_KEY_STORAGE = 'pretty_rusty_api_key';
+
+// The server requires an API key unless started with AUTH_DISABLED=1. Browsers
+// cannot set headers on a WebSocket upgrade, so the key goes in the query
+// string, which the server only reads on /ws. A key
+// passed once as ?api_key=... on the dashboard URL is remembered locally.
+function apiKey(): string | null {
+  const fromUrl = new URLSearchParams(window.location.search).get('api_key');
+  if (fromUrl) {
+    localStorage.setItem(API_KEY_STORAGE, fromUrl);
+    return fromUrl;
+  }
+  return localStorage.getItem(API_KEY_STORAGE);
+}
+
 // Accept any parsed JSON -- the caller decides how to handle snapshots vs messages
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
 export function useWebSocket(onMessage: (msg: any) => void) {
@@ -12,7 +27,9 @@ export function useWebSocket(onMessage: (msg: any) => void) {
   const connect = useCallback(() => {
     const protocol = window.location.protocol === 'https:' ? 'wss' : 'ws';
     const host = window.location.host;
-    const ws = new WebSocket(`${protocol}://${host}/ws`);
+    const key = apiKey();
+    const query = key ? `?api_key=${encodeURIComponent(key)}` : '';
+    const ws = new WebSocket(`${protocol}://${host}/ws${query}`);
 
     ws.onopen = () => {
       setConnected(true);
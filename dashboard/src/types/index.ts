@@ -1,8 +1,10 @@
+export type Asset = 'btc' | 'eth' | 'sol';
+
 export interface ModelState {
   name: string;
-  probability: number;
-  ev: number;
-  kelly_size: number;
+  asset?: Asset;
+  // Latest signal per expiry horizon
+  signals: Record<string, Signal>;
   cumulative_pnl: number;
   unrealized_pnl: number;
   total_pnl: number;
@@ -17,9 +19,43 @@ export interface ModelState {
   beta_alpha: number;
   beta_beta: number;
   open_position_count: number;
+  // Realized P/L per expiry horizon; on snapshots only
+  realized_by_horizon?: Record<string, number>;
+  open_positions?: OpenPosition[];
+  // Realized plus marked P/L per horizon, from model_update
+  pnl_by_horizon?: Record<string, number>;
+  paused?: boolean;
+  last_risk_decision?: RiskDecision | null;
+}
+
+export interface Signal {
+  probability: number;
+  ev: number;
+  kelly_size: number;
+}
+
+export interface OpenPosition {
+  trade_id: string;
+  market_ticker: string;
+  horizon: string;
+  side: string;
+  entry_price: number;
+  contracts: number;
+  unrealized: number;
+}
+
+export interface RiskDecision {
+  action: string;
+  outcome: 'blocked' | 'scaled';
+  reason: string;
+  requested_contracts: number;
+  allowed_contracts: number;
+  timestamp: string;
 }
 
 export interface ActiveMarket {
+  asset: Asset;
+  horizon: string;
   ticker: string;
   event_ticker: string;
   series_ticker: string;
@@ -33,6 +69,16 @@ export interface ActiveMarket {
   expiration_time: string;
   status: string;
   result: string | null;
+  depth: MarketDepth;
+}
+
+// Unset fields were not fetched (their liquidity gate is off)
+export interface MarketDepth {
+  open_interest: number | null;
+  yes_bid_size: number | null;
+  no_bid_size: number | null;
+  recent_trades: number | null;
+  recent_volume: number | null;
 }
 
 export interface VolatilityState {
@@ -45,18 +91,56 @@ export interface VolatilityState {
   sample_count: number;
 }
 
-export interface EngineSnapshot {
-  engine_state: string;
-  btc_price: number;
-  btc_timestamp: string;
+export interface ApiHealth {
+  breaker: 'closed' | 'open' | 'half_open';
+  consecutive_failures: number;
+  last_error: string | null;
+  opened_at: string | null;
+  reads_per_sec: number;
+}
+
+export interface Announcement {
+  type: 'info' | 'warning' | 'error';
+  message: string;
+  delivery_time: string;
+  status: string;
+}
+
+export interface ExchangeInfo {
+  phase: 'open' | 'closed' | 'maintenance';
+  reason: string;
+  estimated_resume_time: string | null;
+  next_maintenance: [string, string] | null;
+  announcements: Announcement[];
+}
+
+export interface AssetSnapshot {
+  asset: Asset;
+  price: number;
+  price_timestamp: string;
+  horizons: HorizonSnapshot[];
+  models: ModelState[];
+}
+
+export interface HorizonSnapshot {
+  name: string;
   active_market: ActiveMarket | null;
   volatility: VolatilityState;
-  models: ModelState[];
+  entry_gate: string | null;
+}
+
+export interface EngineSnapshot {
+  engine_state: string;
+  assets: AssetSnapshot[];
+  kalshi_api?: ApiHealth;
+  exchange?: ExchangeInfo;
 }
 
 export interface TradeRow {
   id: string;
   model_name: string;
+  asset?: Asset;
+  horizon?: string;
   market_ticker: string;
   side: string;
   action: string;
@@ -70,14 +154,34 @@ export interface TradeRow {
   fees_estimate: number;
   entry_time: string;
   settle_time: string | null;
+  exit_price?: number | null;
+  exit_reason?: string | null;
+  // Present on rows from GET /api/trades (not on WS-built rows)
+  status?: 'open' | 'partial' | 'closed' | 'settled';
+  remaining_contracts?: number;
+  realized_fees?: number;
+  fills?: Fill[];
+}
+
+export interface Fill {
+  kind: 'entry' | 'exit' | 'settlement';
+  price: number;
+  contracts: number;
+  fee: number;
+  reason: string | null;
+  timestamp: string;
 }
 
 export type WsMessage =
-  | { type: 'btc_price'; price: number; timestamp: string }
-  | { type: 'market_state'; ticker: string; strike: number | null; ttl_seconds: number; yes_bid: string | null; yes_ask: string | null; status: string }
-  | { type: 'model_update'; model: string; probability: number; ev: number; kelly_size: number; cumulative_pnl: number; unrealized_pnl: number; total_pnl: number; total_trades: number; winning_trades: number; sharpe: number; max_drawdown: number; brier_score: number; daily_pnl: number; current_exposure: number; open_position_count: number }
-  | { type: 'new_trade'; model: string; side: string; action: string; price: number; contracts: number; ev: number; timestamp: string }
-  | { type: 'trade_exited'; model: string; trade_id: string; side: string; entry_price: number; exit_price: number; contracts: number; pnl: number; reason: string; timestamp: string }
-  | { type: 'trade_settled'; model: string; trade_id: string; outcome: string; pnl: number; timestamp: string }
-  | { type: 'metrics_update'; model: string; sharpe: number; max_drawdown: number; win_rate: number; brier: number; total_trades: number; daily_pnl: number }
-  | { type: 'engine_state'; state: string; reason: string };
+  | { type: 'price'; asset: Asset; price: number; timestamp: string }
+  | { type: 'market_state'; asset: Asset; horizon: string; ticker: string; strike: number | null; ttl_seconds: number; yes_bid: string | null; yes_ask: string | null; status: string; depth: MarketDepth; entry_gate: string | null }
+  | { type: 'model_update'; model: string; asset: Asset; signals: Record<string, Signal>; cumulative_pnl: number; unrealized_pnl: number; total_pnl: number; total_trades: number; winning_trades: number; sharpe: number; max_drawdown: number; brier_score: number; daily_pnl: number; current_exposure: number; open_position_count: number; pnl_by_horizon: Record<string, number>; paused: boolean; last_risk_decision: RiskDecision | null }
+  | { type: 'new_trade'; model: string; asset: Asset; side: string; action: string; price: number; contracts: number; ev: number; timestamp: string }
+  | { type: 'trade_exited'; model: string; asset: Asset; trade_id: string; side: string; entry_price: number; exit_price: number; contracts: number; pnl: number; reason: string; timestamp: string }
+  | { type: 'trade_settled'; model: string; asset: Asset; trade_id: string; outcome: string; pnl: number; timestamp: string }
+  | { type: 'metrics_update'; model: string; asset: Asset; sharpe: number; max_drawdown: number; win_rate: number; brier: number; total_trades: number; daily_pnl: number }
+  | { type: 'engine_state'; state: string; reason: string }
+  | { type: 'kalshi_api'; breaker: string; consecutive_failures: number; last_error: string | null }
+  | { type: 'exchange_status'; phase: string; reason: string; estimated_resume_time: string | null }
+  | { type: 'announcement'; level: string; message: string; delivery_time: string }
+  | { type: 'day_rollover'; previous_day: string; trading_day: string; daily_pnl: [string, Asset, number][]; timestamp: string };
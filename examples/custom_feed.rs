@@ -0,0 +1,97 @@
+//! Embed the engine and drive it from your own event source.
+//!
+//! The binary feeds `engine::run_engine` from the crypto price poller, the
+//! Kalshi scanner and a one-second ticker. Here a synthetic feed sends the
+//! same `EngineEvent`s instead: one market, a price path that drifts above
+//! the strike, a tick after every price, then the market's settlement.
+//! Trades land in a throwaway database exactly as they would in production.
+//!
+//!     cargo run --example custom_feed
+
+use pretty_rusty::config::AppConfig;
+use pretty_rusty::state::{ActiveMarket, DbCommand, EngineEvent, MarketDepth};
+use pretty_rusty::{db, engine, AppState, Asset, EngineResult};
+use tokio::sync::{mpsc, oneshot};
+
+#[tokio::main]
+async fn main() -> EngineResult<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+        )
+        .init();
+
+    // Default profile, no credentials needed: nothing here talks to Kalshi
+    let config = AppConfig::load(None, None)?;
+    let data_dir = std::env::temp_dir().join(format!("pretty_rusty_example_{}", std::process::id()));
+    let (writer_conn, pool) = db::init_db(&data_dir)?;
+
+    let (engine_tx, engine_rx) = mpsc::channel(512);
+    let (db_tx, db_rx) = db::channel(1024);
+    let state = AppState::new(config.clone(), pool.clone(), engine_tx.clone(), db_tx.clone());
+
+    let writer = tokio::spawn(db::run_db_writer(writer_conn, db_rx, state.metrics.clone()));
+    let engine = tokio::spawn(engine::run_engine(state.clone(), config, engine_rx, Vec::new()));
+
+    // ── The custom event source ──
+    let close = chrono::Utc::now() + chrono::Duration::minutes(20);
+    let market = ActiveMarket {
+        asset: Asset::Btc,
+        // Must name one of the profile's horizons (the default has one)
+        horizon: pretty_rusty::horizon::DEFAULT_HORIZON.into(),
+        ticker: "KXBTCD-EXAMPLE-T100000".into(),
+        event_ticker: "KXBTCD-EXAMPLE".into(),
+        series_ticker: "KXBTCD".into(),
+        strike: Some(100_000.0),
+        yes_bid: Some("0.55".into()),
+        yes_ask: Some("0.57".into()),
+        no_bid: Some("0.43".into()),
+        no_ask: Some("0.45".into()),
+        last_price: Some("0.56".into()),
+        close_time: close.to_rfc3339(),
+        expiration_time: close.to_rfc3339(),
+        status: "active".into(),
+        result: None,
+        // Only read by liquidity gates that need depth; they are off by default
+        depth: MarketDepth::default(),
+    };
+    engine_tx.send(EngineEvent::MarketUpdate(Box::new(market.clone()))).await.ok();
+
+    let start_ms = chrono::Utc::now().timestamp_millis();
+    for i in 0..120 {
+        let price = 100_050.0 + 2.0 * i as f64 + 20.0 * (i as f64 * 0.9).sin();
+        let timestamp_ms = start_ms + 2_000 * i;
+        engine_tx.send(EngineEvent::Price { asset: Asset::Btc, price, timestamp_ms }).await.ok();
+        engine_tx.send(EngineEvent::Tick).await.ok();
+    }
+    engine_tx
+        .send(EngineEvent::MarketSettled { asset: Asset::Btc, ticker: market.ticker.clone(), result: "yes".into() })
+        .await
+        .ok();
+
+    // Let the engine catch up, then read the published snapshot
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    let snapshot = state.snapshot_rx.borrow().clone();
+    let btc = &snapshot.assets[0];
+    println!("engine {:?}, BTC {:.2}", snapshot.engine_state, btc.price);
+    for m in &btc.models {
+        println!(
+            "{:<15} trades {:>3}  won {:>3}  P/L {:+.2}",
+            m.name, m.total_trades, m.winning_trades, m.cumulative_pnl
+        );
+    }
+
+    // Same order as the binary's shutdown: engine first, then drain the writer
+    engine_tx.send(EngineEvent::Shutdown).await.ok();
+    engine.await.ok();
+    let (reply, done) = oneshot::channel();
+    db_tx.send(DbCommand::Flush { reply }).await.ok();
+    done.await.ok();
+    writer.abort();
+
+    let trades: i64 = pool.read(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM trades", [], |r| r.get(0))?)).await?;
+    println!("{trades} trades written to {}", db::db_path(&data_dir).display());
+    std::fs::remove_dir_all(&data_dir).ok();
+    Ok(())
+}
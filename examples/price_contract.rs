@@ -0,0 +1,78 @@
+//! Price one KXBTCD contract with the library's building blocks, the way a
+//! research notebook would: estimate volatility from a price series, run
+//! every pricing model, and size the trade with EV and Kelly.
+//!
+//!     cargo run --example price_contract
+
+use pretty_rusty::config::TradingParams;
+use pretty_rusty::state::ModelParams;
+use pretty_rusty::{
+    compute_ev, compute_kelly, models, Calibrator, EvParams, KellyParams, VolContext, VolatilityEngine,
+};
+
+fn main() {
+    let trading = TradingParams::default();
+    let strategy = &trading.strategy;
+
+    // Ten minutes of 2-second BTC prices (the feed's cadence): a gentle
+    // random walk around $100,250
+    let mut vol = VolatilityEngine::new(trading.volatility);
+    let mut price = 100_250.0_f64;
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    for _ in 0..300 {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        let shock = (seed % 2001) as f64 / 1000.0 - 1.0;
+        price *= 1.0 + 0.0002 * shock;
+        vol.update(price);
+    }
+    assert!(vol.is_ready(), "not enough samples for the volatility estimate");
+
+    let (strike, ttl_seconds, yes_ask) = (100_000.0, 25.0 * 60.0, 0.62);
+    let state = vol.state;
+    let sigma = vol.annualized_vol();
+    let params = ModelParams::new(price, strike, ttl_seconds, sigma);
+    let vol_ctx = VolContext {
+        jump_intensity: state.jump_intensity,
+        jump_mean: state.jump_mean,
+        jump_var: state.jump_var,
+        student_t_nu: state.student_t_nu,
+    };
+    println!("spot {price:.2}, strike {strike}, {:.0} min left, sigma {sigma:.3}, YES ask {yes_ask}", ttl_seconds / 60.0);
+
+    // A fresh calibrator passes probabilities through until it has seen
+    // enough settled trades
+    let calibrator = Calibrator::new();
+    for model in models::enabled(&models::MODEL_NAMES.map(String::from)) {
+        let p = calibrator.calibrate(model.probability(&params, &vol_ctx));
+        let ev = compute_ev(
+            &EvParams {
+                probability: p,
+                contract_price: yes_ask,
+                fee_rate: strategy.fee_rate,
+                slippage: strategy.slippage,
+                fill_probability: strategy.fill_probability,
+            },
+            strategy.ev_threshold,
+        );
+        let side_price = if ev.buy_yes { yes_ask } else { 1.0 - yes_ask };
+        let kelly = compute_kelly(&KellyParams {
+            model_probability: if ev.buy_yes { p } else { 1.0 - p },
+            alpha: 20.0,
+            beta: 20.0,
+            contract_price: side_price,
+            fractional_gamma: strategy.fractional_kelly,
+            lambda: strategy.kelly_lambda,
+            max_position: trading.risk.max_position_size,
+        });
+        println!(
+            "{:<15} P(YES) {:.3}  EV {:+.4} ({})  {}",
+            model.name(),
+            p,
+            ev.ev,
+            if ev.buy_yes { "YES" } else { "NO" },
+            if ev.is_signal { format!("trade {:.0} contracts", kelly.contracts) } else { "no trade".into() },
+        );
+    }
+}
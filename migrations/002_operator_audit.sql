@@ -0,0 +1,11 @@
+-- Every operator control action (pause, resume, flatten, halt, unhalt)
+CREATE TABLE IF NOT EXISTS operator_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    model_name TEXT,          -- NULL for global actions
+    reason TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_operator_audit_time ON operator_audit(timestamp);
@@ -0,0 +1,15 @@
+-- Open positions saved on shutdown (SHUTDOWN_POSITION_POLICY=persist).
+-- Read and cleared on the next startup; the trades themselves stay in `trades`.
+CREATE TABLE IF NOT EXISTS open_positions (
+    trade_id TEXT PRIMARY KEY,
+    model_name TEXT NOT NULL,
+    market_ticker TEXT NOT NULL,
+    side TEXT NOT NULL,
+    entry_price REAL NOT NULL,
+    contracts REAL NOT NULL,
+    model_probability REAL NOT NULL,
+    entry_btc_price REAL NOT NULL,
+    peak_unrealized REAL NOT NULL DEFAULT 0.0,
+    leg INTEGER NOT NULL DEFAULT 0,
+    persisted_at TEXT NOT NULL
+);
@@ -0,0 +1,7 @@
+-- Early exits (take profit, stops, flatten) used to be recorded only as
+-- outcome = 'exit:<reason>' with the exit price discarded.
+ALTER TABLE trades ADD COLUMN exit_price REAL;
+ALTER TABLE trades ADD COLUMN exit_reason TEXT;
+
+-- Exit prices of old rows are unrecoverable; the reason is not
+UPDATE trades SET exit_reason = substr(outcome, 6) WHERE outcome LIKE 'exit:%';
@@ -0,0 +1,81 @@
+-- Fills: every change to a position is a row here. A trade is one position
+-- (one entry); partial exits, full exits and settlement are further fills.
+CREATE TABLE IF NOT EXISTS fills (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    trade_id TEXT NOT NULL,
+    kind TEXT NOT NULL,       -- 'entry', 'exit' or 'settlement'
+    price REAL NOT NULL,      -- settlement: 1.0 or 0.0
+    contracts REAL NOT NULL,
+    fee REAL NOT NULL DEFAULT 0.0,
+    reason TEXT,              -- entry: action; exit: exit rule; settlement: paid/expired
+    timestamp TEXT NOT NULL,
+    FOREIGN KEY (trade_id) REFERENCES trades(id)
+);
+CREATE INDEX IF NOT EXISTS idx_fills_trade ON fills(trade_id, id);
+
+-- Contracts still held, and fees already deducted from `pnl` (which is now
+-- the running realized P/L across all exit fills)
+ALTER TABLE trades ADD COLUMN remaining_contracts REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE trades ADD COLUMN realized_fees REAL NOT NULL DEFAULT 0.0;
+
+-- ── Convert existing rows ──
+
+-- 1. Entry fill for every real trade
+INSERT INTO fills (trade_id, kind, price, contracts, fee, reason, timestamp)
+SELECT id, 'entry', entry_price, contracts, fees_estimate, action, entry_time
+FROM trades WHERE id NOT LIKE '%-partial';
+
+-- 2. Partial take-profits were stored as fake trades '<id>-partial' whose
+--    entry_price was the exit price and whose ev was the realized P/L
+INSERT INTO fills (trade_id, kind, price, contracts, fee, reason, timestamp)
+SELECT t.id, 'exit', p.entry_price, p.contracts, p.fees_estimate, 'partial_take_profit', p.entry_time
+FROM trades p JOIN trades t ON p.id = t.id || '-partial';
+
+-- 3. Full exits: the old code discarded the exit price, but pnl was
+--    (exit - entry) * n - 0.02 * exit * n, so exit = (pnl / n + entry) / 0.98
+INSERT INTO fills (trade_id, kind, price, contracts, fee, reason, timestamp)
+SELECT id, 'exit', px, n, 0.02 * px * n, exit_reason, COALESCE(settle_time, entry_time)
+FROM (
+    SELECT id, exit_reason, settle_time, entry_time, n,
+           COALESCE(exit_price, (pnl / n + entry_price) / 0.98) AS px
+    FROM (
+        SELECT t.*, t.contracts - COALESCE(
+            (SELECT SUM(f.contracts) FROM fills f WHERE f.trade_id = t.id AND f.kind = 'exit'), 0) AS n
+        FROM trades t
+        WHERE t.outcome LIKE 'exit:%' AND t.pnl IS NOT NULL
+    )
+    WHERE n > 0
+);
+
+-- 4. Settlements (the old code settled the full size even after a partial)
+INSERT INTO fills (trade_id, kind, price, contracts, fee, reason, timestamp)
+SELECT t.id, 'settlement',
+       CASE WHEN t.outcome = 'win' THEN 1.0 ELSE 0.0 END,
+       t.contracts - COALESCE(
+           (SELECT SUM(f.contracts) FROM fills f WHERE f.trade_id = t.id AND f.kind = 'exit'), 0),
+       0.0,
+       CASE WHEN t.outcome = 'win' THEN 'paid' ELSE 'expired' END,
+       COALESCE(t.settle_time, t.entry_time)
+FROM trades t
+WHERE t.outcome IN ('win', 'loss') AND t.id NOT LIKE '%-partial';
+
+-- 5. Trade-level fields from the fills
+UPDATE trades SET
+    remaining_contracts = CASE WHEN outcome IS NULL
+        THEN contracts - COALESCE(
+            (SELECT SUM(f.contracts) FROM fills f WHERE f.trade_id = trades.id AND f.kind = 'exit'), 0)
+        ELSE 0.0 END,
+    realized_fees = COALESCE(
+            (SELECT SUM(f.fee) FROM fills f WHERE f.trade_id = trades.id AND f.kind = 'exit'), 0)
+        + CASE WHEN outcome IN ('win', 'loss') THEN fees_estimate ELSE 0.0 END,
+    exit_price = COALESCE(exit_price,
+        (SELECT f.price FROM fills f
+         WHERE f.trade_id = trades.id AND f.kind IN ('exit', 'settlement') AND outcome IS NOT NULL
+         ORDER BY f.id DESC LIMIT 1)),
+    exit_reason = CASE WHEN outcome IN ('win', 'loss') THEN 'settlement' ELSE exit_reason END,
+    pnl = CASE WHEN EXISTS (SELECT 1 FROM trades p WHERE p.id = trades.id || '-partial')
+        THEN COALESCE(pnl, 0) + (SELECT SUM(p.ev) FROM trades p WHERE p.id = trades.id || '-partial')
+        ELSE pnl END
+WHERE id NOT LIKE '%-partial';
+
+DELETE FROM trades WHERE id LIKE '%-partial';
@@ -0,0 +1,2 @@
+-- Keyset pagination for /api/trades orders by (entry_time, id)
+CREATE INDEX IF NOT EXISTS idx_trades_entry ON trades(entry_time, id);
@@ -0,0 +1,17 @@
+-- Market context at entry, for analytics: seconds to market close and the
+-- volatility regime the engine was in
+ALTER TABLE trades ADD COLUMN entry_ttl_seconds REAL;
+ALTER TABLE trades ADD COLUMN entry_regime TEXT;
+
+-- Backfill from the market close time and the nearest preceding snapshot
+UPDATE trades SET entry_ttl_seconds = (
+    SELECT (julianday(m.close_time) - julianday(trades.entry_time)) * 86400.0
+    FROM markets m
+    WHERE m.ticker = trades.market_ticker AND julianday(m.close_time) IS NOT NULL
+);
+
+UPDATE trades SET entry_regime = (
+    SELECT s.regime FROM model_snapshots s
+    WHERE s.model_name = trades.model_name AND s.timestamp <= trades.entry_time
+    ORDER BY s.timestamp DESC LIMIT 1
+);
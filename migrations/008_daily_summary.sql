@@ -0,0 +1,18 @@
+-- End-of-day results per model, one row per trading day (see trading_day.rs)
+CREATE TABLE IF NOT EXISTS daily_summary (
+    trading_day TEXT NOT NULL,       -- YYYY-MM-DD, local date the day starts on
+    model_name TEXT NOT NULL,
+    day_start TEXT NOT NULL,         -- RFC 3339 UTC
+    day_end TEXT NOT NULL,
+    realized_pnl REAL NOT NULL,
+    trades_opened INTEGER NOT NULL,
+    trades_closed INTEGER NOT NULL,
+    wins INTEGER NOT NULL,
+    fees REAL NOT NULL,
+    cumulative_pnl REAL NOT NULL,    -- engine cumulative P/L at close
+    max_drawdown REAL NOT NULL,
+    exposure REAL NOT NULL,          -- carried into the next day
+    open_positions INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (trading_day, model_name)
+);
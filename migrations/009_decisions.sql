@@ -0,0 +1,25 @@
+-- Decision journal: one row per model per tick with the inputs to the
+-- entry decision and why it went the way it did (see db/decisions.rs)
+CREATE TABLE IF NOT EXISTS decisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp TEXT NOT NULL,         -- RFC 3339 UTC
+    model_name TEXT NOT NULL,
+    market_ticker TEXT NOT NULL,
+    btc_price REAL NOT NULL,
+    ttl_seconds REAL NOT NULL,
+    raw_probability REAL NOT NULL,
+    probability REAL NOT NULL,       -- calibrated
+    ev_yes REAL NOT NULL,
+    ev_no REAL NOT NULL,
+    kelly_contracts REAL NOT NULL,
+    risk_verdict TEXT,               -- allowed / scaled / blocked; NULL if no order was sized
+    risk_reason TEXT,
+    action TEXT NOT NULL,            -- buy / scale_in / none
+    reason TEXT NOT NULL,            -- reason code, e.g. no_edge, holding, risk_blocked
+    side TEXT,
+    contracts REAL,
+    exit_reason TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_decisions_model_time ON decisions(model_name, timestamp);
+CREATE INDEX IF NOT EXISTS idx_decisions_time ON decisions(timestamp);
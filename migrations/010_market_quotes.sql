@@ -0,0 +1,16 @@
+-- Quotes for the active market as the engine received them (one row per
+-- scanner update), so `pretty_rusty backtest` can replay them against the
+-- recorded BTC prices
+CREATE TABLE IF NOT EXISTS market_quotes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp TEXT NOT NULL,         -- RFC 3339 UTC
+    ticker TEXT NOT NULL,
+    yes_bid REAL,
+    yes_ask REAL,
+    no_bid REAL,
+    no_ask REAL,
+    last_price REAL,
+    status TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_quotes_time ON market_quotes(timestamp);
@@ -0,0 +1,65 @@
+-- Prices for every underlying asset (btc, eth, sol); replaces btc_prices.
+-- Feeds the volatility estimate on replay (see paper/backtest.rs)
+CREATE TABLE IF NOT EXISTS underlying_prices (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    asset TEXT NOT NULL,
+    timestamp TEXT NOT NULL,         -- RFC 3339 UTC
+    price REAL NOT NULL,
+    source TEXT NOT NULL DEFAULT 'freecryptoapi'
+);
+
+INSERT INTO underlying_prices (asset, timestamp, price, source)
+    SELECT 'btc', timestamp, price, source FROM btc_prices ORDER BY id;
+DROP TABLE btc_prices;
+
+CREATE INDEX IF NOT EXISTS idx_underlying_prices_asset_time ON underlying_prices(asset, timestamp);
+
+-- Each model runs once per asset, so per-model rows are keyed by asset too.
+-- Everything recorded before this migration was BTC.
+ALTER TABLE trades ADD COLUMN asset TEXT NOT NULL DEFAULT 'btc';
+ALTER TABLE decisions ADD COLUMN asset TEXT NOT NULL DEFAULT 'btc';
+ALTER TABLE model_snapshots ADD COLUMN asset TEXT NOT NULL DEFAULT 'btc';
+
+CREATE TABLE risk_state_new (
+    model_name TEXT NOT NULL,
+    asset TEXT NOT NULL DEFAULT 'btc',
+    current_exposure REAL NOT NULL DEFAULT 0.0,
+    daily_pnl REAL NOT NULL DEFAULT 0.0,
+    max_drawdown REAL NOT NULL DEFAULT 0.0,
+    peak_equity REAL NOT NULL DEFAULT 0.0,
+    total_trades INTEGER NOT NULL DEFAULT 0,
+    winning_trades INTEGER NOT NULL DEFAULT 0,
+    last_updated TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (model_name, asset)
+);
+INSERT INTO risk_state_new (model_name, current_exposure, daily_pnl, max_drawdown, peak_equity, total_trades, winning_trades, last_updated)
+    SELECT model_name, current_exposure, daily_pnl, max_drawdown, peak_equity, total_trades, winning_trades, last_updated FROM risk_state;
+DROP TABLE risk_state;
+ALTER TABLE risk_state_new RENAME TO risk_state;
+
+CREATE TABLE daily_summary_new (
+    trading_day TEXT NOT NULL,
+    model_name TEXT NOT NULL,
+    asset TEXT NOT NULL DEFAULT 'btc',
+    day_start TEXT NOT NULL,
+    day_end TEXT NOT NULL,
+    realized_pnl REAL NOT NULL,
+    trades_opened INTEGER NOT NULL,
+    trades_closed INTEGER NOT NULL,
+    wins INTEGER NOT NULL,
+    fees REAL NOT NULL,
+    cumulative_pnl REAL NOT NULL,
+    max_drawdown REAL NOT NULL,
+    exposure REAL NOT NULL,
+    open_positions INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (trading_day, model_name, asset)
+);
+INSERT INTO daily_summary_new (trading_day, model_name, day_start, day_end, realized_pnl, trades_opened, trades_closed,
+        wins, fees, cumulative_pnl, max_drawdown, exposure, open_positions, created_at)
+    SELECT trading_day, model_name, day_start, day_end, realized_pnl, trades_opened, trades_closed,
+        wins, fees, cumulative_pnl, max_drawdown, exposure, open_positions, created_at FROM daily_summary;
+DROP TABLE daily_summary;
+ALTER TABLE daily_summary_new RENAME TO daily_summary;
+
+CREATE INDEX IF NOT EXISTS idx_trades_asset_model ON trades(asset, model_name, entry_time);
@@ -0,0 +1,4 @@
+-- Expiry horizon (see src/horizon.rs) each trade's market was picked for,
+-- so P/L can be broken down by horizon. Earlier trades all came from the
+-- single default horizon.
+ALTER TABLE trades ADD COLUMN horizon TEXT NOT NULL DEFAULT 'hourly';
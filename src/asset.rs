@@ -0,0 +1,102 @@
+//! The underlying assets the engine can trade.
+//!
+//! Each asset has its own price feed, volatility estimate, active market
+//! and set of models (see `engine`), and its own list of Kalshi series.
+//! Kalshi lists the crypto above/below contracts with the same structure
+//! for every coin, so only the series tickers and the feed symbol differ.
+
+use crate::errors::{EngineError, EngineResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Asset {
+    #[default]
+    Btc,
+    Eth,
+    Sol,
+}
+
+impl Asset {
+    pub const ALL: [Asset; 3] = [Asset::Btc, Asset::Eth, Asset::Sol];
+
+    /// Lowercase name, as stored in the database and used in config keys
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Btc => "btc",
+            Self::Eth => "eth",
+            Self::Sol => "sol",
+        }
+    }
+
+    /// Ticker symbol on the crypto price feed
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Btc => "BTC",
+            Self::Eth => "ETH",
+            Self::Sol => "SOL",
+        }
+    }
+
+    /// Kalshi's hourly above/below series for the asset
+    pub fn default_series(self) -> &'static str {
+        match self {
+            Self::Btc => "KXBTCD",
+            Self::Eth => "KXETHD",
+            Self::Sol => "KXSOLD",
+        }
+    }
+}
+
+impl std::fmt::Display for Asset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Asset {
+    type Err = EngineError;
+
+    fn from_str(s: &str) -> EngineResult<Self> {
+        let s = s.trim();
+        Self::ALL
+            .into_iter()
+            .find(|a| a.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| EngineError::Config(format!("unknown asset {s:?} (expected btc, eth or sol)")))
+    }
+}
+
+/// An asset and the Kalshi series traded on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetConfig {
+    pub asset: Asset,
+    /// Series tickers, e.g. `KXBTCD`; markets from all of them compete for
+    /// the asset's active market
+    pub series: Vec<String>,
+}
+
+impl AssetConfig {
+    /// Whether `ticker` (a market or event ticker) belongs to one of the
+    /// asset's series.
+    pub fn series_of(&self, ticker: &str) -> Option<&str> {
+        let prefix = ticker.split('-').next().unwrap_or(ticker);
+        self.series.iter().map(String::as_str).find(|s| *s == prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_series_lookup() {
+        assert_eq!("ETH".parse::<Asset>().unwrap(), Asset::Eth);
+        assert!("doge".parse::<Asset>().is_err());
+
+        let btc = AssetConfig { asset: Asset::Btc, series: vec!["KXBTCD".into(), "KXBTC".into()] };
+        assert_eq!(btc.series_of("KXBTCD-26MAR02H15-T100000"), Some("KXBTCD"));
+        assert_eq!(btc.series_of("KXBTC-26MAR0217"), Some("KXBTC"));
+        // A shared prefix is not a match
+        assert_eq!(btc.series_of("KXBTCE-26MAR02H15-T100000"), None);
+        assert_eq!(btc.series_of("KXETHD-26MAR02H15-T3000"), None);
+    }
+}
@@ -0,0 +1,66 @@
+//! The engine's notion of "now".
+//!
+//! Everything that decides on time (time to expiry, trade timestamps, the
+//! trading day, which markets are still open) reads `AppState::clock`
+//! instead of the system clock, so a test can run hours of market activity
+//! in milliseconds by advancing a simulated clock step by step.
+//! Request signing and operator audit stamps stay on wall-clock time.
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default)]
+pub enum Clock {
+    #[default]
+    System,
+    /// Stands still until `advance` or `set`; clones share the same time
+    Simulated(Arc<AtomicI64>),
+}
+
+impl Clock {
+    /// A simulated clock reading `start`.
+    pub fn simulated(start: DateTime<Utc>) -> Self {
+        Self::Simulated(Arc::new(AtomicI64::new(start.timestamp_millis())))
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Self::System => Utc::now(),
+            Self::Simulated(ms) => DateTime::from_timestamp_millis(ms.load(Ordering::Acquire)).unwrap_or_default(),
+        }
+    }
+
+    /// Move a simulated clock forward; no-op on the system clock.
+    pub fn advance(&self, by: Duration) {
+        if let Self::Simulated(ms) = self {
+            ms.fetch_add(by.num_milliseconds(), Ordering::AcqRel);
+        }
+    }
+
+    /// Jump a simulated clock to `to`; no-op on the system clock.
+    pub fn set(&self, to: DateTime<Utc>) {
+        if let Self::Simulated(ms) = self {
+            ms.store(to.timestamp_millis(), Ordering::Release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulated_clock_is_shared_and_only_moves_when_told() {
+        let start = DateTime::parse_from_rfc3339("2026-10-18T14:00:00Z").unwrap().with_timezone(&Utc);
+        let clock = Clock::simulated(start);
+        let other = clock.clone();
+        assert_eq!(clock.now(), start);
+
+        other.advance(Duration::seconds(90));
+        assert_eq!(clock.now(), start + Duration::seconds(90));
+
+        clock.set(start);
+        assert_eq!(other.now(), start);
+    }
+}
@@ -1,73 +1,498 @@
+mod file;
+
+use crate::asset::{Asset, AssetConfig};
 use crate::errors::{EngineError, EngineResult};
-use std::path::PathBuf;
+use crate::horizon::{self, Horizon};
+use crate::kalshi::selector::SelectionConfig;
+use crate::models::volatility::VolParams;
+use crate::paper::simulator::StrategyParams;
+use crate::risk::liquidity::LiquidityGates;
+use crate::risk::portfolio::PortfolioLimits;
+use crate::risk::var::TailRiskConfig;
+use crate::server::auth::{self, ApiKey};
+use crate::trading_day::TradingCalendar;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
+    /// Profile selected from the config file (`paper` without a file)
+    pub profile: String,
+    pub mode: Mode,
+    /// Re-read on SIGHUP or POST /api/control/reload-config
+    pub config_file: Option<PathBuf>,
+    /// Pricing models to run, by name, in display order
+    pub models: Vec<String>,
+    /// Strategy, risk and volatility parameters; hot-reloadable
+    pub trading: TradingParams,
     pub kalshi_api_key_id: String,
     pub kalshi_private_key_path: PathBuf,
     pub kalshi_base_url: String,
     pub crypto_api_key: String,
     pub crypto_api_base_url: String,
-    pub btc_series_ticker: String,
-    pub fractional_kelly: f64,
+    /// Underlyings to trade and their Kalshi series, in display order
+    pub assets: Vec<AssetConfig>,
+    /// Expiry horizons, each with its own market per asset
+    pub horizons: Vec<Horizon>,
+    /// Market selection policy of the scanners
+    pub selection: SelectionConfig,
+    pub server_port: u16,
+    /// Hashed API keys from API_KEYS and API_KEYS_FILE (see server::auth)
+    pub api_keys: Vec<ApiKey>,
+    /// Re-read on POST /api/control/reload-keys to rotate keys without a restart
+    pub api_keys_file: Option<PathBuf>,
+    /// AUTH_DISABLED=1: read endpoints answer without a key (local dev only)
+    pub auth_disabled: bool,
+    /// Allowed CORS origins. Empty = same-origin only, ["*"] = any origin.
+    pub cors_allowed_origins: Vec<String>,
+    /// What to do with open positions on SIGTERM / ctrl-c
+    pub shutdown_position_policy: ShutdownPolicy,
+    /// Upper bound on the whole shutdown sequence (Railway kills after 30s)
+    pub shutdown_timeout_secs: u64,
+    /// When daily counters (daily P/L, the daily loss limit) reset
+    pub trading_calendar: TradingCalendar,
+    /// Decision journal rows older than this are pruned at rollover (0 = keep)
+    pub decision_retention_days: u32,
+}
+
+/// What a profile is for. Only `paper` can be run by the engine today.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    #[default]
+    Paper,
+    Live,
+    Backtest,
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Paper => write!(f, "paper"),
+            Self::Live => write!(f, "live"),
+            Self::Backtest => write!(f, "backtest"),
+        }
+    }
+}
+
+/// The parameters the engine can swap at runtime through
+/// `EngineEvent::ConfigUpdate`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TradingParams {
+    pub strategy: StrategyParams,
+    pub risk: RiskParams,
+    pub volatility: VolParams,
+    pub liquidity: LiquidityGates,
+}
+
+impl TradingParams {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        self.strategy.validate(errors);
+        self.risk.validate(errors);
+        self.volatility.validate(errors);
+        self.liquidity.validate(errors);
+    }
+
+    /// Dotted paths of every field that differs from `other`, e.g.
+    /// `risk.portfolio.max_notional`.
+    pub fn changed_fields(&self, other: &Self) -> Vec<String> {
+        fn walk(prefix: &str, a: &serde_json::Value, b: &serde_json::Value, out: &mut Vec<String>) {
+            match (a, b) {
+                (serde_json::Value::Object(a), serde_json::Value::Object(b)) => {
+                    for (key, av) in a {
+                        let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                        walk(&path, av, b.get(key).unwrap_or(&serde_json::Value::Null), out);
+                    }
+                }
+                _ if a != b => out.push(prefix.to_string()),
+                _ => {}
+            }
+        }
+        let mut out = Vec::new();
+        if let (Ok(a), Ok(b)) = (serde_json::to_value(self), serde_json::to_value(other)) {
+            walk("", &a, &b, &mut out);
+        }
+        out
+    }
+}
+
+/// Per-model limits plus the portfolio caps and tail-risk gate.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskParams {
+    /// Contracts per order, per model
     pub max_position_size: f64,
-    pub ev_threshold: f64,
+    /// A model stops entering once its daily P/L is below minus this
     pub max_daily_drawdown: f64,
-    pub server_port: u16,
+    /// Caps across all models combined (see risk::portfolio)
+    pub portfolio: PortfolioLimits,
+    /// Monte Carlo VaR/ES settings and the per-asset ES entry limit (see
+    /// risk::var)
+    pub tail: TailRiskConfig,
+}
+
+impl Default for RiskParams {
+    fn default() -> Self {
+        Self {
+            max_position_size: 50.0,
+            max_daily_drawdown: 100.0,
+            portfolio: PortfolioLimits::default(),
+            tail: TailRiskConfig::default(),
+        }
+    }
+}
+
+impl RiskParams {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        let mut check = |ok: bool, field: &str, rule: &str, value: f64| {
+            if !ok {
+                errors.push(format!("risk.{field} must be {rule}, got {value}"));
+            }
+        };
+        let p = &self.portfolio;
+        check(self.max_position_size >= 1.0, "max_position_size", ">= 1", self.max_position_size);
+        check(self.max_daily_drawdown > 0.0, "max_daily_drawdown", "> 0", self.max_daily_drawdown);
+        check(p.max_notional > 0.0, "portfolio.max_notional", "> 0", p.max_notional);
+        check(p.max_net_delta > 0.0, "portfolio.max_net_delta", "> 0", p.max_net_delta);
+        check(p.max_market_notional > 0.0, "portfolio.max_market_notional", "> 0", p.max_market_notional);
+        check(p.max_daily_loss > 0.0, "portfolio.max_daily_loss", "> 0", p.max_daily_loss);
+        check(self.tail.scenarios >= 100, "tail.scenarios", ">= 100", self.tail.scenarios as f64);
+        check(self.tail.max_asset_es99 >= 0.0, "tail.max_asset_es99", ">= 0 (0 disables the gate)", self.tail.max_asset_es99);
+    }
+}
+
+/// Result of re-reading the config file for a hot reload.
+#[derive(Debug)]
+pub struct Reloaded {
+    pub trading: TradingParams,
+    /// Sections that changed in the file but only take effect on restart
+    pub restart_required: Vec<&'static str>,
+}
+
+/// Open-position handling on shutdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownPolicy {
+    /// Close every open position at the current bid, or at its last mark
+    /// when its market is no longer active
+    Flatten,
+    /// Leave trades open in the DB; they settle when the market resolves
+    Hold,
+    /// Like `Hold`, but also save position state (legs, trailing-stop peak)
+    /// so exit management resumes after restart
+    Persist,
+}
+
+impl std::fmt::Display for ShutdownPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Flatten => write!(f, "flatten"),
+            Self::Hold => write!(f, "hold"),
+            Self::Persist => write!(f, "persist"),
+        }
+    }
+}
+
+impl std::str::FromStr for ShutdownPolicy {
+    type Err = EngineError;
+
+    fn from_str(s: &str) -> EngineResult<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "flatten" => Ok(Self::Flatten),
+            "hold" => Ok(Self::Hold),
+            "persist" => Ok(Self::Persist),
+            other => Err(EngineError::Config(format!(
+                "SHUTDOWN_POSITION_POLICY must be flatten, hold or persist, got {other:?}"
+            ))),
+        }
+    }
 }
 
 impl AppConfig {
-    pub fn from_env() -> EngineResult<Self> {
-        dotenvy::dotenv().ok();
-
-        let fractional_kelly = env_var_or("FRACTIONAL_KELLY", "0.2")
-            .parse::<f64>()
-            .map_err(|e| EngineError::Config(format!("FRACTIONAL_KELLY: {e}")))?;
-
-        let max_position_size = env_var_or("MAX_POSITION_SIZE", "50")
-            .parse::<f64>()
-            .map_err(|e| EngineError::Config(format!("MAX_POSITION_SIZE: {e}")))?;
-
-        let ev_threshold = env_var_or("EV_THRESHOLD", "0.02")
-            .parse::<f64>()
-            .map_err(|e| EngineError::Config(format!("EV_THRESHOLD: {e}")))?;
-
-        let max_daily_drawdown = env_var_or("MAX_DAILY_DRAWDOWN", "100.0")
-            .parse::<f64>()
-            .map_err(|e| EngineError::Config(format!("MAX_DAILY_DRAWDOWN: {e}")))?;
-
-        // Railway injects PORT; fall back to SERVER_PORT, then 3001
-        let port_str = std::env::var("PORT")
-            .or_else(|_| std::env::var("SERVER_PORT"))
-            .unwrap_or_else(|_| "3001".to_string());
-        let server_port = port_str
+    /// Load `requested` (default: the file's `default_profile`, else
+    /// `paper`) from `config_file` (default: `config.toml` if present), then
+    /// apply env var overrides. Secrets only come from the environment and
+    /// are checked by `require_credentials`, since not every command needs them.
+    pub fn load(config_file: Option<PathBuf>, requested: Option<String>) -> EngineResult<Self> {
+        let config_file = config_file
+            .filter(|p| !p.as_os_str().is_empty())
+            .or_else(|| Some(PathBuf::from(file::DEFAULT_PATH)).filter(|p| p.exists()));
+        let requested = requested.filter(|p| !p.is_empty());
+        let (profile, mut file) = match &config_file {
+            Some(path) => file::load(path, requested.as_deref())?,
+            None => match requested.as_deref() {
+                None | Some(file::BUILTIN_PROFILE) => (file::BUILTIN_PROFILE.to_string(), file::Profile::default()),
+                Some(other) => {
+                    return Err(EngineError::Config(format!(
+                        "profile {other:?} needs a config file (pass --config or add {})",
+                        file::DEFAULT_PATH
+                    )))
+                }
+            },
+        };
+
+        let mut trading = file.trading();
+        apply_trading_env(&mut trading)?;
+
+        apply_profile_env(&mut file)?;
+
+        let api_keys_file = std::env::var("API_KEYS_FILE").ok().filter(|p| !p.is_empty()).map(PathBuf::from);
+        let api_keys = load_api_keys(api_keys_file.as_deref())?;
+        let auth_disabled = matches!(env_var_or("AUTH_DISABLED", "").trim(), "1" | "true");
+
+        let shutdown_position_policy = env_var_or("SHUTDOWN_POSITION_POLICY", "persist").parse()?;
+
+        let shutdown_timeout_secs = env_var_or("SHUTDOWN_TIMEOUT_SECS", "20")
+            .parse::<u64>()
+            .map_err(|e| EngineError::Config(format!("SHUTDOWN_TIMEOUT_SECS: {e}")))?;
+
+        let trading_calendar = TradingCalendar::parse(
+            &env_var_or("TRADING_DAY_TZ", "UTC"),
+            &env_var_or("TRADING_DAY_ROLLOVER", "00:00"),
+        )?;
+
+        let decision_retention_days = env_var_or("DECISION_RETENTION_DAYS", "7")
+            .parse::<u32>()
+            .map_err(|e| EngineError::Config(format!("DECISION_RETENTION_DAYS: {e}")))?;
+
+        let assets = file.feeds.asset_configs();
+        let config = Self {
+            profile,
+            mode: file.mode,
+            config_file,
+            models: file.models.enabled,
+            trading,
+            kalshi_api_key_id: env_var_or("KALSHI_API_KEY_ID", ""),
+            kalshi_private_key_path: PathBuf::from(env_var_or("KALSHI_PRIVATE_KEY_PATH", "")),
+            kalshi_base_url: file.feeds.kalshi_base_url,
+            crypto_api_key: env_var_or("CRYPTO_API_KEY", ""),
+            crypto_api_base_url: file.feeds.crypto_api_base_url,
+            assets,
+            horizons: file.horizons,
+            selection: file.selection,
+            server_port: file.server.port,
+            api_keys,
+            api_keys_file,
+            auth_disabled,
+            cors_allowed_origins: file.server.cors_allowed_origins,
+            shutdown_position_policy,
+            shutdown_timeout_secs,
+            trading_calendar,
+            decision_retention_days,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Every problem at once, so a bad file is fixed in one edit.
+    pub fn validate(&self) -> EngineResult<()> {
+        let mut errors = Vec::new();
+        self.trading.validate(&mut errors);
+        file::validate_models(&self.models, &mut errors);
+        for (field, url) in [("feeds.kalshi_base_url", &self.kalshi_base_url), ("feeds.crypto_api_base_url", &self.crypto_api_base_url)] {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                errors.push(format!("{field} must be an http(s) URL, got {url:?}"));
+            }
+        }
+        file::validate_assets(&self.assets, &mut errors);
+        horizon::validate(&self.horizons, &mut errors);
+        self.selection.validate(&mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(EngineError::Config(format!("profile {:?}: {}", self.profile, errors.join("; "))))
+        }
+    }
+
+    pub fn asset(&self, asset: Asset) -> Option<&AssetConfig> {
+        self.assets.iter().find(|a| a.asset == asset)
+    }
+
+    /// The enabled asset whose series `ticker` (a market or event ticker)
+    /// belongs to.
+    pub fn asset_of(&self, ticker: &str) -> Option<Asset> {
+        self.assets.iter().find(|a| a.series_of(ticker).is_some()).map(|a| a.asset)
+    }
+
+    /// The secrets a command needs: Kalshi credentials always, the crypto
+    /// feed key only for commands that stream prices.
+    pub fn require_credentials(&self, crypto_feed: bool) -> EngineResult<()> {
+        let mut missing = Vec::new();
+        if self.kalshi_api_key_id.is_empty() {
+            missing.push("KALSHI_API_KEY_ID");
+        }
+        if self.kalshi_private_key_path.as_os_str().is_empty() {
+            missing.push("KALSHI_PRIVATE_KEY_PATH");
+        }
+        if crypto_feed && self.crypto_api_key.is_empty() {
+            missing.push("CRYPTO_API_KEY");
+        }
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(EngineError::Config(format!("missing env var: {}", missing.join(", "))))
+        }
+    }
+
+    /// Re-read the active profile for a hot reload. Only trading params are
+    /// swapped; other sections that changed are reported, not applied.
+    pub fn reload(&self) -> EngineResult<Reloaded> {
+        let Some(path) = &self.config_file else {
+            return Err(EngineError::Config("no config file to reload (pass --config or set CONFIG_FILE)".into()));
+        };
+        let (_, mut file) = file::load(path, Some(&self.profile))?;
+        apply_profile_env(&mut file)?;
+
+        let mut trading = file.trading();
+        apply_trading_env(&mut trading)?;
+        let mut errors = Vec::new();
+        trading.validate(&mut errors);
+        if !errors.is_empty() {
+            return Err(EngineError::Config(format!("profile {:?}: {}", self.profile, errors.join("; "))));
+        }
+
+        let mut restart_required = Vec::new();
+        if file.mode != self.mode {
+            restart_required.push("mode");
+        }
+        if file.models.enabled != self.models {
+            restart_required.push("models");
+        }
+        let feeds = &file.feeds;
+        if feeds.kalshi_base_url != self.kalshi_base_url
+            || feeds.crypto_api_base_url != self.crypto_api_base_url
+            || feeds.asset_configs() != self.assets
+        {
+            restart_required.push("feeds");
+        }
+        if file.horizons != self.horizons {
+            restart_required.push("horizons");
+        }
+        if file.selection != self.selection {
+            restart_required.push("selection");
+        }
+        // The gates reload, but only within what the scanners fetch for them
+        let (gates, held) = trading.liquidity.fetched_by(&self.trading.liquidity);
+        trading.liquidity = gates;
+        if held {
+            restart_required.push("liquidity");
+        }
+        if file.server.port != self.server_port || file.server.cors_allowed_origins != self.cors_allowed_origins {
+            restart_required.push("server");
+        }
+        Ok(Reloaded { trading, restart_required })
+    }
+}
+
+/// Env overrides for the restart-only sections (feeds, server).
+fn apply_profile_env(file: &mut file::Profile) -> EngineResult<()> {
+    if let Ok(url) = std::env::var("KALSHI_BASE_URL") {
+        file.feeds.kalshi_base_url = url;
+    }
+    if let Ok(url) = std::env::var("CRYPTO_API_BASE_URL") {
+        file.feeds.crypto_api_base_url = url;
+    }
+    if let Some(assets) = std::env::var("ASSETS").ok().filter(|a| !a.is_empty()) {
+        file.feeds.assets = assets.split(',').map(str::parse).collect::<EngineResult<_>>()?;
+    }
+    // BTC_SERIES_TICKER, ETH_SERIES_TICKER, ...: comma-separated
+    for asset in Asset::ALL {
+        if let Ok(series) = std::env::var(format!("{}_SERIES_TICKER", asset.symbol())) {
+            file.feeds.series.insert(asset, series.split(',').map(|s| s.trim().to_string()).collect());
+        }
+    }
+
+    // Railway injects PORT; fall back to SERVER_PORT, then the profile
+    if let Ok(port_str) = std::env::var("PORT").or_else(|_| std::env::var("SERVER_PORT")) {
+        file.server.port = port_str
             .parse::<u16>()
             .map_err(|e| EngineError::Config(format!("PORT/SERVER_PORT: {e}")))?;
+    }
+
+    if let Some(origins) = std::env::var("CORS_ALLOWED_ORIGINS").ok().filter(|o| !o.is_empty()) {
+        file.server.cors_allowed_origins = origins
+            .split(',')
+            .map(|o| o.trim().to_string())
+            .filter(|o| !o.is_empty())
+            .collect();
+    }
+    Ok(())
+}
 
-        Ok(Self {
-            kalshi_api_key_id: env_var("KALSHI_API_KEY_ID")?,
-            kalshi_private_key_path: PathBuf::from(env_var("KALSHI_PRIVATE_KEY_PATH")?),
-            kalshi_base_url: env_var_or(
-                "KALSHI_BASE_URL",
-                "https://api.elections.kalshi.com/trade-api/v2",
-            ),
-            crypto_api_key: env_var("CRYPTO_API_KEY")?,
-            crypto_api_base_url: env_var_or(
-                "CRYPTO_API_BASE_URL",
-                "https://api.freecryptoapi.com/v1",
-            ),
-            btc_series_ticker: env_var_or("BTC_SERIES_TICKER", "KXBTCD"),
-            fractional_kelly,
-            max_position_size,
-            ev_threshold,
-            max_daily_drawdown,
-            server_port,
-        })
-    }
-}
-
-fn env_var(key: &str) -> EngineResult<String> {
-    std::env::var(key).map_err(|_| EngineError::Config(format!("missing env var: {key}")))
+/// Env vars that predate the config file still win over it, so existing
+/// deployments keep their settings. They apply again on every reload.
+fn apply_trading_env(trading: &mut TradingParams) -> EngineResult<()> {
+    let strategy = &mut trading.strategy;
+    let risk = &mut trading.risk;
+    override_env("FRACTIONAL_KELLY", &mut strategy.fractional_kelly)?;
+    override_env("EV_THRESHOLD", &mut strategy.ev_threshold)?;
+    override_env("MAX_POSITION_SIZE", &mut risk.max_position_size)?;
+    override_env("MAX_DAILY_DRAWDOWN", &mut risk.max_daily_drawdown)?;
+    override_env("PORTFOLIO_MAX_NOTIONAL", &mut risk.portfolio.max_notional)?;
+    override_env("PORTFOLIO_MAX_NET_DELTA", &mut risk.portfolio.max_net_delta)?;
+    override_env("PORTFOLIO_MAX_MARKET_NOTIONAL", &mut risk.portfolio.max_market_notional)?;
+    override_env("PORTFOLIO_MAX_DAILY_LOSS", &mut risk.portfolio.max_daily_loss)?;
+    override_env("RISK_VAR_SCENARIOS", &mut risk.tail.scenarios)?;
+    override_env("RISK_MAX_ES", &mut risk.tail.max_asset_es99)?;
+    Ok(())
+}
+
+#[cfg(test)]
+impl AppConfig {
+    /// Defaults for tests that need an `AppState`; never reads the environment.
+    pub fn for_tests() -> Self {
+        Self {
+            profile: "test".into(),
+            mode: Mode::Paper,
+            config_file: None,
+            models: crate::models::MODEL_NAMES.iter().map(|m| m.to_string()).collect(),
+            trading: TradingParams::default(),
+            kalshi_api_key_id: "test".into(),
+            kalshi_private_key_path: PathBuf::from("/dev/null"),
+            kalshi_base_url: "http://127.0.0.1:1".into(),
+            crypto_api_key: "test".into(),
+            crypto_api_base_url: "http://127.0.0.1:1".into(),
+            assets: vec![AssetConfig { asset: Asset::Btc, series: vec!["KXBTCD".into()] }],
+            horizons: vec![Horizon::default()],
+            selection: SelectionConfig::default(),
+            server_port: 0,
+            api_keys: Vec::new(),
+            api_keys_file: None,
+            auth_disabled: false,
+            cors_allowed_origins: Vec::new(),
+            shutdown_position_policy: ShutdownPolicy::Persist,
+            shutdown_timeout_secs: 1,
+            trading_calendar: TradingCalendar::default(),
+            decision_retention_days: 7,
+        }
+    }
+}
+
+/// Load API keys from the API_KEYS env var plus the optional keys file.
+pub fn load_api_keys(file: Option<&Path>) -> EngineResult<Vec<ApiKey>> {
+    let mut keys = auth::parse_api_keys(&env_var_or("API_KEYS", ""))
+        .map_err(|e| EngineError::Config(format!("API_KEYS: {e}")))?;
+
+    if let Some(path) = file {
+        let src = std::fs::read_to_string(path)
+            .map_err(|e| EngineError::Config(format!("API_KEYS_FILE {}: {e}", path.display())))?;
+        keys.extend(
+            auth::parse_api_keys(&src)
+                .map_err(|e| EngineError::Config(format!("API_KEYS_FILE {}: {e}", path.display())))?,
+        );
+    }
+
+    Ok(keys)
+}
+
+/// Parse `key` into `value` if it is set and non-empty.
+fn override_env<T>(key: &str, value: &mut T) -> EngineResult<()>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    if let Some(raw) = std::env::var(key).ok().filter(|v| !v.trim().is_empty()) {
+        *value = raw.trim().parse().map_err(|e| EngineError::Config(format!("{key}: {e}")))?;
+    }
+    Ok(())
 }
 
 fn env_var_or(key: &str, default: &str) -> String {
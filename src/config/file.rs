@@ -0,0 +1,415 @@
+//! The TOML config file: named profiles under `[profiles.<name>]`.
+//!
+//! A profile may set `extends = "<other>"` to start from another profile and
+//! override only what differs; tables merge key by key, everything else is
+//! replaced. Every section is optional and falls back to the built-in
+//! defaults, but unknown keys are errors so a typo never silently reverts a
+//! limit to its default.
+
+use super::{Mode, RiskParams, TradingParams};
+use crate::asset::{Asset, AssetConfig};
+use crate::errors::{EngineError, EngineResult};
+use crate::horizon::Horizon;
+use crate::kalshi::selector::SelectionConfig;
+use crate::risk::liquidity::LiquidityGates;
+use crate::models::volatility::VolParams;
+use crate::models::MODEL_NAMES;
+use crate::paper::simulator::StrategyParams;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Loaded when CONFIG_FILE is unset and this file exists
+pub const DEFAULT_PATH: &str = "config.toml";
+
+/// The profile used when neither PROFILE nor `default_profile` names one
+pub const BUILTIN_PROFILE: &str = "paper";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Root {
+    default_profile: Option<String>,
+    #[serde(default)]
+    profiles: toml::Table,
+}
+
+/// One fully resolved profile.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub mode: Mode,
+    pub models: ModelsSection,
+    pub strategy: StrategyParams,
+    pub risk: RiskParams,
+    pub volatility: VolParams,
+    /// Market-quality gates before entries (see `risk::liquidity`)
+    pub liquidity: LiquidityGates,
+    /// Expiry horizons traded side by side (see `horizon`)
+    pub horizons: Vec<Horizon>,
+    /// How the scanner picks each horizon's market (see `kalshi::selector`)
+    pub selection: SelectionConfig,
+    pub feeds: FeedsSection,
+    pub server: ServerSection,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            mode: Mode::default(),
+            models: ModelsSection::default(),
+            strategy: StrategyParams::default(),
+            risk: RiskParams::default(),
+            volatility: VolParams::default(),
+            liquidity: LiquidityGates::default(),
+            horizons: vec![Horizon::default()],
+            selection: SelectionConfig::default(),
+            feeds: FeedsSection::default(),
+            server: ServerSection::default(),
+        }
+    }
+}
+
+impl Profile {
+    pub fn trading(&self) -> TradingParams {
+        TradingParams {
+            strategy: self.strategy.clone(),
+            risk: self.risk,
+            volatility: self.volatility,
+            liquidity: self.liquidity,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelsSection {
+    /// Pricing models to run, by name
+    pub enabled: Vec<String>,
+}
+
+impl Default for ModelsSection {
+    fn default() -> Self {
+        Self { enabled: MODEL_NAMES.iter().map(|m| m.to_string()).collect() }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeedsSection {
+    pub kalshi_base_url: String,
+    pub crypto_api_base_url: String,
+    /// Underlyings to trade, each with its own feed, markets and models
+    pub assets: Vec<Asset>,
+    /// Kalshi series per asset; an asset left out trades its default series
+    pub series: BTreeMap<Asset, Vec<String>>,
+    /// Older spelling of `series.btc = ["..."]`
+    pub btc_series_ticker: Option<String>,
+}
+
+impl Default for FeedsSection {
+    fn default() -> Self {
+        Self {
+            kalshi_base_url: "https://api.elections.kalshi.com/trade-api/v2".into(),
+            crypto_api_base_url: "https://api.freecryptoapi.com/v1".into(),
+            assets: vec![Asset::Btc],
+            series: BTreeMap::new(),
+            btc_series_ticker: None,
+        }
+    }
+}
+
+impl FeedsSection {
+    /// The enabled assets with their series, in the order listed.
+    pub fn asset_configs(&self) -> Vec<AssetConfig> {
+        self.assets
+            .iter()
+            .map(|&asset| {
+                let legacy = self.btc_series_ticker.clone().filter(|_| asset == Asset::Btc).map(|s| vec![s]);
+                let series = self
+                    .series
+                    .get(&asset)
+                    .cloned()
+                    .or(legacy)
+                    .unwrap_or_else(|| vec![asset.default_series().to_string()]);
+                AssetConfig { asset, series }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSection {
+    pub port: u16,
+    pub cors_allowed_origins: Vec<String>,
+}
+
+impl Default for ServerSection {
+    fn default() -> Self {
+        Self { port: 3001, cors_allowed_origins: Vec::new() }
+    }
+}
+
+/// Read `path` and resolve `requested` (or the file's default profile).
+pub fn load(path: &Path, requested: Option<&str>) -> EngineResult<(String, Profile)> {
+    let src = std::fs::read_to_string(path)
+        .map_err(|e| EngineError::Config(format!("config file {}: {e}", path.display())))?;
+    parse(&src, requested).map_err(|e| match e {
+        EngineError::Config(msg) => EngineError::Config(format!("{}: {msg}", path.display())),
+        other => other,
+    })
+}
+
+pub fn parse(src: &str, requested: Option<&str>) -> EngineResult<(String, Profile)> {
+    let root: Root = toml::from_str(src).map_err(|e| EngineError::Config(e.message().to_string()))?;
+    let name = requested
+        .map(str::to_string)
+        .or(root.default_profile)
+        .unwrap_or_else(|| BUILTIN_PROFILE.to_string());
+
+    let table = resolve(&root.profiles, &name, &mut Vec::new())?;
+    let profile = toml::Value::Table(table)
+        .try_into::<Profile>()
+        .map_err(|e| EngineError::Config(format!("profile {name:?}: {}", e.message())))?;
+    Ok((name, profile))
+}
+
+/// Flatten the `extends` chain of `name` into one table.
+fn resolve(profiles: &toml::Table, name: &str, chain: &mut Vec<String>) -> EngineResult<toml::Table> {
+    if chain.iter().any(|n| n == name) {
+        chain.push(name.to_string());
+        return Err(EngineError::Config(format!("profile cycle: {}", chain.join(" -> "))));
+    }
+    chain.push(name.to_string());
+
+    let mut table = match profiles.get(name) {
+        Some(toml::Value::Table(t)) => t.clone(),
+        Some(_) => return Err(EngineError::Config(format!("profiles.{name} must be a table"))),
+        None => {
+            let known: Vec<&str> = profiles.keys().map(String::as_str).collect();
+            return Err(EngineError::Config(format!(
+                "no profile {name:?} (available: {})",
+                if known.is_empty() { "none".to_string() } else { known.join(", ") }
+            )));
+        }
+    };
+
+    match table.remove("extends") {
+        None => Ok(table),
+        Some(toml::Value::String(parent)) => {
+            let mut base = resolve(profiles, &parent, chain)?;
+            merge(&mut base, table);
+            Ok(base)
+        }
+        Some(_) => Err(EngineError::Config(format!("profiles.{name}.extends must be a profile name"))),
+    }
+}
+
+/// Overlay `over` onto `base`, recursing into tables present in both.
+fn merge(base: &mut toml::Table, over: toml::Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(b)), toml::Value::Table(o)) => merge(b, o),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+pub fn validate_assets(assets: &[AssetConfig], errors: &mut Vec<String>) {
+    if assets.is_empty() {
+        errors.push("feeds.assets must name at least one asset".to_string());
+    }
+    for (i, a) in assets.iter().enumerate() {
+        if assets[..i].iter().any(|b| b.asset == a.asset) {
+            errors.push(format!("feeds.assets: {} listed twice", a.asset));
+        }
+        if a.series.is_empty() || a.series.iter().any(|s| s.trim().is_empty()) {
+            errors.push(format!("feeds.series.{} must list non-empty series tickers", a.asset));
+        }
+        for s in &a.series {
+            if let Some(other) = assets[..i].iter().find(|b| b.series.contains(s)) {
+                errors.push(format!("feeds.series: {s:?} is listed for both {} and {}", other.asset, a.asset));
+            }
+        }
+    }
+}
+
+pub fn validate_models(models: &[String], errors: &mut Vec<String>) {
+    if models.is_empty() {
+        errors.push("models.enabled must name at least one model".to_string());
+    }
+    for (i, name) in models.iter().enumerate() {
+        if !MODEL_NAMES.contains(&name.as_str()) {
+            errors.push(format!("models.enabled: unknown model {name:?} (expected one of {})", MODEL_NAMES.join(", ")));
+        } else if models[..i].contains(name) {
+            errors.push(format!("models.enabled: {name:?} listed twice"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = r#"
+        default_profile = "paper"
+
+        [profiles.paper]
+        models.enabled = ["Black-Scholes", "Student-t"]
+
+        [profiles.paper.strategy]
+        ev_threshold = 0.03
+
+        [profiles.paper.risk]
+        max_position_size = 40
+
+        [profiles.paper.risk.portfolio]
+        max_notional = 120
+
+        [profiles.small]
+        extends = "paper"
+        mode = "live"
+
+        [profiles.small.risk]
+        max_position_size = 5
+
+        [profiles.small.risk.portfolio]
+        max_daily_loss = 25
+    "#;
+
+    #[test]
+    fn test_extends_merges_nested_tables() {
+        let (name, p) = parse(SRC, Some("small")).unwrap();
+        assert_eq!(name, "small");
+        assert_eq!(p.mode, Mode::Live);
+        assert_eq!(p.models.enabled, vec!["Black-Scholes", "Student-t"]);
+        assert_eq!(p.strategy.ev_threshold, 0.03);
+        assert_eq!(p.risk.max_position_size, 5.0);
+        // Sibling keys in a nested table survive the override
+        assert_eq!(p.risk.portfolio.max_notional, 120.0);
+        assert_eq!(p.risk.portfolio.max_daily_loss, 25.0);
+        // Untouched sections keep the built-in defaults
+        assert_eq!(p.volatility, VolParams::default());
+        assert_eq!(p.risk.tail, crate::risk::var::TailRiskConfig::default());
+    }
+
+    #[test]
+    fn test_default_profile_and_missing_profile() {
+        let (name, p) = parse(SRC, None).unwrap();
+        assert_eq!(name, "paper");
+        assert_eq!(p.mode, Mode::Paper);
+
+        let err = parse(SRC, Some("prod")).unwrap_err().to_string();
+        assert!(err.contains("no profile \"prod\"") && err.contains("paper, small"), "{err}");
+    }
+
+    #[test]
+    fn test_unknown_keys_and_cycles_are_errors() {
+        let typo = "[profiles.paper.risk]\nmax_positon_size = 5\n";
+        let err = parse(typo, None).unwrap_err().to_string();
+        assert!(err.contains("max_positon_size"), "{err}");
+
+        let cycle = "[profiles.a]\nextends = \"b\"\n[profiles.b]\nextends = \"a\"\n";
+        let err = parse(cycle, Some("a")).unwrap_err().to_string();
+        assert!(err.contains("a -> b -> a"), "{err}");
+    }
+
+    #[test]
+    fn test_validation_collects_every_error() {
+        let src = r#"
+            [profiles.paper]
+            models.enabled = ["Black-Scholes", "Heston"]
+            strategy.fill_probability = 1.5
+            risk.max_daily_drawdown = 0
+        "#;
+        let (_, p) = parse(src, None).unwrap();
+        let mut errors = Vec::new();
+        p.trading().validate(&mut errors);
+        validate_models(&p.models.enabled, &mut errors);
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(errors.iter().any(|e| e.contains("strategy.fill_probability")));
+        assert!(errors.iter().any(|e| e.contains("risk.max_daily_drawdown")));
+        assert!(errors.iter().any(|e| e.contains("\"Heston\"")));
+    }
+
+    #[test]
+    fn test_asset_series_default_and_override() {
+        let src = r#"
+            [profiles.paper.feeds]
+            assets = ["btc", "eth"]
+            series.btc = ["KXBTCD", "KXBTC"]
+        "#;
+        let (_, p) = parse(src, None).unwrap();
+        let assets = p.feeds.asset_configs();
+        assert_eq!(assets.len(), 2);
+        assert_eq!(assets[0].series, vec!["KXBTCD", "KXBTC"]);
+        assert_eq!((assets[1].asset, assets[1].series.as_slice()), (Asset::Eth, &["KXETHD".to_string()][..]));
+
+        // The old single-series key still works for BTC
+        let (_, p) = parse("[profiles.paper.feeds]\nbtc_series_ticker = \"KXBTC\"\n", None).unwrap();
+        assert_eq!(p.feeds.asset_configs()[0].series, vec!["KXBTC"]);
+
+        let err = parse("[profiles.paper.feeds]\nassets = [\"doge\"]\n", None).unwrap_err().to_string();
+        assert!(err.contains("doge"), "{err}");
+
+        let mut errors = Vec::new();
+        let shared = vec!["KXBTCD".to_string()];
+        validate_assets(
+            &[AssetConfig { asset: Asset::Btc, series: shared.clone() }, AssetConfig { asset: Asset::Eth, series: shared }],
+            &mut errors,
+        );
+        assert_eq!(errors.len(), 1, "{errors:?}");
+    }
+
+    #[test]
+    fn test_horizons_replace_the_default() {
+        let src = r#"
+            [[profiles.paper.horizons]]
+            name = "hourly"
+            max_ttl_secs = 3600.0
+
+            [[profiles.paper.horizons]]
+            name = "daily"
+            min_ttl_secs = 3600.0
+            ewma_lambda = 0.99
+        "#;
+        let (_, p) = parse(src, None).unwrap();
+        let names: Vec<&str> = p.horizons.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, ["hourly", "daily"]);
+        assert_eq!(p.horizons[1].ewma_lambda, Some(0.99));
+        assert_eq!(Profile::default().horizons, vec![Horizon::default()]);
+
+        let err = parse("[[profiles.paper.horizons]]\nname = \"x\"\nmax_ttl = 60\n", None).unwrap_err().to_string();
+        assert!(err.contains("max_ttl"), "{err}");
+    }
+
+    #[test]
+    fn test_changed_fields_names_dotted_paths() {
+        let (_, paper) = parse(SRC, Some("paper")).unwrap();
+        let (_, small) = parse(SRC, Some("small")).unwrap();
+        assert!(paper.trading().changed_fields(&paper.trading()).is_empty());
+        assert_eq!(
+            paper.trading().changed_fields(&small.trading()),
+            vec!["risk.max_position_size", "risk.portfolio.max_daily_loss"]
+        );
+    }
+
+    #[test]
+    fn test_shipped_config_profiles_validate() {
+        let src = include_str!("../../config.toml");
+        for name in ["paper", "live-small", "backtest"] {
+            let (_, p) = parse(src, Some(name)).unwrap();
+            let mut errors = Vec::new();
+            p.trading().validate(&mut errors);
+            validate_models(&p.models.enabled, &mut errors);
+            assert!(errors.is_empty(), "{name}: {errors:?}");
+        }
+        // The paper profile spells out the built-in defaults
+        let (_, paper) = parse(src, None).unwrap();
+        assert_eq!(paper.trading(), TradingParams::default());
+        assert_eq!(paper.horizons, vec![Horizon::default()]);
+        assert_eq!(paper.selection, SelectionConfig::default());
+    }
+}
@@ -0,0 +1,293 @@
+//! Decision journal: every model's decision on every tick, with the inputs
+//! that drove it and a reason code when it did not trade.
+//!
+//! The engine buffers records and sends them as one `InsertDecisions`
+//! command per batch, so the journal costs one transaction every few
+//! seconds. Rows older than `DECISION_RETENTION_DAYS` are pruned at the
+//! trading-day rollover.
+//!
+//! `/api/decisions?model=Student-t&at=2026-10-18T14:03:00Z` answers "why
+//! didn't Student-t trade at 14:03": the rows within `window` seconds of
+//! that instant plus a count of each reason code.
+
+use crate::asset::Asset;
+use crate::errors::{EngineError, EngineResult};
+use crate::state::{Decision, DecisionRecord};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+
+pub(super) const INSERT_DECISION: &str = "INSERT INTO decisions (timestamp, model_name, market_ticker, btc_price, ttl_seconds,
+         raw_probability, probability, ev_yes, ev_no, kelly_contracts, risk_verdict, risk_reason,
+         action, reason, side, contracts, exit_reason, asset)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)";
+
+/// Default half-width of the `at` window, seconds
+const DEFAULT_WINDOW_SECS: i64 = 60;
+
+pub(super) fn insert(stmt: &mut rusqlite::CachedStatement<'_>, r: &DecisionRecord) -> rusqlite::Result<usize> {
+    let (action, reason, side, contracts) = match &r.decision {
+        Decision::PlacePaperTrade(order) => (order.action, "placed", Some(order.side), Some(order.contracts)),
+        Decision::NoAction { reason } => ("none", *reason, None, None),
+    };
+    let risk_reason = r.risk.map(|v| v.reason).filter(|reason| !reason.is_empty());
+    stmt.execute(rusqlite::params![
+        r.timestamp,
+        r.model_name,
+        r.market_ticker,
+        r.btc_price,
+        r.ttl_seconds,
+        r.raw_probability,
+        r.probability,
+        r.ev_yes,
+        r.ev_no,
+        r.kelly_contracts,
+        r.risk.map(|v| v.outcome),
+        risk_reason,
+        action,
+        reason,
+        side,
+        contracts,
+        r.exit_reason,
+        r.asset,
+    ])
+}
+
+/// Filters for `/api/decisions`. Set fields are ANDed.
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+pub struct DecisionFilter {
+    pub model: Option<String>,
+    pub asset: Option<Asset>,
+    pub market: Option<String>,
+    /// `buy`, `scale_in` or `none`
+    pub action: Option<String>,
+    /// Reason code, e.g. `no_edge` or `risk_blocked`
+    pub reason: Option<String>,
+    /// Centre of a time window (RFC 3339); see `window`
+    pub at: Option<String>,
+    /// Half-width of the `at` window in seconds (default 60)
+    pub window: Option<i64>,
+    /// Lower bound, inclusive (RFC 3339 or `YYYY-MM-DD`)
+    pub since: Option<String>,
+    /// Upper bound, exclusive
+    pub until: Option<String>,
+}
+
+impl DecisionFilter {
+    /// WHERE clause (without the keyword) and its parameters.
+    fn to_sql(&self) -> EngineResult<(String, Vec<Box<dyn ToSql>>)> {
+        let mut clauses: Vec<&str> = Vec::new();
+        let mut params: Vec<Box<dyn ToSql>> = Vec::new();
+
+        if let Some(model) = &self.model {
+            clauses.push("model_name = ?");
+            params.push(Box::new(model.clone()));
+        }
+        if let Some(asset) = self.asset {
+            clauses.push("asset = ?");
+            params.push(Box::new(asset));
+        }
+        if let Some(market) = &self.market {
+            clauses.push("market_ticker = ?");
+            params.push(Box::new(market.clone()));
+        }
+        if let Some(action) = &self.action {
+            if !matches!(action.as_str(), "buy" | "scale_in" | "none") {
+                return Err(EngineError::Parse(format!("action must be buy, scale_in or none, got {action:?}")));
+            }
+            clauses.push("action = ?");
+            params.push(Box::new(action.clone()));
+        }
+        if let Some(reason) = &self.reason {
+            clauses.push("reason = ?");
+            params.push(Box::new(reason.clone()));
+        }
+        if let Some(at) = &self.at {
+            let at = parse_instant(at)?;
+            let window = Duration::seconds(self.window.unwrap_or(DEFAULT_WINDOW_SECS).clamp(1, 86_400));
+            clauses.push("timestamp >= ? AND timestamp <= ?");
+            params.push(Box::new((at - window).to_rfc3339()));
+            params.push(Box::new((at + window).to_rfc3339()));
+        }
+        if let Some(since) = &self.since {
+            clauses.push("timestamp >= ?");
+            params.push(Box::new(normalize_bound(since)?));
+        }
+        if let Some(until) = &self.until {
+            clauses.push("timestamp < ?");
+            params.push(Box::new(normalize_bound(until)?));
+        }
+
+        let sql = if clauses.is_empty() { "1".to_string() } else { clauses.join(" AND ") };
+        Ok((sql, params))
+    }
+}
+
+fn parse_instant(s: &str) -> EngineResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s.trim())
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| EngineError::Parse(format!("expected an RFC 3339 time, got {s:?}: {e}")))
+}
+
+/// Journal and trade timestamps are written by `to_rfc3339()` (`+00:00`
+/// suffix), so bounds are reformatted the same way to compare correctly as
+/// strings.
+pub(super) fn normalize_bound(s: &str) -> EngineResult<String> {
+    if NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").is_ok() {
+        return Ok(s.trim().to_string());
+    }
+    parse_instant(s).map(|t| t.to_rfc3339())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DecisionRow {
+    pub timestamp: String,
+    pub model_name: String,
+    pub asset: Asset,
+    pub market_ticker: String,
+    pub btc_price: f64,
+    pub ttl_seconds: f64,
+    pub raw_probability: f64,
+    pub probability: f64,
+    pub ev_yes: f64,
+    pub ev_no: f64,
+    pub kelly_contracts: f64,
+    pub risk_verdict: Option<String>,
+    pub risk_reason: Option<String>,
+    pub action: String,
+    pub reason: String,
+    pub side: Option<String>,
+    pub contracts: Option<f64>,
+    pub exit_reason: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DecisionPage {
+    /// Newest first, at most `limit`
+    pub decisions: Vec<DecisionRow>,
+    /// Count of each reason code over every matching row, most common first
+    pub reasons: Vec<(String, i64)>,
+}
+
+pub fn query_decisions(conn: &Connection, filter: &DecisionFilter, limit: usize) -> EngineResult<DecisionPage> {
+    let (where_sql, mut params) = filter.to_sql()?;
+
+    let reasons = {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT reason, COUNT(*) FROM decisions WHERE {where_sql} GROUP BY reason ORDER BY COUNT(*) DESC, reason"
+        ))?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+
+    params.push(Box::new(limit as i64));
+    let mut stmt = conn.prepare(&format!(
+        "SELECT timestamp, model_name, market_ticker, btc_price, ttl_seconds, raw_probability, probability,
+                ev_yes, ev_no, kelly_contracts, risk_verdict, risk_reason, action, reason, side, contracts, exit_reason, asset
+         FROM decisions WHERE {where_sql} ORDER BY timestamp DESC, id DESC LIMIT ?"
+    ))?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+        Ok(DecisionRow {
+            timestamp: row.get(0)?,
+            model_name: row.get(1)?,
+            market_ticker: row.get(2)?,
+            btc_price: row.get(3)?,
+            ttl_seconds: row.get(4)?,
+            raw_probability: row.get(5)?,
+            probability: row.get(6)?,
+            ev_yes: row.get(7)?,
+            ev_no: row.get(8)?,
+            kelly_contracts: row.get(9)?,
+            risk_verdict: row.get(10)?,
+            risk_reason: row.get(11)?,
+            action: row.get(12)?,
+            reason: row.get(13)?,
+            side: row.get(14)?,
+            contracts: row.get(15)?,
+            exit_reason: row.get(16)?,
+            asset: row.get(17)?,
+        })
+    })?;
+    let decisions = rows.filter_map(|r| r.ok()).collect();
+
+    Ok(DecisionPage { decisions, reasons })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{PaperOrder, RiskVerdict};
+
+    fn record(timestamp: &str, model: &'static str, decision: Decision, risk: Option<RiskVerdict>) -> DecisionRecord {
+        DecisionRecord {
+            timestamp: timestamp.to_string(),
+            model_name: model,
+            asset: Asset::Btc,
+            market_ticker: "M".into(),
+            btc_price: 100_000.0,
+            ttl_seconds: 600.0,
+            raw_probability: 0.55,
+            probability: 0.56,
+            ev_yes: 0.03,
+            ev_no: -0.08,
+            kelly_contracts: 4.0,
+            risk,
+            exit_reason: None,
+            decision,
+        }
+    }
+
+    #[test]
+    fn test_journal_round_trip_and_at_window() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        super::super::migrations::migrate(&mut conn).unwrap();
+        {
+            let mut stmt = conn.prepare_cached(INSERT_DECISION).unwrap();
+            let blocked = RiskVerdict { outcome: "blocked", reason: "max position size exceeded" };
+            let rows = [
+                record("2026-10-18T14:02:30+00:00", "Student-t", Decision::NoAction { reason: "no_edge" }, None),
+                record("2026-10-18T14:03:00+00:00", "Student-t", Decision::NoAction { reason: "risk_blocked" }, Some(blocked)),
+                record("2026-10-18T14:03:01+00:00", "Black-Scholes", Decision::NoAction { reason: "no_edge" }, None),
+                record(
+                    "2026-10-18T14:10:00+00:00",
+                    "Student-t",
+                    Decision::PlacePaperTrade(PaperOrder {
+                        model: "Student-t",
+                        market_ticker: "M".into(),
+                        side: "yes",
+                        action: "buy",
+                        price: 0.5,
+                        contracts: 4.0,
+                        probability: 0.56,
+                        ev: 0.03,
+                        kelly_fraction: 0.1,
+                    }),
+                    Some(RiskVerdict { outcome: "allowed", reason: "" }),
+                ),
+            ];
+            for r in &rows {
+                insert(&mut stmt, r).unwrap();
+            }
+        }
+
+        let filter = DecisionFilter {
+            model: Some("Student-t".into()),
+            at: Some("2026-10-18T14:03:00Z".into()),
+            ..Default::default()
+        };
+        let page = query_decisions(&conn, &filter, 10).unwrap();
+        assert_eq!(page.decisions.len(), 2);
+        assert_eq!(page.decisions[0].reason, "risk_blocked");
+        assert_eq!(page.decisions[0].risk_reason.as_deref(), Some("max position size exceeded"));
+        assert_eq!(page.reasons, vec![("no_edge".to_string(), 1), ("risk_blocked".to_string(), 1)]);
+
+        let placed = DecisionFilter { action: Some("buy".into()), ..Default::default() };
+        let page = query_decisions(&conn, &placed, 10).unwrap();
+        assert_eq!(page.decisions.len(), 1);
+        assert_eq!(page.decisions[0].reason, "placed");
+        assert_eq!(page.decisions[0].risk_reason, None);
+
+        let bad = DecisionFilter { action: Some("sell".into()), ..Default::default() };
+        assert!(query_decisions(&conn, &bad, 10).is_err());
+    }
+}
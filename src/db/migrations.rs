@@ -0,0 +1,224 @@
+//! Versioned schema migrations.
+//!
+//! Each file in `migrations/` is embedded at compile time and applied at most
+//! once, in order, inside its own transaction. Applied versions are recorded
+//! in `schema_version`. A database whose version is newer than the newest
+//! migration known to this binary is refused rather than written to.
+//!
+//! Databases created before this table existed have no recorded version;
+//! 001-003 only use `IF NOT EXISTS`, so they are simply re-applied and
+//! recorded.
+//!
+//! To add a migration: create `migrations/NNN_name.sql` and append it to
+//! `MIGRATIONS`. Never edit a migration that has shipped.
+
+use crate::errors::{EngineError, EngineResult};
+use rusqlite::Connection;
+
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "init",
+        sql: include_str!("../../migrations/001_init.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "operator_audit",
+        sql: include_str!("../../migrations/002_operator_audit.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "open_positions",
+        sql: include_str!("../../migrations/003_open_positions.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "trade_exit_columns",
+        sql: include_str!("../../migrations/004_trade_exit_columns.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "fills",
+        sql: include_str!("../../migrations/005_fills.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "trade_history_index",
+        sql: include_str!("../../migrations/006_trade_history_index.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "trade_entry_context",
+        sql: include_str!("../../migrations/007_trade_entry_context.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "daily_summary",
+        sql: include_str!("../../migrations/008_daily_summary.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "decisions",
+        sql: include_str!("../../migrations/009_decisions.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "market_quotes",
+        sql: include_str!("../../migrations/010_market_quotes.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "underlying_assets",
+        sql: include_str!("../../migrations/011_underlying_assets.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "trade_horizon",
+        sql: include_str!("../../migrations/012_trade_horizon.sql"),
+    },
+];
+
+/// Newest schema version this binary knows about.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// Currently applied version (0 for a fresh or pre-versioning database).
+pub fn current_version(conn: &Connection) -> EngineResult<u32> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );",
+    )?;
+    let version: Option<u32> = conn.query_row("SELECT MAX(version) FROM schema_version", [], |r| r.get(0))?;
+    Ok(version.unwrap_or(0))
+}
+
+/// Apply every pending migration. Returns the resulting schema version.
+pub fn migrate(conn: &mut Connection) -> EngineResult<u32> {
+    let current = current_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        return Err(EngineError::Database(format!(
+            "database schema version {current} is newer than this build supports ({latest}); \
+             refusing to start (upgrade the binary or restore a matching backup)"
+        )));
+    }
+
+    for m in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(m.sql).map_err(|e| {
+            EngineError::Database(format!("migration {:03}_{} failed: {e}", m.version, m.name))
+        })?;
+        tx.execute(
+            "INSERT INTO schema_version (version, name) VALUES (?1, ?2)",
+            rusqlite::params![m.version, m.name],
+        )?;
+        tx.commit()?;
+        tracing::info!(version = m.version, name = m.name, "applied migration");
+    }
+
+    Ok(latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_are_ordered_and_unique() {
+        for pair in MIGRATIONS.windows(2) {
+            assert!(pair[0].version < pair[1].version, "{} !< {}", pair[0].version, pair[1].version);
+        }
+    }
+
+    #[test]
+    fn test_fresh_database_and_rerun() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(migrate(&mut conn).unwrap(), latest_version());
+        assert_eq!(migrate(&mut conn).unwrap(), latest_version());
+
+        let applied: u32 = conn
+            .query_row("SELECT COUNT(*) FROM schema_version", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(applied as usize, MIGRATIONS.len());
+    }
+
+    #[test]
+    fn test_legacy_database_is_upgraded_and_backfilled() {
+        // A database created by the old init_db: tables but no schema_version
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.execute_batch(
+            "INSERT INTO markets (ticker, event_ticker, series_ticker, open_time, close_time, expiration_time)
+             VALUES ('M', 'E', 'KXBTCD', '', '', '');
+             INSERT INTO trades (id, model_name, market_ticker, side, action, entry_price, contracts,
+                                 model_probability, ev, kelly_fraction, outcome, pnl, entry_time)
+             VALUES ('t1', 'Black-Scholes', 'M', 'yes', 'buy', 0.4, 10, 0.6, 0.1, 0.05, 'exit:take_profit', 1.0, 'now');
+             -- t2 sold 5 at 0.7 as a fake '-partial' trade, then settled
+             INSERT INTO trades (id, model_name, market_ticker, side, action, entry_price, contracts,
+                                 model_probability, ev, kelly_fraction, outcome, pnl, fees_estimate, entry_time)
+             VALUES ('t2', 'Black-Scholes', 'M', 'yes', 'buy', 0.4, 10, 0.6, 0.1, 0.05, 'win', 5.92, 0.08, 'now'),
+                    ('t2-partial', 'Black-Scholes', 'M', 'yes', 'sell', 0.7, 5, 0.6, 1.43, 0.0, 'win', 0.0, 0.07, 'now');
+             INSERT INTO btc_prices (timestamp, price) VALUES ('2026-03-02T14:00:00+00:00', 100000.0);",
+        )
+        .unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        let reason: Option<String> = conn
+            .query_row("SELECT exit_reason FROM trades WHERE id = 't1'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(reason.as_deref(), Some("take_profit"));
+
+        // The partial row becomes an exit fill on its parent
+        let trades: u32 = conn.query_row("SELECT COUNT(*) FROM trades", [], |r| r.get(0)).unwrap();
+        assert_eq!(trades, 2);
+        let fills: Vec<(String, f64, f64)> = conn
+            .prepare("SELECT kind, price, contracts FROM fills WHERE trade_id = 't2' ORDER BY id")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            fills,
+            vec![
+                ("entry".to_string(), 0.4, 10.0),
+                ("exit".to_string(), 0.7, 5.0),
+                ("settlement".to_string(), 1.0, 5.0),
+            ]
+        );
+        let pnl: f64 = conn.query_row("SELECT pnl FROM trades WHERE id = 't2'", [], |r| r.get(0)).unwrap();
+        assert!((pnl - 7.35).abs() < 1e-9);
+
+        // BTC history moves to the per-asset price table
+        let (asset, price): (String, f64) = conn
+            .query_row("SELECT asset, price FROM underlying_prices", [], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap();
+        assert_eq!((asset.as_str(), price), ("btc", 100_000.0));
+        let asset: String = conn.query_row("SELECT asset FROM trades WHERE id = 't2'", [], |r| r.get(0)).unwrap();
+        assert_eq!(asset, "btc");
+    }
+
+    #[test]
+    fn test_refuses_newer_schema() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, name) VALUES (?1, 'future')",
+            [latest_version() + 1],
+        )
+        .unwrap();
+        assert!(migrate(&mut conn).is_err());
+    }
+}
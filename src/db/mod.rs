@@ -0,0 +1,840 @@
+use crate::asset::Asset;
+use crate::errors::{EngineError, EngineResult};
+use crate::metrics::Metrics;
+use crate::state::{DbCommand, OpenPosition};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+
+pub mod migrations;
+pub mod decisions;
+pub mod pool;
+pub mod trades;
+
+pub use pool::DbPool;
+
+/// Commands committed together; a full batch commits without waiting
+const BATCH_MAX_COMMANDS: usize = 256;
+
+/// How long the writer keeps a transaction open for more commands
+const BATCH_WINDOW: Duration = Duration::from_millis(50);
+
+/// Prepared statements kept on the writer connection, comfortably above
+/// the number of distinct statements it runs
+const STATEMENT_CACHE: usize = 64;
+
+/// Stored as its lowercase name (`btc`, `eth`, `sol`)
+impl ToSql for Asset {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for Asset {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|e| FromSqlError::Other(Box::new(e)))
+    }
+}
+
+/// The database file inside `data_dir`.
+pub fn db_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("pretty_rusty.db")
+}
+
+/// Open the database, migrate it and return the writer's read-write
+/// connection together with the read-only pool for everything else.
+pub fn init_db(data_dir: &Path) -> EngineResult<(Connection, DbPool)> {
+    std::fs::create_dir_all(data_dir).map_err(|e| EngineError::Database(format!("create dir: {e}")))?;
+    let db_path = db_path(data_dir);
+    let mut conn = Connection::open(&db_path)?;
+
+    conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL; PRAGMA cache_size=-64000;")?;
+    conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE);
+
+    let version = migrations::migrate(&mut conn)?;
+
+    tracing::info!(schema_version = version, "database initialized at {}", db_path.display());
+    Ok((conn, DbPool::open(db_path)))
+}
+
+/// A command stamped with its enqueue time, for the writer lag metric.
+pub struct Queued {
+    cmd: DbCommand,
+    queued_at: Instant,
+}
+
+pub type DbReceiver = mpsc::Receiver<Queued>;
+
+/// Sending half of the writer channel; same surface as `mpsc::Sender`.
+#[derive(Clone)]
+pub struct DbSender(mpsc::Sender<Queued>);
+
+impl DbSender {
+    pub async fn send(&self, cmd: DbCommand) -> Result<(), mpsc::error::SendError<DbCommand>> {
+        self.0
+            .send(Queued { cmd, queued_at: Instant::now() })
+            .await
+            .map_err(|e| mpsc::error::SendError(e.0.cmd))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    pub fn max_capacity(&self) -> usize {
+        self.0.max_capacity()
+    }
+}
+
+pub fn channel(capacity: usize) -> (DbSender, DbReceiver) {
+    let (tx, rx) = mpsc::channel(capacity);
+    (DbSender(tx), rx)
+}
+
+/// Dedicated DB writer task. The ONLY task that writes to the database.
+///
+/// Commands are grouped into one transaction until `BATCH_MAX_COMMANDS`
+/// arrive, `BATCH_WINDOW` passes or a `Flush` asks for a commit. Each
+/// command runs in its own savepoint, so a failing command is rolled back
+/// alone and the rest of the batch still commits.
+pub async fn run_db_writer(conn: Connection, mut rx: DbReceiver, metrics: Arc<Metrics>) {
+    tracing::info!("db writer task started");
+
+    while let Some(first) = rx.recv().await {
+        let deadline = tokio::time::Instant::now() + BATCH_WINDOW;
+        let mut queued_at: Vec<Instant> = Vec::with_capacity(16);
+        let mut flushes: Vec<oneshot::Sender<()>> = Vec::new();
+
+        if let Err(e) = conn.execute_batch("BEGIN") {
+            metrics.db_write_errors.inc(&["begin"]);
+            tracing::error!("db begin failed: {e}");
+        }
+
+        let mut next = Some(first);
+        while let Some(q) = next.take() {
+            // Depth after taking this command: what is still waiting behind it
+            metrics.db_queue_depth.observe(rx.len() as f64);
+            queued_at.push(q.queued_at);
+
+            if let DbCommand::Flush { reply } = q.cmd {
+                // FIFO channel: everything queued before this is in the batch
+                flushes.push(reply);
+                break;
+            }
+            execute_in_savepoint(&conn, q.cmd, &metrics);
+
+            if queued_at.len() >= BATCH_MAX_COMMANDS {
+                break;
+            }
+            next = match rx.try_recv() {
+                Ok(q) => Some(q),
+                Err(mpsc::error::TryRecvError::Empty) => {
+                    tokio::time::timeout_at(deadline, rx.recv()).await.ok().flatten()
+                }
+                Err(mpsc::error::TryRecvError::Disconnected) => None,
+            };
+        }
+
+        let start = Instant::now();
+        if let Err(e) = conn.execute_batch("COMMIT") {
+            metrics.db_write_errors.inc(&["commit"]);
+            tracing::error!(commands = queued_at.len(), "db commit failed, batch lost: {e}");
+            let _ = conn.execute_batch("ROLLBACK");
+        }
+        let committed = Instant::now();
+        metrics.db_commit_seconds.observe(committed.duration_since(start).as_secs_f64());
+        metrics.db_batch_size.observe(queued_at.len() as f64);
+        for t in queued_at {
+            metrics.db_writer_lag_seconds.observe(committed.duration_since(t).as_secs_f64());
+        }
+        for reply in flushes {
+            let _ = reply.send(());
+        }
+    }
+
+    tracing::info!("db writer task shutting down");
+}
+
+/// Run one command in a savepoint of the open batch transaction.
+fn execute_in_savepoint(conn: &Connection, cmd: DbCommand, metrics: &Metrics) {
+    let kind = cmd.kind();
+    let start = Instant::now();
+    let result = conn
+        .execute_batch("SAVEPOINT cmd")
+        .map_err(EngineError::from)
+        .and_then(|()| execute_command(conn, cmd));
+    let result = match result {
+        Ok(()) => conn.execute_batch("RELEASE cmd").map_err(EngineError::from),
+        Err(e) => {
+            let _ = conn.execute_batch("ROLLBACK TO cmd; RELEASE cmd");
+            Err(e)
+        }
+    };
+    metrics.db_write_seconds.observe(&[kind], start.elapsed().as_secs_f64());
+
+    if let Err(e) = result {
+        metrics.db_write_errors.inc(&[kind]);
+        tracing::error!("db write error: {e}");
+    }
+}
+
+const INSERT_FILL: &str =
+    "INSERT INTO fills (trade_id, kind, price, contracts, fee, reason, timestamp) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)";
+
+/// Execute through the connection's statement cache.
+fn exec(conn: &Connection, sql: &str, params: impl rusqlite::Params) -> rusqlite::Result<usize> {
+    conn.prepare_cached(sql)?.execute(params)
+}
+
+/// Run one command. The writer calls this inside a savepoint, so
+/// multi-statement commands are atomic without a transaction of their own;
+/// the backtest calls it directly inside one transaction for the whole run.
+pub fn execute_command(conn: &Connection, cmd: DbCommand) -> EngineResult<()> {
+    match cmd {
+        DbCommand::InsertPrice { asset, timestamp, price } => {
+            exec(conn,
+                "INSERT INTO underlying_prices (asset, timestamp, price) VALUES (?1, ?2, ?3)",
+                rusqlite::params![asset, timestamp, price],
+            )?;
+        }
+        DbCommand::InsertMarket {
+            ticker, event_ticker, series_ticker, strike_price,
+            open_time, close_time, expiration_time,
+        } => {
+            exec(conn,
+                "INSERT OR REPLACE INTO markets (ticker, event_ticker, series_ticker, strike_price, open_time, close_time, expiration_time)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                rusqlite::params![ticker, event_ticker, series_ticker, strike_price, open_time, close_time, expiration_time],
+            )?;
+        }
+        DbCommand::InsertMarketQuote {
+            timestamp, ticker, yes_bid, yes_ask, no_bid, no_ask, last_price, status,
+        } => {
+            exec(conn,
+                "INSERT INTO market_quotes (timestamp, ticker, yes_bid, yes_ask, no_bid, no_ask, last_price, status)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                rusqlite::params![timestamp, ticker, yes_bid, yes_ask, no_bid, no_ask, last_price, status],
+            )?;
+        }
+        DbCommand::InsertTrade {
+            id, model_name, asset, horizon, market_ticker, side, action, entry_price,
+            contracts, model_probability, ev, kelly_fraction, fees_estimate, entry_time,
+            entry_ttl_seconds, entry_regime,
+        } => {
+            exec(conn,
+                "INSERT INTO trades (id, model_name, market_ticker, side, action, entry_price, contracts, model_probability, ev, kelly_fraction, fees_estimate, entry_time, remaining_contracts, entry_ttl_seconds, entry_regime, asset, horizon)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?7, ?13, ?14, ?15, ?16)",
+                rusqlite::params![id, model_name, market_ticker, side, action, entry_price, contracts, model_probability, ev, kelly_fraction, fees_estimate, entry_time, entry_ttl_seconds, entry_regime, asset, horizon],
+            )?;
+            exec(conn, INSERT_FILL, rusqlite::params![id, "entry", entry_price, contracts, fees_estimate, action, entry_time])?;
+        }
+        DbCommand::SettleTrade {
+            trade_id, outcome, settlement_price, contracts, fees, pnl, settle_time,
+        } => {
+            // The fill records which side paid out; the exchange charges no
+            // fee on settlement, `fees` is the entry fee booked now
+            let result = if settlement_price > 0.5 { "paid" } else { "expired" };
+            exec(conn, INSERT_FILL, rusqlite::params![trade_id, "settlement", settlement_price, contracts, 0.0, result, settle_time])?;
+            exec(conn,
+                "UPDATE trades SET outcome = ?1, pnl = COALESCE(pnl, 0) + ?2, realized_fees = realized_fees + ?3,
+                        remaining_contracts = 0, settle_time = ?4, exit_price = ?5, exit_reason = 'settlement'
+                 WHERE id = ?6",
+                rusqlite::params![outcome, pnl, fees, settle_time, settlement_price, trade_id],
+            )?;
+        }
+        DbCommand::ExitTrade {
+            trade_id, exit_price, contracts, exit_fee, fees, pnl, reason, exit_time, closed,
+        } => {
+            exec(conn, INSERT_FILL, rusqlite::params![trade_id, "exit", exit_price, contracts, exit_fee, reason, exit_time])?;
+            exec(conn,
+                "UPDATE trades SET pnl = COALESCE(pnl, 0) + ?1, realized_fees = realized_fees + ?2,
+                        remaining_contracts = MAX(remaining_contracts - ?3, 0)
+                 WHERE id = ?4",
+                rusqlite::params![pnl, fees, contracts, trade_id],
+            )?;
+            if closed {
+                // outcome keeps the `exit:<reason>` form the dashboard keys on
+                exec(conn,
+                    "UPDATE trades SET outcome = ?1, settle_time = ?2, exit_price = ?3, exit_reason = ?4,
+                            remaining_contracts = 0
+                     WHERE id = ?5",
+                    rusqlite::params![format!("exit:{reason}"), exit_time, exit_price, reason, trade_id],
+                )?;
+            }
+        }
+        DbCommand::InsertSnapshot {
+            model_name, asset, timestamp, btc_price, market_ticker,
+            probability, ev, kelly_size, cumulative_pnl, volatility, regime,
+        } => {
+            exec(conn,
+                "INSERT INTO model_snapshots (model_name, timestamp, btc_price, market_ticker, probability, ev, kelly_size, cumulative_pnl, volatility, regime, asset)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                rusqlite::params![model_name, timestamp, btc_price, market_ticker, probability, ev, kelly_size, cumulative_pnl, volatility, regime, asset],
+            )?;
+        }
+        DbCommand::UpdateRiskState {
+            model_name, asset, exposure, daily_pnl, max_drawdown, peak_equity,
+            total_trades, winning_trades,
+        } => {
+            exec(conn,
+                "INSERT OR REPLACE INTO risk_state (model_name, asset, current_exposure, daily_pnl, max_drawdown, peak_equity, total_trades, winning_trades, last_updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
+                rusqlite::params![model_name, asset, exposure, daily_pnl, max_drawdown, peak_equity, total_trades, winning_trades],
+            )?;
+        }
+        DbCommand::UpdateMarketResult { ticker, result, settlement_value } => {
+            exec(conn,
+                "UPDATE markets SET result = ?1, settlement_value = ?2 WHERE ticker = ?3",
+                rusqlite::params![result, settlement_value, ticker],
+            )?;
+        }
+        DbCommand::GetPendingTrades { market_ticker, reply } => {
+            let trades = get_pending_trades(conn, &market_ticker)?;
+            let _ = reply.send(trades);
+        }
+        DbCommand::InsertOperatorAudit { timestamp, actor, action, model_name, reason } => {
+            exec(conn,
+                "INSERT INTO operator_audit (timestamp, actor, action, model_name, reason) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![timestamp, actor, action, model_name, reason],
+            )?;
+        }
+        DbCommand::PersistPositions { positions, timestamp } => {
+            exec(conn, "DELETE FROM open_positions", [])?;
+            for (model_name, p) in &positions {
+                exec(conn,
+                    "INSERT INTO open_positions (trade_id, model_name, market_ticker, side, entry_price, contracts, model_probability, entry_btc_price, peak_unrealized, leg, persisted_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                    rusqlite::params![p.trade_id, model_name, p.market_ticker, p.side, p.entry_price, p.contracts, p.model_probability, p.entry_btc_price, p.peak_unrealized, p.leg, timestamp],
+                )?;
+            }
+        }
+        DbCommand::InsertDailySummary {
+            trading_day, model_name, asset, day_start, day_end, realized_pnl,
+            cumulative_pnl, max_drawdown, exposure, open_positions,
+        } => {
+            exec(conn,
+                "INSERT OR REPLACE INTO daily_summary (trading_day, model_name, day_start, day_end, realized_pnl,
+                     trades_opened, trades_closed, wins, fees, cumulative_pnl, max_drawdown, exposure, open_positions, asset)
+                 VALUES (?1, ?2, ?3, ?4, ?5,
+                     (SELECT COUNT(*) FROM trades WHERE model_name = ?2 AND asset = ?10 AND entry_time >= ?3 AND entry_time < ?4),
+                     (SELECT COUNT(*) FROM trades WHERE model_name = ?2 AND asset = ?10 AND settle_time >= ?3 AND settle_time < ?4),
+                     (SELECT COUNT(*) FROM trades WHERE model_name = ?2 AND asset = ?10 AND settle_time >= ?3 AND settle_time < ?4 AND pnl > 0),
+                     (SELECT COALESCE(SUM(realized_fees), 0) FROM trades WHERE model_name = ?2 AND asset = ?10 AND settle_time >= ?3 AND settle_time < ?4),
+                     ?6, ?7, ?8, ?9, ?10)",
+                rusqlite::params![trading_day, model_name, day_start, day_end, realized_pnl, cumulative_pnl, max_drawdown, exposure, open_positions, asset],
+            )?;
+        }
+        DbCommand::InsertDecisions { records } => {
+            let mut stmt = conn.prepare_cached(decisions::INSERT_DECISION)?;
+            for r in &records {
+                decisions::insert(&mut stmt, r)?;
+            }
+        }
+        DbCommand::PruneDecisions { before } => {
+            let n = exec(conn, "DELETE FROM decisions WHERE timestamp < ?1", [before])?;
+            tracing::info!(rows = n, "pruned decision journal");
+        }
+        DbCommand::Flush { reply } => {
+            // Normally answered by the writer loop after its commit
+            let _ = reply.send(());
+        }
+    }
+    Ok(())
+}
+
+const TRADE_COLUMNS: &str = "id, model_name, market_ticker, side, action, entry_price, contracts, model_probability, ev, kelly_fraction, outcome, pnl, fees_estimate, entry_time, settle_time, exit_price, exit_reason, remaining_contracts, realized_fees, entry_ttl_seconds, entry_regime, asset, horizon";
+
+/// Map a row selected with `TRADE_COLUMNS`.
+fn trade_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TradeRow> {
+    let contracts: f64 = row.get(6)?;
+    let outcome: Option<String> = row.get(10)?;
+    let remaining_contracts: f64 = row.get(17)?;
+    Ok(TradeRow {
+        status: trade_status(outcome.as_deref(), remaining_contracts, contracts),
+        id: row.get(0)?,
+        model_name: row.get(1)?,
+        market_ticker: row.get(2)?,
+        side: row.get(3)?,
+        action: row.get(4)?,
+        entry_price: row.get(5)?,
+        contracts,
+        model_probability: row.get(7)?,
+        ev: row.get(8)?,
+        kelly_fraction: row.get(9)?,
+        outcome,
+        pnl: row.get(11)?,
+        fees_estimate: row.get(12)?,
+        entry_time: row.get(13)?,
+        settle_time: row.get(14)?,
+        exit_price: row.get(15)?,
+        exit_reason: row.get(16)?,
+        remaining_contracts,
+        realized_fees: row.get(18)?,
+        entry_ttl_seconds: row.get(19)?,
+        entry_regime: row.get(20)?,
+        asset: row.get(21)?,
+        horizon: row.get(22)?,
+        fills: Vec::new(),
+    })
+}
+
+/// Attach each trade's fills, oldest first.
+fn load_fills(conn: &Connection, trades: &mut [TradeRow]) -> EngineResult<()> {
+    if trades.is_empty() {
+        return Ok(());
+    }
+    let placeholders = vec!["?"; trades.len()].join(", ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT trade_id, kind, price, contracts, fee, reason, timestamp FROM fills
+         WHERE trade_id IN ({placeholders}) ORDER BY id"
+    ))?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(trades.iter().map(|t| &t.id)), |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            FillRow {
+                kind: row.get(1)?,
+                price: row.get(2)?,
+                contracts: row.get(3)?,
+                fee: row.get(4)?,
+                reason: row.get(5)?,
+                timestamp: row.get(6)?,
+            },
+        ))
+    })?;
+
+    let mut by_trade: HashMap<String, Vec<FillRow>> = HashMap::new();
+    for (trade_id, fill) in rows.filter_map(|r| r.ok()) {
+        by_trade.entry(trade_id).or_default().push(fill);
+    }
+    for trade in trades {
+        trade.fills = by_trade.remove(&trade.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// Trades on `market_ticker` that have not been closed or settled.
+pub fn get_pending_trades(conn: &Connection, market_ticker: &str) -> EngineResult<Vec<TradeRow>> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {TRADE_COLUMNS} FROM trades WHERE market_ticker = ?1 AND outcome IS NULL"
+    ))?;
+    let rows = stmt.query_map(rusqlite::params![market_ticker], trade_from_row)?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+// ── Query helpers (REST reads run these on a pooled read-only connection) ──
+
+/// Read and clear positions saved by a `persist` shutdown. Called once at
+/// startup on the writer's connection, before the writer task starts.
+/// Hold-time ticks restart at zero.
+pub fn take_persisted_positions(conn: &Connection) -> EngineResult<Vec<(String, Asset, OpenPosition)>> {
+    let tx = conn.unchecked_transaction()?;
+    let positions = {
+        let mut stmt = tx.prepare(
+            "SELECT p.model_name, p.trade_id, p.market_ticker, p.side, p.entry_price, p.contracts, p.model_probability, p.entry_btc_price, p.peak_unrealized, p.leg,
+                    COALESCE(m.strike_price, 0.0), COALESCE(m.close_time, ''), t.asset, t.horizon
+             FROM open_positions p JOIN trades t ON t.id = p.trade_id
+             LEFT JOIN markets m ON m.ticker = p.market_ticker
+             WHERE t.outcome IS NULL",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Asset>(12)?,
+                OpenPosition {
+                    trade_id: row.get(1)?,
+                    market_ticker: row.get(2)?,
+                    horizon: row.get(13)?,
+                    strike: row.get(10)?,
+                    close_time: row.get(11)?,
+                    side: row.get(3)?,
+                    entry_price: row.get(4)?,
+                    contracts: row.get(5)?,
+                    model_probability: row.get(6)?,
+                    entry_tick: 0,
+                    entry_btc_price: row.get(7)?,
+                    peak_unrealized: row.get(8)?,
+                    leg: row.get(9)?,
+                    unrealized: 0.0,
+                },
+            ))
+        })?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    tx.execute("DELETE FROM open_positions", [])?;
+    tx.commit()?;
+    Ok(positions)
+}
+
+/// Markets with trades still awaiting settlement (e.g. held across a restart).
+pub fn get_unsettled_tickers(conn: &Connection) -> EngineResult<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT market_ticker FROM trades WHERE outcome IS NULL")?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// One model's P/L on one asset, oldest first. Every asset runs the same
+/// model names, so the asset is needed to keep their curves apart.
+pub fn get_model_pnl_series(
+    conn: &Connection,
+    model_name: &str,
+    asset: Asset,
+    limit: usize,
+) -> EngineResult<Vec<(String, f64)>> {
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, cumulative_pnl FROM model_snapshots WHERE model_name = ?1 AND asset = ?2 ORDER BY id DESC LIMIT ?3"
+    )?;
+    let rows = stmt.query_map(rusqlite::params![model_name, asset, limit], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+    })?;
+    let mut series = rows.collect::<Result<Vec<_>, _>>()?;
+    series.reverse();
+    Ok(series)
+}
+
+pub fn get_risk_states(conn: &Connection) -> EngineResult<Vec<RiskStateRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT model_name, current_exposure, daily_pnl, max_drawdown, peak_equity, total_trades, winning_trades, last_updated, asset
+         FROM risk_state ORDER BY asset, model_name"
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(RiskStateRow {
+            model_name: row.get(0)?,
+            asset: row.get(8)?,
+            current_exposure: row.get(1)?,
+            daily_pnl: row.get(2)?,
+            max_drawdown: row.get(3)?,
+            peak_equity: row.get(4)?,
+            total_trades: row.get(5)?,
+            winning_trades: row.get(6)?,
+            last_updated: row.get(7)?,
+        })
+    })?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// Tracked markets, most recently closing first, with their results and
+/// the paper trades taken on each. `settled` filters on whether a result
+/// has been recorded.
+pub fn get_markets(conn: &Connection, settled: Option<bool>, limit: usize) -> EngineResult<Vec<MarketRow>> {
+    let filter = match settled {
+        Some(true) => "WHERE m.result IS NOT NULL",
+        Some(false) => "WHERE m.result IS NULL",
+        None => "",
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT m.ticker, m.event_ticker, m.series_ticker, m.strike_price, m.open_time, m.close_time,
+                m.expiration_time, m.result, m.settlement_value,
+                COUNT(t.id), COALESCE(SUM(t.outcome IS NULL), 0), COALESCE(SUM(t.pnl), 0.0)
+         FROM markets m LEFT JOIN trades t ON t.market_ticker = m.ticker
+         {filter}
+         GROUP BY m.ticker
+         ORDER BY m.close_time DESC
+         LIMIT ?1"
+    ))?;
+    let rows = stmt.query_map([limit as i64], |row| {
+        Ok(MarketRow {
+            ticker: row.get(0)?,
+            event_ticker: row.get(1)?,
+            series_ticker: row.get(2)?,
+            strike_price: row.get(3)?,
+            open_time: row.get(4)?,
+            close_time: row.get(5)?,
+            expiration_time: row.get(6)?,
+            result: row.get(7)?,
+            settlement_value: row.get(8)?,
+            trade_count: row.get(9)?,
+            open_trades: row.get(10)?,
+            realized_pnl: row.get(11)?,
+        })
+    })?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// Realized P/L per model and asset from trades closed since `since`
+/// (RFC 3339). Restores `daily_pnl` after a mid-day restart; partial exits
+/// on trades that are still open are not included.
+pub fn get_realized_pnl_since(conn: &Connection, since: &str) -> EngineResult<Vec<(String, Asset, f64)>> {
+    let mut stmt = conn.prepare(
+        "SELECT model_name, asset, SUM(pnl) FROM trades
+         WHERE outcome IS NOT NULL AND settle_time >= ?1
+         GROUP BY model_name, asset",
+    )?;
+    let rows = stmt.query_map([since], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// End-of-day summaries, most recent day first, optionally for one model
+/// and/or asset.
+pub fn get_daily_summaries(
+    conn: &Connection,
+    model_name: Option<&str>,
+    asset: Option<Asset>,
+    limit: usize,
+) -> EngineResult<Vec<DailySummaryRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT trading_day, model_name, day_start, day_end, realized_pnl, trades_opened, trades_closed,
+                wins, fees, cumulative_pnl, max_drawdown, exposure, open_positions, asset
+         FROM daily_summary
+         WHERE (?1 IS NULL OR model_name = ?1) AND (?2 IS NULL OR asset = ?2)
+         ORDER BY trading_day DESC, asset, model_name
+         LIMIT ?3",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![model_name, asset, limit as i64], |row| {
+        Ok(DailySummaryRow {
+            trading_day: row.get(0)?,
+            model_name: row.get(1)?,
+            asset: row.get(13)?,
+            day_start: row.get(2)?,
+            day_end: row.get(3)?,
+            realized_pnl: row.get(4)?,
+            trades_opened: row.get(5)?,
+            trades_closed: row.get(6)?,
+            wins: row.get(7)?,
+            fees: row.get(8)?,
+            cumulative_pnl: row.get(9)?,
+            max_drawdown: row.get(10)?,
+            exposure: row.get(11)?,
+            open_positions: row.get(12)?,
+        })
+    })?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+pub fn get_operator_audit(conn: &Connection, limit: usize) -> EngineResult<Vec<OperatorAuditRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, timestamp, actor, action, model_name, reason FROM operator_audit ORDER BY id DESC LIMIT ?1"
+    )?;
+    let rows = stmt.query_map(rusqlite::params![limit as i64], |row| {
+        Ok(OperatorAuditRow {
+            id: row.get(0)?,
+            timestamp: row.get(1)?,
+            actor: row.get(2)?,
+            action: row.get(3)?,
+            model_name: row.get(4)?,
+            reason: row.get(5)?,
+        })
+    })?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+// ── Row types ──
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TradeRow {
+    pub id: String,
+    pub model_name: String,
+    pub asset: Asset,
+    /// Expiry horizon the market was traded under
+    pub horizon: String,
+    pub market_ticker: String,
+    pub side: String,
+    pub action: String,
+    pub entry_price: f64,
+    pub contracts: f64,
+    pub model_probability: f64,
+    pub ev: f64,
+    pub kelly_fraction: f64,
+    pub outcome: Option<String>,
+    pub pnl: Option<f64>,
+    pub fees_estimate: f64,
+    pub entry_time: String,
+    pub settle_time: Option<String>,
+    /// Price of the fill that closed the trade
+    pub exit_price: Option<f64>,
+    pub exit_reason: Option<String>,
+    pub status: &'static str,
+    /// Contracts still held (0 once closed or settled)
+    pub remaining_contracts: f64,
+    /// Fees already deducted from `pnl`
+    pub realized_fees: f64,
+    /// Seconds to market close at entry (unknown for some legacy rows)
+    pub entry_ttl_seconds: Option<f64>,
+    /// `low` / `high` volatility regime at entry
+    pub entry_regime: Option<String>,
+    /// Entry, exit and settlement fills; only loaded for `/api/trades`
+    pub fills: Vec<FillRow>,
+}
+
+/// `open`, `partial` (some contracts sold), `closed` (exited) or `settled`.
+fn trade_status(outcome: Option<&str>, remaining: f64, contracts: f64) -> &'static str {
+    match outcome {
+        Some(o) if o.starts_with("exit:") => "closed",
+        Some(_) => "settled",
+        None if remaining < contracts => "partial",
+        None => "open",
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FillRow {
+    /// `entry`, `exit` or `settlement`
+    pub kind: String,
+    pub price: f64,
+    pub contracts: f64,
+    pub fee: f64,
+    pub reason: Option<String>,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MarketRow {
+    pub ticker: String,
+    pub event_ticker: String,
+    pub series_ticker: String,
+    pub strike_price: Option<f64>,
+    pub open_time: String,
+    pub close_time: String,
+    pub expiration_time: String,
+    /// `yes` / `no` once the market has settled
+    pub result: Option<String>,
+    pub settlement_value: Option<f64>,
+    pub trade_count: i64,
+    pub open_trades: i64,
+    pub realized_pnl: f64,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RiskStateRow {
+    pub model_name: String,
+    pub asset: Asset,
+    pub current_exposure: f64,
+    pub daily_pnl: f64,
+    pub max_drawdown: f64,
+    pub peak_equity: f64,
+    pub total_trades: i64,
+    pub winning_trades: i64,
+    pub last_updated: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DailySummaryRow {
+    pub trading_day: String,
+    pub model_name: String,
+    pub asset: Asset,
+    pub day_start: String,
+    pub day_end: String,
+    pub realized_pnl: f64,
+    pub trades_opened: i64,
+    pub trades_closed: i64,
+    pub wins: i64,
+    pub fees: f64,
+    pub cumulative_pnl: f64,
+    pub max_drawdown: f64,
+    pub exposure: f64,
+    pub open_positions: i64,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OperatorAuditRow {
+    pub id: i64,
+    pub timestamp: String,
+    pub actor: String,
+    pub action: String,
+    pub model_name: Option<String>,
+    pub reason: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(id: &str) -> DbCommand {
+        DbCommand::InsertTrade {
+            id: id.into(),
+            model_name: "Black-Scholes".into(),
+            asset: Asset::Btc,
+            horizon: "hourly".into(),
+            market_ticker: "M".into(),
+            side: "yes".into(),
+            action: "buy".into(),
+            entry_price: 0.4,
+            contracts: 5.0,
+            model_probability: 0.6,
+            ev: 0.1,
+            kelly_fraction: 0.05,
+            fees_estimate: 0.1,
+            entry_time: "2026-10-18T14:00:00+00:00".into(),
+            entry_ttl_seconds: 600.0,
+            entry_regime: "low".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_isolates_failed_command_and_flush_commits() {
+        let dir = std::env::temp_dir().join(format!("pretty_rusty_writer_{}", std::process::id()));
+        let (conn, pool) = init_db(&dir).unwrap();
+        let metrics = Arc::new(Metrics::new());
+        let (tx, rx) = channel(16);
+        let writer = tokio::spawn(run_db_writer(conn, rx, metrics));
+
+        tx.send(DbCommand::InsertPrice { asset: Asset::Btc, timestamp: "t0".into(), price: 100_000.0 }).await.unwrap();
+        tx.send(DbCommand::InsertMarket {
+            ticker: "M".into(),
+            event_ticker: "E".into(),
+            series_ticker: "KXBTCD".into(),
+            strike_price: Some(100_000.0),
+            open_time: String::new(),
+            close_time: String::new(),
+            expiration_time: String::new(),
+        })
+        .await
+        .unwrap();
+        tx.send(trade("t1")).await.unwrap();
+        // Duplicate primary key: its entry fill must be rolled back with it
+        tx.send(trade("t1")).await.unwrap();
+        tx.send(DbCommand::InsertPrice { asset: Asset::Btc, timestamp: "t1".into(), price: 100_050.0 }).await.unwrap();
+        let (reply, done) = oneshot::channel();
+        tx.send(DbCommand::Flush { reply }).await.unwrap();
+        done.await.unwrap();
+
+        let counts = pool
+            .read(|conn| {
+                let count = |table: &str| -> EngineResult<i64> {
+                    Ok(conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |r| r.get(0))?)
+                };
+                Ok((count("underlying_prices")?, count("trades")?, count("fills")?))
+            })
+            .await
+            .unwrap();
+        assert_eq!(counts, (2, 1, 1));
+
+        // Readers are read-only
+        assert!(pool.get().unwrap().execute("DELETE FROM trades", []).is_err());
+
+        drop(tx);
+        writer.await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_pnl_series_is_per_asset() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrations::migrate(&mut conn).unwrap();
+        // The same model on two assets, snapshots interleaved
+        for (i, (asset, pnl)) in [(Asset::Btc, 1.0), (Asset::Eth, -5.0), (Asset::Btc, 2.0), (Asset::Eth, -6.0)].into_iter().enumerate() {
+            let snapshot = DbCommand::InsertSnapshot {
+                model_name: "Black-Scholes".into(),
+                asset,
+                timestamp: format!("t{i}"),
+                btc_price: 100_000.0,
+                market_ticker: None,
+                probability: None,
+                ev: None,
+                kelly_size: None,
+                cumulative_pnl: pnl,
+                volatility: None,
+                regime: None,
+            };
+            execute_command(&conn, snapshot).unwrap();
+        }
+
+        let series = |asset| get_model_pnl_series(&conn, "Black-Scholes", asset, 10).unwrap();
+        assert_eq!(series(Asset::Btc), vec![("t0".to_string(), 1.0), ("t2".to_string(), 2.0)]);
+        assert_eq!(series(Asset::Eth), vec![("t1".to_string(), -5.0), ("t3".to_string(), -6.0)]);
+        assert!(series(Asset::Sol).is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+//! Read-only connection pool for the REST handlers.
+//!
+//! The writer task owns the only read-write connection. Readers check out a
+//! `SQLITE_OPEN_READ_ONLY` connection of their own, so a slow query never
+//! waits on the writer (WAL lets both run at once) and never holds a lock
+//! the engine needs. Async callers go through `read`, which runs the query
+//! on the blocking pool; `get` is for code that is already off the runtime.
+
+use crate::errors::{EngineError, EngineResult};
+use rusqlite::{Connection, OpenFlags};
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+
+/// Concurrent readers; more requests queue on the semaphore
+const READERS: usize = 4;
+
+#[derive(Clone)]
+pub struct DbPool {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// `None` for a pool wrapping a single connection (tests)
+    path: Option<PathBuf>,
+    idle: Mutex<Vec<Connection>>,
+    permits: Semaphore,
+}
+
+impl DbPool {
+    pub fn open(path: PathBuf) -> Self {
+        Self::with_idle(Some(path), Vec::new())
+    }
+
+    /// A pool that hands out `conn` and nothing else.
+    #[cfg(test)]
+    pub(crate) fn from_connection(conn: Connection) -> Self {
+        Self::with_idle(None, vec![conn])
+    }
+
+    fn with_idle(path: Option<PathBuf>, idle: Vec<Connection>) -> Self {
+        let permits = if path.is_some() { READERS } else { idle.len() };
+        Self { inner: Arc::new(Inner { path, idle: Mutex::new(idle), permits: Semaphore::new(permits) }) }
+    }
+
+    /// Check out a connection, opening one if none is idle. Blocks on file
+    /// I/O; call from `spawn_blocking` or startup code.
+    pub fn get(&self) -> EngineResult<PooledConnection> {
+        let idle = self
+            .inner
+            .idle
+            .lock()
+            .map_err(|e| EngineError::Database(format!("pool lock: {e}")))?
+            .pop();
+        let conn = match (idle, &self.inner.path) {
+            (Some(conn), _) => conn,
+            (None, Some(path)) => {
+                let conn = Connection::open_with_flags(
+                    path,
+                    OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX | OpenFlags::SQLITE_OPEN_URI,
+                )?;
+                conn.busy_timeout(std::time::Duration::from_secs(5))?;
+                conn
+            }
+            (None, None) => return Err(EngineError::Database("pool exhausted".into())),
+        };
+        Ok(PooledConnection { conn: Some(conn), pool: self.inner.clone() })
+    }
+
+    /// Run `f` with a pooled connection on the blocking thread pool.
+    pub async fn read<T, F>(&self, f: F) -> EngineResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> EngineResult<T> + Send + 'static,
+    {
+        let _permit = self
+            .inner
+            .permits
+            .acquire()
+            .await
+            .map_err(|e| EngineError::Database(format!("pool closed: {e}")))?;
+        let pool = self.clone();
+        tokio::task::spawn_blocking(move || f(&*pool.get()?))
+            .await
+            .map_err(|e| EngineError::Database(format!("read task: {e}")))?
+    }
+}
+
+/// A checked-out connection; returned to the pool on drop.
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    pool: Arc<Inner>,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection present until drop")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let (Some(conn), Ok(mut idle)) = (self.conn.take(), self.pool.idle.lock()) {
+            if idle.len() < READERS {
+                idle.push(conn);
+            }
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! Trade history queries: filtered, cursor-paginated reads for
+//! `/api/trades` and `/api/trades/export`.
+//!
+//! Pages are ordered newest first by `(entry_time, id)`. The cursor is that
+//! key for the last row of a page, so a page is one short indexed query and
+//! rows inserted while paging never shift later pages.
+
+use super::decisions::normalize_bound;
+use super::{load_fills, trade_from_row, TradeRow, TRADE_COLUMNS};
+use crate::asset::Asset;
+use crate::errors::{EngineError, EngineResult};
+use base64::Engine as _;
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+
+/// Filters shared by `/api/trades` and `/api/trades/export`. Every field is
+/// optional; set fields are ANDed.
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+pub struct TradeFilter {
+    pub model: Option<String>,
+    /// `btc`, `eth` or `sol`
+    pub asset: Option<Asset>,
+    pub market: Option<String>,
+    /// Expiry horizon name, e.g. `hourly`
+    pub horizon: Option<String>,
+    /// `yes` or `no`
+    pub side: Option<String>,
+    /// `win`, `loss`, `exit` (closed before settlement) or `open`
+    pub outcome: Option<String>,
+    /// e.g. `take_profit`, `strike_cross`, `settlement`
+    pub exit_reason: Option<String>,
+    /// Entry time lower bound, inclusive (RFC 3339 or `YYYY-MM-DD`)
+    pub since: Option<String>,
+    /// Entry time upper bound, exclusive
+    pub until: Option<String>,
+    /// Only trades whose realized |P/L| is at least this
+    pub min_abs_pnl: Option<f64>,
+}
+
+impl TradeFilter {
+    /// Reject unknown `side` / `outcome` values and unparseable bounds.
+    pub fn validate(&self) -> EngineResult<()> {
+        self.to_sql().map(|_| ())
+    }
+
+    /// WHERE clause (without the keyword) and its parameters.
+    fn to_sql(&self) -> EngineResult<(String, Vec<Box<dyn ToSql>>)> {
+        let mut clauses: Vec<&str> = Vec::new();
+        let mut params: Vec<Box<dyn ToSql>> = Vec::new();
+
+        if let Some(model) = &self.model {
+            clauses.push("model_name = ?");
+            params.push(Box::new(model.clone()));
+        }
+        if let Some(asset) = self.asset {
+            clauses.push("asset = ?");
+            params.push(Box::new(asset));
+        }
+        if let Some(market) = &self.market {
+            clauses.push("market_ticker = ?");
+            params.push(Box::new(market.clone()));
+        }
+        if let Some(horizon) = &self.horizon {
+            clauses.push("horizon = ?");
+            params.push(Box::new(horizon.clone()));
+        }
+        if let Some(side) = &self.side {
+            if side != "yes" && side != "no" {
+                return Err(EngineError::Parse(format!("side must be yes or no, got {side:?}")));
+            }
+            clauses.push("side = ?");
+            params.push(Box::new(side.clone()));
+        }
+        match self.outcome.as_deref() {
+            None => {}
+            Some("win") => clauses.push("outcome = 'win'"),
+            Some("loss") => clauses.push("outcome = 'loss'"),
+            Some("exit") => clauses.push("outcome LIKE 'exit:%'"),
+            Some("open") => clauses.push("outcome IS NULL"),
+            Some(other) => {
+                return Err(EngineError::Parse(format!(
+                    "outcome must be win, loss, exit or open, got {other:?}"
+                )))
+            }
+        }
+        if let Some(reason) = &self.exit_reason {
+            clauses.push("exit_reason = ?");
+            params.push(Box::new(reason.clone()));
+        }
+        // Timestamps are RFC 3339 UTC, so string order is time order and a
+        // bare date compares as midnight
+        if let Some(since) = &self.since {
+            clauses.push("entry_time >= ?");
+            params.push(Box::new(normalize_bound(since)?));
+        }
+        if let Some(until) = &self.until {
+            clauses.push("entry_time < ?");
+            params.push(Box::new(normalize_bound(until)?));
+        }
+        if let Some(min) = self.min_abs_pnl {
+            clauses.push("ABS(COALESCE(pnl, 0)) >= ?");
+            params.push(Box::new(min));
+        }
+
+        let sql = if clauses.is_empty() { "1".to_string() } else { clauses.join(" AND ") };
+        Ok((sql, params))
+    }
+}
+
+/// One page of trades, newest first.
+#[derive(Debug, serde::Serialize)]
+pub struct TradePage {
+    pub trades: Vec<TradeRow>,
+    /// Pass as `cursor` to fetch the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Fetch up to `limit` trades after `cursor`. Fills are attached only when
+/// `with_fills` is set.
+pub fn query_trades(
+    conn: &Connection,
+    filter: &TradeFilter,
+    cursor: Option<&str>,
+    limit: usize,
+    with_fills: bool,
+) -> EngineResult<TradePage> {
+    let (mut where_sql, mut params) = filter.to_sql()?;
+    if let Some(cursor) = cursor {
+        let (entry_time, id) = decode_cursor(cursor)?;
+        where_sql.push_str(" AND (entry_time < ? OR (entry_time = ? AND id < ?))");
+        params.push(Box::new(entry_time.clone()));
+        params.push(Box::new(entry_time));
+        params.push(Box::new(id));
+    }
+    // One extra row tells us whether another page exists
+    params.push(Box::new((limit + 1) as i64));
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {TRADE_COLUMNS} FROM trades WHERE {where_sql} ORDER BY entry_time DESC, id DESC LIMIT ?"
+    ))?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), trade_from_row)?;
+    // A row that cannot be decoded fails the page rather than vanishing
+    // from it (and from an export built out of pages)
+    let mut trades = rows.collect::<Result<Vec<_>, _>>()?;
+
+    let next_cursor = if trades.len() > limit {
+        trades.truncate(limit);
+        trades.last().map(|t| encode_cursor(&t.entry_time, &t.id))
+    } else {
+        None
+    };
+    if with_fills {
+        load_fills(conn, &mut trades)?;
+    }
+    Ok(TradePage { trades, next_cursor })
+}
+
+/// Every closed or settled trade realized in `[since, until)` (by close
+/// time), oldest first, optionally for one model and/or asset. Input for
+/// `paper::analytics`.
+pub fn closed_trades(
+    conn: &Connection,
+    model: Option<&str>,
+    asset: Option<Asset>,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> EngineResult<Vec<TradeRow>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {TRADE_COLUMNS} FROM trades
+         WHERE outcome IS NOT NULL AND pnl IS NOT NULL
+           AND (?1 IS NULL OR model_name = ?1)
+           AND (?2 IS NULL OR asset = ?2)
+           AND (?3 IS NULL OR settle_time >= ?3)
+           AND (?4 IS NULL OR settle_time < ?4)
+         ORDER BY settle_time, id"
+    ))?;
+    let since = since.map(normalize_bound).transpose()?;
+    let until = until.map(normalize_bound).transpose()?;
+    let rows = stmt.query_map(rusqlite::params![model, asset, since, until], trade_from_row)?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+fn encode_cursor(entry_time: &str, id: &str) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(format!("{entry_time}\n{id}"))
+}
+
+fn decode_cursor(cursor: &str) -> EngineResult<(String, String)> {
+    let invalid = || EngineError::Parse("invalid cursor".into());
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .map_err(|_| invalid())?;
+    let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+    let (entry_time, id) = text.split_once('\n').ok_or_else(invalid)?;
+    Ok((entry_time.to_string(), id.to_string()))
+}
+
+/// Header row for `csv_row`.
+pub const CSV_HEADER: &str = "id,model_name,asset,horizon,market_ticker,side,action,status,entry_time,entry_price,contracts,\
+remaining_contracts,close_time,exit_price,exit_reason,outcome,pnl,realized_fees,fees_estimate,\
+model_probability,ev,kelly_fraction,entry_ttl_seconds,entry_regime\n";
+
+/// One trade as a CSV line (with trailing newline).
+pub fn csv_row(t: &TradeRow) -> String {
+    let opt_f = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+    let fields = [
+        csv_escape(&t.id),
+        csv_escape(&t.model_name),
+        t.asset.to_string(),
+        csv_escape(&t.horizon),
+        csv_escape(&t.market_ticker),
+        csv_escape(&t.side),
+        csv_escape(&t.action),
+        t.status.to_string(),
+        csv_escape(&t.entry_time),
+        t.entry_price.to_string(),
+        t.contracts.to_string(),
+        t.remaining_contracts.to_string(),
+        csv_escape(t.settle_time.as_deref().unwrap_or_default()),
+        opt_f(t.exit_price),
+        csv_escape(t.exit_reason.as_deref().unwrap_or_default()),
+        csv_escape(t.outcome.as_deref().unwrap_or_default()),
+        opt_f(t.pnl),
+        t.realized_fees.to_string(),
+        t.fees_estimate.to_string(),
+        t.model_probability.to_string(),
+        t.ev.to_string(),
+        t.kelly_fraction.to_string(),
+        opt_f(t.entry_ttl_seconds),
+        csv_escape(t.entry_regime.as_deref().unwrap_or_default()),
+    ];
+    let mut line = fields.join(",");
+    line.push('\n');
+    line
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db_with_trades() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        super::super::migrations::migrate(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO markets (ticker, event_ticker, series_ticker, open_time, close_time, expiration_time)
+             VALUES ('M1', 'E', 'KXBTCD', '', '', ''), ('M2', 'E', 'KXBTCD', '', '', '');",
+        )
+        .unwrap();
+        for i in 0..5 {
+            let (market, outcome, pnl) = match i {
+                0 => ("M1", Some("win"), Some(3.0)),
+                1 => ("M1", Some("exit:strike_cross"), Some(-0.5)),
+                2 => ("M2", Some("loss"), Some(-4.0)),
+                _ => ("M2", None, None),
+            };
+            conn.execute(
+                "INSERT INTO trades (id, model_name, market_ticker, side, action, entry_price, contracts,
+                                     model_probability, ev, kelly_fraction, outcome, pnl, entry_time)
+                 VALUES (?1, 'Black-Scholes', ?2, 'yes', 'buy', 0.4, 10, 0.6, 0.1, 0.05, ?3, ?4, ?5)",
+                rusqlite::params![format!("t{i}"), market, outcome, pnl, format!("2026-10-0{}T12:00:00+00:00", i + 1)],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn test_cursor_pages_cover_everything_once() {
+        let db = db_with_trades();
+        let filter = TradeFilter::default();
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = query_trades(&db, &filter, cursor.as_deref(), 2, false).unwrap();
+            seen.extend(page.trades.into_iter().map(|t| t.id));
+            match page.next_cursor {
+                Some(c) => cursor = Some(c),
+                None => break,
+            }
+        }
+        assert_eq!(seen, vec!["t4", "t3", "t2", "t1", "t0"]);
+    }
+
+    #[test]
+    fn test_filters() {
+        let db = db_with_trades();
+        let ids = |filter: TradeFilter| -> Vec<String> {
+            query_trades(&db, &filter, None, 100, false)
+                .unwrap()
+                .trades
+                .into_iter()
+                .map(|t| t.id)
+                .collect()
+        };
+
+        assert_eq!(ids(TradeFilter { market: Some("M1".into()), ..Default::default() }), vec!["t1", "t0"]);
+        db.execute_batch("UPDATE trades SET asset = 'eth' WHERE id = 't2'").unwrap();
+        assert_eq!(ids(TradeFilter { asset: Some(Asset::Eth), ..Default::default() }), vec!["t2"]);
+        assert_eq!(ids(TradeFilter { outcome: Some("exit".into()), ..Default::default() }), vec!["t1"]);
+        assert_eq!(ids(TradeFilter { outcome: Some("open".into()), ..Default::default() }), vec!["t4", "t3"]);
+        assert_eq!(ids(TradeFilter { min_abs_pnl: Some(1.0), ..Default::default() }), vec!["t2", "t0"]);
+        assert_eq!(
+            ids(TradeFilter {
+                since: Some("2026-10-02".into()),
+                until: Some("2026-10-04".into()),
+                ..Default::default()
+            }),
+            vec!["t2", "t1"]
+        );
+        // Offsets are converted to UTC: 08:00-04:00 is 12:00Z
+        assert_eq!(
+            ids(TradeFilter {
+                since: Some("2026-10-02T08:00:00-04:00".into()),
+                until: Some("2026-10-03T12:00:00Z".into()),
+                ..Default::default()
+            }),
+            vec!["t1"]
+        );
+        assert!(query_trades(&db, &TradeFilter { since: Some("yesterday".into()), ..Default::default() }, None, 10, false).is_err());
+        assert!(query_trades(&db, &TradeFilter { side: Some("up".into()), ..Default::default() }, None, 10, false).is_err());
+        assert!(query_trades(&db, &TradeFilter::default(), Some("not a cursor"), 10, false).is_err());
+
+        db.execute_batch("UPDATE trades SET contracts = 'many' WHERE id = 't2'").unwrap();
+        assert!(query_trades(&db, &TradeFilter::default(), None, 100, false).is_err());
+    }
+
+    #[test]
+    fn test_closed_trades_bounds() {
+        let db = db_with_trades();
+        db.execute_batch("UPDATE trades SET settle_time = entry_time WHERE outcome IS NOT NULL").unwrap();
+        let ids = |since: &str, until: &str| -> Vec<String> {
+            closed_trades(&db, None, None, Some(since), Some(until)).unwrap().into_iter().map(|t| t.id).collect()
+        };
+        assert_eq!(ids("2026-10-01", "2026-10-03"), vec!["t0", "t1"]);
+        assert_eq!(ids("2026-10-02T08:00:00-04:00", "2026-10-03T12:00:00Z"), vec!["t1"]);
+        assert!(closed_trades(&db, None, None, Some("last week"), None).is_err());
+
+        // One asset's trades only
+        db.execute_batch("UPDATE trades SET asset = 'eth' WHERE id = 't1'").unwrap();
+        let on = |asset| -> Vec<String> {
+            closed_trades(&db, None, Some(asset), None, None).unwrap().into_iter().map(|t| t.id).collect()
+        };
+        assert_eq!(on(Asset::Eth), vec!["t1"]);
+        assert!(!on(Asset::Btc).contains(&"t1".to_string()));
+
+        // A row that cannot be decoded is an error, not a gap in the report
+        db.execute_batch("UPDATE trades SET contracts = 'many' WHERE id = 't0'").unwrap();
+        assert!(closed_trades(&db, None, None, None, None).is_err());
+    }
+
+    #[test]
+    fn test_csv_row_matches_header() {
+        let db = db_with_trades();
+        let trade = query_trades(&db, &TradeFilter::default(), None, 1, false).unwrap().trades.remove(0);
+        let row = csv_row(&trade);
+        assert_eq!(row.split(',').count(), CSV_HEADER.split(',').count());
+        assert!(row.starts_with("t4,Black-Scholes,btc,hourly,M2,"), "{row}");
+    }
+
+    #[test]
+    fn test_csv_escaping() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
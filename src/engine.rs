@@ -0,0 +1,850 @@
+//! The engine loop: one task that owns all trading state and reacts to
+//! `EngineEvent`s.
+//!
+//! Nothing here knows where events come from. The binary feeds it from the
+//! crypto price poller, the Kalshi market scanner and a one-second ticker;
+//! an embedder can send the same events from anywhere (a replay, a test
+//! clock, another exchange feed). See `examples/custom_feed.rs`.
+
+use crate::asset::Asset;
+use crate::config;
+use crate::db;
+use crate::errors;
+use crate::horizon::Horizon;
+use crate::kalshi::exchange::ExchangePhase;
+use crate::models::{self, calibration::Calibrator, volatility::VolatilityEngine, PricingModel};
+use crate::paper::simulator::{self, EngineAction};
+use crate::risk;
+use crate::risk::portfolio::PortfolioBook;
+use crate::risk::var::HorizonVols;
+use crate::state::*;
+use crate::trading_day::{self, TradingCalendar};
+use portable_atomic::Ordering;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Ticks between Monte Carlo VaR/ES refreshes
+const TAIL_RISK_EVERY_TICKS: u64 = 30;
+
+/// Decision journal rows buffered before they are sent to the writer
+/// (one row per model per tick, so about 10 seconds' worth)
+const JOURNAL_BATCH_ROWS: usize = 30;
+
+/// One asset's trading state: its price history, a market and volatility
+/// estimate per expiry horizon, and a model state and calibrator per
+/// enabled model. Pipelines share the engine state, the trading params and
+/// the exchange, nothing else. An asset's horizons share its models, so a
+/// model's limits and P/L cover every market it holds on that asset.
+struct Pipeline {
+    asset: Asset,
+    price: f64,
+    price_timestamp: String,
+    prices: VecDeque<(i64, f64)>,
+    horizons: Vec<HorizonBook>,
+    model_states: Vec<ModelState>,
+    calibrators: Vec<Calibrator>,
+}
+
+/// One horizon's active market and the params it is priced with.
+struct HorizonBook {
+    horizon: Horizon,
+    /// The engine's trading params with the horizon's overrides
+    trading: config::TradingParams,
+    vol_engine: VolatilityEngine,
+    active_market: Option<ActiveMarket>,
+}
+
+impl HorizonBook {
+    fn new(horizon: &Horizon, trading: &config::TradingParams) -> Self {
+        let trading = horizon.params(trading);
+        Self {
+            horizon: horizon.clone(),
+            vol_engine: VolatilityEngine::new(trading.volatility),
+            trading,
+            active_market: None,
+        }
+    }
+}
+
+impl Pipeline {
+    fn new(asset: Asset, pricing_models: &[&dyn PricingModel], config: &config::AppConfig) -> Self {
+        Self {
+            asset,
+            price: 0.0,
+            price_timestamp: String::new(),
+            prices: VecDeque::with_capacity(2000),
+            horizons: config.horizons.iter().map(|h| HorizonBook::new(h, &config.trading)).collect(),
+            model_states: pricing_models.iter().map(|m| ModelState::new(m.name(), asset)).collect(),
+            calibrators: pricing_models.iter().map(|_| Calibrator::new()).collect(),
+        }
+    }
+
+    /// The Syncing -> Trading preconditions, for any horizon of this asset
+    fn is_ready(&self) -> bool {
+        self.horizons.iter().any(|h| h.vol_engine.is_ready() && h.active_market.is_some())
+    }
+
+    fn snapshot(&self) -> AssetSnapshot {
+        AssetSnapshot {
+            asset: self.asset,
+            price: self.price,
+            price_timestamp: self.price_timestamp.clone(),
+            horizons: self
+                .horizons
+                .iter()
+                .map(|h| HorizonSnapshot {
+                    name: h.horizon.name.clone(),
+                    active_market: h.active_market.clone(),
+                    volatility: h.vol_engine.state,
+                    entry_gate: h.active_market.as_ref().and_then(|m| h.trading.liquidity.check(m)),
+                })
+                .collect(),
+            models: self.model_states.clone(),
+        }
+    }
+}
+
+/// Core engine loop. Receives events, updates state, runs models, emits actions.
+/// This is the hot path. No locks, no IO in the decision logic.
+///
+/// Runs until `EngineEvent::Shutdown` (after applying the shutdown position
+/// policy) or until every sender of `rx` is dropped. Trades, journal rows
+/// and risk state go to `state.db_tx`, so a DB writer (`db::run_db_writer`)
+/// must be draining it. `restored_positions` are `(model, asset, position)`
+/// triples from `db::take_persisted_positions`.
+pub async fn run_engine(
+    state: Arc<AppState>,
+    mut config: config::AppConfig,
+    mut rx: mpsc::Receiver<EngineEvent>,
+    restored_positions: Vec<(String, Asset, OpenPosition)>,
+) {
+    tracing::info!("engine task started");
+
+    // ── Local engine state (owned, no locks needed) ──
+    let mut engine_state = EngineState::Connecting;
+    let mut exchange = ExchangePhase::Open;
+
+    // Pricing model instances (created once, reused); only the models the
+    // profile enables run, in the order it lists them
+    let enabled_models = models::enabled(&config.models);
+    let pricing_models: Vec<&dyn PricingModel> = enabled_models.iter().map(|m| m.as_ref()).collect();
+
+    // One pipeline per configured asset, each with its own copy of the models
+    let mut pipelines: Vec<Pipeline> = config
+        .assets
+        .iter()
+        .map(|a| Pipeline::new(a.asset, &pricing_models, &config))
+        .collect();
+
+    let restored = !restored_positions.is_empty();
+    for (model_name, asset, pos) in restored_positions {
+        let ms = pipelines
+            .iter_mut()
+            .filter(|p| p.asset == asset)
+            .flat_map(|p| p.model_states.iter_mut())
+            .find(|m| m.name == model_name);
+        match ms {
+            Some(ms) => {
+                tracing::info!(model = ms.name, asset = %asset, trade = %pos.trade_id, market = %pos.market_ticker, "restored open position");
+                ms.current_exposure += pos.entry_price * pos.contracts;
+                ms.open_positions.push(pos);
+            }
+            None => tracing::warn!(model = %model_name, asset = %asset, trade = %pos.trade_id, "persisted position for a model or asset that is not running"),
+        }
+    }
+
+    // Pick up today's realized P/L so a mid-day restart doesn't reset the
+    // daily loss limit
+    let calendar = config.trading_calendar;
+    let mut trading_day = calendar.day_of(state.clock.now());
+    let day_start = calendar.start_of(trading_day).to_rfc3339();
+    match state.db.read(move |conn| db::get_realized_pnl_since(conn, &day_start)).await {
+        Ok(rows) => {
+            for (model_name, asset, pnl) in rows {
+                if let Some(ms) = model_state_mut(&mut pipelines, asset, &model_name) {
+                    ms.daily_pnl = pnl;
+                }
+            }
+        }
+        Err(e) => tracing::error!("failed to restore daily P/L: {e}"),
+    }
+    tracing::info!(trading_day = %trading_day, calendar = %calendar, "trading day");
+
+    if restored {
+        publish_snapshot(&state, engine_state, &pipelines);
+    }
+
+    let mut tick_counter: u64 = 0;
+    let mut journal: Vec<DecisionRecord> =
+        Vec::with_capacity(JOURNAL_BATCH_ROWS + pricing_models.len() * pipelines.len() * config.horizons.len());
+
+    while let Some(event) = rx.recv().await {
+        state.metrics.engine_channel_depth.observe(rx.len() as f64);
+        let kind = event.kind();
+        let is_shutdown = matches!(event, EngineEvent::Shutdown);
+        let started = std::time::Instant::now();
+
+        if matches!(event, EngineEvent::Tick) {
+            roll_trading_day(&mut trading_day, &calendar, &mut pipelines, &state).await;
+        }
+
+        let result = process_event(
+            event,
+            &mut engine_state,
+            &mut exchange,
+            &mut pipelines,
+            &pricing_models,
+            &mut config,
+            &state,
+            &mut tick_counter,
+            &mut journal,
+        )
+        .await;
+
+        state
+            .metrics
+            .engine_event_seconds
+            .observe(&[kind], started.elapsed().as_secs_f64());
+        state.counters.events_processed.fetch_add(1, Ordering::Relaxed);
+
+        if let Err(e) = result {
+            tracing::error!(error = %e, "engine error");
+            state.counters.errors_recovered.fetch_add(1, Ordering::Relaxed);
+
+            // On unrecoverable state corruption, halt
+            if matches!(e, errors::EngineError::StateCorruption(_)) {
+                engine_state = EngineState::Halted;
+                state.broadcast(WsMessage::EngineStateMsg {
+                    state: "halted".into(),
+                    reason: e.to_string(),
+                });
+                tracing::error!("ENGINE HALTED: {e}");
+            }
+        }
+
+        if is_shutdown {
+            break;
+        }
+    }
+
+    tracing::info!("engine task shutting down");
+}
+
+fn model_state_mut<'a>(pipelines: &'a mut [Pipeline], asset: Asset, name: &str) -> Option<&'a mut ModelState> {
+    pipelines
+        .iter_mut()
+        .find(|p| p.asset == asset)
+        .and_then(|p| p.model_states.iter_mut().find(|m| m.name == name))
+}
+
+/// Close out the trading day once `now` has passed its end: write a
+/// summary per model and asset, reset the daily counters and notify the
+/// dashboard. If the process slept through several boundaries, the summary
+/// covers the whole gap and is filed under the last day it was running.
+async fn roll_trading_day(
+    current: &mut chrono::NaiveDate,
+    calendar: &TradingCalendar,
+    pipelines: &mut [Pipeline],
+    state: &Arc<AppState>,
+) {
+    let now = state.clock.now();
+    let today = calendar.day_of(now);
+    if today <= *current {
+        return;
+    }
+    let previous = std::mem::replace(current, today);
+
+    let daily_pnl: Vec<(String, Asset, f64)> = pipelines
+        .iter()
+        .flat_map(|p| p.model_states.iter())
+        .map(|ms| (ms.name.to_string(), ms.asset, ms.daily_pnl))
+        .collect();
+    for pipeline in pipelines.iter_mut() {
+        for cmd in trading_day::close_day(calendar, previous, today, &mut pipeline.model_states) {
+            let _ = state.db_tx.send(cmd).await;
+        }
+    }
+
+    let retention_days = state.config.decision_retention_days;
+    if retention_days > 0 {
+        let before = (now - chrono::Duration::days(i64::from(retention_days))).to_rfc3339();
+        let _ = state.db_tx.send(DbCommand::PruneDecisions { before }).await;
+    }
+
+    tracing::info!(previous_day = %previous, trading_day = %today, "trading day rollover");
+    state.broadcast(WsMessage::DayRollover {
+        previous_day: previous.to_string(),
+        trading_day: today.to_string(),
+        daily_pnl,
+        timestamp: now.to_rfc3339(),
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_event(
+    event: EngineEvent,
+    engine_state: &mut EngineState,
+    exchange: &mut ExchangePhase,
+    pipelines: &mut [Pipeline],
+    pricing_models: &[&dyn PricingModel],
+    config: &mut config::AppConfig,
+    state: &Arc<AppState>,
+    tick_counter: &mut u64,
+    journal: &mut Vec<DecisionRecord>,
+) -> Result<(), errors::EngineError> {
+    match event {
+        EngineEvent::Price { asset, price, timestamp_ms } => {
+            let Some(p) = pipelines.iter_mut().find(|p| p.asset == asset) else {
+                tracing::debug!(asset = %asset, "price for an asset that is not traded, ignored");
+                return Ok(());
+            };
+            p.price = price;
+            state.counters.prices_received.fetch_add(1, Ordering::Relaxed);
+
+            // Store in ring buffer
+            if p.prices.len() >= 2000 {
+                p.prices.pop_front();
+            }
+            p.prices.push_back((timestamp_ms, price));
+
+            // Update each horizon's volatility estimate
+            for h in p.horizons.iter_mut() {
+                h.vol_engine.update(price);
+            }
+
+            let ts = chrono::DateTime::from_timestamp_millis(timestamp_ms)
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default();
+            p.price_timestamp = ts.clone();
+
+            // State transitions
+            match engine_state {
+                EngineState::Connecting => {
+                    *engine_state = EngineState::Syncing;
+                    tracing::info!(asset = %asset, price = price, "first price received, entering Syncing");
+                    state.broadcast(WsMessage::EngineStateMsg {
+                        state: "syncing".into(),
+                        reason: "first price received".into(),
+                    });
+                }
+                EngineState::Syncing if p.is_ready() => {
+                    transition(engine_state, open_state(*exchange), "vol ready, market active", state);
+                }
+                _ => {}
+            }
+
+            // Broadcast price
+            state.broadcast(WsMessage::Price {
+                asset,
+                price,
+                timestamp: ts.clone(),
+            });
+
+            // Every price is kept: backtests replay the exact series the
+            // volatility engine saw (writes are batched, so this is cheap)
+            let _ = state.db_tx.send(DbCommand::InsertPrice {
+                asset,
+                timestamp: ts,
+                price,
+            }).await;
+        }
+
+        EngineEvent::MarketUpdate(market) => {
+            let Some(p) = pipelines.iter_mut().find(|p| p.asset == market.asset) else {
+                tracing::debug!(asset = %market.asset, ticker = %market.ticker, "market for an asset that is not traded, ignored");
+                return Ok(());
+            };
+            let Some(h) = p.horizons.iter_mut().find(|h| h.horizon.name == market.horizon) else {
+                tracing::debug!(horizon = %market.horizon, ticker = %market.ticker, "market for a horizon that is not traded, ignored");
+                return Ok(());
+            };
+
+            // Broadcast market state
+            let ttl = compute_ttl_secs(&market.close_time, state.clock.now());
+
+            state.broadcast(WsMessage::MarketState {
+                asset: market.asset,
+                horizon: market.horizon.clone(),
+                ticker: market.ticker.clone(),
+                strike: market.strike,
+                ttl_seconds: ttl,
+                yes_bid: market.yes_bid.clone(),
+                yes_ask: market.yes_ask.clone(),
+                status: market.status.clone(),
+                depth: market.depth.clone(),
+                entry_gate: h.trading.liquidity.check(&market),
+            });
+
+            // Insert to DB if new. Positions on the old market stay with
+            // their models (they are keyed by market) until it settles.
+            if h.active_market.as_ref().map(|m| &m.ticker) != Some(&market.ticker) {
+                tracing::info!(
+                    asset = %market.asset,
+                    horizon = %market.horizon,
+                    ticker = %market.ticker,
+                    strike = ?market.strike,
+                    yes_ask = ?market.yes_ask,
+                    "switching to new market"
+                );
+
+                let _ = state.db_tx.send(DbCommand::InsertMarket {
+                    ticker: market.ticker.clone(),
+                    event_ticker: market.event_ticker.clone(),
+                    series_ticker: market.series_ticker.clone(),
+                    strike_price: market.strike,
+                    open_time: String::new(),
+                    close_time: market.close_time.clone(),
+                    expiration_time: market.expiration_time.clone(),
+                }).await;
+            }
+
+            let quote = |q: &Option<String>| q.as_deref().and_then(|v| v.parse::<f64>().ok());
+            let _ = state.db_tx.send(DbCommand::InsertMarketQuote {
+                timestamp: state.clock.now().to_rfc3339(),
+                ticker: market.ticker.clone(),
+                yes_bid: quote(&market.yes_bid),
+                yes_ask: quote(&market.yes_ask),
+                no_bid: quote(&market.no_bid),
+                no_ask: quote(&market.no_ask),
+                last_price: quote(&market.last_price),
+                status: market.status.clone(),
+            }).await;
+
+            h.active_market = Some(*market);
+
+            // Check if we should transition to Trading
+            if *engine_state == EngineState::Syncing && p.is_ready() {
+                transition(engine_state, open_state(*exchange), "market + vol ready", state);
+            }
+        }
+
+        EngineEvent::MarketSettled { asset, ticker, result } => {
+            let Some(p) = pipelines.iter_mut().find(|p| p.asset == asset) else {
+                tracing::warn!(asset = %asset, ticker = %ticker, "settlement for an asset that is not traded, ignored");
+                return Ok(());
+            };
+            tracing::info!(asset = %asset, ticker = %ticker, result = %result, "processing market settlement");
+
+            // Get pending trades from DB
+            let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+            let _ = state.db_tx.send(DbCommand::GetPendingTrades {
+                market_ticker: ticker.clone(),
+                reply: reply_tx,
+            }).await;
+
+            if let Ok(pending) = reply_rx.await {
+                tracing::info!(
+                    ticker = %ticker,
+                    pending_count = pending.len(),
+                    "settling trades"
+                );
+
+                let now = state.clock.now().to_rfc3339();
+                let actions = simulator::settle_trades(
+                    &mut p.model_states,
+                    &mut p.calibrators,
+                    &ticker,
+                    &result,
+                    &pending,
+                    &now,
+                );
+
+                execute_actions(actions, state).await;
+
+                // Log post-settlement P/L
+                for ms in p.model_states.iter() {
+                    tracing::info!(
+                        model = ms.name,
+                        asset = %asset,
+                        pnl = ms.cumulative_pnl,
+                        trades = ms.total_trades,
+                        wins = ms.winning_trades,
+                        "post-settlement state"
+                    );
+                }
+
+                // Immediately update snapshot so dashboard sees P/L change
+                publish_snapshot(state, *engine_state, pipelines);
+            } else {
+                tracing::warn!(ticker = %ticker, "failed to get pending trades for settlement");
+            }
+
+            // Clear the horizon trading it -- scanner will find the next one
+            if let Some(p) = pipelines.iter_mut().find(|p| p.asset == asset) {
+                for h in p.horizons.iter_mut().filter(|h| h.active_market.as_ref().is_some_and(|m| m.ticker == ticker)) {
+                    h.active_market = None;
+                }
+            }
+
+            // Update market result in DB
+            let _ = state.db_tx.send(DbCommand::UpdateMarketResult {
+                ticker,
+                result,
+                settlement_value: None,
+            }).await;
+        }
+
+        EngineEvent::KalshiApiHealth(health) => {
+            // Market quotes stop refreshing while the breaker is open; the
+            // dashboard shows why
+            tracing::info!(breaker = %health.breaker, failures = health.consecutive_failures, "kalshi API health");
+            state.broadcast(WsMessage::KalshiApi {
+                breaker: health.breaker.to_string(),
+                consecutive_failures: health.consecutive_failures,
+                last_error: health.last_error.clone(),
+            });
+            state.snapshot_tx.send_modify(|snapshot| snapshot.kalshi_api = *health);
+        }
+
+        EngineEvent::Exchange(info) => {
+            let previous = state.snapshot_rx.borrow().exchange.announcements.clone();
+            for a in info.announcements.iter().filter(|a| !previous.contains(a)) {
+                tracing::warn!(level = %a.kind, message = %a.message, "exchange announcement");
+                state.broadcast(WsMessage::Announcement {
+                    level: a.kind.clone(),
+                    message: a.message.clone(),
+                    delivery_time: a.delivery_time.clone(),
+                });
+            }
+
+            if info.phase != *exchange {
+                *exchange = info.phase;
+                state.broadcast(WsMessage::ExchangeStatus {
+                    phase: info.phase.to_string(),
+                    reason: info.reason.clone(),
+                    estimated_resume_time: info.estimated_resume_time.clone(),
+                });
+                // Only the trading states follow the exchange; an operator
+                // pause or halt, or a sync in progress, stays as it is
+                if matches!(
+                    *engine_state,
+                    EngineState::Trading | EngineState::ExchangeClosed | EngineState::Maintenance
+                ) {
+                    let reason = format!("exchange {}: {}", info.phase, info.reason);
+                    transition(engine_state, open_state(*exchange), &reason, state);
+                }
+            }
+
+            state.snapshot_tx.send_modify(|snapshot| snapshot.exchange = *info);
+            publish_snapshot(state, *engine_state, pipelines);
+        }
+
+        EngineEvent::Tick => {
+            *tick_counter += 1;
+            state.counters.ticks_processed.fetch_add(1, Ordering::Relaxed);
+
+            // Only run models while Trading, or while entries are blocked
+            // (exits still managed)
+            let entries_blocked = match *engine_state {
+                EngineState::Trading => None,
+                EngineState::Paused => Some("engine_paused"),
+                EngineState::ExchangeClosed => Some("exchange_closed"),
+                EngineState::Maintenance => Some("exchange_maintenance"),
+                EngineState::Connecting | EngineState::Syncing | EngineState::Halted => return Ok(()),
+            };
+
+            let now_t = state.clock.now();
+            let now = now_t.to_rfc3339();
+
+            // Portfolio caps cover every asset: one book for the tick, which
+            // each horizon's orders are added to as they are accepted
+            let mut book = portfolio_book(pipelines, pricing_models, now_t);
+
+            for p in pipelines.iter_mut() {
+                // Another asset may have brought the engine to Trading while
+                // this one is still warming up
+                if p.price <= 0.0 {
+                    continue;
+                }
+
+                // Each horizon's market, priced with its own volatility
+                let vols = horizon_vols(&p.horizons);
+                for h in p.horizons.iter().filter(|h| h.vol_engine.is_ready()) {
+                    // Run the decision loop (hot path, pure computation)
+                    let actions = simulator::run_tick(
+                        pricing_models,
+                        &mut p.model_states,
+                        &mut p.calibrators,
+                        &h.vol_engine.state,
+                        &vols,
+                        &h.active_market,
+                        p.price,
+                        &h.trading,
+                        &mut book,
+                        &now,
+                        *tick_counter,
+                        entries_blocked,
+                        journal,
+                    );
+
+                    state.counters.decisions_made.fetch_add(1, Ordering::Relaxed);
+
+                    // Execute actions (DB writes + WS broadcasts)
+                    execute_actions(actions, state).await;
+                }
+
+                // Tail risk is too heavy for every tick; refresh it
+                // periodically. It covers positions on every horizon, each at
+                // its own horizon's volatility.
+                if *tick_counter % TAIL_RISK_EVERY_TICKS == 0 && !vols.is_empty() {
+                    let report = risk::var::tail_report(
+                        pricing_models,
+                        &p.model_states,
+                        p.price,
+                        &vols,
+                        config.trading.risk.tail.scenarios,
+                        *tick_counter,
+                        &now,
+                    );
+                    state.tail_risk.write().unwrap_or_else(|e| e.into_inner()).insert(p.asset, report);
+                }
+            }
+
+            if journal.len() >= JOURNAL_BATCH_ROWS {
+                let records = std::mem::take(journal);
+                let _ = state.db_tx.send(DbCommand::InsertDecisions { records }).await;
+            }
+
+            // Update snapshot for dashboard (watch channel -- cheap, no lock)
+            if *tick_counter % 2 == 0 {
+                publish_snapshot(state, *engine_state, pipelines);
+            }
+        }
+
+        EngineEvent::Shutdown => {
+            let now = state.clock.now().to_rfc3339();
+            let policy = config.shutdown_position_policy;
+            let open: usize = pipelines
+                .iter()
+                .flat_map(|p| p.model_states.iter())
+                .map(|m| m.open_positions.len())
+                .sum();
+            tracing::warn!(policy = %policy, open_positions = open, "shutdown event received");
+
+            if !journal.is_empty() {
+                let records = std::mem::take(journal);
+                let _ = state.db_tx.send(DbCommand::InsertDecisions { records }).await;
+            }
+
+            match policy {
+                config::ShutdownPolicy::Flatten => {
+                    for p in pipelines.iter_mut() {
+                        let markets: Vec<_> = p.horizons.iter().filter_map(|h| h.active_market.as_ref()).collect();
+                        let actions = simulator::flatten_positions(&mut p.model_states, &markets, "shutdown", config.trading.strategy.fee_rate, &now);
+                        execute_actions(actions, state).await;
+                    }
+                }
+                config::ShutdownPolicy::Hold => {}
+                config::ShutdownPolicy::Persist => {
+                    // The asset comes back from the trade row on restart
+                    let positions = pipelines
+                        .iter()
+                        .flat_map(|p| p.model_states.iter())
+                        .flat_map(|m| m.open_positions.iter().map(|p| (m.name.to_string(), p.clone())))
+                        .collect();
+                    let _ = state.db_tx.send(DbCommand::PersistPositions { positions, timestamp: now.clone() }).await;
+                }
+            }
+
+            // Final risk state so the dashboard and restart see the last numbers
+            for ms in pipelines.iter().flat_map(|p| p.model_states.iter()) {
+                let _ = state.db_tx.send(DbCommand::UpdateRiskState {
+                    model_name: ms.name.to_string(),
+                    asset: ms.asset,
+                    exposure: ms.current_exposure,
+                    daily_pnl: ms.daily_pnl,
+                    max_drawdown: ms.max_drawdown,
+                    peak_equity: ms.peak_equity,
+                    total_trades: ms.total_trades,
+                    winning_trades: ms.winning_trades,
+                }).await;
+            }
+
+            transition(engine_state, EngineState::Halted, &format!("shutdown ({policy})"), state);
+            publish_snapshot(state, *engine_state, pipelines);
+        }
+
+        EngineEvent::Pause { model, reason } => {
+            match model {
+                // A model is paused on every asset it runs on
+                Some(name) => {
+                    for ms in pipelines.iter_mut().flat_map(|p| p.model_states.iter_mut()).filter(|m| m.name == name) {
+                        ms.paused = true;
+                        tracing::warn!(model = ms.name, asset = %ms.asset, reason = %reason, "model paused by operator");
+                    }
+                }
+                None => {
+                    if *engine_state == EngineState::Halted {
+                        tracing::warn!("pause ignored: engine is halted");
+                    } else {
+                        transition(engine_state, EngineState::Paused, &reason, state);
+                    }
+                }
+            }
+            publish_snapshot(state, *engine_state, pipelines);
+        }
+
+        EngineEvent::Resume { model, reason } => {
+            match model {
+                Some(name) => {
+                    for ms in pipelines.iter_mut().flat_map(|p| p.model_states.iter_mut()).filter(|m| m.name == name) {
+                        ms.paused = false;
+                        tracing::warn!(model = ms.name, asset = %ms.asset, reason = %reason, "model resumed by operator");
+                    }
+                }
+                None => match *engine_state {
+                    EngineState::Paused => {
+                        let next = ready_state(pipelines, *exchange);
+                        transition(engine_state, next, &reason, state);
+                    }
+                    EngineState::Halted => tracing::warn!("resume ignored: engine is halted, use unhalt"),
+                    _ => {}
+                },
+            }
+            publish_snapshot(state, *engine_state, pipelines);
+        }
+
+        EngineEvent::Flatten { reason } => {
+            let now = state.clock.now().to_rfc3339();
+            for p in pipelines.iter_mut() {
+                let markets: Vec<_> = p.horizons.iter().filter_map(|h| h.active_market.as_ref()).collect();
+                let actions = simulator::flatten_positions(&mut p.model_states, &markets, "operator_flatten", config.trading.strategy.fee_rate, &now);
+                tracing::warn!(asset = %p.asset, reason = %reason, actions = actions.len(), "flattening all positions");
+                execute_actions(actions, state).await;
+            }
+
+            // Flatten without a pause would just re-enter on the next signal
+            if *engine_state != EngineState::Halted {
+                transition(engine_state, EngineState::Paused, &reason, state);
+            }
+            publish_snapshot(state, *engine_state, pipelines);
+        }
+
+        EngineEvent::Halt { reason } => {
+            tracing::error!(reason = %reason, "ENGINE HALTED by operator");
+            transition(engine_state, EngineState::Halted, &reason, state);
+            publish_snapshot(state, *engine_state, pipelines);
+        }
+
+        EngineEvent::ConfigUpdate(trading) => {
+            let changed = config.trading.changed_fields(&trading);
+            for h in pipelines.iter_mut().flat_map(|p| p.horizons.iter_mut()) {
+                h.trading = h.horizon.params(&trading);
+                h.vol_engine.params = h.trading.volatility;
+            }
+            config.trading = *trading;
+            tracing::warn!(changed = ?changed, "trading params updated");
+        }
+
+        EngineEvent::Unhalt { reason } => {
+            if *engine_state == EngineState::Halted {
+                // Always re-sync: prices and the active market may be stale
+                transition(engine_state, EngineState::Syncing, &reason, state);
+                publish_snapshot(state, *engine_state, pipelines);
+            } else {
+                tracing::warn!(state = %engine_state, "unhalt ignored: engine is not halted");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Move the state machine to `next` and tell dashboard clients why.
+fn transition(engine_state: &mut EngineState, next: EngineState, reason: &str, state: &Arc<AppState>) {
+    tracing::info!(from = %engine_state, to = %next, reason = reason, "engine state change");
+    *engine_state = next;
+    state.broadcast(WsMessage::EngineStateMsg {
+        state: next.to_string(),
+        reason: reason.to_string(),
+    });
+}
+
+/// State to return to after an operator pause: the exchange's trading
+/// state only if the normal Syncing -> Trading preconditions still hold
+/// for at least one asset.
+fn ready_state(pipelines: &[Pipeline], exchange: ExchangePhase) -> EngineState {
+    if pipelines.iter().any(Pipeline::is_ready) {
+        open_state(exchange)
+    } else {
+        EngineState::Syncing
+    }
+}
+
+/// Trading while the exchange is open; otherwise the state that manages
+/// open positions without new entries.
+fn open_state(exchange: ExchangePhase) -> EngineState {
+    match exchange {
+        ExchangePhase::Open => EngineState::Trading,
+        ExchangePhase::Closed => EngineState::ExchangeClosed,
+        ExchangePhase::Maintenance => EngineState::Maintenance,
+    }
+}
+
+/// Every model's exposure and daily P/L on every asset, for the portfolio
+/// caps. Deltas are priced with the first (reference) model.
+fn portfolio_book(
+    pipelines: &[Pipeline],
+    pricing_models: &[&dyn PricingModel],
+    now: chrono::DateTime<chrono::Utc>,
+) -> PortfolioBook {
+    let mut book = PortfolioBook::default();
+    let Some(model) = pricing_models.first() else {
+        return book;
+    };
+    for p in pipelines {
+        book.add_states(*model, &p.model_states, p.price, &horizon_vols(&p.horizons), now);
+    }
+    book
+}
+
+/// Each warm horizon's volatility, for pricing an asset's positions
+fn horizon_vols(horizons: &[HorizonBook]) -> HorizonVols<'_> {
+    let mut vols = HorizonVols::default();
+    for h in horizons.iter().filter(|h| h.vol_engine.is_ready()) {
+        vols.push(&h.horizon.name, &h.vol_engine.state);
+    }
+    vols
+}
+
+/// Push the latest engine state to the watch channel (dashboard + REST).
+fn publish_snapshot(state: &Arc<AppState>, engine_state: EngineState, pipelines: &[Pipeline]) {
+    let assets = pipelines.iter().map(Pipeline::snapshot).collect();
+    // `kalshi_api` and `exchange` are set by `KalshiApiHealth` and `Exchange`
+    // events and left as they are
+    state.snapshot_tx.send_modify(|s| {
+        s.engine_state = engine_state;
+        s.assets = assets;
+    });
+}
+
+/// Execute engine actions (cold path -- involves channel sends)
+async fn execute_actions(
+    actions: smallvec::SmallVec<[EngineAction; 16]>,
+    state: &Arc<AppState>,
+) {
+    for action in actions {
+        match action {
+            EngineAction::PlaceTrade { .. } => {
+                state.counters.trades_placed.fetch_add(1, Ordering::Relaxed);
+            }
+            EngineAction::ExitTrade { model_name, pnl, reason, .. } => {
+                tracing::info!(model = model_name, pnl = pnl, reason = reason, "trade exited");
+            }
+            EngineAction::BroadcastUpdate(msg) => {
+                state.broadcast(msg);
+            }
+            EngineAction::DbWrite(cmd) => {
+                let _ = state.db_tx.send(cmd).await;
+            }
+            EngineAction::SettleTrade { .. } => {
+                // Logging handled in simulator
+            }
+        }
+    }
+}
+
+fn compute_ttl_secs(close_time: &str, now: chrono::DateTime<chrono::Utc>) -> f64 {
+    simulator::compute_ttl(close_time, now).max(0.0)
+}
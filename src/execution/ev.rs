@@ -12,6 +12,7 @@
 /// All inputs are f64. Pure function, no side effects, no allocations.
 
 /// Parameters for EV computation. Stack-allocated.
+#[allow(clippy::empty_line_after_doc_comments)]
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct EvParams {
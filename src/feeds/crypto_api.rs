@@ -1,16 +1,22 @@
+use crate::asset::Asset;
 use crate::errors::{EngineError, EngineResult};
+use crate::metrics::Metrics;
 use crate::state::EngineEvent;
 use reqwest::Client;
+use std::sync::Arc;
 use tokio::sync::mpsc;
 
-/// FreeCryptoAPI REST client. Polls BTC price at configurable interval.
-/// Sends BtcPrice events to engine via bounded channel.
-pub async fn run_btc_feed(
+/// FreeCryptoAPI REST client. Polls one asset's price at configurable
+/// interval; the binary runs one feed per configured asset.
+/// Sends Price events to engine via bounded channel.
+pub async fn run_price_feed(
+    asset: Asset,
     api_key: String,
     base_url: String,
     engine_tx: mpsc::Sender<EngineEvent>,
+    metrics: Arc<Metrics>,
 ) {
-    tracing::info!("BTC price feed started (FreeCryptoAPI)");
+    tracing::info!(asset = %asset, "price feed started (FreeCryptoAPI)");
 
     let client = Client::builder()
         .timeout(std::time::Duration::from_secs(5))
@@ -24,29 +30,31 @@ pub async fn run_btc_feed(
     loop {
         interval.tick().await;
 
-        match fetch_btc_price(&client, &api_key, &base_url).await {
+        match fetch_price(asset, &client, &api_key, &base_url, &metrics).await {
             Ok(price) => {
                 consecutive_errors = 0;
                 let timestamp_ms = chrono::Utc::now().timestamp_millis();
 
                 if engine_tx
-                    .send(EngineEvent::BtcPrice {
+                    .send(EngineEvent::Price {
+                        asset,
                         price,
                         timestamp_ms,
                     })
                     .await
                     .is_err()
                 {
-                    tracing::error!("engine channel closed, btc feed shutting down");
+                    tracing::error!(asset = %asset, "engine channel closed, price feed shutting down");
                     return;
                 }
             }
             Err(e) => {
                 consecutive_errors += 1;
                 tracing::warn!(
+                    asset = %asset,
                     error = %e,
                     consecutive = consecutive_errors,
-                    "btc price fetch failed"
+                    "price fetch failed"
                 );
 
                 // Exponential backoff on repeated failures (cap at 30s)
@@ -94,15 +102,24 @@ struct SymbolData {
     highest: Option<String>,
 }
 
-async fn fetch_btc_price(client: &Client, api_key: &str, base_url: &str) -> EngineResult<f64> {
-    let url = format!("{}/getData?symbol=BTC", base_url.trim_end_matches('/'));
+async fn fetch_price(
+    asset: Asset,
+    client: &Client,
+    api_key: &str,
+    base_url: &str,
+    metrics: &Metrics,
+) -> EngineResult<f64> {
+    let url = format!("{}/getData?symbol={}", base_url.trim_end_matches('/'), asset.symbol());
 
+    let start = std::time::Instant::now();
     let resp = client
         .get(&url)
         .header("Authorization", format!("Bearer {api_key}"))
         .send()
-        .await
-        .map_err(|e| EngineError::CryptoFeed(format!("request failed: {e}")))?;
+        .await;
+    let status = resp.as_ref().ok().map(|r| r.status().as_u16());
+    metrics.observe_api("crypto", "/getData", status, start.elapsed().as_secs_f64());
+    let resp = resp.map_err(|e| EngineError::CryptoFeed(format!("request failed: {e}")))?;
 
     let status = resp.status();
     if !status.is_success() {
@@ -121,7 +138,7 @@ async fn fetch_btc_price(client: &Client, api_key: &str, base_url: &str) -> Engi
         .as_ref()
         .and_then(|syms| syms.first())
         .and_then(|s| s.last.as_deref())
-        .ok_or_else(|| EngineError::CryptoFeed("no BTC symbol in response".into()))?;
+        .ok_or_else(|| EngineError::CryptoFeed(format!("no {} symbol in response", asset.symbol())))?;
 
     let price: f64 = price_str
         .parse()
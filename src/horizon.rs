@@ -0,0 +1,140 @@
+//! Expiry horizons: the engine trades one market per horizon per asset at
+//! the same time, e.g. the contract closing this hour next to the one
+//! closing at the end of the day.
+//!
+//! A horizon is a band of time-to-close. The scanner picks the soonest
+//! closing market inside each band, and the engine prices it with a
+//! volatility estimate and entry cutoff tuned for that band (a slower EWMA
+//! suits a day-long contract better than the 2-second returns an hourly
+//! one reacts to). Positions belong to their market, so a model can hold
+//! one position per horizon.
+
+use crate::config::TradingParams;
+
+/// The horizon the built-in config runs: every market, which is how the
+/// engine traded before horizons existed.
+pub const DEFAULT_HORIZON: &str = "hourly";
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Horizon {
+    /// Stored on trades and shown on the dashboard, e.g. "hourly"
+    pub name: String,
+    /// Markets closing at least this many seconds from now
+    #[serde(default)]
+    pub min_ttl_secs: f64,
+    /// ... and less than this many; unset = no upper bound
+    #[serde(default)]
+    pub max_ttl_secs: Option<f64>,
+    /// Overrides `volatility.ewma_lambda` for this horizon's estimate
+    #[serde(default)]
+    pub ewma_lambda: Option<f64>,
+    /// Overrides `strategy.min_entry_ttl` for this horizon's markets
+    #[serde(default)]
+    pub min_entry_ttl: Option<f64>,
+}
+
+impl Default for Horizon {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_HORIZON.to_string(),
+            min_ttl_secs: 0.0,
+            max_ttl_secs: None,
+            ewma_lambda: None,
+            min_entry_ttl: None,
+        }
+    }
+}
+
+impl Horizon {
+    /// Whether a market `ttl_seconds` from close falls in this horizon.
+    pub fn contains(&self, ttl_seconds: f64) -> bool {
+        ttl_seconds >= self.min_ttl_secs && self.max_ttl_secs.is_none_or(|max| ttl_seconds < max)
+    }
+
+    /// `trading` with this horizon's overrides applied.
+    pub fn params(&self, trading: &TradingParams) -> TradingParams {
+        let mut params = trading.clone();
+        if let Some(lambda) = self.ewma_lambda {
+            params.volatility.ewma_lambda = lambda;
+        }
+        if let Some(ttl) = self.min_entry_ttl {
+            params.strategy.min_entry_ttl = ttl;
+        }
+        params
+    }
+}
+
+/// Names must be unique and bands must not overlap, so every market belongs
+/// to at most one horizon.
+pub fn validate(horizons: &[Horizon], errors: &mut Vec<String>) {
+    if horizons.is_empty() {
+        errors.push("horizons must list at least one horizon".to_string());
+    }
+    for (i, h) in horizons.iter().enumerate() {
+        let field = format!("horizons.{}", h.name);
+        if h.name.trim().is_empty() {
+            errors.push("horizons: every horizon needs a name".to_string());
+        } else if horizons[..i].iter().any(|o| o.name == h.name) {
+            errors.push(format!("horizons: {:?} listed twice", h.name));
+        }
+        if h.min_ttl_secs < 0.0 {
+            errors.push(format!("{field}.min_ttl_secs must be >= 0, got {}", h.min_ttl_secs));
+        }
+        if let Some(max) = h.max_ttl_secs.filter(|max| *max <= h.min_ttl_secs) {
+            errors.push(format!("{field}.max_ttl_secs must be above min_ttl_secs, got {max}"));
+        }
+        if let Some(lambda) = h.ewma_lambda.filter(|l| !(*l > 0.0 && *l < 1.0)) {
+            errors.push(format!("{field}.ewma_lambda must be in (0, 1), got {lambda}"));
+        }
+        if let Some(ttl) = h.min_entry_ttl.filter(|t| *t < 0.0) {
+            errors.push(format!("{field}.min_entry_ttl must be >= 0, got {ttl}"));
+        }
+        let overlaps = |o: &Horizon| {
+            o.min_ttl_secs < h.max_ttl_secs.unwrap_or(f64::INFINITY)
+                && h.min_ttl_secs < o.max_ttl_secs.unwrap_or(f64::INFINITY)
+        };
+        if let Some(other) = horizons[..i].iter().find(|o| overlaps(o)) {
+            errors.push(format!("horizons: {:?} and {:?} overlap", other.name, h.name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn band(name: &str, min: f64, max: Option<f64>) -> Horizon {
+        Horizon { name: name.into(), min_ttl_secs: min, max_ttl_secs: max, ..Horizon::default() }
+    }
+
+    #[test]
+    fn test_bands_and_overrides() {
+        let hourly = band("hourly", 0.0, Some(3600.0));
+        assert!(hourly.contains(0.0) && hourly.contains(3599.0));
+        assert!(!hourly.contains(3600.0));
+        assert!(Horizon::default().contains(86_400.0 * 30.0));
+
+        let daily = Horizon { ewma_lambda: Some(0.99), min_entry_ttl: Some(1800.0), ..band("daily", 3600.0, None) };
+        let params = daily.params(&TradingParams::default());
+        assert_eq!(params.volatility.ewma_lambda, 0.99);
+        assert_eq!(params.strategy.min_entry_ttl, 1800.0);
+        assert_eq!(hourly.params(&TradingParams::default()), TradingParams::default());
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut errors = Vec::new();
+        validate(&[band("hourly", 0.0, Some(3600.0)), band("daily", 3600.0, None)], &mut errors);
+        assert!(errors.is_empty(), "{errors:?}");
+
+        validate(
+            &[band("hourly", 0.0, Some(3600.0)), band("daily", 1800.0, None), band("daily", 7200.0, Some(7200.0))],
+            &mut errors,
+        );
+        assert_eq!(errors.len(), 4, "{errors:?}");
+        assert!(errors.iter().any(|e| e.contains("\"hourly\" and \"daily\" overlap")));
+        assert!(errors.iter().any(|e| e.contains("listed twice")));
+        assert!(errors.iter().any(|e| e.contains("max_ttl_secs")));
+    }
+}
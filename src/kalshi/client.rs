@@ -1,18 +1,31 @@
 use super::auth::KalshiAuth;
+use super::limits::{self, ApiHealth, BreakerConfig, CircuitBreaker, RateLimiter, RetryPolicy};
 use super::types::*;
+use crate::clock::Clock;
 use crate::errors::{EngineError, EngineResult};
+use crate::metrics::Metrics;
 use reqwest::Client;
+use std::sync::Arc;
+use std::time::Instant;
 
 /// Kalshi REST API client. All methods return Result, never panic.
+///
+/// Every call waits on a token bucket shared by all clones, retries 429s,
+/// 5xx and network errors with jittered backoff, and goes through a
+/// circuit breaker (see `limits`).
 #[derive(Clone)]
 pub struct KalshiClient {
     client: Client,
     base_url: String,
     auth: KalshiAuth,
+    metrics: Arc<Metrics>,
+    limiter: Arc<RateLimiter>,
+    breaker: Arc<CircuitBreaker>,
+    retry: RetryPolicy,
 }
 
 impl KalshiClient {
-    pub fn new(base_url: &str, auth: KalshiAuth) -> Self {
+    pub fn new(base_url: &str, auth: KalshiAuth, metrics: Arc<Metrics>) -> Self {
         Self {
             client: Client::builder()
                 .timeout(std::time::Duration::from_secs(10))
@@ -21,48 +34,163 @@ impl KalshiClient {
                 .unwrap_or_default(),
             base_url: base_url.trim_end_matches('/').to_string(),
             auth,
+            metrics,
+            limiter: Arc::new(RateLimiter::new(limits::DEFAULT_READS_PER_SEC)),
+            breaker: Arc::new(CircuitBreaker::new(BreakerConfig::default(), Clock::System)),
+            retry: RetryPolicy::default(),
         }
     }
 
-    async fn auth_get<T: serde::de::DeserializeOwned>(&self, path: &str) -> EngineResult<T> {
-        let url = format!("{}{}", self.base_url, path);
-        let (key_id, timestamp, signature) = self.auth.sign_request("GET", path, "")?;
+    /// Replace the retry policy.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
 
-        let resp = self
-            .client
-            .get(&url)
-            .header("KALSHI-ACCESS-KEY", &key_id)
-            .header("KALSHI-ACCESS-TIMESTAMP", &timestamp)
-            .header("KALSHI-ACCESS-SIGNATURE", &signature)
-            .send()
-            .await?;
+    /// Replace the circuit breaker; `clock` times its cooldown.
+    pub fn with_breaker(mut self, config: BreakerConfig, clock: Clock) -> Self {
+        self.breaker = Arc::new(CircuitBreaker::new(config, clock));
+        self
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            return Err(EngineError::KalshiApi {
-                status: status.as_u16(),
-                body,
-            });
+    /// Requests per second allowed by the token bucket.
+    pub fn with_rate_limit(self, per_sec: f64) -> Self {
+        self.limiter.set_rate(per_sec);
+        self
+    }
+
+    /// Size the token bucket to the account's read limit. Leaves the
+    /// default in place (and returns the error) if the limits can't be read.
+    pub async fn apply_account_limits(&self) -> EngineResult<AccountLimitsResponse> {
+        let limits = self.get_account_limits().await?;
+        if let Some(read) = limits.read_limit.filter(|r| *r > 0.0) {
+            self.limiter.set_rate(read);
         }
+        tracing::info!(
+            tier = limits.usage_tier.as_deref().unwrap_or("unknown"),
+            reads_per_sec = self.limiter.rate(),
+            "kalshi rate limit"
+        );
+        Ok(limits)
+    }
+
+    /// Breaker state, recent failures and the current rate limit
+    pub fn health(&self) -> ApiHealth {
+        self.breaker.health(self.limiter.rate())
+    }
 
-        resp.json::<T>().await.map_err(|e| EngineError::Parse(format!("GET {path}: {e}")))
+    /// `endpoint` is the path template (e.g. "/markets/{ticker}") used as the
+    /// metrics label, so per-ticker paths don't blow up series cardinality.
+    async fn auth_get<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &'static str,
+        path: &str,
+    ) -> EngineResult<T> {
+        self.get(endpoint, path, true).await
+    }
+
+    async fn public_get<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &'static str,
+        path: &str,
+    ) -> EngineResult<T> {
+        self.get(endpoint, path, false).await
     }
 
-    async fn public_get<T: serde::de::DeserializeOwned>(&self, path: &str) -> EngineResult<T> {
+    /// One logical call: breaker check, then attempts until success, a
+    /// non-retryable error or the retry policy gives up.
+    async fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &'static str,
+        path: &str,
+        signed: bool,
+    ) -> EngineResult<T> {
+        self.breaker.allow()?;
+
+        let mut attempt = 0;
+        let result = loop {
+            attempt += 1;
+            self.limiter.acquire().await;
+            let (result, retry_after) = self.attempt(endpoint, path, signed).await;
+            let err = match result {
+                Err(e) if limits::is_degradation(&e) => e,
+                other => break other,
+            };
+            if let Some(wait) = retry_after {
+                self.limiter.hold_for(wait);
+            }
+            let delay = self.retry.delay(attempt, retry_after, &mut rand::thread_rng());
+            match delay {
+                Some(delay) => {
+                    tracing::debug!(endpoint, attempt, delay_ms = delay.as_millis() as u64, error = %err, "retrying kalshi request");
+                    self.metrics.api_retries.inc(&["kalshi", endpoint]);
+                    tokio::time::sleep(delay).await;
+                }
+                None => break Err(err),
+            }
+        };
+
+        match &result {
+            Err(e) if limits::is_degradation(e) => self.breaker.record_failure(e),
+            _ => self.breaker.record_success(),
+        }
+        result
+    }
+
+    /// A single request. Also returns the `Retry-After` delay of a 429.
+    async fn attempt<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &'static str,
+        path: &str,
+        signed: bool,
+    ) -> (EngineResult<T>, Option<std::time::Duration>) {
         let url = format!("{}{}", self.base_url, path);
-        let resp = self.client.get(&url).send().await?;
+        let mut req = self.client.get(&url);
+        if signed {
+            // Signed per attempt: the timestamp must be fresh
+            let (key_id, timestamp, signature) =
+                match self.auth.sign_request("GET", &signing_path(&self.base_url, path), "") {
+                    Ok(signed) => signed,
+                    Err(e) => return (Err(e), None),
+                };
+            req = req
+                .header("KALSHI-ACCESS-KEY", &key_id)
+                .header("KALSHI-ACCESS-TIMESTAMP", &timestamp)
+                .header("KALSHI-ACCESS-SIGNATURE", &signature);
+        }
+
+        let start = Instant::now();
+        let resp = match self.observe(endpoint, start, req.send().await) {
+            Ok(resp) => resp,
+            Err(e) => return (Err(e.into()), None),
+        };
 
         let status = resp.status();
         if !status.is_success() {
+            let retry_after = resp
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .filter(|_| status.as_u16() == 429)
+                .and_then(|v| limits::parse_retry_after(v, chrono::Utc::now()));
             let body = resp.text().await.unwrap_or_default();
-            return Err(EngineError::KalshiApi {
-                status: status.as_u16(),
-                body,
-            });
+            return (Err(EngineError::KalshiApi { status: status.as_u16(), body }), retry_after);
         }
 
-        resp.json::<T>().await.map_err(|e| EngineError::Parse(format!("GET {path}: {e}")))
+        let result = resp.json::<T>().await.map_err(|e| EngineError::Parse(format!("GET {path}: {e}")));
+        (result, None)
+    }
+
+    fn observe(
+        &self,
+        endpoint: &'static str,
+        start: Instant,
+        resp: reqwest::Result<reqwest::Response>,
+    ) -> reqwest::Result<reqwest::Response> {
+        let status = resp.as_ref().ok().map(|r| r.status().as_u16());
+        self.metrics
+            .observe_api("kalshi", endpoint, status, start.elapsed().as_secs_f64());
+        resp
     }
 
     // ── Public endpoints ──
@@ -80,11 +208,11 @@ impl KalshiClient {
         if let Some(l) = limit { parts.push(format!("limit={l}")); }
         if let Some(c) = cursor { parts.push(format!("cursor={c}")); }
         let query = if parts.is_empty() { String::new() } else { format!("?{}", parts.join("&")) };
-        self.public_get(&format!("/markets{query}")).await
+        self.public_get("/markets", &format!("/markets{query}")).await
     }
 
     pub async fn get_market(&self, ticker: &str) -> EngineResult<GetMarketResponse> {
-        self.public_get(&format!("/markets/{ticker}")).await
+        self.public_get("/markets/{ticker}", &format!("/markets/{ticker}")).await
     }
 
     pub async fn get_market_trades(&self, ticker: Option<&str>, limit: Option<u32>) -> EngineResult<GetTradesResponse> {
@@ -92,7 +220,7 @@ impl KalshiClient {
         if let Some(t) = ticker { parts.push(format!("ticker={t}")); }
         if let Some(l) = limit { parts.push(format!("limit={l}")); }
         let query = if parts.is_empty() { String::new() } else { format!("?{}", parts.join("&")) };
-        self.public_get(&format!("/markets/trades{query}")).await
+        self.public_get("/markets/trades", &format!("/markets/trades{query}")).await
     }
 
     pub async fn get_events(
@@ -106,21 +234,67 @@ impl KalshiClient {
         if let Some(s) = status { parts.push(format!("status={s}")); }
         if let Some(l) = limit { parts.push(format!("limit={l}")); }
         let query = if parts.is_empty() { String::new() } else { format!("?{}", parts.join("&")) };
-        self.public_get(&format!("/events{query}")).await
+        self.public_get("/events", &format!("/events{query}")).await
     }
 
     pub async fn get_event(&self, event_ticker: &str) -> EngineResult<GetEventResponse> {
-        self.public_get(&format!("/events/{event_ticker}")).await
+        self.public_get("/events/{event_ticker}", &format!("/events/{event_ticker}")).await
     }
 
     pub async fn get_series(&self) -> EngineResult<GetSeriesResponse> {
-        self.public_get("/series").await
+        self.public_get("/series", "/series").await
+    }
+
+    pub async fn get_exchange_status(&self) -> EngineResult<ExchangeStatus> {
+        self.public_get("/exchange/status", "/exchange/status").await
+    }
+
+    pub async fn get_exchange_schedule(&self) -> EngineResult<GetExchangeScheduleResponse> {
+        self.public_get("/exchange/schedule", "/exchange/schedule").await
+    }
+
+    pub async fn get_exchange_announcements(&self) -> EngineResult<GetExchangeAnnouncementsResponse> {
+        self.public_get("/exchange/announcements", "/exchange/announcements").await
     }
 
     // ── Authenticated endpoints ──
 
+    pub async fn get_account_limits(&self) -> EngineResult<AccountLimitsResponse> {
+        self.auth_get("/account/limits", "/account/limits").await
+    }
+
+    pub async fn get_balance(&self) -> EngineResult<BalanceResponse> {
+        self.auth_get("/portfolio/balance", "/portfolio/balance").await
+    }
+
     pub async fn get_orderbook(&self, ticker: &str, depth: Option<u32>) -> EngineResult<OrderbookResponse> {
         let depth_param = depth.map(|d| format!("?depth={d}")).unwrap_or_default();
-        self.auth_get(&format!("/markets/{ticker}/orderbook{depth_param}")).await
+        self.auth_get("/markets/{ticker}/orderbook", &format!("/markets/{ticker}/orderbook{depth_param}")).await
+    }
+}
+
+/// Kalshi signs the full URL path (`/trade-api/v2/portfolio/balance`), not
+/// the path relative to the base URL, and without the query string.
+fn signing_path(base_url: &str, path: &str) -> String {
+    let prefix = reqwest::Url::parse(base_url)
+        .map(|u| u.path().trim_end_matches('/').to_string())
+        .unwrap_or_default();
+    let path = path.split_once('?').map_or(path, |(p, _)| p);
+    format!("{prefix}{path}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_path_includes_base_path_and_drops_query() {
+        let base = "https://api.elections.kalshi.com/trade-api/v2";
+        assert_eq!(signing_path(base, "/portfolio/balance"), "/trade-api/v2/portfolio/balance");
+        assert_eq!(
+            signing_path(base, "/markets/KXBTCD-T1/orderbook?depth=5"),
+            "/trade-api/v2/markets/KXBTCD-T1/orderbook"
+        );
+        assert_eq!(signing_path("http://127.0.0.1:8080/", "/portfolio/balance"), "/portfolio/balance");
     }
 }
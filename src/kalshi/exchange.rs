@@ -0,0 +1,316 @@
+//! Exchange status, trading hours and announcements.
+//!
+//! `ExchangeMonitor` polls `/exchange/status`, `/exchange/schedule` and
+//! `/exchange/announcements` and sends the engine an `EngineEvent::Exchange`
+//! whenever the picture changes. The status endpoint is authoritative; the
+//! schedule (standard hours and maintenance windows) stands in for it when
+//! status can't be fetched.
+
+use super::client::KalshiClient;
+use super::types::{Announcement, DailySchedule, ExchangeStatus, Schedule};
+use crate::clock::Clock;
+use crate::state::EngineEvent;
+use chrono::{DateTime, Datelike, NaiveTime, Timelike, Utc, Weekday};
+use chrono_tz::America::New_York;
+use tokio::sync::mpsc;
+
+/// Seconds between polls in the binary
+pub const POLL_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExchangePhase {
+    #[default]
+    Open,
+    /// Outside trading hours, or trading paused by Kalshi
+    Closed,
+    Maintenance,
+}
+
+impl std::fmt::Display for ExchangePhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => write!(f, "open"),
+            Self::Closed => write!(f, "closed"),
+            Self::Maintenance => write!(f, "maintenance"),
+        }
+    }
+}
+
+/// What the engine and dashboard know about the exchange.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct ExchangeInfo {
+    pub phase: ExchangePhase,
+    /// Short explanation, e.g. "trading inactive" or "scheduled maintenance"
+    pub reason: String,
+    pub estimated_resume_time: Option<String>,
+    /// Current or next scheduled maintenance window (start, end)
+    pub next_maintenance: Option<(String, String)>,
+    /// Active announcements, oldest first
+    pub announcements: Vec<Announcement>,
+}
+
+/// Polls the exchange endpoints every `POLL_SECS` until the engine channel
+/// closes.
+pub async fn run_exchange_monitor(client: KalshiClient, engine_tx: mpsc::Sender<EngineEvent>, clock: Clock) {
+    tracing::info!("exchange monitor started");
+    let mut monitor = ExchangeMonitor::new(client, engine_tx, clock);
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(POLL_SECS));
+    loop {
+        interval.tick().await;
+        if !monitor.poll().await {
+            tracing::error!("engine channel closed, exchange monitor shutting down");
+            return;
+        }
+    }
+}
+
+/// The monitor's state between polls; tests drive `poll` directly.
+pub struct ExchangeMonitor {
+    client: KalshiClient,
+    engine_tx: mpsc::Sender<EngineEvent>,
+    clock: Clock,
+    schedule: Option<Schedule>,
+    /// Last info sent to the engine
+    last: Option<ExchangeInfo>,
+}
+
+impl ExchangeMonitor {
+    pub fn new(client: KalshiClient, engine_tx: mpsc::Sender<EngineEvent>, clock: Clock) -> Self {
+        Self { client, engine_tx, clock, schedule: None, last: None }
+    }
+
+    /// One round of all three endpoints. Returns false once the engine
+    /// channel is closed.
+    pub async fn poll(&mut self) -> bool {
+        match self.client.get_exchange_schedule().await {
+            Ok(resp) => self.schedule = resp.schedule.or(self.schedule.take()),
+            Err(e) => tracing::debug!(error = %e, "exchange schedule fetch failed"),
+        }
+        let status = match self.client.get_exchange_status().await {
+            Ok(status) => Some(status),
+            Err(e) => {
+                tracing::warn!(error = %e, "exchange status fetch failed");
+                None
+            }
+        };
+        let announcements = match self.client.get_exchange_announcements().await {
+            Ok(resp) => active_announcements(resp.announcements.unwrap_or_default()),
+            Err(e) => {
+                tracing::debug!(error = %e, "exchange announcements fetch failed");
+                self.last.as_ref().map(|l| l.announcements.clone()).unwrap_or_default()
+            }
+        };
+
+        let now = self.clock.now();
+        let (phase, reason) = match phase_at(status.as_ref(), self.schedule.as_ref(), now) {
+            Some((phase, reason)) => (phase, reason.to_string()),
+            // Nothing to go on: keep what we last said
+            None => match &self.last {
+                Some(last) => (last.phase, last.reason.clone()),
+                None => return true,
+            },
+        };
+        let info = ExchangeInfo {
+            phase,
+            reason,
+            estimated_resume_time: status.and_then(|s| s.exchange_estimated_resume_time),
+            next_maintenance: self.schedule.as_ref().and_then(|s| next_maintenance(s, now)),
+            announcements,
+        };
+
+        if self.last.as_ref() != Some(&info) {
+            if self.last.as_ref().is_none_or(|l| l.phase != info.phase) {
+                tracing::info!(phase = %info.phase, reason = %info.reason, "exchange phase");
+            }
+            self.last = Some(info.clone());
+            if self.engine_tx.send(EngineEvent::Exchange(Box::new(info))).await.is_err() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Phase from the status endpoint if we have it, else from the schedule.
+/// `None` when neither is known.
+pub fn phase_at(
+    status: Option<&ExchangeStatus>,
+    schedule: Option<&Schedule>,
+    now: DateTime<Utc>,
+) -> Option<(ExchangePhase, &'static str)> {
+    if let Some(status) = status {
+        return Some(if !status.exchange_active {
+            (ExchangePhase::Maintenance, "exchange under maintenance")
+        } else if !status.trading_active {
+            (ExchangePhase::Closed, "trading inactive")
+        } else {
+            (ExchangePhase::Open, "trading active")
+        });
+    }
+
+    let schedule = schedule?;
+    let in_window = schedule.maintenance_windows.iter().any(|w| {
+        matches!((parse_time(&w.start_datetime), parse_time(&w.end_datetime)), (Some(s), Some(e)) if s <= now && now < e)
+    });
+    Some(if in_window {
+        (ExchangePhase::Maintenance, "scheduled maintenance")
+    } else {
+        match in_standard_hours(schedule, now) {
+            Some(false) => (ExchangePhase::Closed, "outside trading hours"),
+            Some(true) => (ExchangePhase::Open, "within trading hours"),
+            None => (ExchangePhase::Open, "no trading hours scheduled for now"),
+        }
+    })
+}
+
+/// Whether `now` falls in a session of the weekly schedule in effect, or
+/// `None` if no weekly schedule covers `now`.
+fn in_standard_hours(schedule: &Schedule, now: DateTime<Utc>) -> Option<bool> {
+    let week = schedule.standard_hours.iter().find(|w| {
+        matches!((parse_time(&w.start_time), parse_time(&w.end_time)), (Some(s), Some(e)) if s <= now && now < e)
+    })?;
+    let et = now.with_timezone(&New_York);
+    let sessions = match et.weekday() {
+        Weekday::Mon => &week.monday,
+        Weekday::Tue => &week.tuesday,
+        Weekday::Wed => &week.wednesday,
+        Weekday::Thu => &week.thursday,
+        Weekday::Fri => &week.friday,
+        Weekday::Sat => &week.saturday,
+        Weekday::Sun => &week.sunday,
+    };
+    let minute = et.hour() * 60 + et.minute();
+    Some(sessions.iter().any(|s| session_contains(s, minute)))
+}
+
+/// `minute` is minutes past midnight ET. A close at or before the open
+/// (e.g. "00:00") runs to midnight.
+fn session_contains(session: &DailySchedule, minute: u32) -> bool {
+    let hhmm = |s: &str| NaiveTime::parse_from_str(s, "%H:%M").ok().map(|t| t.hour() * 60 + t.minute());
+    let (Some(open), Some(close)) = (hhmm(&session.open_time), hhmm(&session.close_time)) else {
+        return false;
+    };
+    let close = if close <= open { 24 * 60 } else { close };
+    open <= minute && minute < close
+}
+
+/// The maintenance window in progress, or the next one to start
+fn next_maintenance(schedule: &Schedule, now: DateTime<Utc>) -> Option<(String, String)> {
+    schedule
+        .maintenance_windows
+        .iter()
+        .filter_map(|w| Some((parse_time(&w.start_datetime)?, parse_time(&w.end_datetime)?)))
+        .filter(|(_, end)| *end > now)
+        .min_by_key(|(start, _)| *start)
+        .map(|(start, end)| (start.to_rfc3339(), end.to_rfc3339()))
+}
+
+fn active_announcements(mut announcements: Vec<Announcement>) -> Vec<Announcement> {
+    announcements.retain(|a| a.status == "active");
+    announcements.sort_by(|a, b| a.delivery_time.cmp(&b.delivery_time));
+    announcements
+}
+
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kalshi::types::{MaintenanceWindow, WeeklySchedule};
+
+    fn at(s: &str) -> DateTime<Utc> {
+        parse_time(s).unwrap()
+    }
+
+    fn status(exchange_active: bool, trading_active: bool) -> ExchangeStatus {
+        ExchangeStatus { exchange_active, trading_active, exchange_estimated_resume_time: None }
+    }
+
+    fn session(open: &str, close: &str) -> Vec<DailySchedule> {
+        vec![DailySchedule { open_time: open.into(), close_time: close.into() }]
+    }
+
+    /// Weekdays 08:00-18:00 ET, Sunday evening only, closed Saturday
+    fn schedule() -> Schedule {
+        Schedule {
+            standard_hours: vec![WeeklySchedule {
+                start_time: "2026-01-01T00:00:00Z".into(),
+                end_time: "2027-01-01T00:00:00Z".into(),
+                monday: session("08:00", "18:00"),
+                tuesday: session("08:00", "18:00"),
+                wednesday: session("08:00", "18:00"),
+                thursday: session("08:00", "18:00"),
+                friday: session("08:00", "18:00"),
+                saturday: Vec::new(),
+                sunday: session("18:00", "00:00"),
+            }],
+            maintenance_windows: vec![MaintenanceWindow {
+                start_datetime: "2026-10-22T07:00:00Z".into(),
+                end_datetime: "2026-10-22T09:00:00Z".into(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_status_endpoint_wins_over_the_schedule() {
+        // Saturday: closed by the schedule, but Kalshi says trading is on
+        let now = at("2026-10-17T15:00:00Z");
+        let s = schedule();
+        assert_eq!(phase_at(Some(&status(true, true)), Some(&s), now).unwrap().0, ExchangePhase::Open);
+        assert_eq!(phase_at(Some(&status(true, false)), None, now).unwrap().0, ExchangePhase::Closed);
+        assert_eq!(phase_at(Some(&status(false, false)), None, now).unwrap().0, ExchangePhase::Maintenance);
+        assert_eq!(phase_at(None, None, now), None);
+    }
+
+    #[test]
+    fn test_schedule_hours_are_eastern_time() {
+        let s = schedule();
+        let phase = |t: &str| phase_at(None, Some(&s), at(t)).unwrap().0;
+        // Monday 2026-10-19, EDT (UTC-4): open 12:00Z to 22:00Z
+        assert_eq!(phase("2026-10-19T11:59:00Z"), ExchangePhase::Closed);
+        assert_eq!(phase("2026-10-19T12:00:00Z"), ExchangePhase::Open);
+        assert_eq!(phase("2026-10-19T21:59:00Z"), ExchangePhase::Open);
+        assert_eq!(phase("2026-10-19T22:00:00Z"), ExchangePhase::Closed);
+        // Saturday, then Sunday's session that runs to midnight ET
+        assert_eq!(phase("2026-10-17T16:00:00Z"), ExchangePhase::Closed);
+        assert_eq!(phase("2026-10-18T23:30:00Z"), ExchangePhase::Open);
+        assert_eq!(phase("2026-10-19T03:59:00Z"), ExchangePhase::Open);
+        // After DST ends, Monday opens at 13:00Z
+        assert_eq!(phase("2026-11-02T12:30:00Z"), ExchangePhase::Closed);
+        assert_eq!(phase("2026-11-02T13:00:00Z"), ExchangePhase::Open);
+        // No weekly schedule covers 2027: don't claim closed
+        assert_eq!(phase("2027-03-06T16:00:00Z"), ExchangePhase::Open);
+    }
+
+    #[test]
+    fn test_maintenance_windows() {
+        let s = schedule();
+        assert_eq!(phase_at(None, Some(&s), at("2026-10-22T08:00:00Z")).unwrap().0, ExchangePhase::Maintenance);
+        assert_eq!(
+            next_maintenance(&s, at("2026-10-20T00:00:00Z")),
+            Some(("2026-10-22T07:00:00+00:00".into(), "2026-10-22T09:00:00+00:00".into()))
+        );
+        assert!(next_maintenance(&s, at("2026-10-22T08:00:00Z")).is_some());
+        assert_eq!(next_maintenance(&s, at("2026-10-22T09:00:00Z")), None);
+    }
+
+    #[test]
+    fn test_only_active_announcements_are_kept_oldest_first() {
+        let a = |message: &str, delivery_time: &str, status: &str| Announcement {
+            kind: "info".into(),
+            message: message.into(),
+            delivery_time: delivery_time.into(),
+            status: status.into(),
+        };
+        let kept = active_announcements(vec![
+            a("later", "2026-10-18T12:00:00Z", "active"),
+            a("gone", "2026-10-18T10:00:00Z", "inactive"),
+            a("earlier", "2026-10-18T11:00:00Z", "active"),
+        ]);
+        let messages: Vec<_> = kept.iter().map(|a| a.message.as_str()).collect();
+        assert_eq!(messages, ["earlier", "later"]);
+    }
+}
@@ -0,0 +1,487 @@
+//! Client-side protection for the Kalshi REST API.
+//!
+//! - `RateLimiter`: a token bucket sized to the account's read limit
+//!   (`GET /account/limits`), shared by every clone of the client.
+//! - `RetryPolicy`: jittered exponential backoff for 429s, 5xx and network
+//!   errors, deferring to `Retry-After` when Kalshi sends one.
+//! - `CircuitBreaker`: after enough consecutive failed calls, fail fast for
+//!   a cooldown instead of queueing more requests against a dead API, then
+//!   let a single probe call through. Its state is what the engine snapshot
+//!   shows as `kalshi_api`.
+
+use crate::clock::Clock;
+use crate::errors::EngineError;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Reads per second before the account's tier is known (Kalshi's Basic tier)
+pub const DEFAULT_READS_PER_SEC: f64 = 20.0;
+
+// ── Token bucket ──
+
+/// Token bucket holding up to one second of requests.
+pub struct RateLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    rate: f64,
+    tokens: f64,
+    last: Instant,
+    /// Set from a 429's `Retry-After`: nobody sends before then
+    hold_until: Option<Instant>,
+}
+
+impl Bucket {
+    /// Take a token at `now`, or say how long until one is available.
+    fn take(&mut self, now: Instant) -> Option<Duration> {
+        if let Some(until) = self.hold_until {
+            if now < until {
+                return Some(until - now);
+            }
+            self.hold_until = None;
+        }
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new(per_sec: f64) -> Self {
+        let rate = per_sec.max(0.1);
+        Self {
+            bucket: Mutex::new(Bucket { rate, tokens: rate, last: Instant::now(), hold_until: None }),
+        }
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.lock().rate
+    }
+
+    /// Resize the bucket, e.g. once the account's tier is known.
+    pub fn set_rate(&self, per_sec: f64) {
+        let mut bucket = self.lock();
+        bucket.rate = per_sec.max(0.1);
+        bucket.tokens = bucket.tokens.min(bucket.rate);
+    }
+
+    /// Wait for a token.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = self.lock().take(Instant::now());
+            match wait {
+                None => return,
+                Some(wait) => tokio::time::sleep(wait).await,
+            }
+        }
+    }
+
+    /// Stop everyone sending for `wait` (Kalshi said so with a 429).
+    pub fn hold_for(&self, wait: Duration) {
+        let mut bucket = self.lock();
+        let until = Instant::now() + wait;
+        bucket.hold_until = Some(bucket.hold_until.map_or(until, |u| u.max(until)));
+        bucket.tokens = 0.0;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.bucket.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ── Retries ──
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Attempts per call, including the first
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    /// Cap on the backoff, and the longest `Retry-After` worth waiting for
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_attempts: 3, base_delay: Duration::from_millis(250), max_delay: Duration::from_secs(5) }
+    }
+}
+
+impl RetryPolicy {
+    /// Single attempt, no retries
+    pub fn none() -> Self {
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    /// Delay before retrying after failed attempt number `attempt`
+    /// (1-based), or `None` to give up. A `Retry-After` longer than
+    /// `max_delay` gives up rather than stall the caller.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>, rng: &mut impl Rng) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        if let Some(wait) = retry_after {
+            return (wait <= self.max_delay).then_some(wait);
+        }
+        // Full jitter: uniform in [0, base * 2^(attempt-1)], capped
+        let ceiling = self
+            .base_delay
+            .saturating_mul(1u32 << (attempt - 1).min(16))
+            .min(self.max_delay);
+        Some(ceiling.mul_f64(rng.gen::<f64>()))
+    }
+}
+
+/// 429 and 5xx are worth another try; other statuses won't change.
+pub fn is_retryable_status(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
+}
+
+/// Errors that say Kalshi is unreachable or overloaded, rather than that
+/// the request itself was wrong. These are retried and trip the breaker.
+pub fn is_degradation(e: &EngineError) -> bool {
+    match e {
+        EngineError::Network(_) => true,
+        EngineError::KalshiApi { status, .. } => is_retryable_status(*status),
+        _ => false,
+    }
+}
+
+/// Parse a `Retry-After` header: either delay-seconds or an HTTP date.
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((at - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+// ── Circuit breaker ──
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    /// Failing fast until the cooldown ends
+    Open,
+    /// Cooldown over; one probe call decides whether to close or reopen,
+    /// and other calls fail fast while it is in flight
+    HalfOpen,
+}
+
+impl std::fmt::Display for BreakerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Closed => write!(f, "closed"),
+            Self::Open => write!(f, "open"),
+            Self::HalfOpen => write!(f, "half_open"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BreakerConfig {
+    /// Consecutive failed calls (after retries) that open the breaker
+    pub failure_threshold: u32,
+    pub cooldown: chrono::Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self { failure_threshold: 5, cooldown: chrono::Duration::seconds(30) }
+    }
+}
+
+/// Kalshi API health as shown in the engine snapshot.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ApiHealth {
+    pub breaker: BreakerState,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// When the breaker last opened (RFC 3339)
+    pub opened_at: Option<String>,
+    pub reads_per_sec: f64,
+}
+
+impl Default for ApiHealth {
+    fn default() -> Self {
+        Self {
+            breaker: BreakerState::Closed,
+            consecutive_failures: 0,
+            last_error: None,
+            opened_at: None,
+            reads_per_sec: DEFAULT_READS_PER_SEC,
+        }
+    }
+}
+
+/// Cooldowns run on the engine clock, so simulated tests see the breaker
+/// close again as simulated time passes.
+pub struct CircuitBreaker {
+    config: BreakerConfig,
+    clock: Clock,
+    inner: Mutex<BreakerInner>,
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    /// When the half-open probe in flight was let through
+    probe_started: Option<DateTime<Utc>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: BreakerConfig, clock: Clock) -> Self {
+        Self {
+            config,
+            clock,
+            inner: Mutex::new(BreakerInner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                last_error: None,
+                probe_started: None,
+            }),
+        }
+    }
+
+    /// Err while open. Once the cooldown has passed, one call goes through
+    /// as the probe (half-open) and the rest keep failing until it reports
+    /// back. A probe that never reports (its caller was cancelled) stops
+    /// holding the slot after another cooldown.
+    pub fn allow(&self) -> Result<(), EngineError> {
+        let mut inner = self.lock();
+        let now = self.clock.now();
+        match inner.state {
+            BreakerState::Closed => return Ok(()),
+            BreakerState::Open => {
+                let opened_at = inner.opened_at.unwrap_or_default();
+                if now < opened_at + self.config.cooldown {
+                    return Err(Self::rejected(&inner, "open"));
+                }
+                inner.state = BreakerState::HalfOpen;
+                tracing::info!("kalshi circuit breaker half-open, probing");
+            }
+            BreakerState::HalfOpen => {
+                if inner.probe_started.is_some_and(|t| now < t + self.config.cooldown) {
+                    return Err(Self::rejected(&inner, "probing"));
+                }
+            }
+        }
+        inner.probe_started = Some(now);
+        Ok(())
+    }
+
+    fn rejected(inner: &BreakerInner, state: &str) -> EngineError {
+        EngineError::KalshiApi {
+            status: 503,
+            body: format!(
+                "circuit breaker {state} after {} failures: {}",
+                inner.consecutive_failures,
+                inner.last_error.as_deref().unwrap_or("")
+            ),
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.lock();
+        if inner.state != BreakerState::Closed {
+            tracing::info!(failures = inner.consecutive_failures, "kalshi circuit breaker closed");
+        }
+        inner.state = BreakerState::Closed;
+        inner.consecutive_failures = 0;
+        inner.probe_started = None;
+    }
+
+    pub fn record_failure(&self, error: &EngineError) {
+        let mut inner = self.lock();
+        inner.consecutive_failures += 1;
+        inner.last_error = Some(error.to_string());
+        inner.probe_started = None;
+        let trip = match inner.state {
+            BreakerState::Closed => inner.consecutive_failures >= self.config.failure_threshold,
+            BreakerState::HalfOpen => true,
+            BreakerState::Open => false,
+        };
+        if trip {
+            inner.state = BreakerState::Open;
+            inner.opened_at = Some(self.clock.now());
+            tracing::warn!(
+                failures = inner.consecutive_failures,
+                cooldown_secs = self.config.cooldown.num_seconds(),
+                error = %error,
+                "kalshi circuit breaker open"
+            );
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.lock().state
+    }
+
+    /// Health with `reads_per_sec` filled in by the caller
+    pub fn health(&self, reads_per_sec: f64) -> ApiHealth {
+        let inner = self.lock();
+        ApiHealth {
+            breaker: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            last_error: inner.last_error.clone(),
+            opened_at: inner.opened_at.map(|t| t.to_rfc3339()),
+            reads_per_sec,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn bucket(rate: f64, now: Instant) -> Bucket {
+        Bucket { rate, tokens: rate, last: now, hold_until: None }
+    }
+
+    #[test]
+    fn test_bucket_allows_a_burst_then_refills_at_the_rate() {
+        let t0 = Instant::now();
+        let mut b = bucket(2.0, t0);
+        assert_eq!(b.take(t0), None);
+        assert_eq!(b.take(t0), None);
+        let wait = b.take(t0).unwrap();
+        assert!((wait.as_secs_f64() - 0.5).abs() < 1e-9, "{wait:?}");
+        assert_eq!(b.take(t0 + Duration::from_millis(500)), None);
+        // Never holds more than one second's worth
+        assert_eq!(b.take(t0 + Duration::from_secs(60)), None);
+        assert_eq!(b.take(t0 + Duration::from_secs(60)), None);
+        assert!(b.take(t0 + Duration::from_secs(60)).is_some());
+    }
+
+    #[test]
+    fn test_bucket_respects_a_hold() {
+        let t0 = Instant::now();
+        let mut b = bucket(10.0, t0);
+        b.hold_until = Some(t0 + Duration::from_secs(2));
+        assert_eq!(b.take(t0 + Duration::from_secs(1)), Some(Duration::from_secs(1)));
+        assert_eq!(b.take(t0 + Duration::from_secs(2)), None);
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_with_jitter_and_honours_retry_after() {
+        let policy = RetryPolicy { max_attempts: 4, base_delay: Duration::from_millis(100), max_delay: Duration::from_millis(300) };
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            assert!(policy.delay(1, None, &mut rng).unwrap() <= Duration::from_millis(100));
+            assert!(policy.delay(2, None, &mut rng).unwrap() <= Duration::from_millis(200));
+            assert!(policy.delay(3, None, &mut rng).unwrap() <= Duration::from_millis(300));
+        }
+        assert_eq!(policy.delay(4, None, &mut rng), None);
+
+        let wait = Duration::from_millis(250);
+        assert_eq!(policy.delay(1, Some(wait), &mut rng), Some(wait));
+        // Longer than we're willing to wait: give up
+        assert_eq!(policy.delay(1, Some(Duration::from_secs(2)), &mut rng), None);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2026-10-18T14:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(parse_retry_after("3", now), Some(Duration::from_secs(3)));
+        assert_eq!(parse_retry_after(" 0 ", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("Sun, 18 Oct 2026 14:00:07 GMT", now), Some(Duration::from_secs(7)));
+        // A date in the past means now
+        assert_eq!(parse_retry_after("Sun, 18 Oct 2026 13:59:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-1", now), None);
+    }
+
+    #[test]
+    fn test_breaker_opens_fails_fast_then_probes_after_cooldown() {
+        let start = DateTime::parse_from_rfc3339("2026-10-18T14:00:00Z").unwrap().with_timezone(&Utc);
+        let clock = Clock::simulated(start);
+        let config = BreakerConfig { failure_threshold: 3, cooldown: chrono::Duration::seconds(30) };
+        let breaker = CircuitBreaker::new(config, clock.clone());
+        let err = EngineError::KalshiApi { status: 503, body: "down".into() };
+
+        breaker.record_failure(&err);
+        breaker.record_failure(&err);
+        assert!(breaker.allow().is_ok());
+        breaker.record_failure(&err);
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(matches!(breaker.allow(), Err(EngineError::KalshiApi { status: 503, .. })));
+
+        // Probe fails: straight back to open for another cooldown
+        clock.advance(chrono::Duration::seconds(30));
+        assert!(breaker.allow().is_ok());
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        breaker.record_failure(&err);
+        assert_eq!(breaker.state(), BreakerState::Open);
+        clock.advance(chrono::Duration::seconds(29));
+        assert!(breaker.allow().is_err());
+
+        clock.advance(chrono::Duration::seconds(1));
+        assert!(breaker.allow().is_ok());
+        breaker.record_success();
+        let health = breaker.health(20.0);
+        assert_eq!(health.breaker, BreakerState::Closed);
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(health.last_error.as_deref(), Some("kalshi API error: 503 down"));
+    }
+
+    #[test]
+    fn test_half_open_lets_one_probe_through_at_a_time() {
+        let start = DateTime::parse_from_rfc3339("2026-10-18T14:00:00Z").unwrap().with_timezone(&Utc);
+        let clock = Clock::simulated(start);
+        let config = BreakerConfig { failure_threshold: 1, cooldown: chrono::Duration::seconds(30) };
+        let breaker = CircuitBreaker::new(config, clock.clone());
+        let err = EngineError::KalshiApi { status: 503, body: "down".into() };
+
+        breaker.record_failure(&err);
+        clock.advance(chrono::Duration::seconds(30));
+        assert!(breaker.allow().is_ok());
+        // Everyone else fails fast while the probe is out
+        assert!(matches!(breaker.allow(), Err(EngineError::KalshiApi { status: 503, .. })));
+        assert!(breaker.allow().is_err());
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        breaker.record_success();
+        assert!(breaker.allow().is_ok());
+        assert!(breaker.allow().is_ok());
+
+        // A probe that never reports back frees the slot after a cooldown
+        breaker.record_failure(&err);
+        clock.advance(chrono::Duration::seconds(30));
+        assert!(breaker.allow().is_ok());
+        clock.advance(chrono::Duration::seconds(29));
+        assert!(breaker.allow().is_err());
+        clock.advance(chrono::Duration::seconds(1));
+        assert!(breaker.allow().is_ok());
+        assert!(breaker.allow().is_err());
+    }
+
+    #[test]
+    fn test_only_overload_and_network_errors_count_as_degradation() {
+        assert!(is_degradation(&EngineError::Network("timed out".into())));
+        assert!(is_degradation(&EngineError::KalshiApi { status: 429, body: String::new() }));
+        assert!(is_degradation(&EngineError::KalshiApi { status: 502, body: String::new() }));
+        assert!(!is_degradation(&EngineError::KalshiApi { status: 404, body: String::new() }));
+        assert!(!is_degradation(&EngineError::Parse("bad json".into())));
+    }
+}
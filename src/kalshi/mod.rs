@@ -1,4 +1,7 @@
 pub mod auth;
 pub mod client;
+pub mod exchange;
+pub mod limits;
 pub mod types;
 pub mod scanner;
+pub mod selector;
@@ -1,45 +1,136 @@
 use super::client::KalshiClient;
-use super::types::Market;
+use super::limits::BreakerState;
+use super::selector::{self, MarketSelector, SelectionContext};
+use super::types::{Market, OrderbookResponse, Trade};
+use crate::asset::{Asset, AssetConfig};
 use crate::config::AppConfig;
-use crate::state::{ActiveMarket, EngineEvent};
-use chrono::Utc;
-use tokio::sync::mpsc;
+use crate::horizon::Horizon;
+use crate::risk::liquidity::LiquidityGates;
+use crate::state::{ActiveMarket, EngineEvent, EngineSnapshot, MarketDepth};
+use crate::clock::Clock;
+use chrono::{DateTime, Utc};
+use tokio::sync::{mpsc, watch};
 
-/// Polls Kalshi for active BTC binary markets.
+/// Polls Kalshi for one asset's active binary markets; the binary runs one
+/// scanner per configured asset.
 /// Sends MarketUpdate / MarketSettled events to the engine via bounded channel.
 ///
-/// Market selection strategy:
-///   1. Get all open/active binary markets in the BTC series.
-///   2. Group by close_time, pick the soonest-closing group.
-///   3. Among those, pick the market with yes_ask closest to $0.50 (near ATM).
-///   4. Track previously active markets for settlement checking.
+/// Market selection strategy, once per configured expiry horizon:
+///   1. Get all open/active binary markets in the asset's series.
+///   2. Keep those whose time to close falls in the horizon's band.
+///   3. Group by close_time, pick the soonest-closing group (the ladder).
+///   4. Let the configured `MarketSelector` pick one market of the ladder
+///      (by default the one with yes_ask closest to $0.50, near ATM).
+///   5. Fetch the depth the liquidity gates need for the chosen market.
+///   6. Track previously active markets for settlement checking.
+///
+/// Selectors that price the ladder read the asset's price and the
+/// horizon's volatility from the engine's latest snapshot.
+///
+/// `pending_settlement` seeds the settlement list with markets that still
+/// had open trades when the process last stopped; markets outside the
+/// asset's series are left to the other scanners.
 pub async fn run_market_scanner(
     config: AppConfig,
+    asset: Asset,
     client: KalshiClient,
     engine_tx: mpsc::Sender<EngineEvent>,
+    pending_settlement: Vec<String>,
+    snapshots: watch::Receiver<EngineSnapshot>,
+    clock: Clock,
 ) {
-    tracing::info!("market scanner started, series={}", config.btc_series_ticker);
+    let mut scanner = MarketScanner::new(config, asset, client, engine_tx, pending_settlement, snapshots, clock);
+    tracing::info!(
+        asset = %asset,
+        series = ?scanner.asset.series,
+        selector = scanner.selector.name(),
+        pending = scanner.pending_settlement.len(),
+        "market scanner started"
+    );
 
     let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
-    let mut current_ticker: Option<String> = None;
-    // Track old market tickers that need settlement checking
-    let mut pending_settlement: Vec<String> = Vec::new();
-
     loop {
         interval.tick().await;
+        if !scanner.poll().await {
+            tracing::error!("engine channel closed, scanner shutting down");
+            return;
+        }
+    }
+}
+
+/// The scanner's state between polls. `run_market_scanner` polls it every
+/// 5 seconds; tests drive `poll` directly against a simulated clock.
+pub struct MarketScanner {
+    asset: AssetConfig,
+    horizons: Vec<Horizon>,
+    selector: Box<dyn MarketSelector>,
+    /// Gates as of startup: which depth to fetch per market
+    liquidity: LiquidityGates,
+    client: KalshiClient,
+    engine_tx: mpsc::Sender<EngineEvent>,
+    snapshots: watch::Receiver<EngineSnapshot>,
+    clock: Clock,
+    /// Market currently sent for each horizon, by index into `horizons`
+    current: Vec<Option<String>>,
+    pending_settlement: Vec<String>,
+    /// Breaker state last reported to the engine
+    breaker: BreakerState,
+}
+
+impl MarketScanner {
+    /// Panics if `asset` is not in `config.assets`.
+    pub fn new(
+        config: AppConfig,
+        asset: Asset,
+        client: KalshiClient,
+        engine_tx: mpsc::Sender<EngineEvent>,
+        mut pending_settlement: Vec<String>,
+        snapshots: watch::Receiver<EngineSnapshot>,
+        clock: Clock,
+    ) -> Self {
+        let asset = config.asset(asset).cloned().expect("scanner for an asset that is not configured");
+        pending_settlement.retain(|t| asset.series_of(t).is_some());
+        Self {
+            asset,
+            current: vec![None; config.horizons.len()],
+            horizons: config.horizons,
+            selector: config.selection.selector(),
+            liquidity: config.trading.liquidity,
+            client,
+            engine_tx,
+            snapshots,
+            clock,
+            pending_settlement,
+            breaker: BreakerState::Closed,
+        }
+    }
+
+    /// Markets switched away from that have not settled yet
+    pub fn pending_settlement(&self) -> &[String] {
+        &self.pending_settlement
+    }
+
+    /// One round: settlement checks, market selection, then a health report
+    /// if the client's circuit breaker changed state. Returns false once the
+    /// engine channel is closed.
+    pub async fn poll(&mut self) -> bool {
+        let (client, engine_tx) = (&self.client, &self.engine_tx);
 
         // ── 1. Check settlement of ALL previously-tracked markets ──
         let mut settled: Vec<usize> = Vec::new();
-        for (idx, ticker) in pending_settlement.iter().enumerate() {
+        for (idx, ticker) in self.pending_settlement.iter().enumerate() {
             match client.get_market(ticker).await {
                 Ok(resp) => {
                     if let Some(market) = resp.market {
-                        if market.is_settled() {
-                            let result = market.result.clone().unwrap_or_default();
+                        // A closed market has no result until Kalshi determines
+                        // it; settling before then would book every trade a loss
+                        let result = market.result.clone().filter(|r| !r.is_empty());
+                        if let (true, Some(result)) = (market.is_settled(), result) {
                             tracing::info!(ticker = %ticker, result = %result, "market settled");
 
                             let _ = engine_tx
                                 .send(EngineEvent::MarketSettled {
+                                    asset: self.asset.asset,
                                     ticker: ticker.clone(),
                                     result,
                                 })
@@ -56,81 +147,128 @@ pub async fn run_market_scanner(
         }
         // Remove settled ones (reverse order to preserve indices)
         for idx in settled.into_iter().rev() {
-            pending_settlement.remove(idx);
+            self.pending_settlement.remove(idx);
         }
 
-        // ── 2. Scan for the best active market ──
-        match scan_for_market(&config, &client).await {
-            Ok(Some(market)) => {
-                let ticker = market.ticker.clone().unwrap_or_default();
-                let is_new = current_ticker.as_ref() != Some(&ticker);
+        // ── 2. Scan for the best active market of each horizon ──
+        match open_markets(&self.asset, client).await {
+            Ok(markets) => {
+                let now = self.clock.now();
+                let snapshot = self.snapshots.borrow().clone();
+                let asset = snapshot.asset(self.asset.asset);
+                for (horizon, current) in self.horizons.iter().zip(self.current.iter_mut()) {
+                    let vol = asset.and_then(|a| a.horizon(&horizon.name)).map(|h| h.volatility);
+                    let ctx = SelectionContext {
+                        now,
+                        spot: asset.map(|a| a.price).filter(|p| *p > 0.0),
+                        sigma: vol.filter(|v| v.sample_count > 0).map(|v| v.annualized()),
+                    };
+                    let Some(market) = find_horizon_market(&markets, horizon, self.selector.as_ref(), &ctx) else {
+                        // No active market; if we had one, move it to settlement tracking
+                        if let Some(old_ticker) = current.take() {
+                            if !self.pending_settlement.contains(&old_ticker) {
+                                self.pending_settlement.push(old_ticker);
+                            }
+                        }
+                        tracing::debug!(asset = %self.asset.asset, horizon = %horizon.name, "no active market found");
+                        continue;
+                    };
+                    let ticker = market.ticker.clone().unwrap_or_default();
+                    let is_new = current.as_ref() != Some(&ticker);
 
-                let am = market_to_active(&config, &market);
+                    let depth = market_depth(client, &market, &self.liquidity, now).await;
+                    let am = market_to_active(&self.asset, horizon, &market, depth);
 
-                if is_new {
-                    // If we were tracking a different market, move it to settlement tracking
-                    if let Some(old_ticker) = current_ticker.take() {
-                        if !pending_settlement.contains(&old_ticker) {
-                            tracing::info!(old = %old_ticker, new = %ticker, "switching market, tracking old for settlement");
-                            pending_settlement.push(old_ticker);
+                    if is_new {
+                        // If we were tracking a different market, move it to settlement tracking
+                        if let Some(old_ticker) = current.take() {
+                            if !self.pending_settlement.contains(&old_ticker) {
+                                tracing::info!(old = %old_ticker, new = %ticker, "switching market, tracking old for settlement");
+                                self.pending_settlement.push(old_ticker);
+                            }
                         }
-                    }
 
-                    tracing::info!(
-                        ticker = %ticker,
-                        strike = ?market.strike_price(),
-                        yes_ask = ?market.yes_ask_dollars,
-                        "tracking new market"
-                    );
-                    current_ticker = Some(ticker.clone());
-                }
+                        tracing::info!(
+                            asset = %self.asset.asset,
+                            horizon = %horizon.name,
+                            ticker = %ticker,
+                            strike = ?market.strike_price(),
+                            yes_ask = ?market.yes_ask_dollars,
+                            "tracking new market"
+                        );
+                        *current = Some(ticker.clone());
+                    }
 
-                if engine_tx.send(EngineEvent::MarketUpdate(Box::new(am))).await.is_err() {
-                    tracing::error!("engine channel closed, scanner shutting down");
-                    return;
-                }
-            }
-            Ok(None) => {
-                // No active market; if we had one, move it to settlement tracking
-                if let Some(old_ticker) = current_ticker.take() {
-                    if !pending_settlement.contains(&old_ticker) {
-                        pending_settlement.push(old_ticker);
+                    if engine_tx.send(EngineEvent::MarketUpdate(Box::new(am))).await.is_err() {
+                        return false;
                     }
                 }
-                tracing::debug!("no active BTC market found");
             }
             Err(e) => {
-                tracing::warn!(error = %e, "market scanner error");
+                tracing::warn!(asset = %self.asset.asset, error = %e, "market scanner error");
             }
         }
 
         // Cap pending settlement list to avoid unbounded growth
-        if pending_settlement.len() > 20 {
-            pending_settlement.drain(0..pending_settlement.len() - 20);
+        if self.pending_settlement.len() > 20 {
+            self.pending_settlement.drain(0..self.pending_settlement.len() - 20);
         }
+
+        // ── 3. Report circuit breaker changes ──
+        let health = self.client.health();
+        if health.breaker != self.breaker {
+            self.breaker = health.breaker;
+            if self.engine_tx.send(EngineEvent::KalshiApiHealth(Box::new(health))).await.is_err() {
+                return false;
+            }
+        }
+        true
     }
 }
 
-async fn scan_for_market(
-    config: &AppConfig,
-    client: &KalshiClient,
-) -> Result<Option<Market>, crate::errors::EngineError> {
-    let series = &config.btc_series_ticker;
-
-    let resp = client.get_markets(Some(series), Some("open"), Some(100), None).await?;
-    let mut markets = resp.markets.unwrap_or_default();
+/// Every open market in the asset's series (first page of each).
+pub async fn open_markets(asset: &AssetConfig, client: &KalshiClient) -> Result<Vec<Market>, crate::errors::EngineError> {
+    let mut markets = Vec::new();
+    for series in &asset.series {
+        let resp = client.get_markets(Some(series), Some("open"), Some(100), None).await?;
+        let mut found = resp.markets.unwrap_or_default();
 
-    if markets.is_empty() {
-        let resp2 = client.get_markets(Some(series), Some("active"), Some(100), None).await?;
-        markets = resp2.markets.unwrap_or_default();
+        if found.is_empty() {
+            let resp2 = client.get_markets(Some(series), Some("active"), Some(100), None).await?;
+            found = resp2.markets.unwrap_or_default();
+        }
+        markets.extend(found);
     }
 
-    Ok(find_best_market(markets))
+    Ok(markets)
 }
 
-fn find_best_market(markets: Vec<Market>) -> Option<Market> {
-    let now = Utc::now();
+/// The market `horizon` should trade at `ctx.now`: the best of those whose
+/// time to close falls in the horizon's band.
+pub fn find_horizon_market(
+    markets: &[Market],
+    horizon: &Horizon,
+    selector: &dyn MarketSelector,
+    ctx: &SelectionContext,
+) -> Option<Market> {
+    let now = ctx.now;
+    let in_band = markets
+        .iter()
+        .filter(|m| {
+            m.close_time
+                .as_deref()
+                .and_then(parse_datetime)
+                .is_some_and(|close| horizon.contains((close - now).num_seconds() as f64))
+        })
+        .cloned()
+        .collect();
+    find_best_market(in_band, selector, ctx)
+}
 
+/// The market the engine should trade at `ctx.now`, if any: `selector`'s
+/// pick of the soonest-closing ladder.
+pub fn find_best_market(markets: Vec<Market>, selector: &dyn MarketSelector, ctx: &SelectionContext) -> Option<Market> {
+    let now = ctx.now;
     let candidates: Vec<_> = markets
         .into_iter()
         .filter(|m| m.is_active() && m.market_type.as_deref() == Some("binary"))
@@ -152,9 +290,8 @@ fn find_best_market(markets: Vec<Market>) -> Option<Market> {
         .min()?
         .timestamp();
 
-    // Among markets with the soonest close time (within 60s tolerance),
-    // pick the one with yes_ask closest to $0.50 (nearest to ATM).
-    candidates
+    // The ladder: markets with the soonest close time (within 60s tolerance)
+    let ladder = candidates
         .into_iter()
         .filter(|m| {
             m.close_time
@@ -163,18 +300,11 @@ fn find_best_market(markets: Vec<Market>) -> Option<Market> {
                 .map(|dt| (dt.timestamp() - earliest_ts).abs() < 60)
                 .unwrap_or(false)
         })
-        .min_by_key(|m| {
-            let yes_ask = m
-                .yes_ask_dollars
-                .as_ref()
-                .and_then(|s| s.parse::<f64>().ok())
-                .unwrap_or(0.0);
-            // Distance from 0.50 -- lower = closer to ATM
-            ((yes_ask - 0.50).abs() * 10000.0) as i64
-        })
+        .collect();
+    selector::pick(selector, ladder, ctx)
 }
 
-fn parse_datetime(s: &str) -> Option<chrono::DateTime<Utc>> {
+pub(super) fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
     chrono::DateTime::parse_from_rfc3339(s)
         .ok()
         .map(|dt| dt.with_timezone(&Utc))
@@ -185,11 +315,68 @@ fn parse_datetime(s: &str) -> Option<chrono::DateTime<Utc>> {
         })
 }
 
-fn market_to_active(config: &AppConfig, m: &Market) -> ActiveMarket {
+/// Open interest from the listing, plus the order book and recent trades if
+/// `gates` need them. A failed request leaves its fields unset, which the
+/// gates treat as failing.
+async fn market_depth(client: &KalshiClient, m: &Market, gates: &LiquidityGates, now: DateTime<Utc>) -> MarketDepth {
+    let count = |fp: &Option<String>| fp.as_deref().and_then(|s| s.parse::<f64>().ok());
+    let mut depth = MarketDepth {
+        open_interest: count(&m.open_interest_fp).or(m.open_interest.map(|n| n as f64)),
+        ..MarketDepth::default()
+    };
+    let ticker = m.ticker_str();
+    if gates.needs_book() {
+        match client.get_orderbook(ticker, Some(1)).await {
+            Ok(book) => (depth.yes_bid_size, depth.no_bid_size) = top_of_book(&book),
+            Err(e) => tracing::debug!(ticker = %ticker, error = %e, "order book fetch failed"),
+        }
+    }
+    if gates.needs_trades() {
+        match client.get_market_trades(Some(ticker), Some(100)).await {
+            Ok(resp) => {
+                let since = now - chrono::Duration::milliseconds((gates.recent_window_secs * 1000.0) as i64);
+                let recent: Vec<&Trade> = resp
+                    .trades
+                    .iter()
+                    .flatten()
+                    .filter(|t| t.created_time.as_deref().and_then(parse_datetime).is_some_and(|at| at >= since))
+                    .collect();
+                depth.recent_trades = Some(recent.len() as u32);
+                depth.recent_volume = Some(recent.iter().filter_map(|t| count(&t.count_fp)).sum());
+            }
+            Err(e) => tracing::debug!(ticker = %ticker, error = %e, "recent trades fetch failed"),
+        }
+    }
+    depth
+}
+
+/// Contracts at the best YES and best NO bid. Levels are `[price, size]`;
+/// the best bid is the highest price, wherever the exchange lists it.
+fn top_of_book(resp: &OrderbookResponse) -> (Option<f64>, Option<f64>) {
+    fn best(levels: impl Iterator<Item = (f64, f64)>) -> Option<f64> {
+        levels.max_by(|a, b| a.0.total_cmp(&b.0)).map(|(_, size)| size)
+    }
+    if let Some(fp) = &resp.orderbook_fp {
+        let side = |levels: &Option<Vec<Vec<String>>>| {
+            best(levels.iter().flatten().filter_map(|l| Some((l.first()?.parse().ok()?, l.get(1)?.parse().ok()?))))
+        };
+        return (side(&fp.yes_dollars), side(&fp.no_dollars));
+    }
+    let side = |levels: &Option<Vec<Vec<serde_json::Value>>>| {
+        best(levels.iter().flatten().filter_map(|l| Some((l.first()?.as_f64()?, l.get(1)?.as_f64()?))))
+    };
+    resp.orderbook.as_ref().map_or((None, None), |book| (side(&book.yes), side(&book.no)))
+}
+
+fn market_to_active(asset: &AssetConfig, horizon: &Horizon, m: &Market, depth: MarketDepth) -> ActiveMarket {
+    let ticker = m.ticker.clone().unwrap_or_default();
+    let series_ticker = asset.series_of(&ticker).unwrap_or(&asset.series[0]).to_string();
     ActiveMarket {
-        ticker: m.ticker.clone().unwrap_or_default(),
+        asset: asset.asset,
+        horizon: horizon.name.clone(),
+        ticker,
         event_ticker: m.event_ticker.clone().unwrap_or_default(),
-        series_ticker: config.btc_series_ticker.clone(),
+        series_ticker,
         strike: m.strike_price(),
         yes_bid: m.yes_bid_dollars.clone(),
         yes_ask: m.yes_ask_dollars.clone(),
@@ -200,5 +387,6 @@ fn market_to_active(config: &AppConfig, m: &Market) -> ActiveMarket {
         expiration_time: m.expiration_time.clone().unwrap_or_default(),
         status: m.status.clone().unwrap_or_default(),
         result: m.result.clone(),
+        depth,
     }
 }
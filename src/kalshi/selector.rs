@@ -0,0 +1,357 @@
+//! Market selection policies. For each horizon the scanner narrows the open
+//! markets down to a ladder: the active binary markets of the soonest close
+//! time, one per strike. A `MarketSelector` then picks the ladder's market
+//! to trade. Which one runs is set per deployment (`selection.policy`).
+//!
+//! Score-driven policies can move to another strike of the same ladder
+//! between polls; positions on the old market are held to settlement like
+//! after any other switch.
+
+use super::scanner::parse_datetime;
+use super::types::Market;
+use crate::models::black_scholes::BlackScholesDigital;
+use crate::models::{PricingModel, VolContext};
+use crate::state::ModelParams;
+use chrono::{DateTime, Utc};
+
+/// What the scanner knows besides the markets themselves.
+#[derive(Debug, Clone, Copy)]
+pub struct SelectionContext {
+    pub now: DateTime<Utc>,
+    /// Latest price of the underlying; unset until the feed has one
+    pub spot: Option<f64>,
+    /// Annualized volatility of the horizon's estimate; unset until it is warm
+    pub sigma: Option<f64>,
+}
+
+impl SelectionContext {
+    /// Market data only: model-driven selectors have nothing to go on.
+    pub fn at(now: DateTime<Utc>) -> Self {
+        Self { now, spot: None, sigma: None }
+    }
+}
+
+pub trait MarketSelector: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Higher is better. None when the market lacks what this selector ranks
+    /// by; such markets lose to any market with a score.
+    fn score(&self, market: &Market, ctx: &SelectionContext) -> Option<f64>;
+
+    /// Scores for a whole ladder, in order. Selectors that rank markets
+    /// relative to each other (see `Composite`) override this.
+    fn scores(&self, ladder: &[Market], ctx: &SelectionContext) -> Vec<Option<f64>> {
+        ladder.iter().map(|m| self.score(m, ctx)).collect()
+    }
+}
+
+/// The ladder's market to trade: the highest score, the earliest listed on
+/// ties. When the selector can score none of them (e.g. `MaxEdge` before
+/// the price feed is up) the nearest-ATM market is taken instead.
+pub fn pick(selector: &dyn MarketSelector, ladder: Vec<Market>, ctx: &SelectionContext) -> Option<Market> {
+    let mut scores = selector.scores(&ladder, ctx);
+    if scores.iter().all(Option::is_none) {
+        scores = NearestAtm.scores(&ladder, ctx);
+    }
+    let mut best: Option<(usize, f64)> = None;
+    for (i, score) in scores.into_iter().enumerate() {
+        if let Some(score) = score.filter(|s| s.is_finite()) {
+            if best.is_none_or(|(_, top)| score > top) {
+                best = Some((i, score));
+            }
+        }
+    }
+    let (i, _) = best?;
+    ladder.into_iter().nth(i)
+}
+
+/// YES ask closest to $0.50. The scanner's original rule: the market the
+/// models are least sure about, where a small edge moves the price most.
+pub struct NearestAtm;
+
+impl MarketSelector for NearestAtm {
+    fn name(&self) -> &'static str {
+        "nearest_atm"
+    }
+
+    fn score(&self, market: &Market, _ctx: &SelectionContext) -> Option<f64> {
+        Some(-(market.yes_ask_f64().unwrap_or(0.0) - 0.50).abs())
+    }
+}
+
+/// The largest gap between the Black-Scholes probability and the price of
+/// the cheaper side to buy, per contract before fees.
+pub struct MaxEdge {
+    model: BlackScholesDigital,
+}
+
+impl MaxEdge {
+    pub fn new() -> Self {
+        Self { model: BlackScholesDigital::new() }
+    }
+}
+
+impl Default for MaxEdge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarketSelector for MaxEdge {
+    fn name(&self) -> &'static str {
+        "max_edge"
+    }
+
+    fn score(&self, market: &Market, ctx: &SelectionContext) -> Option<f64> {
+        let (spot, sigma) = (ctx.spot.filter(|s| *s > 0.0)?, ctx.sigma.filter(|s| *s > 0.0)?);
+        let strike = market.strike_price().filter(|k| *k > 0.0)?;
+        let close = parse_datetime(market.close_time.as_deref()?)?;
+        let ttl = (close - ctx.now).num_milliseconds() as f64 / 1000.0;
+        let vol_ctx = VolContext { jump_intensity: 0.0, jump_mean: 0.0, jump_var: 0.0, student_t_nu: 5.0 };
+        let p = self.model.probability(&ModelParams::new(spot, strike, ttl, sigma), &vol_ctx);
+
+        let yes = market.yes_ask_f64().map(|ask| p - ask);
+        let no_ask = market.no_ask_dollars.as_deref().and_then(|s| s.parse::<f64>().ok());
+        let no = no_ask.or_else(|| market.yes_bid_f64().map(|bid| 1.0 - bid)).map(|ask| (1.0 - p) - ask);
+        match (yes, no) {
+            (Some(yes), Some(no)) => Some(yes.max(no)),
+            (edge, None) | (None, edge) => edge,
+        }
+    }
+}
+
+/// Most contracts traded plus held: volume and open interest.
+pub struct Liquidity;
+
+impl MarketSelector for Liquidity {
+    fn name(&self) -> &'static str {
+        "liquidity"
+    }
+
+    fn score(&self, market: &Market, _ctx: &SelectionContext) -> Option<f64> {
+        let count = |fp: &Option<String>, whole: Option<i64>| {
+            fp.as_deref().and_then(|s| s.parse::<f64>().ok()).or(whole.map(|n| n as f64))
+        };
+        let volume = count(&market.volume_fp, market.volume);
+        let open_interest = count(&market.open_interest_fp, market.open_interest);
+        match (volume, open_interest) {
+            (None, None) => None,
+            (v, oi) => Some(v.unwrap_or(0.0) + oi.unwrap_or(0.0)),
+        }
+    }
+}
+
+/// Narrowest YES bid/ask spread.
+pub struct TightestSpread;
+
+impl MarketSelector for TightestSpread {
+    fn name(&self) -> &'static str {
+        "tightest_spread"
+    }
+
+    fn score(&self, market: &Market, _ctx: &SelectionContext) -> Option<f64> {
+        Some(market.yes_bid_f64()? - market.yes_ask_f64()?)
+    }
+}
+
+/// A weighted sum of the other selectors. Each one's scores are rescaled to
+/// [0, 1] across the ladder first, so that cents of edge and thousands of
+/// contracts of volume can be weighed against each other.
+pub struct Composite {
+    parts: Vec<(f64, Box<dyn MarketSelector>)>,
+}
+
+impl Composite {
+    pub fn new(weights: &CompositeWeights) -> Self {
+        let parts: [(f64, Box<dyn MarketSelector>); 4] = [
+            (weights.atm, Box::new(NearestAtm)),
+            (weights.edge, Box::new(MaxEdge::new())),
+            (weights.liquidity, Box::new(Liquidity)),
+            (weights.spread, Box::new(TightestSpread)),
+        ];
+        Self { parts: parts.into_iter().filter(|(w, _)| *w > 0.0).collect() }
+    }
+}
+
+impl MarketSelector for Composite {
+    fn name(&self) -> &'static str {
+        "composite"
+    }
+
+    fn score(&self, market: &Market, ctx: &SelectionContext) -> Option<f64> {
+        self.scores(std::slice::from_ref(market), ctx).pop().flatten()
+    }
+
+    fn scores(&self, ladder: &[Market], ctx: &SelectionContext) -> Vec<Option<f64>> {
+        let mut total = vec![None; ladder.len()];
+        for (weight, part) in &self.parts {
+            let scores = part.scores(ladder, ctx);
+            let scored = scores.iter().flatten().copied().filter(|s| s.is_finite());
+            let (lo, hi) = scored.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), s| (lo.min(s), hi.max(s)));
+            for (sum, score) in total.iter_mut().zip(scores) {
+                let Some(score) = score.filter(|s| s.is_finite()) else { continue };
+                // One distinct score ranks everything it covers first
+                let scaled = if hi > lo { (score - lo) / (hi - lo) } else { 1.0 };
+                *sum = Some(sum.unwrap_or(0.0) + weight * scaled);
+            }
+        }
+        total
+    }
+}
+
+// ── Config ──
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionPolicy {
+    #[default]
+    NearestAtm,
+    MaxEdge,
+    Liquidity,
+    TightestSpread,
+    Composite,
+}
+
+/// `[profiles.<name>.selection]`
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SelectionConfig {
+    pub policy: SelectionPolicy,
+    /// Used by the `composite` policy only
+    pub weights: CompositeWeights,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompositeWeights {
+    pub atm: f64,
+    pub edge: f64,
+    pub liquidity: f64,
+    pub spread: f64,
+}
+
+impl Default for CompositeWeights {
+    fn default() -> Self {
+        Self { atm: 1.0, edge: 0.0, liquidity: 0.0, spread: 0.0 }
+    }
+}
+
+impl SelectionConfig {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        let w = &self.weights;
+        for (field, weight) in [("atm", w.atm), ("edge", w.edge), ("liquidity", w.liquidity), ("spread", w.spread)] {
+            if !(weight >= 0.0 && weight.is_finite()) {
+                errors.push(format!("selection.weights.{field} must be >= 0, got {weight}"));
+            }
+        }
+        if self.policy == SelectionPolicy::Composite && w.atm + w.edge + w.liquidity + w.spread <= 0.0 {
+            errors.push("selection.weights: the composite policy needs a weight above 0".to_string());
+        }
+    }
+
+    pub fn selector(&self) -> Box<dyn MarketSelector> {
+        match self.policy {
+            SelectionPolicy::NearestAtm => Box::new(NearestAtm),
+            SelectionPolicy::MaxEdge => Box::new(MaxEdge::new()),
+            SelectionPolicy::Liquidity => Box::new(Liquidity),
+            SelectionPolicy::TightestSpread => Box::new(TightestSpread),
+            SelectionPolicy::Composite => Box::new(Composite::new(&self.weights)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 14, 30, 0).unwrap()
+    }
+
+    /// One strike of the 15:00 ladder
+    fn market(strike: f64, yes_bid: f64, yes_ask: f64, volume: i64) -> Market {
+        serde_json::from_value(serde_json::json!({
+            "ticker": format!("KXBTCD-26MAR02H15-T{strike}"),
+            "market_type": "binary",
+            "status": "active",
+            "yes_bid_dollars": format!("{yes_bid:.2}"),
+            "yes_ask_dollars": format!("{yes_ask:.2}"),
+            "no_ask_dollars": format!("{:.2}", 1.0 - yes_bid),
+            "volume_fp": format!("{volume}.00"),
+            "open_interest_fp": "100.00",
+            "close_time": "2026-03-02T15:00:00Z",
+            "floor_strike": strike,
+        }))
+        .unwrap()
+    }
+
+    fn ladder() -> Vec<Market> {
+        vec![
+            // Deep in the money, thin
+            market(99_000.0, 0.90, 0.93, 50),
+            // At the money, wide
+            market(100_000.0, 0.40, 0.55, 800),
+            // Busiest, tight, YES cheap for a strike this close
+            market(100_250.0, 0.25, 0.27, 5_000),
+            market(101_000.0, 0.03, 0.06, 200),
+        ]
+    }
+
+    fn picked(selector: &dyn MarketSelector, ctx: &SelectionContext) -> f64 {
+        pick(selector, ladder(), ctx).and_then(|m| m.strike_price()).unwrap()
+    }
+
+    #[test]
+    fn test_single_policies() {
+        let ctx = SelectionContext::at(now());
+        assert_eq!(picked(&NearestAtm, &ctx), 100_000.0);
+        assert_eq!(picked(&Liquidity, &ctx), 100_250.0);
+        assert_eq!(picked(&TightestSpread, &ctx), 100_250.0);
+
+        // BTC at 100,200 with 50% vol: the 100,250 strike is close to a coin
+        // flip half an hour out, but YES costs 27c
+        let ctx = SelectionContext { spot: Some(100_200.0), sigma: Some(0.5), ..ctx };
+        let edge = MaxEdge::new();
+        assert_eq!(picked(&edge, &ctx), 100_250.0);
+        let atm_edge = edge.score(&ladder()[1], &ctx).unwrap();
+        assert!(atm_edge < edge.score(&ladder()[2], &ctx).unwrap());
+    }
+
+    #[test]
+    fn test_max_edge_falls_back_to_nearest_atm_without_a_price() {
+        assert_eq!(picked(&MaxEdge::new(), &SelectionContext::at(now())), 100_000.0);
+        assert!(pick(&MaxEdge::new(), Vec::new(), &SelectionContext::at(now())).is_none());
+    }
+
+    #[test]
+    fn test_composite_weighs_normalized_scores() {
+        let ctx = SelectionContext::at(now());
+        let atm_only = Composite::new(&CompositeWeights::default());
+        assert_eq!(picked(&atm_only, &ctx), 100_000.0);
+
+        // Liquidity outweighs ATM-ness once it counts for more
+        let weights = CompositeWeights { atm: 1.0, liquidity: 2.0, ..CompositeWeights::default() };
+        assert_eq!(picked(&Composite::new(&weights), &ctx), 100_250.0);
+
+        let scores = Composite::new(&weights).scores(&ladder(), &ctx);
+        assert!(scores.iter().all(|s| s.is_some_and(|s| (0.0..=3.0).contains(&s))));
+    }
+
+    #[test]
+    fn test_config() {
+        let config: SelectionConfig = toml::from_str("policy = \"composite\"\n[weights]\nedge = 1.0\nliquidity = 0.5\n").unwrap();
+        assert_eq!(config.policy, SelectionPolicy::Composite);
+        assert_eq!(config.weights.atm, 1.0);
+        assert_eq!(config.selector().name(), "composite");
+        assert_eq!(SelectionConfig::default().selector().name(), "nearest_atm");
+
+        let mut errors = Vec::new();
+        config.validate(&mut errors);
+        assert!(errors.is_empty(), "{errors:?}");
+        let zero = CompositeWeights { atm: 0.0, edge: -1.0, liquidity: 0.0, spread: 0.0 };
+        SelectionConfig { policy: SelectionPolicy::Composite, weights: zero }.validate(&mut errors);
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(toml::from_str::<SelectionConfig>("policy = \"cheapest\"").is_err());
+    }
+}
@@ -109,6 +109,21 @@ pub struct OrderbookResponse {
     pub orderbook_fp: Option<OrderbookFp>,
 }
 
+/// Balances in cents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceResponse {
+    pub balance: Option<i64>,
+    pub portfolio_value: Option<i64>,
+}
+
+/// `GET /account/limits`: requests per second allowed for the account's tier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountLimitsResponse {
+    pub usage_tier: Option<String>,
+    pub read_limit: Option<f64>,
+    pub write_limit: Option<f64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Orderbook {
     pub yes: Option<Vec<Vec<serde_json::Value>>>,
@@ -174,3 +189,77 @@ pub struct Series {
 pub struct GetSeriesResponse {
     pub series: Option<Vec<Series>>,
 }
+
+// ── Exchange ──
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeStatus {
+    /// False during exchange maintenance
+    pub exchange_active: bool,
+    /// False outside trading hours, or while Kalshi has paused trading
+    pub trading_active: bool,
+    pub exchange_estimated_resume_time: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetExchangeScheduleResponse {
+    pub schedule: Option<Schedule>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Schedule {
+    #[serde(default)]
+    pub standard_hours: Vec<WeeklySchedule>,
+    #[serde(default)]
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+}
+
+/// Trading sessions per weekday, in effect from `start_time` to
+/// `end_time`. Session times are ET, "HH:MM".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklySchedule {
+    pub start_time: String,
+    pub end_time: String,
+    #[serde(default)]
+    pub monday: Vec<DailySchedule>,
+    #[serde(default)]
+    pub tuesday: Vec<DailySchedule>,
+    #[serde(default)]
+    pub wednesday: Vec<DailySchedule>,
+    #[serde(default)]
+    pub thursday: Vec<DailySchedule>,
+    #[serde(default)]
+    pub friday: Vec<DailySchedule>,
+    #[serde(default)]
+    pub saturday: Vec<DailySchedule>,
+    #[serde(default)]
+    pub sunday: Vec<DailySchedule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailySchedule {
+    pub open_time: String,
+    pub close_time: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub start_datetime: String,
+    pub end_datetime: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetExchangeAnnouncementsResponse {
+    pub announcements: Option<Vec<Announcement>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Announcement {
+    /// "info", "warning" or "error"
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub message: String,
+    pub delivery_time: String,
+    /// "active" or "inactive"
+    pub status: String,
+}
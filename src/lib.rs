@@ -0,0 +1,87 @@
+//! Paper-trading engine for Kalshi's hourly crypto binary markets: BTC
+//! (`KXBTCD`) by default, and ETH and SOL alongside it ([`asset`]).
+//!
+//! The pieces are usable on their own:
+//!
+//! - [`models`]: digital option pricers behind [`PricingModel`]
+//!   (Black-Scholes, Merton jump-diffusion, Student-t), the
+//!   [`VolatilityEngine`] that estimates their inputs from a price stream, and
+//!   the isotonic [`Calibrator`].
+//! - [`execution`]: fee- and fill-adjusted expected value ([`compute_ev`]).
+//! - [`risk`]: Kelly sizing ([`compute_kelly`]), per-model and portfolio
+//!   limits, Monte Carlo VaR/ES.
+//! - [`paper`]: the decision step ([`paper::simulator::run_tick`]), settlement,
+//!   analytics and offline replay ([`paper::backtest`]).
+//! - [`kalshi`]: a REST client with RSA request signing ([`KalshiClient`]).
+//!
+//! and together they make the engine: [`engine::run_engine`] owns all
+//! trading state and reacts to [`EngineEvent`]s sent over a channel, writing
+//! through [`db`] and publishing snapshots that [`server`] serves. The
+//! `pretty_rusty` binary wires it to live feeds; `examples/` shows pricing a
+//! contract directly and driving the engine from a custom event source.
+//!
+//! ```
+//! use pretty_rusty::models::black_scholes::BlackScholesDigital;
+//! use pretty_rusty::state::ModelParams;
+//! use pretty_rusty::{PricingModel, VolContext};
+//!
+//! // BTC $200 above the strike, 30 minutes left, 60% annualized vol
+//! let params = ModelParams::new(100_200.0, 100_000.0, 30.0 * 60.0, 0.60);
+//! let vol_ctx = VolContext { jump_intensity: 0.0, jump_mean: 0.0, jump_var: 0.0, student_t_nu: 5.0 };
+//! let p = BlackScholesDigital::new().probability(&params, &vol_ctx);
+//! assert!(p > 0.5 && p < 1.0);
+//! ```
+//!
+//! The public types are `Send + Sync`: `PricingModel` requires it, and
+//! `KalshiClient`, `AppState` and `DbPool` are cheap to clone and share
+//! across tasks. The estimators (`VolatilityEngine`, `Calibrator`,
+//! `ModelState`) are plain data updated through `&mut`, so each is owned by
+//! one task at a time.
+
+pub mod asset;
+pub mod clock;
+pub mod config;
+pub mod db;
+pub mod engine;
+pub mod errors;
+pub mod execution;
+pub mod feeds;
+pub mod horizon;
+pub mod kalshi;
+pub mod metrics;
+pub mod models;
+pub mod paper;
+pub mod risk;
+pub mod server;
+pub mod state;
+pub mod supervisor;
+pub mod trading_day;
+
+pub use asset::Asset;
+pub use errors::{EngineError, EngineResult};
+pub use execution::ev::{compute_ev, EvParams, EvResult};
+pub use kalshi::client::KalshiClient;
+pub use models::calibration::Calibrator;
+pub use models::volatility::VolatilityEngine;
+pub use models::{PricingModel, VolContext};
+pub use risk::kelly::{compute_kelly, KellyParams, KellyResult};
+pub use state::{AppState, EngineEvent};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_public_types_are_send_and_sync() {
+        assert_send_sync::<Box<dyn PricingModel>>();
+        assert_send_sync::<VolatilityEngine>();
+        assert_send_sync::<Calibrator>();
+        assert_send_sync::<state::ModelState>();
+        assert_send_sync::<KalshiClient>();
+        assert_send_sync::<AppState>();
+        assert_send_sync::<db::DbPool>();
+        assert_send_sync::<config::AppConfig>();
+    }
+}
@@ -0,0 +1,369 @@
+//! Prometheus metrics, rendered in the text exposition format on `/metrics`.
+//!
+//! Hot-path instruments (engine, DB writer) are fixed-bucket histograms
+//! backed by atomics. Labeled series for external APIs live behind a mutex,
+//! which is fine at a few requests per second. Per-model gauges are not
+//! stored at all: they are read from the latest `EngineSnapshot` at scrape
+//! time.
+
+use crate::state::{AppState, EngineSnapshot};
+use portable_atomic::{AtomicF64, AtomicU64, Ordering};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+
+/// Latency buckets in seconds (100us .. 10s).
+const LATENCY_BUCKETS: &[f64] = &[
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+    10.0,
+];
+
+/// Queue depth buckets (channel slots in use).
+const DEPTH_BUCKETS: &[f64] = &[0.0, 1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 512.0, 1024.0];
+
+/// Commands per DB writer transaction.
+const BATCH_BUCKETS: &[f64] = &[1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 256.0];
+
+// ── Histogram ──
+
+/// Fixed-bucket histogram. `observe` is lock-free.
+pub struct Histogram {
+    bounds: &'static [f64],
+    /// Non-cumulative per-bucket counts; the last slot is +Inf
+    buckets: Box<[AtomicU64]>,
+    sum: AtomicF64,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            buckets: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicF64::new(0.0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    #[inline]
+    pub fn observe(&self, value: f64) {
+        let idx = self.bounds.iter().position(|&b| value <= b).unwrap_or(self.bounds.len());
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0u64;
+        for (i, bound) in self.bounds.iter().enumerate() {
+            cumulative += self.buckets[i].load(Ordering::Relaxed);
+            let _ = writeln!(out, "{name}_bucket{{{labels}{sep}le=\"{bound}\"}} {cumulative}");
+        }
+        cumulative += self.buckets[self.bounds.len()].load(Ordering::Relaxed);
+        let _ = writeln!(out, "{name}_bucket{{{labels}{sep}le=\"+Inf\"}} {cumulative}");
+        let braces = if labels.is_empty() { String::new() } else { format!("{{{labels}}}") };
+        let _ = writeln!(out, "{name}_sum{braces} {}", self.sum.load(Ordering::Relaxed));
+        let _ = writeln!(out, "{name}_count{braces} {}", self.count.load(Ordering::Relaxed));
+    }
+}
+
+// ── Labeled families ──
+
+/// Histogram family keyed by a fixed set of label values.
+pub struct HistogramVec {
+    label_names: &'static [&'static str],
+    bounds: &'static [f64],
+    series: Mutex<BTreeMap<Vec<String>, Histogram>>,
+}
+
+impl HistogramVec {
+    pub fn new(label_names: &'static [&'static str], bounds: &'static [f64]) -> Self {
+        Self { label_names, bounds, series: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn observe(&self, label_values: &[&str], value: f64) {
+        let mut series = self.series.lock().unwrap_or_else(|e| e.into_inner());
+        let key: Vec<String> = label_values.iter().map(|v| v.to_string()).collect();
+        series
+            .entry(key)
+            .or_insert_with(|| Histogram::new(self.bounds))
+            .observe(value);
+    }
+
+    fn render(&self, out: &mut String, name: &str) {
+        let series = self.series.lock().unwrap_or_else(|e| e.into_inner());
+        for (values, hist) in series.iter() {
+            hist.render(out, name, &format_labels(self.label_names, values));
+        }
+    }
+}
+
+/// Monotonic counter family keyed by label values.
+pub struct CounterVec {
+    label_names: &'static [&'static str],
+    series: Mutex<BTreeMap<Vec<String>, u64>>,
+}
+
+impl CounterVec {
+    pub fn new(label_names: &'static [&'static str]) -> Self {
+        Self { label_names, series: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn inc(&self, label_values: &[&str]) {
+        let mut series = self.series.lock().unwrap_or_else(|e| e.into_inner());
+        let key: Vec<String> = label_values.iter().map(|v| v.to_string()).collect();
+        *series.entry(key).or_insert(0) += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str) {
+        let series = self.series.lock().unwrap_or_else(|e| e.into_inner());
+        for (values, count) in series.iter() {
+            let _ = writeln!(out, "{name}{{{}}} {count}", format_labels(self.label_names, values));
+        }
+    }
+}
+
+// ── Registry ──
+
+/// All instruments not already covered by `PerfCounters`.
+pub struct Metrics {
+    /// Wall time of `process_event`, by event kind
+    pub engine_event_seconds: HistogramVec,
+    /// Engine channel slots in use, sampled per event
+    pub engine_channel_depth: Histogram,
+    /// DB writer channel slots in use, sampled per command
+    pub db_queue_depth: Histogram,
+    /// Time to execute one DB command, by command kind
+    pub db_write_seconds: HistogramVec,
+    pub db_write_errors: CounterVec,
+    /// Enqueue to commit, per command: how far the writer is behind
+    pub db_writer_lag_seconds: Histogram,
+    /// Commands per writer transaction
+    pub db_batch_size: Histogram,
+    /// Time to commit one writer transaction
+    pub db_commit_seconds: Histogram,
+    /// Outbound HTTP latency by api ("kalshi" / "crypto") and endpoint
+    pub api_request_seconds: HistogramVec,
+    /// Outbound HTTP requests by api, endpoint and status ("200", "429", "error", ...)
+    pub api_requests: CounterVec,
+    /// Retried outbound requests by api and endpoint
+    pub api_retries: CounterVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            engine_event_seconds: HistogramVec::new(&["event"], LATENCY_BUCKETS),
+            engine_channel_depth: Histogram::new(DEPTH_BUCKETS),
+            db_queue_depth: Histogram::new(DEPTH_BUCKETS),
+            db_write_seconds: HistogramVec::new(&["command"], LATENCY_BUCKETS),
+            db_write_errors: CounterVec::new(&["command"]),
+            db_writer_lag_seconds: Histogram::new(LATENCY_BUCKETS),
+            db_batch_size: Histogram::new(BATCH_BUCKETS),
+            db_commit_seconds: Histogram::new(LATENCY_BUCKETS),
+            api_request_seconds: HistogramVec::new(&["api", "endpoint"], LATENCY_BUCKETS),
+            api_requests: CounterVec::new(&["api", "endpoint", "status"]),
+            api_retries: CounterVec::new(&["api", "endpoint"]),
+        }
+    }
+
+    /// Record one outbound request. `status` is the HTTP code, or `None` if
+    /// the request failed before a response (timeout, connect error).
+    pub fn observe_api(&self, api: &str, endpoint: &str, status: Option<u16>, seconds: f64) {
+        self.api_request_seconds.observe(&[api, endpoint], seconds);
+        let status = status.map_or_else(|| "error".to_string(), |s| s.to_string());
+        self.api_requests.inc(&[api, endpoint, &status]);
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Render every metric in Prometheus text format.
+pub fn render(state: &AppState) -> String {
+    use portable_atomic::Ordering::Relaxed;
+
+    let mut out = String::with_capacity(16 * 1024);
+    let c = &state.counters;
+    let m = &state.metrics;
+
+    for (name, help, value) in [
+        ("pretty_rusty_ticks_processed_total", "Engine ticks processed", c.ticks_processed.load(Relaxed)),
+        ("pretty_rusty_events_processed_total", "Engine events handled", c.events_processed.load(Relaxed)),
+        ("pretty_rusty_prices_received_total", "BTC price updates received", c.prices_received.load(Relaxed)),
+        ("pretty_rusty_decisions_made_total", "Decision loop runs", c.decisions_made.load(Relaxed)),
+        ("pretty_rusty_trades_placed_total", "Paper trades placed", c.trades_placed.load(Relaxed)),
+        ("pretty_rusty_errors_recovered_total", "Engine errors recovered from", c.errors_recovered.load(Relaxed)),
+        ("pretty_rusty_ws_messages_sent_total", "WebSocket messages broadcast", c.ws_messages_sent.load(Relaxed)),
+    ] {
+        header(&mut out, name, help, "counter");
+        let _ = writeln!(out, "{name} {value}");
+    }
+
+    header(&mut out, "pretty_rusty_engine_event_seconds", "process_event latency by event kind", "histogram");
+    m.engine_event_seconds.render(&mut out, "pretty_rusty_engine_event_seconds");
+
+    header(&mut out, "pretty_rusty_engine_channel_depth", "Engine event channel depth", "histogram");
+    m.engine_channel_depth.render(&mut out, "pretty_rusty_engine_channel_depth", "");
+
+    header(&mut out, "pretty_rusty_db_queue_depth", "DB writer channel depth", "histogram");
+    m.db_queue_depth.render(&mut out, "pretty_rusty_db_queue_depth", "");
+
+    header(&mut out, "pretty_rusty_db_write_seconds", "DB command execution latency", "histogram");
+    m.db_write_seconds.render(&mut out, "pretty_rusty_db_write_seconds");
+
+    header(&mut out, "pretty_rusty_db_write_errors_total", "Failed DB commands", "counter");
+    m.db_write_errors.render(&mut out, "pretty_rusty_db_write_errors_total");
+
+    header(&mut out, "pretty_rusty_db_writer_lag_seconds", "DB command enqueue-to-commit latency", "histogram");
+    m.db_writer_lag_seconds.render(&mut out, "pretty_rusty_db_writer_lag_seconds", "");
+
+    header(&mut out, "pretty_rusty_db_batch_size", "DB commands per writer transaction", "histogram");
+    m.db_batch_size.render(&mut out, "pretty_rusty_db_batch_size", "");
+
+    header(&mut out, "pretty_rusty_db_commit_seconds", "DB writer transaction commit latency", "histogram");
+    m.db_commit_seconds.render(&mut out, "pretty_rusty_db_commit_seconds", "");
+
+    header(&mut out, "pretty_rusty_api_request_seconds", "Outbound API request latency", "histogram");
+    m.api_request_seconds.render(&mut out, "pretty_rusty_api_request_seconds");
+
+    header(&mut out, "pretty_rusty_api_requests_total", "Outbound API requests by status", "counter");
+    m.api_requests.render(&mut out, "pretty_rusty_api_requests_total");
+
+    header(&mut out, "pretty_rusty_api_retries_total", "Outbound API requests retried", "counter");
+    m.api_retries.render(&mut out, "pretty_rusty_api_retries_total");
+
+    // Clone so the watch lock isn't held while formatting
+    let snapshot = state.snapshot_rx.borrow().clone();
+    render_snapshot(&mut out, &snapshot);
+    out
+}
+
+/// Gauges read from the latest engine snapshot.
+fn render_snapshot(out: &mut String, snap: &EngineSnapshot) {
+    header(out, "pretty_rusty_engine_state", "1 for the current engine state", "gauge");
+    for s in ["connecting", "syncing", "trading", "paused", "exchange_closed", "maintenance", "halted"] {
+        let v = u8::from(snap.engine_state.to_string() == s);
+        let _ = writeln!(out, "pretty_rusty_engine_state{{state=\"{s}\"}} {v}");
+    }
+
+    header(out, "pretty_rusty_kalshi_breaker_state", "1 for the Kalshi API circuit breaker's state", "gauge");
+    for s in ["closed", "open", "half_open"] {
+        let v = u8::from(snap.kalshi_api.breaker.to_string() == s);
+        let _ = writeln!(out, "pretty_rusty_kalshi_breaker_state{{state=\"{s}\"}} {v}");
+    }
+
+    header(out, "pretty_rusty_underlying_price", "Last price seen by the engine per asset", "gauge");
+    for a in &snap.assets {
+        let _ = writeln!(out, "pretty_rusty_underlying_price{{asset=\"{}\"}} {}", a.asset, a.price);
+    }
+
+    header(out, "pretty_rusty_model_pnl", "Model P/L in dollars by kind", "gauge");
+    for ms in snap.models() {
+        let (model, asset) = (escape(ms.name), ms.asset);
+        for (kind, v) in [
+            ("realized", ms.cumulative_pnl),
+            ("unrealized", ms.unrealized_pnl),
+            ("daily", ms.daily_pnl),
+        ] {
+            let _ = writeln!(out, "pretty_rusty_model_pnl{{model=\"{model}\",asset=\"{asset}\",kind=\"{kind}\"}} {v}");
+        }
+    }
+
+    header(out, "pretty_rusty_model_exposure", "Model exposure in dollars", "gauge");
+    for ms in snap.models() {
+        let _ = writeln!(
+            out,
+            "pretty_rusty_model_exposure{{model=\"{}\",asset=\"{}\"}} {}",
+            escape(ms.name),
+            ms.asset,
+            ms.current_exposure
+        );
+    }
+
+    header(out, "pretty_rusty_model_open_positions", "Open positions per model", "gauge");
+    for ms in snap.models() {
+        let _ = writeln!(
+            out,
+            "pretty_rusty_model_open_positions{{model=\"{}\",asset=\"{}\"}} {}",
+            escape(ms.name),
+            ms.asset,
+            ms.open_positions.len()
+        );
+    }
+
+    header(out, "pretty_rusty_model_max_drawdown", "Model max drawdown in dollars", "gauge");
+    for ms in snap.models() {
+        let _ = writeln!(
+            out,
+            "pretty_rusty_model_max_drawdown{{model=\"{}\",asset=\"{}\"}} {}",
+            escape(ms.name),
+            ms.asset,
+            ms.max_drawdown
+        );
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn format_labels(names: &[&str], values: &[String]) -> String {
+    names
+        .iter()
+        .zip(values)
+        .map(|(n, v)| format!("{n}=\"{}\"", escape(v)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Escape a label value per the exposition format.
+fn escape(v: &str) -> String {
+    v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let h = Histogram::new(&[1.0, 2.0]);
+        h.observe(0.5);
+        h.observe(1.5);
+        h.observe(9.0);
+        let mut out = String::new();
+        h.render(&mut out, "x", "");
+        assert!(out.contains("x_bucket{le=\"1\"} 1\n"), "{out}");
+        assert!(out.contains("x_bucket{le=\"2\"} 2\n"), "{out}");
+        assert!(out.contains("x_bucket{le=\"+Inf\"} 3\n"), "{out}");
+        assert!(out.contains("x_sum 11\n"), "{out}");
+        assert!(out.contains("x_count 3\n"), "{out}");
+    }
+
+    #[test]
+    fn test_labeled_series() {
+        let m = Metrics::new();
+        m.observe_api("kalshi", "/markets", Some(200), 0.01);
+        m.observe_api("kalshi", "/markets", None, 10.0);
+        let mut out = String::new();
+        m.api_requests.render(&mut out, "req");
+        assert!(out.contains("req{api=\"kalshi\",endpoint=\"/markets\",status=\"200\"} 1"), "{out}");
+        assert!(out.contains("req{api=\"kalshi\",endpoint=\"/markets\",status=\"error\"} 1"), "{out}");
+
+        out.clear();
+        m.api_request_seconds.render(&mut out, "lat");
+        assert!(out.contains("lat_count{api=\"kalshi\",endpoint=\"/markets\"} 2"), "{out}");
+    }
+
+    #[test]
+    fn test_label_escaping() {
+        assert_eq!(escape(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(escape("x\ny"), "x\\ny");
+    }
+}
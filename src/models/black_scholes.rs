@@ -28,6 +28,12 @@ impl BlackScholesDigital {
     }
 }
 
+impl Default for BlackScholesDigital {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PricingModel for BlackScholesDigital {
     #[inline]
     fn name(&self) -> &'static str {
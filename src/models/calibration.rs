@@ -1,12 +1,11 @@
+const NUM_BUCKETS: usize = 10;
+
 /// Isotonic regression calibrator.
 ///
 /// Buckets model predictions into bins, tracks realized frequency,
 /// and applies pool-adjacent-violators (PAV) to produce calibrated probabilities.
 ///
 /// All operations are in-place on fixed-size arrays. No heap allocation after init.
-
-const NUM_BUCKETS: usize = 10;
-
 #[derive(Debug, Clone)]
 pub struct Calibrator {
     /// Per-bucket: (predicted_count, realized_count)
@@ -143,6 +142,12 @@ impl Calibrator {
     }
 }
 
+impl Default for Calibrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[inline]
 fn prob_to_bucket(prob: f64) -> usize {
     let idx = (prob * NUM_BUCKETS as f64) as usize;
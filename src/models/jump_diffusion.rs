@@ -12,11 +12,6 @@ use statrs::distribution::{ContinuousCDF, Normal};
 /// Truncated Poisson sum (K_max=10). All stack-allocated.
 const K_MAX: usize = 10;
 
-/// Precomputed factorials for k=0..10
-const FACTORIALS: [f64; 11] = [
-    1.0, 1.0, 2.0, 6.0, 24.0, 120.0, 720.0, 5040.0, 40320.0, 362880.0, 3628800.0,
-];
-
 pub struct JumpDiffusionDigital {
     normal: Normal,
 }
@@ -28,6 +23,12 @@ impl JumpDiffusionDigital {
     }
 }
 
+impl Default for JumpDiffusionDigital {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PricingModel for JumpDiffusionDigital {
     #[inline]
     fn name(&self) -> &'static str {
@@ -101,6 +102,31 @@ mod tests {
         assert!((p_jd - p_bs).abs() < 0.01, "JD with no jumps ({p_jd}) should match BS ({p_bs})");
     }
 
+    #[test]
+    fn test_poisson_weights_match_factorial_form() {
+        // The sum as written out in the doc comment, with k! from a table
+        const FACTORIALS: [f64; K_MAX + 1] = [
+            1.0, 1.0, 2.0, 6.0, 24.0, 120.0, 720.0, 5040.0, 40320.0, 362880.0, 3628800.0,
+        ];
+        let jd = JumpDiffusionDigital::new();
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let ctx = VolContext { jump_intensity: 2000.0, jump_mean: 0.0, jump_var: 1e-5, student_t_nu: 5.0 };
+        for (spot, ttl) in [(100_000.0, 900.0), (100_400.0, 3600.0), (99_500.0, 86_400.0)] {
+            let params = ModelParams::new(spot, 100_000.0, ttl, 0.5);
+            let lambda_t = ctx.jump_intensity * params.ttl_years;
+            let expected: f64 = (0..=K_MAX)
+                .map(|k| {
+                    let weight = (-lambda_t).exp() * lambda_t.powi(k as i32) / FACTORIALS[k];
+                    let sigma_k_sq = params.sigma * params.sigma + k as f64 * ctx.jump_var / params.ttl_years;
+                    let d2_k = (params.ln_s_k - 0.5 * sigma_k_sq * params.ttl_years) / (sigma_k_sq.sqrt() * params.sqrt_t);
+                    weight * normal.cdf(d2_k)
+                })
+                .sum();
+            let p = jd.probability(&params, &ctx);
+            assert!((p - expected.clamp(0.001, 0.999)).abs() < 1e-12, "{p} vs {expected}");
+        }
+    }
+
     #[test]
     fn test_with_jumps_differs() {
         let jd = JumpDiffusionDigital::new();
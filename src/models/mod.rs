@@ -6,6 +6,25 @@ pub mod calibration;
 
 use crate::state::ModelParams;
 
+/// Every pricing model the engine can run, by `PricingModel::name`
+pub const MODEL_NAMES: [&str; 3] = ["Black-Scholes", "Jump-Diffusion", "Student-t"];
+
+/// The models named in `names` (see `MODEL_NAMES`), in that order.
+/// Unknown names are skipped; config validation rejects them.
+pub fn enabled(names: &[String]) -> Vec<Box<dyn PricingModel>> {
+    names
+        .iter()
+        .filter_map(|name| -> Option<Box<dyn PricingModel>> {
+            match name.as_str() {
+                "Black-Scholes" => Some(Box::new(black_scholes::BlackScholesDigital::new())),
+                "Jump-Diffusion" => Some(Box::new(jump_diffusion::JumpDiffusionDigital::new())),
+                "Student-t" => Some(Box::new(student_t::StudentTDigital::new())),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
 /// All pricing models implement this trait.
 /// probability() must be a pure function: deterministic output from inputs only.
 /// Send + Sync required for use across tokio tasks.
@@ -17,6 +17,12 @@ impl StudentTDigital {
     }
 }
 
+impl Default for StudentTDigital {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PricingModel for StudentTDigital {
     #[inline]
     fn name(&self) -> &'static str {
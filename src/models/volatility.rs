@@ -1,12 +1,6 @@
 use crate::state::{VolRegime, VolatilityState};
 use std::collections::VecDeque;
 
-/// EWMA decay factor (lambda = 0.94 is standard for short-horizon)
-const EWMA_LAMBDA: f64 = 0.94;
-
-/// Threshold multiplier for jump detection (returns > JUMP_THRESHOLD * sigma)
-const JUMP_THRESHOLD: f64 = 3.0;
-
 /// Rolling window for jump intensity estimation (number of observations)
 const JUMP_WINDOW: usize = 300;
 
@@ -14,11 +8,41 @@ const JUMP_WINDOW: usize = 300;
 const SHORT_VOL_WINDOW: usize = 30;
 const LONG_VOL_WINDOW: usize = 300;
 
-/// Regime threshold: short_vol / long_vol > this = high regime
-const REGIME_THRESHOLD: f64 = 1.5;
+/// Estimator settings from the `[volatility]` config section. Scalars only:
+/// the rolling windows size pre-allocated buffers and stay fixed.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VolParams {
+    /// EWMA decay factor (lambda = 0.94 is standard for short-horizon)
+    pub ewma_lambda: f64,
+    /// Returns larger than this many sigma count as jumps
+    pub jump_threshold: f64,
+    /// short_vol / long_vol above this = high regime
+    pub regime_threshold: f64,
+    /// Minimum samples before vol estimates are considered reliable
+    pub min_samples: u64,
+}
 
-/// Minimum samples before vol estimates are considered reliable
-const MIN_SAMPLES: u64 = 20;
+impl Default for VolParams {
+    fn default() -> Self {
+        Self { ewma_lambda: 0.94, jump_threshold: 3.0, regime_threshold: 1.5, min_samples: 20 }
+    }
+}
+
+impl VolParams {
+    /// Append a message per out-of-range field to `errors`.
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        let mut check = |ok: bool, field: &str, rule: &str, value: f64| {
+            if !ok {
+                errors.push(format!("volatility.{field} must be {rule}, got {value}"));
+            }
+        };
+        check(self.ewma_lambda > 0.0 && self.ewma_lambda < 1.0, "ewma_lambda", "in (0, 1)", self.ewma_lambda);
+        check(self.jump_threshold > 0.0, "jump_threshold", "> 0", self.jump_threshold);
+        check(self.regime_threshold > 0.0, "regime_threshold", "> 0", self.regime_threshold);
+        check(self.min_samples >= 2, "min_samples", ">= 2", self.min_samples as f64);
+    }
+}
 
 /// Volatility engine. Maintains state across ticks.
 /// All updates are in-place, no allocations after construction.
@@ -31,15 +55,18 @@ pub struct VolatilityEngine {
     prev_price: f64,
     /// Current state (stack-allocated)
     pub state: VolatilityState,
+    /// Replaced on a config reload; takes effect from the next update
+    pub params: VolParams,
 }
 
 impl VolatilityEngine {
-    pub fn new() -> Self {
+    pub fn new(params: VolParams) -> Self {
         Self {
             returns: VecDeque::with_capacity(LONG_VOL_WINDOW + 10),
             jump_buffer: VecDeque::with_capacity(JUMP_WINDOW + 10),
             prev_price: 0.0,
             state: VolatilityState::default(),
+            params,
         }
     }
 
@@ -78,14 +105,15 @@ impl VolatilityEngine {
 
         // EWMA volatility update
         let r_sq = log_return * log_return;
-        self.state.ewma_vol = (EWMA_LAMBDA * self.state.ewma_vol * self.state.ewma_vol
-            + (1.0 - EWMA_LAMBDA) * r_sq)
+        let lambda = self.params.ewma_lambda;
+        self.state.ewma_vol = (lambda * self.state.ewma_vol * self.state.ewma_vol
+            + (1.0 - lambda) * r_sq)
             .sqrt();
 
         // Clamp vol to sane range
         self.state.ewma_vol = self.state.ewma_vol.clamp(1e-8, 1.0);
 
-        if self.state.sample_count < MIN_SAMPLES {
+        if self.state.sample_count < self.params.min_samples {
             return;
         }
 
@@ -101,7 +129,7 @@ impl VolatilityEngine {
 
     fn update_jump_stats(&mut self) {
         let sigma = self.state.ewma_vol;
-        let threshold = JUMP_THRESHOLD * sigma;
+        let threshold = self.params.jump_threshold * sigma;
 
         let mut jump_count: u32 = 0;
         let mut jump_sum: f64 = 0.0;
@@ -145,7 +173,7 @@ impl VolatilityEngine {
 
         if long_var > 1e-16 {
             let ratio = short_var / long_var;
-            self.state.regime = if ratio > REGIME_THRESHOLD {
+            self.state.regime = if ratio > self.params.regime_threshold {
                 VolRegime::High
             } else {
                 VolRegime::Low
@@ -194,18 +222,18 @@ impl VolatilityEngine {
     pub fn annualized_vol(&self) -> f64 {
         // Each observation ~2s. Per year: 365.25 * 24 * 3600 / 2 = ~15_778_800
         // Annual vol = per-obs vol * sqrt(obs/year)
-        let obs_per_year: f64 = 365.25 * 24.0 * 3600.0 / 2.0;
-        self.state.ewma_vol * obs_per_year.sqrt()
+        self.state.annualized()
     }
 
     #[inline]
     pub fn is_ready(&self) -> bool {
-        self.state.sample_count >= MIN_SAMPLES
+        self.state.sample_count >= self.params.min_samples
     }
 }
 
 /// Compute variance of the last `window` elements in a VecDeque. No allocation.
 #[inline]
+#[allow(clippy::needless_range_loop)]
 fn variance_of_last(data: &VecDeque<f64>, window: usize) -> f64 {
     let n = data.len().min(window);
     if n < 2 {
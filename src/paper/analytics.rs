@@ -0,0 +1,404 @@
+//! Performance analytics over closed trades.
+//!
+//! Unlike `tracker`, which reports the engine's live in-memory state, this
+//! works from the `trades` table over an arbitrary date range. All
+//! functions are pure -- they take trades and return computed values.
+//!
+//! Models are reported per asset: the same model on BTC and on ETH are two
+//! strategies with their own curves. A trade counts once, at its close
+//! time, with its full realized P/L (partial exits included). Returns are
+//! P/L over entry cost, the same basis `ModelState::record_return` uses.
+
+use crate::asset::Asset;
+use crate::db::TradeRow;
+use std::collections::BTreeMap;
+
+/// ~96 trades/day (every 15 min); same basis as `ModelState::compute_sharpe`
+const TRADES_PER_YEAR: f64 = 96.0 * 365.0;
+
+/// Upper bounds (seconds to close at entry) for the TTL buckets.
+const TTL_BUCKETS: &[(f64, &str)] = &[
+    (300.0, "<5m"),
+    (600.0, "5-10m"),
+    (900.0, "10-15m"),
+    (1800.0, "15-30m"),
+    (3600.0, "30-60m"),
+    (f64::INFINITY, "60m+"),
+];
+
+/// Upper bounds (entry EV per contract) for the EV buckets.
+const EV_BUCKETS: &[(f64, &str)] = &[
+    (0.02, "<0.02"),
+    (0.05, "0.02-0.05"),
+    (0.10, "0.05-0.10"),
+    (0.20, "0.10-0.20"),
+    (f64::INFINITY, "0.20+"),
+];
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AnalyticsReport {
+    pub trades: usize,
+    pub models: Vec<ModelAnalytics>,
+    /// P/L by (asset, model, exit reason)
+    pub attribution: Vec<Bucket>,
+    pub by_ttl: Vec<Bucket>,
+    pub by_ev: Vec<Bucket>,
+    pub by_regime: Vec<Bucket>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ModelAnalytics {
+    pub asset: Asset,
+    pub model: String,
+    pub trades: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub hit_rate: f64,
+    pub total_pnl: f64,
+    pub total_fees: f64,
+    pub avg_win: f64,
+    pub avg_loss: f64,
+    /// Gross wins / gross losses; `None` with no losing trades
+    pub profit_factor: Option<f64>,
+    pub sharpe: f64,
+    pub sortino: f64,
+    pub max_drawdown: f64,
+    /// Annualized P/L over max drawdown; `None` with no drawdown
+    pub calmar: Option<f64>,
+    pub equity_curve: Vec<CurvePoint>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CurvePoint {
+    pub t: String,
+    pub equity: f64,
+    /// Distance below the running peak (>= 0)
+    pub drawdown: f64,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct Bucket {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset: Option<Asset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub key: String,
+    pub trades: usize,
+    pub wins: usize,
+    pub hit_rate: f64,
+    pub pnl: f64,
+    pub avg_pnl: f64,
+}
+
+/// Build the full report. `trades` must be closed trades in close-time
+/// order (`db::trades::closed_trades`). `period_days` is the length of the
+/// requested range, used to annualize Calmar; when `None` the span of the
+/// trades themselves is used.
+pub fn compute_report(trades: &[TradeRow], period_days: Option<f64>) -> AnalyticsReport {
+    let mut by_model: BTreeMap<(Asset, &str), Vec<&TradeRow>> = BTreeMap::new();
+    for t in trades {
+        by_model.entry((t.asset, &t.model_name)).or_default().push(t);
+    }
+    let models = by_model
+        .into_iter()
+        .map(|((asset, name), trades)| model_analytics(asset, name, &trades, period_days))
+        .collect();
+
+    AnalyticsReport {
+        trades: trades.len(),
+        models,
+        attribution: group(trades, |t| (Some((t.asset, t.model_name.clone())), exit_reason(t))),
+        by_ttl: in_bucket_order(
+            TTL_BUCKETS,
+            group(trades, |t| {
+                let key = match t.entry_ttl_seconds {
+                    Some(ttl) => bucket_label(TTL_BUCKETS, ttl),
+                    None => "unknown",
+                };
+                (None, key.to_string())
+            }),
+        ),
+        by_ev: in_bucket_order(
+            EV_BUCKETS,
+            group(trades, |t| (None, bucket_label(EV_BUCKETS, t.ev).to_string())),
+        ),
+        by_regime: group(trades, |t| {
+            (None, t.entry_regime.clone().unwrap_or_else(|| "unknown".into()))
+        }),
+    }
+}
+
+fn model_analytics(asset: Asset, name: &str, trades: &[&TradeRow], period_days: Option<f64>) -> ModelAnalytics {
+    let pnls: Vec<f64> = trades.iter().map(|t| pnl(t)).collect();
+    let returns: Vec<f64> = trades
+        .iter()
+        .map(|t| pnl(t) / (t.entry_price * t.contracts).max(0.01))
+        .collect();
+
+    let wins: Vec<f64> = pnls.iter().copied().filter(|p| *p > 0.0).collect();
+    let losses: Vec<f64> = pnls.iter().copied().filter(|p| *p <= 0.0).collect();
+    let gross_win: f64 = wins.iter().sum();
+    let gross_loss: f64 = -losses.iter().sum::<f64>();
+    let total_pnl = gross_win - gross_loss;
+
+    let mut equity_curve = Vec::with_capacity(trades.len());
+    let (mut equity, mut peak, mut max_drawdown) = (0.0_f64, 0.0_f64, 0.0_f64);
+    for (t, p) in trades.iter().zip(&pnls) {
+        equity += p;
+        peak = peak.max(equity);
+        let drawdown = peak - equity;
+        max_drawdown = max_drawdown.max(drawdown);
+        equity_curve.push(CurvePoint {
+            t: t.settle_time.clone().unwrap_or_default(),
+            equity,
+            drawdown,
+        });
+    }
+
+    let days = period_days.unwrap_or_else(|| span_days(trades)).max(1.0);
+    let annual_pnl = total_pnl * 365.0 / days;
+
+    ModelAnalytics {
+        asset,
+        model: name.to_string(),
+        trades: trades.len(),
+        wins: wins.len(),
+        losses: losses.len(),
+        hit_rate: ratio(wins.len(), trades.len()),
+        total_pnl,
+        total_fees: trades.iter().map(|t| t.realized_fees).sum(),
+        avg_win: mean(&wins),
+        avg_loss: mean(&losses),
+        profit_factor: (gross_loss > 0.0).then(|| gross_win / gross_loss),
+        sharpe: sharpe(&returns),
+        sortino: sortino(&returns),
+        max_drawdown,
+        calmar: (max_drawdown > 0.0).then(|| annual_pnl / max_drawdown),
+        equity_curve,
+    }
+}
+
+/// Group trades by ((asset, model), key), sorted by key.
+fn group(trades: &[TradeRow], key: impl Fn(&TradeRow) -> (Option<(Asset, String)>, String)) -> Vec<Bucket> {
+    let mut buckets: BTreeMap<(Option<(Asset, String)>, String), Bucket> = BTreeMap::new();
+    for t in trades {
+        let (owner, key) = key(t);
+        let b = buckets.entry((owner.clone(), key.clone())).or_insert_with(|| Bucket {
+            asset: owner.as_ref().map(|(asset, _)| *asset),
+            model: owner.map(|(_, model)| model),
+            key,
+            ..Default::default()
+        });
+        b.trades += 1;
+        b.pnl += pnl(t);
+        if pnl(t) > 0.0 {
+            b.wins += 1;
+        }
+    }
+    buckets
+        .into_values()
+        .map(|mut b| {
+            b.hit_rate = ratio(b.wins, b.trades);
+            b.avg_pnl = b.pnl / b.trades as f64;
+            b
+        })
+        .collect()
+}
+
+#[inline]
+fn pnl(t: &TradeRow) -> f64 {
+    t.pnl.unwrap_or(0.0)
+}
+
+/// Why the trade closed: the exit rule, `settlement`, or for rows that
+/// predate `exit_reason` the raw outcome.
+fn exit_reason(t: &TradeRow) -> String {
+    t.exit_reason
+        .clone()
+        .or_else(|| t.outcome.clone())
+        .unwrap_or_else(|| "unknown".into())
+}
+
+/// Re-sort range buckets low to high (`unknown` last).
+fn in_bucket_order(buckets: &[(f64, &str)], mut groups: Vec<Bucket>) -> Vec<Bucket> {
+    groups.sort_by_key(|b| {
+        buckets
+            .iter()
+            .position(|(_, label)| *label == b.key)
+            .unwrap_or(buckets.len())
+    });
+    groups
+}
+
+fn bucket_label(buckets: &[(f64, &'static str)], value: f64) -> &'static str {
+    buckets
+        .iter()
+        .find(|(upper, _)| value < *upper)
+        .map_or("unknown", |(_, label)| label)
+}
+
+/// Length of a `since`..`until` range in days, when both ends are given
+/// as RFC 3339 timestamps or `YYYY-MM-DD` dates.
+pub fn period_days(since: Option<&str>, until: Option<&str>) -> Option<f64> {
+    let parse = |s: &str| {
+        chrono::DateTime::parse_from_rfc3339(s)
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+            .ok()
+            .or_else(|| {
+                chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    .map(|dt| dt.and_utc())
+            })
+    };
+    let (since, until) = (parse(since?)?, parse(until?)?);
+    Some((until - since).num_seconds() as f64 / 86_400.0)
+}
+
+fn span_days(trades: &[&TradeRow]) -> f64 {
+    let parse = |t: Option<&&TradeRow>| {
+        t.and_then(|t| t.settle_time.as_deref())
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+    };
+    match (parse(trades.first()), parse(trades.last())) {
+        (Some(first), Some(last)) => (last - first).num_seconds() as f64 / 86_400.0,
+        _ => 0.0,
+    }
+}
+
+fn ratio(n: usize, d: usize) -> f64 {
+    if d == 0 {
+        0.0
+    } else {
+        n as f64 / d as f64
+    }
+}
+
+fn mean(xs: &[f64]) -> f64 {
+    if xs.is_empty() {
+        0.0
+    } else {
+        xs.iter().sum::<f64>() / xs.len() as f64
+    }
+}
+
+/// Annualized Sharpe of per-trade returns.
+pub fn sharpe(returns: &[f64]) -> f64 {
+    let n = returns.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let m = mean(returns);
+    let var = returns.iter().map(|r| (r - m) * (r - m)).sum::<f64>() / (n as f64 - 1.0);
+    let std = var.sqrt();
+    if std < 1e-12 {
+        return 0.0;
+    }
+    (m / std) * TRADES_PER_YEAR.sqrt()
+}
+
+/// Annualized Sortino of per-trade returns: like Sharpe, but only returns
+/// below zero count towards the deviation.
+pub fn sortino(returns: &[f64]) -> f64 {
+    let n = returns.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let downside = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n as f64).sqrt();
+    if downside < 1e-12 {
+        return 0.0;
+    }
+    (mean(returns) / downside) * TRADES_PER_YEAR.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(model: &str, pnl: f64, reason: &str, ttl: Option<f64>, ev: f64, t: &str) -> TradeRow {
+        TradeRow {
+            id: format!("{model}-{t}"),
+            model_name: model.into(),
+            asset: Asset::Btc,
+            horizon: "hourly".into(),
+            market_ticker: "M".into(),
+            side: "yes".into(),
+            action: "buy".into(),
+            entry_price: 0.5,
+            contracts: 10.0,
+            model_probability: 0.6,
+            ev,
+            kelly_fraction: 0.05,
+            outcome: Some(if reason == "settlement" { "win".into() } else { format!("exit:{reason}") }),
+            pnl: Some(pnl),
+            fees_estimate: 0.1,
+            entry_time: t.into(),
+            settle_time: Some(t.into()),
+            exit_price: None,
+            exit_reason: Some(reason.into()),
+            status: "closed",
+            remaining_contracts: 0.0,
+            realized_fees: 0.2,
+            entry_ttl_seconds: ttl,
+            entry_regime: Some("low".into()),
+            fills: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_model_stats_and_curve() {
+        let trades = vec![
+            trade("A", 2.0, "take_profit", Some(700.0), 0.06, "2026-10-01T00:00:00+00:00"),
+            trade("A", -3.0, "strike_cross", Some(400.0), 0.03, "2026-10-02T00:00:00+00:00"),
+            trade("A", 4.0, "settlement", Some(1000.0), 0.12, "2026-10-03T00:00:00+00:00"),
+        ];
+        let report = compute_report(&trades, Some(10.0));
+        let a = &report.models[0];
+
+        assert_eq!((a.trades, a.wins, a.losses), (3, 2, 1));
+        assert!((a.total_pnl - 3.0).abs() < 1e-12);
+        assert!((a.avg_win - 3.0).abs() < 1e-12);
+        assert!((a.profit_factor.unwrap() - 2.0).abs() < 1e-12);
+        assert!((a.max_drawdown - 3.0).abs() < 1e-12);
+        // 3.0 over 10 days, annualized, over a 3.0 drawdown
+        assert!((a.calmar.unwrap() - 36.5).abs() < 1e-9);
+        let curve: Vec<(f64, f64)> = a.equity_curve.iter().map(|p| (p.equity, p.drawdown)).collect();
+        assert_eq!(curve, vec![(2.0, 0.0), (-1.0, 3.0), (3.0, 0.0)]);
+        assert!(a.sortino > a.sharpe);
+    }
+
+    #[test]
+    fn test_buckets() {
+        let trades = vec![
+            trade("A", 1.0, "take_profit", Some(400.0), 0.06, "1"),
+            trade("A", -1.0, "take_profit", Some(550.0), 0.01, "2"),
+            trade("B", 1.0, "settlement", None, 0.25, "3"),
+        ];
+        let report = compute_report(&trades, None);
+
+        let keys = |b: &[Bucket]| b.iter().map(|b| (b.key.clone(), b.trades)).collect::<Vec<_>>();
+        assert_eq!(keys(&report.by_ttl), vec![("5-10m".into(), 2), ("unknown".into(), 1)]);
+        assert_eq!(
+            keys(&report.by_ev),
+            vec![("<0.02".into(), 1), ("0.05-0.10".into(), 1), ("0.20+".into(), 1)]
+        );
+        assert_eq!(report.attribution.len(), 2);
+        assert_eq!(report.attribution[0].model.as_deref(), Some("A"));
+        assert!((report.attribution[0].hit_rate - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_same_model_on_two_assets_reported_apart() {
+        let mut eth = trade("A", -5.0, "settlement", None, 0.05, "3");
+        eth.asset = Asset::Eth;
+        let trades = vec![trade("A", 2.0, "settlement", None, 0.05, "1"), trade("A", 1.0, "settlement", None, 0.05, "2"), eth];
+        let report = compute_report(&trades, None);
+
+        let models: Vec<(Asset, &str, usize)> =
+            report.models.iter().map(|m| (m.asset, m.model.as_str(), m.trades)).collect();
+        assert_eq!(models, vec![(Asset::Btc, "A", 2), (Asset::Eth, "A", 1)]);
+        assert_eq!(report.models[0].max_drawdown, 0.0);
+        let owners: Vec<_> = report.attribution.iter().map(|b| (b.asset, b.trades)).collect();
+        assert_eq!(owners, vec![(Some(Asset::Btc), 2), (Some(Asset::Eth), 1)]);
+    }
+}
@@ -0,0 +1,571 @@
+//! Offline replay of recorded history through the paper strategy.
+//!
+//! The live engine records every underlying price (`underlying_prices`),
+//! every quote for each asset's active market (`market_quotes`) and each
+//! market's strike, close time and result (`markets`). A replay covers one
+//! asset and one expiry horizon: its prices and the quotes of markets in its
+//! series whose time to close was in the horizon's band when quoted. Models
+//! share limits across horizons live but not here, so replaying horizons
+//! one at a time is a per-horizon view. It feeds those back through the same steps
+//! as the engine loop: the volatility engine on every price, a market switch
+//! on every new ticker, `simulator::run_tick` once per second of replay
+//! time, `settle_trades` once a market with a recorded result has closed,
+//! and the trading-day rollover.
+//!
+//! Quotes are recorded without the order book or trade tape behind them, so
+//! of the liquidity gates only `max_spread` applies to a replay.
+//!
+//! Trades go to a separate database through the writer's own
+//! `db::execute_command`, so `report` works on a backtest exactly as it
+//! does on the live database. Nothing is written to the history database.
+
+use crate::asset::AssetConfig;
+use crate::config::TradingParams;
+use crate::db;
+use crate::errors::{EngineError, EngineResult};
+use crate::horizon::Horizon;
+use crate::models::calibration::Calibrator;
+use crate::models::volatility::VolatilityEngine;
+use crate::models::PricingModel;
+use crate::paper::simulator::{self, parse_time, EngineAction};
+use crate::risk::portfolio::PortfolioBook;
+use crate::risk::var::HorizonVols;
+use crate::state::{ActiveMarket, DbCommand, DecisionRecord, MarketDepth, ModelState};
+use crate::trading_day::{self, TradingCalendar};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+/// Ticks are skipped while the last price is older than this: the engine
+/// wasn't receiving prices, so it wouldn't have traded either
+const STALE_PRICE_SECS: i64 = 10;
+
+/// Gaps in the recording longer than this are jumped over, not ticked through
+const MAX_GAP_SECS: i64 = 60;
+
+/// The price feed polls every 2s and `VolatilityEngine` annualizes on that basis
+const FEED_INTERVAL_SECS: f64 = 2.0;
+
+/// Decision journal rows buffered before they are written
+const JOURNAL_BATCH_ROWS: usize = 256;
+
+/// What to replay and with which settings.
+pub struct Replay<'a> {
+    pub asset: &'a AssetConfig,
+    pub horizon: &'a Horizon,
+    pub models: &'a [&'a dyn PricingModel],
+    pub trading: &'a TradingParams,
+    pub calendar: TradingCalendar,
+    /// Lower bound, inclusive (RFC 3339)
+    pub from: Option<String>,
+    /// Upper bound, exclusive
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct ReplayStats {
+    /// First and last recorded event replayed
+    pub start: String,
+    pub end: String,
+    pub prices: usize,
+    pub quotes: usize,
+    pub ticks: u64,
+    pub markets: usize,
+    pub markets_settled: usize,
+    /// Trades still open at the end (their market has no recorded result)
+    pub open_trades: usize,
+    /// Median spacing of the recorded prices
+    pub median_price_gap_secs: f64,
+}
+
+/// Replay `history` into `out`, which must already be migrated.
+pub fn run(history: &Connection, out: &mut Connection, replay: &Replay) -> EngineResult<ReplayStats> {
+    let has_quotes: bool = history.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'market_quotes'",
+        [],
+        |r| r.get(0),
+    )?;
+    if !has_quotes {
+        return Err(EngineError::Database(
+            "history database predates quote recording (schema version 10); run the engine with this build to record some".into(),
+        ));
+    }
+
+    let prices = load_prices(history, replay)?;
+    let (quotes, markets) = load_quotes(history, replay)?;
+    let (Some(first_price), Some(first_quote)) = (prices.first(), quotes.first()) else {
+        return Err(EngineError::Database(format!(
+            "nothing to replay: {} prices and {} quotes recorded in the range",
+            prices.len(),
+            quotes.len()
+        )));
+    };
+
+    let median_gap = median_gap_secs(&prices);
+    if median_gap > FEED_INTERVAL_SECS * 1.5 {
+        tracing::warn!(
+            median_gap_secs = median_gap,
+            "prices were recorded less often than the feed polls; volatility will be overstated"
+        );
+    }
+
+    let start = first_price.0.min(first_quote.0);
+    let end = prices[prices.len() - 1].0.max(quotes[quotes.len() - 1].0);
+
+    let mut trading = replay.horizon.params(replay.trading);
+    trading.liquidity = trading.liquidity.quotes_only();
+    let tx = out.transaction()?;
+    let mut sim = Sim {
+        replay,
+        conn: &tx,
+        vol: VolatilityEngine::new(trading.volatility),
+        trading,
+        model_states: replay.models.iter().map(|m| ModelState::new(m.name(), replay.asset.asset)).collect(),
+        calibrators: replay.models.iter().map(|_| Calibrator::new()).collect(),
+        journal: Vec::with_capacity(JOURNAL_BATCH_ROWS + replay.models.len()),
+        active: None,
+        markets,
+        btc_price: 0.0,
+        last_price_at: None,
+        day: replay.calendar.day_of(start),
+        ticks: 0,
+    };
+
+    let (mut i, mut j) = (0, 0);
+    let mut clock = start;
+    loop {
+        while let Some(&(at, price)) = prices.get(i).filter(|p| p.0 <= clock) {
+            sim.on_price(at, price);
+            i += 1;
+        }
+        while let Some((_, market)) = quotes.get(j).filter(|q| q.0 <= clock) {
+            sim.on_quote(market)?;
+            j += 1;
+        }
+        sim.settle_closed(clock)?;
+        sim.tick(clock)?;
+
+        let next = match (prices.get(i), quotes.get(j)) {
+            (Some(p), Some(q)) => p.0.min(q.0),
+            (Some(p), None) => p.0,
+            (None, Some(q)) => q.0,
+            (None, None) => break,
+        };
+        clock += Duration::seconds(1);
+        if next - clock > Duration::seconds(MAX_GAP_SECS) {
+            clock = next;
+        }
+    }
+    // Markets that closed after the last event still settle if their
+    // result was recorded
+    sim.settle_closed(DateTime::<Utc>::MAX_UTC)?;
+    sim.flush_journal()?;
+
+    let ticks = sim.ticks;
+    let markets = sim.markets.values().filter(|m| m.seen).count();
+    let markets_settled = sim.markets.values().filter(|m| m.settled).count();
+    let open_trades: i64 = tx.query_row("SELECT COUNT(*) FROM trades WHERE outcome IS NULL", [], |r| r.get(0))?;
+    tx.commit()?;
+
+    Ok(ReplayStats {
+        start: start.to_rfc3339(),
+        end: end.to_rfc3339(),
+        prices: prices.len(),
+        quotes: quotes.len(),
+        ticks,
+        markets,
+        markets_settled,
+        open_trades: open_trades as usize,
+        median_price_gap_secs: median_gap,
+    })
+}
+
+struct MarketInfo {
+    close: Option<DateTime<Utc>>,
+    close_time: String,
+    result: Option<String>,
+    /// Quoted at least once in the replay
+    seen: bool,
+    settled: bool,
+}
+
+/// The engine loop's state, minus everything that talks to the outside.
+struct Sim<'a> {
+    replay: &'a Replay<'a>,
+    conn: &'a Connection,
+    vol: VolatilityEngine,
+    /// The replay's params with the horizon's overrides
+    trading: TradingParams,
+    model_states: Vec<ModelState>,
+    calibrators: Vec<Calibrator>,
+    journal: Vec<DecisionRecord>,
+    active: Option<ActiveMarket>,
+    markets: HashMap<String, MarketInfo>,
+    btc_price: f64,
+    last_price_at: Option<DateTime<Utc>>,
+    day: NaiveDate,
+    ticks: u64,
+}
+
+impl Sim<'_> {
+    fn on_price(&mut self, at: DateTime<Utc>, price: f64) {
+        self.vol.update(price);
+        self.btc_price = price;
+        self.last_price_at = Some(at);
+    }
+
+    /// Same market-switch handling as the engine's `MarketUpdate`.
+    fn on_quote(&mut self, market: &ActiveMarket) -> EngineResult<()> {
+        if self.active.as_ref().map(|m| &m.ticker) != Some(&market.ticker) {
+            if let Some(info) = self.markets.get_mut(&market.ticker) {
+                if !info.seen {
+                    info.seen = true;
+                    db::execute_command(self.conn, DbCommand::InsertMarket {
+                        ticker: market.ticker.clone(),
+                        event_ticker: market.event_ticker.clone(),
+                        series_ticker: market.series_ticker.clone(),
+                        strike_price: market.strike,
+                        open_time: String::new(),
+                        close_time: market.close_time.clone(),
+                        expiration_time: market.expiration_time.clone(),
+                    })?;
+                }
+            }
+        }
+        self.active = Some(market.clone());
+        Ok(())
+    }
+
+    /// Settle every quoted market with a recorded result that closed by `now`,
+    /// at its close time.
+    fn settle_closed(&mut self, now: DateTime<Utc>) -> EngineResult<()> {
+        let mut due: Vec<String> = self
+            .markets
+            .iter()
+            .filter(|(_, m)| m.seen && !m.settled && m.result.is_some() && m.close.is_some_and(|c| c <= now))
+            .map(|(ticker, _)| ticker.clone())
+            .collect();
+        // Close-time order, as the scanner would see them settle
+        due.sort_by_key(|t| (self.markets[t].close, t.clone()));
+        for ticker in due {
+            let Some(info) = self.markets.get_mut(&ticker) else { continue };
+            info.settled = true;
+            let result = info.result.clone().unwrap_or_default();
+            let timestamp = info.close_time.clone();
+
+            let pending = db::get_pending_trades(self.conn, &ticker)?;
+            let actions =
+                simulator::settle_trades(&mut self.model_states, &mut self.calibrators, &ticker, &result, &pending, &timestamp);
+            self.apply(actions)?;
+            db::execute_command(self.conn, DbCommand::UpdateMarketResult { ticker, result, settlement_value: None })?;
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self, now: DateTime<Utc>) -> EngineResult<()> {
+        let today = self.replay.calendar.day_of(now);
+        if today > self.day {
+            let previous = std::mem::replace(&mut self.day, today);
+            for cmd in trading_day::close_day(&self.replay.calendar, previous, today, &mut self.model_states) {
+                db::execute_command(self.conn, cmd)?;
+            }
+        }
+
+        // The engine only trades once volatility is ready and a market is
+        // active, and only while prices are arriving
+        let fresh = self.last_price_at.is_some_and(|t| now - t <= Duration::seconds(STALE_PRICE_SECS));
+        if self.vol.is_ready() && self.active.is_some() && fresh {
+            let mut vols = HorizonVols::default();
+            vols.push(&self.replay.horizon.name, &self.vol.state);
+            let mut book = PortfolioBook::default();
+            if let Some(model) = self.replay.models.first() {
+                book.add_states(*model, &self.model_states, self.btc_price, &vols, now);
+            }
+            let actions = simulator::run_tick(
+                self.replay.models,
+                &mut self.model_states,
+                &mut self.calibrators,
+                &self.vol.state,
+                &vols,
+                &self.active,
+                self.btc_price,
+                &self.trading,
+                &mut book,
+                &now.to_rfc3339(),
+                self.ticks,
+                None,
+                &mut self.journal,
+            );
+            self.apply(actions)?;
+            if self.journal.len() >= JOURNAL_BATCH_ROWS {
+                self.flush_journal()?;
+            }
+        }
+        self.ticks += 1;
+        Ok(())
+    }
+
+    fn apply(&self, actions: impl IntoIterator<Item = EngineAction>) -> EngineResult<()> {
+        for action in actions {
+            if let EngineAction::DbWrite(cmd) = action {
+                db::execute_command(self.conn, cmd)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush_journal(&mut self) -> EngineResult<()> {
+        if self.journal.is_empty() {
+            return Ok(());
+        }
+        let records = std::mem::take(&mut self.journal);
+        db::execute_command(self.conn, DbCommand::InsertDecisions { records })
+    }
+}
+
+fn load_prices(conn: &Connection, replay: &Replay) -> EngineResult<Vec<(DateTime<Utc>, f64)>> {
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, price FROM underlying_prices
+         WHERE asset = ?1 AND (?2 IS NULL OR timestamp >= ?2) AND (?3 IS NULL OR timestamp < ?3)
+         ORDER BY timestamp, id",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![replay.asset.asset, replay.from, replay.to], |r| {
+        Ok((r.get::<_, String>(0)?, r.get::<_, f64>(1)?))
+    })?;
+    let mut prices: Vec<_> = rows
+        .filter_map(|r| r.ok())
+        .filter_map(|(t, p)| Some((parse_time(&t)?, p)))
+        .collect();
+    // Timestamps are compared as text above; order by instant to be sure
+    prices.sort_by_key(|p| p.0);
+    Ok(prices)
+}
+
+type Quotes = Vec<(DateTime<Utc>, ActiveMarket)>;
+
+fn load_quotes(conn: &Connection, replay: &Replay) -> EngineResult<(Quotes, HashMap<String, MarketInfo>)> {
+    let mut stmt = conn.prepare(
+        "SELECT q.timestamp, q.ticker, q.yes_bid, q.yes_ask, q.no_bid, q.no_ask, q.last_price, q.status,
+                m.event_ticker, m.series_ticker, m.strike_price, m.close_time, m.expiration_time, m.result
+         FROM market_quotes q JOIN markets m ON m.ticker = q.ticker
+         WHERE (?1 IS NULL OR q.timestamp >= ?1) AND (?2 IS NULL OR q.timestamp < ?2)
+         ORDER BY q.timestamp, q.id",
+    )?;
+    let price = |v: Option<f64>| v.map(|p| p.to_string());
+    let rows = stmt.query_map(rusqlite::params![replay.from, replay.to], |r| {
+        let market = ActiveMarket {
+            asset: replay.asset.asset,
+            horizon: replay.horizon.name.clone(),
+            ticker: r.get(1)?,
+            yes_bid: price(r.get(2)?),
+            yes_ask: price(r.get(3)?),
+            no_bid: price(r.get(4)?),
+            no_ask: price(r.get(5)?),
+            last_price: price(r.get(6)?),
+            status: r.get(7)?,
+            event_ticker: r.get(8)?,
+            series_ticker: r.get(9)?,
+            strike: r.get(10)?,
+            close_time: r.get(11)?,
+            expiration_time: r.get(12)?,
+            // Not known while the market trades
+            result: None,
+            depth: MarketDepth::default(),
+        };
+        Ok((r.get::<_, String>(0)?, market, r.get::<_, Option<String>>(13)?))
+    })?;
+
+    let mut quotes = Vec::new();
+    let mut markets = HashMap::new();
+    for (timestamp, market, result) in rows.filter_map(|r| r.ok()) {
+        // Other assets' markets are quoted in the same table
+        if replay.asset.series_of(&market.ticker).is_none() {
+            continue;
+        }
+        let Some(at) = parse_time(&timestamp) else { continue };
+        // As the scanner would have picked it: only while in the horizon's band
+        let ttl = parse_time(&market.close_time).map_or(-1.0, |close| (close - at).num_seconds() as f64);
+        if !replay.horizon.contains(ttl) {
+            continue;
+        }
+        markets.entry(market.ticker.clone()).or_insert_with(|| MarketInfo {
+            close: parse_time(&market.close_time),
+            close_time: market.close_time.clone(),
+            result: result.filter(|r| r == "yes" || r == "no"),
+            seen: false,
+            settled: false,
+        });
+        quotes.push((at, market));
+    }
+    quotes.sort_by_key(|q| q.0);
+    Ok((quotes, markets))
+}
+
+fn median_gap_secs(prices: &[(DateTime<Utc>, f64)]) -> f64 {
+    let mut gaps: Vec<i64> = prices.windows(2).map(|w| (w[1].0 - w[0].0).num_milliseconds()).collect();
+    if gaps.is_empty() {
+        return 0.0;
+    }
+    gaps.sort_unstable();
+    gaps[gaps.len() / 2] as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::Asset;
+    use crate::db::migrations;
+    use crate::models::black_scholes::BlackScholesDigital;
+    use crate::models::student_t::StudentTDigital;
+
+    fn btc() -> AssetConfig {
+        AssetConfig { asset: Asset::Btc, series: vec!["KXBTCD".into()] }
+    }
+
+    fn migrated() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrations::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    /// Ten minutes of BTC well above a strike whose YES is offered cheap,
+    /// on a market that closes two minutes after the recording ends and
+    /// settled YES.
+    fn history() -> Connection {
+        let conn = migrated();
+        let t0 = DateTime::parse_from_rfc3339("2026-10-18T14:00:00Z").unwrap().with_timezone(&Utc);
+        let close = (t0 + Duration::minutes(12)).to_rfc3339();
+        conn.execute(
+            "INSERT INTO markets (ticker, event_ticker, series_ticker, strike_price, open_time, close_time, expiration_time, result)
+             VALUES ('KXBTCD-T100000', 'KXBTCD-E', 'KXBTCD', 100000.0, '', ?1, ?1, 'yes')",
+            [&close],
+        )
+        .unwrap();
+        for i in 0..300 {
+            let at = (t0 + Duration::seconds(2 * i)).to_rfc3339();
+            let price = 100_400.0 + 15.0 * ((i as f64) * 0.7).sin();
+            conn.execute(
+                "INSERT INTO underlying_prices (asset, timestamp, price) VALUES ('btc', ?1, ?2)",
+                rusqlite::params![at, price],
+            )
+            .unwrap();
+        }
+        for i in 0..120 {
+            let at = (t0 + Duration::seconds(5 * i)).to_rfc3339();
+            conn.execute(
+                "INSERT INTO market_quotes (timestamp, ticker, yes_bid, yes_ask, no_bid, no_ask, last_price, status)
+                 VALUES (?1, 'KXBTCD-T100000', 0.60, 0.62, 0.36, 0.38, 0.61, 'active')",
+                [&at],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn replay(history: &Connection) -> (Connection, ReplayStats) {
+        let (bs, st) = (BlackScholesDigital::new(), StudentTDigital::new());
+        let models: [&dyn PricingModel; 2] = [&bs, &st];
+        let trading = TradingParams::default();
+        let mut out = migrated();
+        let stats = run(
+            history,
+            &mut out,
+            &Replay {
+                asset: &btc(),
+                horizon: &Horizon::default(),
+                models: &models,
+                trading: &trading,
+                calendar: TradingCalendar::default(),
+                from: None,
+                to: None,
+            },
+        )
+        .unwrap();
+        (out, stats)
+    }
+
+    fn trade_summary(conn: &Connection) -> Vec<(String, String, f64)> {
+        let mut stmt = conn
+            .prepare("SELECT model_name, COALESCE(outcome, 'open'), ROUND(COALESCE(pnl, 0), 6) FROM trades ORDER BY entry_time, model_name")
+            .unwrap();
+        stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_replay_trades_and_settles_recorded_market() {
+        let history = history();
+        let (out, stats) = replay(&history);
+
+        assert_eq!(stats.prices, 300);
+        assert_eq!(stats.quotes, 120);
+        assert_eq!(stats.markets, 1);
+        assert_eq!(stats.markets_settled, 1);
+        assert_eq!(stats.open_trades, 0);
+        assert!((stats.median_price_gap_secs - 2.0).abs() < 1e-9);
+
+        let trades = trade_summary(&out);
+        assert!(!trades.is_empty(), "cheap YES deep in the money should be bought");
+        let result: String = out
+            .query_row("SELECT result FROM markets WHERE ticker = 'KXBTCD-T100000'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(result, "yes");
+        let decisions: i64 = out.query_row("SELECT COUNT(*) FROM decisions", [], |r| r.get(0)).unwrap();
+        assert!(decisions > 0);
+    }
+
+    #[test]
+    fn test_replay_is_deterministic() {
+        let history = history();
+        let (a, _) = replay(&history);
+        let (b, _) = replay(&history);
+        assert_eq!(trade_summary(&a), trade_summary(&b));
+    }
+
+    #[test]
+    fn test_empty_range_is_an_error() {
+        let history = history();
+        let (bs, trading) = (BlackScholesDigital::new(), TradingParams::default());
+        let models: [&dyn PricingModel; 1] = [&bs];
+        let err = run(
+            &history,
+            &mut migrated(),
+            &Replay {
+                asset: &btc(),
+                horizon: &Horizon::default(),
+                models: &models,
+                trading: &trading,
+                calendar: TradingCalendar::default(),
+                from: Some("2030-01-01".into()),
+                to: None,
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("nothing to replay"), "{err}");
+    }
+
+    #[test]
+    fn test_replay_only_sees_its_asset() {
+        let history = history();
+        let (bs, trading) = (BlackScholesDigital::new(), TradingParams::default());
+        let models: [&dyn PricingModel; 1] = [&bs];
+        let eth = AssetConfig { asset: Asset::Eth, series: vec!["KXETHD".into()] };
+        let err = run(
+            &history,
+            &mut migrated(),
+            &Replay {
+                asset: &eth,
+                horizon: &Horizon::default(),
+                models: &models,
+                trading: &trading,
+                calendar: TradingCalendar::default(),
+                from: None,
+                to: None,
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("0 prices and 0 quotes"), "{err}");
+    }
+}
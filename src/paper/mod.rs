@@ -1,2 +1,4 @@
+pub mod analytics;
+pub mod backtest;
 pub mod simulator;
 pub mod tracker;
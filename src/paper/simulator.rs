@@ -1,10 +1,13 @@
+use crate::asset::Asset;
 use crate::execution::ev::{self, EvParams};
 use crate::models::calibration::Calibrator;
 use crate::models::{PricingModel, VolContext};
 use crate::risk::kelly::{self, KellyParams};
 use crate::risk::limits;
+use crate::risk::portfolio::{self, PortfolioBook, PortfolioCheck};
+use crate::risk::var::{self, EsGate, HorizonVols};
 use crate::state::*;
-use crate::config::AppConfig;
+use crate::config::{RiskParams, TradingParams};
 use smallvec::SmallVec;
 
 /// Output actions from the engine's decision loop.
@@ -60,30 +63,113 @@ pub enum EngineAction {
 //    the coin-flip zone.
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// BTC must cross strike by this $ amount against us to trigger hard exit
-const STRIKE_CROSS_BUFFER: f64 = 25.0;
-/// BTC must move this $ amount further in our favor to trigger a scale-in
-const SCALE_IN_MOVE: f64 = 75.0;
-/// Max legs per model (initial + scale-ins)
-const MAX_LEGS: u32 = 3;
-/// Trailing stop: exit if unrealized drops this fraction below peak
-const TRAILING_STOP_PCT: f64 = 0.50;
-/// Take partial profit: sell half when unrealized > this % of cost
-const PARTIAL_TAKE_PROFIT_PCT: f64 = 0.40;
-/// Hard take profit: sell everything when unrealized > this % of cost
-const FULL_TAKE_PROFIT_PCT: f64 = 0.80;
-/// Time exit: exit if not clearly winning within this many seconds of close
-const UNCERTAIN_EXIT_SECONDS: f64 = 240.0;
-/// BTC must be this far from strike to hold to resolution
-const RESOLUTION_HOLD_DISTANCE: f64 = 200.0;
-/// Don't exit if this close to expiry and clearly winning (let it resolve at $1)
-const RESOLUTION_HOLD_SECONDS: f64 = 120.0;
-/// Minimum hold time before any exit (ticks, ~1 tick/second)
-const MIN_HOLD_TICKS: u64 = 5;
-/// Don't enter with less than this many seconds to expiry
-const MIN_ENTRY_TTL: f64 = 300.0;
-/// Stop-loss: hard cut at this % of entry cost
-const HARD_STOP_LOSS_PCT: f64 = 0.70;
+/// Strategy thresholds, fees and execution assumptions. Loaded from the
+/// `[strategy]` section of the config profile and swapped in place on a
+/// config reload; the defaults are the values the strategy was tuned with.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StrategyParams {
+    /// Minimum EV per contract to call a signal
+    pub ev_threshold: f64,
+    /// Fraction of full Kelly to bet
+    pub fractional_kelly: f64,
+    /// Weight of the Beta-posterior uncertainty penalty in robust Kelly
+    pub kelly_lambda: f64,
+    /// Fee per contract as a fraction of price, charged on entry and exit
+    /// fills (settlement is free)
+    pub fee_rate: f64,
+    /// Expected slippage per contract, dollars
+    pub slippage: f64,
+    /// Probability a limit order at the quote fills
+    pub fill_probability: f64,
+    /// BTC must cross strike by this $ amount against us to trigger hard exit
+    pub strike_cross_buffer: f64,
+    /// BTC must move this $ amount further in our favor to trigger a scale-in
+    pub scale_in_move: f64,
+    /// Max legs per model (initial + scale-ins)
+    pub max_legs: u32,
+    /// Trailing stop arms once peak unrealized exceeds this fraction of cost
+    pub trailing_stop_activation_pct: f64,
+    /// Trailing stop: exit if unrealized drops this fraction below peak
+    pub trailing_stop_pct: f64,
+    /// Take partial profit: sell half when unrealized > this fraction of cost
+    pub partial_take_profit_pct: f64,
+    /// Hard take profit: sell everything when unrealized > this fraction of cost
+    pub full_take_profit_pct: f64,
+    /// Time exit: exit if not clearly winning within this many seconds of close
+    pub uncertain_exit_seconds: f64,
+    /// BTC must be this far from strike to hold to resolution
+    pub resolution_hold_distance: f64,
+    /// Don't exit if this close to expiry and clearly winning (let it resolve at $1)
+    pub resolution_hold_seconds: f64,
+    /// Minimum hold time before any exit (ticks, ~1 tick/second)
+    pub min_hold_ticks: u64,
+    /// Don't enter with less than this many seconds to expiry
+    pub min_entry_ttl: f64,
+    /// Stop-loss: hard cut at this fraction of entry cost
+    pub hard_stop_loss_pct: f64,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            ev_threshold: 0.02,
+            fractional_kelly: 0.2,
+            kelly_lambda: 0.5,
+            fee_rate: 0.02,
+            slippage: 0.005,
+            fill_probability: 0.9,
+            strike_cross_buffer: 25.0,
+            scale_in_move: 75.0,
+            max_legs: 3,
+            trailing_stop_activation_pct: 0.10,
+            trailing_stop_pct: 0.50,
+            partial_take_profit_pct: 0.40,
+            full_take_profit_pct: 0.80,
+            uncertain_exit_seconds: 240.0,
+            resolution_hold_distance: 200.0,
+            resolution_hold_seconds: 120.0,
+            min_hold_ticks: 5,
+            min_entry_ttl: 300.0,
+            hard_stop_loss_pct: 0.70,
+        }
+    }
+}
+
+impl StrategyParams {
+    /// Append a message per out-of-range field to `errors`.
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        let mut check = |ok: bool, field: &str, rule: &str, value: f64| {
+            if !ok {
+                errors.push(format!("strategy.{field} must be {rule}, got {value}"));
+            }
+        };
+        let unit = |x: f64| x > 0.0 && x <= 1.0;
+        check(self.ev_threshold >= 0.0 && self.ev_threshold < 1.0, "ev_threshold", "in [0, 1)", self.ev_threshold);
+        check(unit(self.fractional_kelly), "fractional_kelly", "in (0, 1]", self.fractional_kelly);
+        check(self.kelly_lambda >= 0.0, "kelly_lambda", ">= 0", self.kelly_lambda);
+        check((0.0..0.5).contains(&self.fee_rate), "fee_rate", "in [0, 0.5)", self.fee_rate);
+        check((0.0..0.5).contains(&self.slippage), "slippage", "in [0, 0.5)", self.slippage);
+        check(unit(self.fill_probability), "fill_probability", "in (0, 1]", self.fill_probability);
+        check(self.strike_cross_buffer >= 0.0, "strike_cross_buffer", ">= 0", self.strike_cross_buffer);
+        check(self.scale_in_move > 0.0, "scale_in_move", "> 0", self.scale_in_move);
+        check(self.max_legs >= 1, "max_legs", ">= 1", f64::from(self.max_legs));
+        check(self.trailing_stop_activation_pct >= 0.0, "trailing_stop_activation_pct", ">= 0", self.trailing_stop_activation_pct);
+        check(unit(self.trailing_stop_pct), "trailing_stop_pct", "in (0, 1]", self.trailing_stop_pct);
+        check(self.partial_take_profit_pct > 0.0, "partial_take_profit_pct", "> 0", self.partial_take_profit_pct);
+        check(
+            self.full_take_profit_pct > self.partial_take_profit_pct,
+            "full_take_profit_pct",
+            "above partial_take_profit_pct",
+            self.full_take_profit_pct,
+        );
+        check(self.uncertain_exit_seconds >= 0.0, "uncertain_exit_seconds", ">= 0", self.uncertain_exit_seconds);
+        check(self.resolution_hold_distance >= 0.0, "resolution_hold_distance", ">= 0", self.resolution_hold_distance);
+        check(self.resolution_hold_seconds >= 0.0, "resolution_hold_seconds", ">= 0", self.resolution_hold_seconds);
+        check(self.min_entry_ttl >= 0.0, "min_entry_ttl", ">= 0", self.min_entry_ttl);
+        check(unit(self.hard_stop_loss_pct), "hard_stop_loss_pct", "in (0, 1]", self.hard_stop_loss_pct);
+    }
+}
 
 /// Run the engine decision loop for a single tick.
 ///
@@ -92,24 +178,46 @@ const HARD_STOP_LOSS_PCT: f64 = 0.70;
 ///   2. Exit check: strike crossover, trailing stop, time-based, hard stop
 ///   3. Scale-in check: add to winners when BTC moves further in our favor
 ///   4. Entry check: new position when model detects edge
+///
+/// `entries_blocked` gives the journal reason while the engine takes no new
+/// entries (operator pause, exchange closed); exits are still managed but
+/// phases 3 and 4 are skipped. Models paused individually
+/// (`ModelState::paused`) are treated the same way, as is a market failing
+/// one of `trading.liquidity`'s gates (the gate is the journal reason).
+///
+/// Only positions on `active_market` are marked, exited or scaled into, and
+/// a model holding one market can still enter another: with several expiry
+/// horizons the engine calls this once per horizon's market. Positions on
+/// other markets keep their last mark in `unrealized_pnl`.
+///
+/// `vols` holds the asset's volatility per horizon, so the ES gate values
+/// positions opened on other horizons at their own vol.
+///
+/// `book` is the portfolio across every asset and horizon (see
+/// `PortfolioBook::add_states`); accepted orders are added to it, so the
+/// caller passes the same book to every call in a tick.
+///
+/// Every model's decision for the tick, including why it did not trade, is
+/// appended to `journal`.
 #[allow(clippy::too_many_arguments)]
 pub fn run_tick(
     pricing_models: &[&dyn PricingModel],
     model_states: &mut [ModelState],
     calibrators: &mut [Calibrator],
     vol_state: &VolatilityState,
+    vols: &HorizonVols<'_>,
     active_market: &Option<ActiveMarket>,
     btc_price: f64,
-    config: &AppConfig,
+    trading: &TradingParams,
+    book: &mut PortfolioBook,
     timestamp: &str,
     tick_counter: u64,
+    entries_blocked: Option<&'static str>,
+    journal: &mut Vec<DecisionRecord>,
 ) -> SmallVec<[EngineAction; 16]> {
     let mut actions: SmallVec<[EngineAction; 16]> = SmallVec::new();
 
     let Some(market) = active_market else {
-        for state in model_states.iter_mut() {
-            state.unrealized_pnl = 0.0;
-        }
         return actions;
     };
 
@@ -134,11 +242,17 @@ pub fn run_tick(
         return actions;
     }
 
-    let ttl_seconds = compute_ttl(&market.close_time);
+    // Time to expiry is measured from the tick's own timestamp, not the wall
+    // clock, so a replay prices contracts as they were
+    let now = parse_time(timestamp).unwrap_or_else(chrono::Utc::now);
+    let ttl_seconds = compute_ttl(&market.close_time, now);
     if ttl_seconds <= 0.0 {
         return actions;
     }
 
+    // Too wide or too thin to enter, whatever the models think of it
+    let market_gate = trading.liquidity.check(market);
+
     let annualized_sigma = vol_state.ewma_vol * (365.25_f64 * 24.0 * 3600.0 / 2.0).sqrt();
     let params = ModelParams::new(btc_price, strike, ttl_seconds, annualized_sigma);
 
@@ -150,8 +264,29 @@ pub fn run_tick(
     };
 
     // BTC's relationship to the strike -- this is the core signal
-    let btc_above_strike = btc_price > strike;
     let btc_distance = btc_price - strike; // positive = above, negative = below
+    let strategy = &trading.strategy;
+
+    // Delta of an order here, from the first (reference) model
+    let yes_delta = pricing_models
+        .first()
+        .map_or(0.0, |m| portfolio::yes_delta(*m, btc_price, strike, ttl_seconds, annualized_sigma, &vol_ctx));
+    let mut risk = TickRisk {
+        book,
+        es: EsGate::new(
+            var::open_positions(pricing_models, model_states, btc_price, vols, now),
+            btc_price,
+            annualized_sigma,
+            vol_ctx,
+            strike,
+            ttl_seconds,
+            trading.risk.tail.scenarios,
+            tick_counter,
+        ),
+        asset: market.asset,
+        ticker: &market.ticker,
+        yes_delta,
+    };
 
     for (i, model) in pricing_models.iter().enumerate() {
         let state = &mut model_states[i];
@@ -163,11 +298,11 @@ pub fn run_tick(
         let ev_params = EvParams {
             probability: prob,
             contract_price: yes_ask,
-            fee_rate: 0.02,
-            slippage: 0.005,
-            fill_probability: 0.9,
+            fee_rate: strategy.fee_rate,
+            slippage: strategy.slippage,
+            fill_probability: strategy.fill_probability,
         };
-        let ev_result = ev::compute_ev(&ev_params, config.ev_threshold);
+        let ev_result = ev::compute_ev(&ev_params, strategy.ev_threshold);
 
         let win_prob = if ev_result.buy_yes { prob } else { 1.0 - prob };
         let kelly_result = kelly::compute_kelly(&KellyParams {
@@ -175,9 +310,9 @@ pub fn run_tick(
             alpha: state.beta_alpha,
             beta: state.beta_beta,
             contract_price: if ev_result.buy_yes { yes_ask } else { 1.0 - yes_ask },
-            fractional_gamma: config.fractional_kelly,
-            lambda: 0.5,
-            max_position: config.max_position_size,
+            fractional_gamma: strategy.fractional_kelly,
+            lambda: strategy.kelly_lambda,
+            max_position: trading.risk.max_position_size,
         });
 
         let paper_contracts = if kelly_result.contracts > 0.0 {
@@ -186,27 +321,22 @@ pub fn run_tick(
             kelly_result.contracts
         };
 
-        state.probability = prob;
-        state.ev = ev_result.ev;
-        state.kelly_size = paper_contracts;
+        let signal = Signal { probability: prob, ev: ev_result.ev, kelly_size: paper_contracts };
+        match state.signals.get_mut(&market.horizon) {
+            Some(s) => *s = signal,
+            None => {
+                state.signals.insert(market.horizon.clone(), signal);
+            }
+        }
 
         // ── PHASE 1: Mark-to-Market + Peak Tracking ──
-        let mut total_unrealized = 0.0_f64;
-        for pos in state.open_positions.iter_mut() {
-            let current_bid = if pos.side == "yes" {
-                yes_bid
-            } else {
-                1.0 - yes_ask
-            };
-            let unrealized = (current_bid - pos.entry_price) * pos.contracts;
-            total_unrealized += unrealized;
-
+        mark_to_market(state, &market.ticker, yes_bid, yes_ask);
+        for pos in state.open_positions.iter_mut().filter(|p| p.market_ticker == market.ticker) {
             // Update peak unrealized for trailing stop
-            if unrealized > pos.peak_unrealized {
-                pos.peak_unrealized = unrealized;
+            if pos.unrealized > pos.peak_unrealized {
+                pos.peak_unrealized = pos.unrealized;
             }
         }
-        state.unrealized_pnl = total_unrealized;
 
         // ── PHASE 2: Exit Checks (ordered by priority) ──
         let mut positions_to_exit: SmallVec<[usize; 4]> = SmallVec::new();
@@ -214,6 +344,9 @@ pub fn run_tick(
         let mut partial_exit_indices: SmallVec<[usize; 4]> = SmallVec::new();
 
         for (pos_idx, pos) in state.open_positions.iter().enumerate() {
+            if pos.market_ticker != market.ticker {
+                continue;
+            }
             let current_bid = if pos.side == "yes" {
                 yes_bid
             } else {
@@ -225,7 +358,7 @@ pub fn run_tick(
             let hold_ticks = tick_counter.saturating_sub(pos.entry_tick);
 
             // Skip exits for very new positions (unless strike crossover)
-            let is_new = hold_ticks < MIN_HOLD_TICKS;
+            let is_new = hold_ticks < strategy.min_hold_ticks;
 
             // ─── RULE 1: Strike Crossover Exit (highest priority, ignores hold time) ───
             // If BTC has crossed the strike against our position, the contract value
@@ -233,10 +366,10 @@ pub fn run_tick(
             let position_is_yes = pos.side == "yes";
             let btc_against_us = if position_is_yes {
                 // We hold YES (bet BTC > strike), but BTC has dropped below strike
-                btc_price < strike - STRIKE_CROSS_BUFFER
+                btc_price < strike - strategy.strike_cross_buffer
             } else {
                 // We hold NO (bet BTC < strike), but BTC has risen above strike
-                btc_price > strike + STRIKE_CROSS_BUFFER
+                btc_price > strike + strategy.strike_cross_buffer
             };
 
             if btc_against_us {
@@ -250,7 +383,7 @@ pub fn run_tick(
             }
 
             // ─── RULE 2: Hard Stop-Loss ───
-            if entry_cost > 0.0 && unrealized < -(entry_cost * HARD_STOP_LOSS_PCT) {
+            if entry_cost > 0.0 && unrealized < -(entry_cost * strategy.hard_stop_loss_pct) {
                 positions_to_exit.push(pos_idx);
                 exit_reasons.push("stop_loss");
                 continue;
@@ -258,9 +391,9 @@ pub fn run_tick(
 
             // ─── RULE 3: Trailing Stop ───
             // Once we've had significant gains, don't let them evaporate.
-            // Exit if unrealized drops 50% from peak.
-            if pos.peak_unrealized > entry_cost * 0.10 {
-                let trailing_threshold = pos.peak_unrealized * (1.0 - TRAILING_STOP_PCT);
+            // Exit if unrealized drops `trailing_stop_pct` from peak.
+            if pos.peak_unrealized > entry_cost * strategy.trailing_stop_activation_pct {
+                let trailing_threshold = pos.peak_unrealized * (1.0 - strategy.trailing_stop_pct);
                 if unrealized < trailing_threshold {
                     positions_to_exit.push(pos_idx);
                     exit_reasons.push("trailing_stop");
@@ -269,7 +402,7 @@ pub fn run_tick(
             }
 
             // ─── RULE 4: Full Take-Profit ───
-            if entry_cost > 0.0 && unrealized > entry_cost * FULL_TAKE_PROFIT_PCT {
+            if entry_cost > 0.0 && unrealized > entry_cost * strategy.full_take_profit_pct {
                 positions_to_exit.push(pos_idx);
                 exit_reasons.push("take_profit");
                 continue;
@@ -278,7 +411,7 @@ pub fn run_tick(
             // ─── RULE 5: Partial Take-Profit ───
             // Sell ~half when at significant gain (only for multi-contract positions)
             if entry_cost > 0.0
-                && unrealized > entry_cost * PARTIAL_TAKE_PROFIT_PCT
+                && unrealized > entry_cost * strategy.partial_take_profit_pct
                 && pos.contracts > 1.5
                 && pos.leg == 0
             {
@@ -287,7 +420,7 @@ pub fn run_tick(
             }
 
             // ─── RULE 6: Time-Based Exit ───
-            if ttl_seconds < UNCERTAIN_EXIT_SECONDS {
+            if ttl_seconds < strategy.uncertain_exit_seconds {
                 // Near expiry: should we hold or exit?
                 let on_right_side = if position_is_yes {
                     btc_price > strike
@@ -295,9 +428,9 @@ pub fn run_tick(
                     btc_price < strike
                 };
 
-                let strongly_winning = btc_distance.abs() > RESOLUTION_HOLD_DISTANCE;
+                let strongly_winning = btc_distance.abs() > strategy.resolution_hold_distance;
 
-                if ttl_seconds < RESOLUTION_HOLD_SECONDS && on_right_side && strongly_winning {
+                if ttl_seconds < strategy.resolution_hold_seconds && on_right_side && strongly_winning {
                     // HOLD: We're strongly winning with < 2 min left.
                     // Contract is converging to $1, let it resolve.
                     continue;
@@ -318,10 +451,10 @@ pub fn run_tick(
             exit_contracts: f64,
             exit_price: f64,
             entry_price: f64,
-            fee: f64,
-            pnl: f64,
+            fill: ExitFill,
             trade_id: String,
             side: String,
+            horizon: String,
         }
 
         let partial_exits: SmallVec<[PartialExitData; 4]> = partial_exit_indices
@@ -341,68 +474,69 @@ pub fn run_tick(
                 } else {
                     (1.0 - yes_ask).max(0.01)
                 };
-                let fee = exit_price * exit_contracts * 0.02;
-                let pnl = (exit_price - pos.entry_price) * exit_contracts - fee;
                 Some(PartialExitData {
                     pos_idx,
                     exit_contracts,
                     exit_price,
                     entry_price: pos.entry_price,
-                    fee,
-                    pnl,
+                    fill: exit_fill(pos, exit_price, exit_contracts, strategy.fee_rate),
                     trade_id: pos.trade_id.clone(),
                     side: pos.side.clone(),
+                    horizon: pos.horizon.clone(),
                 })
             })
             .collect();
 
+        // For the journal: the first exit this tick, if any
+        let tick_exit = exit_reasons
+            .first()
+            .copied()
+            .or_else(|| (!partial_exits.is_empty()).then_some("partial_take_profit"));
+
         for pe in partial_exits {
             tracing::info!(
                 model = model.name(),
                 side = %pe.side,
                 contracts_sold = pe.exit_contracts,
-                pnl = pe.pnl,
+                pnl = pe.fill.pnl,
                 "partial take-profit"
             );
 
             state.open_positions[pe.pos_idx].contracts -= pe.exit_contracts;
-            state.cumulative_pnl += pe.pnl;
-            state.daily_pnl += pe.pnl;
+            state.realize(&pe.horizon, pe.fill.pnl);
             state.current_exposure -= pe.entry_price * pe.exit_contracts;
             state.current_exposure = state.current_exposure.max(0.0);
 
-            if pe.pnl > 0.0 {
+            if pe.fill.pnl > 0.0 {
                 state.winning_trades += 1;
                 state.beta_alpha += 1.0;
             }
-            let ret = pe.pnl / (pe.entry_price * pe.exit_contracts).max(0.01);
+            let ret = pe.fill.pnl / (pe.entry_price * pe.exit_contracts).max(0.01);
             state.record_return(ret);
             state.update_drawdown();
             state.compute_sharpe();
 
             actions.push(EngineAction::BroadcastUpdate(WsMessage::NewTrade {
                 model: model.name().to_string(),
+                asset: market.asset,
                 side: pe.side.clone(),
                 action: "partial sell".to_string(),
                 price: pe.exit_price,
                 contracts: pe.exit_contracts,
-                ev: pe.pnl,
+                ev: pe.fill.pnl,
                 timestamp: timestamp.to_string(),
             }));
 
-            actions.push(EngineAction::DbWrite(DbCommand::InsertTrade {
-                id: format!("{}-partial", pe.trade_id),
-                model_name: model.name().to_string(),
-                market_ticker: market.ticker.clone(),
-                side: pe.side,
-                action: "sell".to_string(),
-                entry_price: pe.exit_price,
+            actions.push(EngineAction::DbWrite(DbCommand::ExitTrade {
+                trade_id: pe.trade_id,
+                exit_price: pe.exit_price,
                 contracts: pe.exit_contracts,
-                model_probability: prob,
-                ev: pe.pnl,
-                kelly_fraction: 0.0,
-                fees_estimate: pe.fee,
-                entry_time: timestamp.to_string(),
+                exit_fee: pe.fill.exit_fee,
+                fees: pe.fill.fees,
+                pnl: pe.fill.pnl,
+                reason: "partial_take_profit".to_string(),
+                exit_time: timestamp.to_string(),
+                closed: false,
             }));
         }
 
@@ -422,8 +556,7 @@ pub fn run_tick(
                 (1.0 - yes_ask).max(0.01)
             };
 
-            let fee = exit_price * pos.contracts * 0.02;
-            let pnl = (exit_price - pos.entry_price) * pos.contracts - fee;
+            let pnl = exit_fill(&pos, exit_price, pos.contracts, strategy.fee_rate).pnl;
 
             tracing::info!(
                 model = model.name(),
@@ -438,104 +571,55 @@ pub fn run_tick(
                 "exiting position"
             );
 
-            state.cumulative_pnl += pnl;
-            state.daily_pnl += pnl;
-            state.current_exposure -= pos.entry_price * pos.contracts;
-            state.current_exposure = state.current_exposure.max(0.0);
-
-            if pnl > 0.0 {
-                state.winning_trades += 1;
-                state.beta_alpha += 1.0;
-            } else {
-                state.beta_beta += 1.0;
-            }
-
-            let ret = pnl / (pos.entry_price * pos.contracts).max(0.01);
-            state.record_return(ret);
-            state.update_drawdown();
-            state.compute_sharpe();
-
-            actions.push(EngineAction::ExitTrade {
-                trade_id: pos.trade_id.clone(),
-                model_name: model.name(),
-                exit_price,
-                pnl,
-                reason,
-            });
-
-            actions.push(EngineAction::DbWrite(DbCommand::ExitTrade {
-                trade_id: pos.trade_id.clone(),
-                exit_price,
-                pnl,
-                reason: reason.to_string(),
-                exit_time: timestamp.to_string(),
-            }));
-
-            actions.push(EngineAction::BroadcastUpdate(WsMessage::TradeExited {
-                model: model.name().to_string(),
-                trade_id: pos.trade_id.clone(),
-                side: pos.side.clone(),
-                entry_price: pos.entry_price,
-                exit_price,
-                contracts: pos.contracts,
-                pnl,
-                reason: reason.to_string(),
-                timestamp: timestamp.to_string(),
-            }));
-
-            actions.push(EngineAction::BroadcastUpdate(WsMessage::NewTrade {
-                model: model.name().to_string(),
-                side: pos.side.clone(),
-                action: format!("sell ({reason})"),
-                price: exit_price,
-                contracts: pos.contracts,
-                ev: pnl,
-                timestamp: timestamp.to_string(),
-            }));
+            close_position(state, pos, exit_price, reason, strategy.fee_rate, timestamp, &mut actions);
         }
 
         // Recompute unrealized after exits
-        let mut post_exit_unrealized = 0.0_f64;
-        for pos in state.open_positions.iter() {
-            let bid = if pos.side == "yes" { yes_bid } else { 1.0 - yes_ask };
-            post_exit_unrealized += (bid - pos.entry_price) * pos.contracts;
-        }
-        state.unrealized_pnl = post_exit_unrealized;
+        mark_to_market(state, &market.ticker, yes_bid, yes_ask);
+
+        let entries_enabled = entries_blocked.is_none() && !state.paused && market_gate.is_none();
+        // Legs, holding and scale-ins are per market
+        let in_market = |p: &OpenPosition| p.market_ticker == market.ticker;
+        let holding = state.open_positions.iter().any(in_market);
+        let legs = state.open_positions.iter().filter(|p| in_market(p)).map(|p| p.leg).max().unwrap_or(0);
+        let market_unrealized: f64 = state.open_positions.iter().filter(|p| in_market(p)).map(|p| p.unrealized).sum();
+        let mut placed: Option<PaperOrder> = None;
+        let mut verdict: Option<RiskVerdict> = None;
 
         // ── PHASE 3: Scale-In Check (add to winners) ──
         // Only scale if we have existing positions AND BTC has moved further in our favor
-        if !state.open_positions.is_empty() && ttl_seconds > MIN_ENTRY_TTL {
-            let current_leg_count = state.open_positions.iter().map(|p| p.leg).max().unwrap_or(0);
+        let first_leg = state.open_positions.iter().find(|p| in_market(p)).map(|p| (p.side.clone(), p.entry_btc_price));
+        if let Some((first_side, first_entry_price)) = first_leg.filter(|_| entries_enabled && ttl_seconds > strategy.min_entry_ttl) {
+            let current_leg_count = legs;
 
-            if current_leg_count < MAX_LEGS - 1 {
+            if current_leg_count < strategy.max_legs - 1 {
                 // Check if BTC has moved significantly in our favor since entry
-                let first_pos = &state.open_positions[0];
-                let btc_move_since_entry = btc_price - first_pos.entry_btc_price;
+                let btc_move_since_entry = btc_price - first_entry_price;
 
-                let btc_moved_in_favor = if first_pos.side == "yes" {
-                    btc_move_since_entry > SCALE_IN_MOVE
+                let btc_moved_in_favor = if first_side == "yes" {
+                    btc_move_since_entry > strategy.scale_in_move
                 } else {
-                    btc_move_since_entry < -SCALE_IN_MOVE
+                    btc_move_since_entry < -strategy.scale_in_move
                 };
 
                 // Also require positive unrealized to scale in
-                if btc_moved_in_favor && state.unrealized_pnl > 0.0 && ev_result.is_signal {
-                    let scale_side = first_pos.side.clone();
+                if btc_moved_in_favor && market_unrealized > 0.0 && ev_result.is_signal {
+                    let scale_side = first_side;
                     let scale_price = if scale_side == "yes" { yes_ask } else { 1.0 - yes_ask };
 
                     // Scale-in with 1 contract
                     let scale_contracts = 1.0_f64;
 
-                    let risk = limits::check_risk_limits(
-                        state,
-                        vol_state,
-                        scale_contracts,
-                        scale_price,
-                        config.max_daily_drawdown,
-                        config.max_position_size,
-                    );
+                    let order = ProposedOrder {
+                        action: "scale_in",
+                        side: &scale_side,
+                        contracts: scale_contracts,
+                        price: scale_price,
+                    };
+                    let (scale_contracts, v) = size_order(state, vol_state, &trading.risk, &mut risk, &order, timestamp);
+                    verdict = Some(v);
 
-                    if risk.is_allowed() {
+                    if scale_contracts > 0.0 {
                         let trade_id = uuid::Uuid::new_v4().to_string();
                         let side_str: &'static str = if scale_side == "yes" { "yes" } else { "no" };
 
@@ -552,6 +636,9 @@ pub fn run_tick(
                         state.open_positions.push(OpenPosition {
                             trade_id: trade_id.clone(),
                             market_ticker: market.ticker.clone(),
+                            horizon: market.horizon.clone(),
+                            strike,
+                            close_time: market.close_time.clone(),
                             side: scale_side,
                             entry_price: scale_price,
                             contracts: scale_contracts,
@@ -560,6 +647,7 @@ pub fn run_tick(
                             entry_btc_price: btc_price,
                             peak_unrealized: 0.0,
                             leg: current_leg_count + 1,
+                            unrealized: 0.0,
                         });
 
                         state.current_exposure += scale_contracts * scale_price;
@@ -580,6 +668,8 @@ pub fn run_tick(
 
                         actions.push(EngineAction::DbWrite(DbCommand::InsertTrade {
                             id: trade_id,
+                            asset: market.asset,
+                            horizon: market.horizon.clone(),
                             model_name: model.name().to_string(),
                             market_ticker: market.ticker.clone(),
                             side: side_str.to_string(),
@@ -589,12 +679,27 @@ pub fn run_tick(
                             model_probability: prob,
                             ev: ev_result.ev,
                             kelly_fraction: kelly_result.robust_fraction,
-                            fees_estimate: scale_price * scale_contracts * 0.02,
+                            fees_estimate: fee(scale_price, scale_contracts, strategy.fee_rate),
                             entry_time: timestamp.to_string(),
+                            entry_ttl_seconds: ttl_seconds,
+                            entry_regime: vol_state.regime.to_string(),
                         }));
 
+                        placed = Some(PaperOrder {
+                            model: model.name(),
+                            market_ticker: market.ticker.clone(),
+                            side: side_str,
+                            action: "scale_in",
+                            price: scale_price,
+                            contracts: scale_contracts,
+                            probability: prob,
+                            ev: ev_result.ev,
+                            kelly_fraction: kelly_result.robust_fraction,
+                        });
+
                         actions.push(EngineAction::BroadcastUpdate(WsMessage::NewTrade {
                             model: model.name().to_string(),
+                            asset: market.asset,
                             side: side_str.to_string(),
                             action: "scale in".to_string(),
                             price: scale_price,
@@ -609,36 +714,36 @@ pub fn run_tick(
 
         // ── PHASE 4: New Entry Check ──
         let price = if ev_result.buy_yes { yes_ask } else { 1.0 - yes_ask };
-        let has_position = !state.open_positions.is_empty();
+        let has_position = state.open_positions.iter().any(in_market);
 
         // Don't enter if BTC is already on the wrong side of strike
         // (would immediately trigger strike_cross exit on next tick)
         let entry_side_ok = if ev_result.buy_yes {
-            btc_price >= strike - STRIKE_CROSS_BUFFER
+            btc_price >= strike - strategy.strike_cross_buffer
         } else {
-            btc_price <= strike + STRIKE_CROSS_BUFFER
+            btc_price <= strike + strategy.strike_cross_buffer
         };
 
         // Only enter if: signal, no existing position, enough time, and BTC position makes sense
-        if ev_result.is_signal && paper_contracts > 0.0 && !has_position && ttl_seconds > MIN_ENTRY_TTL && entry_side_ok {
-            let risk = limits::check_risk_limits(
-                state,
-                vol_state,
-                paper_contracts,
+        if entries_enabled && ev_result.is_signal && paper_contracts > 0.0 && !has_position && ttl_seconds > strategy.min_entry_ttl && entry_side_ok {
+            let side: &'static str = if ev_result.buy_yes { "yes" } else { "no" };
+            let order = ProposedOrder {
+                action: "buy",
+                side,
+                contracts: paper_contracts,
                 price,
-                config.max_daily_drawdown,
-                config.max_position_size,
-            );
+            };
+            let (contracts, v) = size_order(state, vol_state, &trading.risk, &mut risk, &order, timestamp);
+            verdict = Some(v);
 
-            if risk.is_allowed() {
+            if contracts > 0.0 {
                 let trade_id = uuid::Uuid::new_v4().to_string();
-                let side: &'static str = if ev_result.buy_yes { "yes" } else { "no" };
 
                 tracing::info!(
                     model = model.name(),
                     side = side,
                     price = price,
-                    contracts = paper_contracts,
+                    contracts = contracts,
                     prob = prob,
                     ev = ev_result.ev,
                     btc = btc_price,
@@ -650,17 +755,21 @@ pub fn run_tick(
                 state.open_positions.push(OpenPosition {
                     trade_id: trade_id.clone(),
                     market_ticker: market.ticker.clone(),
+                    horizon: market.horizon.clone(),
+                    strike,
+                    close_time: market.close_time.clone(),
                     side: side.to_string(),
                     entry_price: price,
-                    contracts: paper_contracts,
+                    contracts,
                     model_probability: prob,
                     entry_tick: tick_counter,
                     entry_btc_price: btc_price,
                     peak_unrealized: 0.0,
                     leg: 0,
+                    unrealized: 0.0,
                 });
 
-                state.current_exposure += paper_contracts * price;
+                state.current_exposure += contracts * price;
                 state.total_trades += 1;
 
                 actions.push(EngineAction::PlaceTrade {
@@ -670,7 +779,7 @@ pub fn run_tick(
                     side,
                     action: "buy",
                     price,
-                    contracts: paper_contracts,
+                    contracts,
                     probability: prob,
                     ev: ev_result.ev,
                     kelly_fraction: kelly_result.robust_fraction,
@@ -678,46 +787,105 @@ pub fn run_tick(
 
                 actions.push(EngineAction::DbWrite(DbCommand::InsertTrade {
                     id: trade_id,
+                    asset: market.asset,
+                    horizon: market.horizon.clone(),
                     model_name: model.name().to_string(),
                     market_ticker: market.ticker.clone(),
                     side: side.to_string(),
                     action: "buy".to_string(),
                     entry_price: price,
-                    contracts: paper_contracts,
+                    contracts,
                     model_probability: prob,
                     ev: ev_result.ev,
                     kelly_fraction: kelly_result.robust_fraction,
-                    fees_estimate: price * paper_contracts * 0.02,
+                    fees_estimate: fee(price, contracts, strategy.fee_rate),
                     entry_time: timestamp.to_string(),
+                    entry_ttl_seconds: ttl_seconds,
+                    entry_regime: vol_state.regime.to_string(),
                 }));
 
+                placed = Some(PaperOrder {
+                    model: model.name(),
+                    market_ticker: market.ticker.clone(),
+                    side,
+                    action: "buy",
+                    price,
+                    contracts,
+                    probability: prob,
+                    ev: ev_result.ev,
+                    kelly_fraction: kelly_result.robust_fraction,
+                });
+
                 actions.push(EngineAction::BroadcastUpdate(WsMessage::NewTrade {
                     model: model.name().to_string(),
+                    asset: market.asset,
                     side: side.to_string(),
                     action: "buy".to_string(),
                     price,
-                    contracts: paper_contracts,
+                    contracts,
                     ev: ev_result.ev,
                     timestamp: timestamp.to_string(),
                 }));
             }
         }
 
+        let decision = match placed {
+            Some(order) => Decision::PlacePaperTrade(order),
+            None => Decision::NoAction {
+                reason: if let Some(reason) = entries_blocked {
+                    reason
+                } else if state.paused {
+                    "model_paused"
+                } else if let Some(gate) = market_gate {
+                    gate
+                } else if verdict.is_some_and(|v| v.outcome == "blocked") {
+                    "risk_blocked"
+                } else if !ev_result.is_signal {
+                    "no_edge"
+                } else if ttl_seconds <= strategy.min_entry_ttl {
+                    "too_close_to_expiry"
+                } else if holding {
+                    if legs >= strategy.max_legs - 1 { "max_legs" } else { "holding" }
+                } else if paper_contracts <= 0.0 {
+                    "zero_kelly"
+                } else if !entry_side_ok {
+                    "wrong_side_of_strike"
+                } else {
+                    "no_entry"
+                },
+            },
+        };
+        let (ev_yes, ev_no) = if ev_result.buy_yes {
+            (ev_result.ev, ev_result.ev_opposite)
+        } else {
+            (ev_result.ev_opposite, ev_result.ev)
+        };
+        journal.push(DecisionRecord {
+            timestamp: timestamp.to_string(),
+            asset: market.asset,
+            model_name: model.name(),
+            market_ticker: market.ticker.clone(),
+            btc_price,
+            ttl_seconds,
+            raw_probability: raw_prob,
+            probability: prob,
+            ev_yes,
+            ev_no,
+            kelly_contracts: paper_contracts,
+            risk: verdict,
+            exit_reason: tick_exit,
+            decision,
+        });
+
         // Re-compute unrealized after all modifications
-        let mut final_unrealized = 0.0_f64;
-        for pos in state.open_positions.iter() {
-            let bid = if pos.side == "yes" { yes_bid } else { 1.0 - yes_ask };
-            final_unrealized += (bid - pos.entry_price) * pos.contracts;
-        }
-        state.unrealized_pnl = final_unrealized;
+        mark_to_market(state, &market.ticker, yes_bid, yes_ask);
 
         // Broadcast model update
         let total_pnl = state.cumulative_pnl + state.unrealized_pnl;
         actions.push(EngineAction::BroadcastUpdate(WsMessage::ModelUpdate {
             model: model.name().to_string(),
-            probability: prob,
-            ev: ev_result.ev,
-            kelly_size: paper_contracts,
+            asset: market.asset,
+            signals: state.signals.clone(),
             cumulative_pnl: state.cumulative_pnl,
             unrealized_pnl: state.unrealized_pnl,
             total_pnl,
@@ -729,10 +897,14 @@ pub fn run_tick(
             daily_pnl: state.daily_pnl,
             current_exposure: state.current_exposure,
             open_position_count: state.open_positions.len(),
+            paused: state.paused,
+            last_risk_decision: state.last_risk_decision.clone(),
+            pnl_by_horizon: state.pnl_by_horizon(),
         }));
 
         actions.push(EngineAction::DbWrite(DbCommand::InsertSnapshot {
             model_name: model.name().to_string(),
+            asset: market.asset,
             timestamp: timestamp.to_string(),
             btc_price,
             market_ticker: Some(market.ticker.clone()),
@@ -748,6 +920,285 @@ pub fn run_tick(
     actions
 }
 
+/// Mark the positions on `ticker` at its current bid and total the model's
+/// unrealized P/L; positions on other markets keep their last mark.
+fn mark_to_market(state: &mut ModelState, ticker: &str, yes_bid: f64, yes_ask: f64) {
+    for pos in state.open_positions.iter_mut().filter(|p| p.market_ticker == ticker) {
+        let bid = if pos.side == "yes" { yes_bid } else { 1.0 - yes_ask };
+        pos.unrealized = (bid - pos.entry_price) * pos.contracts;
+    }
+    state.unrealized_pnl = state.open_positions.iter().map(|p| p.unrealized).sum();
+}
+
+#[inline]
+fn fee(price: f64, contracts: f64, fee_rate: f64) -> f64 {
+    price * contracts * fee_rate
+}
+
+/// Realized result of selling part or all of a position.
+#[derive(Debug, Clone, Copy)]
+struct ExitFill {
+    /// Fee on the sale itself
+    exit_fee: f64,
+    /// `exit_fee` plus the entry fee on the contracts sold
+    fees: f64,
+    /// Net of `fees`
+    pnl: f64,
+}
+
+/// Sell `contracts` of `pos` at `exit_price`. The entry fee is booked pro
+/// rata as contracts leave the position, so a trade's fills always sum to
+/// its full round-trip cost.
+#[inline]
+fn exit_fill(pos: &OpenPosition, exit_price: f64, contracts: f64, fee_rate: f64) -> ExitFill {
+    let exit_fee = fee(exit_price, contracts, fee_rate);
+    let fees = exit_fee + fee(pos.entry_price, contracts, fee_rate);
+    ExitFill {
+        exit_fee,
+        fees,
+        pnl: (exit_price - pos.entry_price) * contracts - fees,
+    }
+}
+
+/// Close a full position at `exit_price`: book the P/L into the model's
+/// state and emit the exit, DB and broadcast actions.
+fn close_position(
+    state: &mut ModelState,
+    pos: OpenPosition,
+    exit_price: f64,
+    reason: &'static str,
+    fee_rate: f64,
+    timestamp: &str,
+    actions: &mut SmallVec<[EngineAction; 16]>,
+) {
+    let fill = exit_fill(&pos, exit_price, pos.contracts, fee_rate);
+    let pnl = fill.pnl;
+
+    state.realize(&pos.horizon, pnl);
+    state.current_exposure -= pos.entry_price * pos.contracts;
+    state.current_exposure = state.current_exposure.max(0.0);
+
+    if pnl > 0.0 {
+        state.winning_trades += 1;
+        state.beta_alpha += 1.0;
+    } else {
+        state.beta_beta += 1.0;
+    }
+
+    let ret = pnl / (pos.entry_price * pos.contracts).max(0.01);
+    state.record_return(ret);
+    state.update_drawdown();
+    state.compute_sharpe();
+
+    actions.push(EngineAction::ExitTrade {
+        trade_id: pos.trade_id.clone(),
+        model_name: state.name,
+        exit_price,
+        pnl,
+        reason,
+    });
+
+    actions.push(EngineAction::DbWrite(DbCommand::ExitTrade {
+        trade_id: pos.trade_id.clone(),
+        exit_price,
+        contracts: pos.contracts,
+        exit_fee: fill.exit_fee,
+        fees: fill.fees,
+        pnl,
+        reason: reason.to_string(),
+        exit_time: timestamp.to_string(),
+        closed: true,
+    }));
+
+    actions.push(EngineAction::BroadcastUpdate(WsMessage::TradeExited {
+        model: state.name.to_string(),
+        asset: state.asset,
+        trade_id: pos.trade_id.clone(),
+        side: pos.side.clone(),
+        entry_price: pos.entry_price,
+        exit_price,
+        contracts: pos.contracts,
+        pnl,
+        reason: reason.to_string(),
+        timestamp: timestamp.to_string(),
+    }));
+
+    actions.push(EngineAction::BroadcastUpdate(WsMessage::NewTrade {
+        model: state.name.to_string(),
+        asset: state.asset,
+        side: pos.side.clone(),
+        action: format!("sell ({reason})"),
+        price: exit_price,
+        contracts: pos.contracts,
+        ev: pnl,
+        timestamp: timestamp.to_string(),
+    }));
+}
+
+/// An entry or scale-in on the active market as the model sized it, before
+/// risk limits.
+struct ProposedOrder<'a> {
+    action: &'static str,
+    side: &'a str,
+    contracts: f64,
+    price: f64,
+}
+
+/// Cross-model risk state for one tick, updated as orders are accepted.
+struct TickRisk<'a> {
+    book: &'a mut PortfolioBook,
+    es: EsGate,
+    asset: Asset,
+    ticker: &'a str,
+    yes_delta: f64,
+}
+
+/// Run the model's own limits, then the portfolio caps, then the asset's ES
+/// limit.
+/// Returns the contracts to place (0 when blocked) with the verdict, and
+/// records any block or scale-down on the model so it shows up in its next
+/// update. Accepted orders are added to `risk` so later models see them.
+fn size_order(
+    state: &mut ModelState,
+    vol_state: &VolatilityState,
+    limits: &RiskParams,
+    risk: &mut TickRisk<'_>,
+    order: &ProposedOrder<'_>,
+    timestamp: &str,
+) -> (f64, RiskVerdict) {
+    let model_check = limits::check_risk_limits(
+        state,
+        vol_state,
+        order.contracts,
+        order.price,
+        limits.max_daily_drawdown,
+        limits.max_position_size,
+    );
+    let mut check = match model_check {
+        limits::RiskCheck::Blocked(reason) => PortfolioCheck::Blocked(reason),
+        limits::RiskCheck::Allowed => portfolio::check_order(
+            &limits.portfolio,
+            risk.book,
+            risk.asset,
+            risk.ticker,
+            order.side,
+            order.contracts,
+            order.price,
+            risk.yes_delta,
+        ),
+    };
+
+    let yes = order.side == "yes";
+    let contracts = match check {
+        PortfolioCheck::Allowed => order.contracts,
+        PortfolioCheck::ScaledDown { contracts, .. } => contracts,
+        PortfolioCheck::Blocked(_) => 0.0,
+    };
+
+    // Only block orders that add tail risk; a hedge may go through even
+    // while the book is over the limit
+    let max_es = limits.tail.max_asset_es99;
+    if contracts > 0.0 && max_es > 0.0 {
+        let (before, after) = risk.es.es99_with(yes, contracts, order.price);
+        if after > max_es && after > before {
+            check = PortfolioCheck::Blocked("expected shortfall limit");
+        }
+    }
+
+    let (outcome, reason, contracts) = match check {
+        PortfolioCheck::Allowed => ("allowed", "", order.contracts),
+        PortfolioCheck::ScaledDown { contracts, reason } => ("scaled", reason, contracts),
+        PortfolioCheck::Blocked(reason) => ("blocked", reason, 0.0),
+    };
+
+    if contracts > 0.0 {
+        risk.book.add(risk.asset, risk.ticker, order.side, contracts, order.price, risk.yes_delta);
+        risk.es.add(yes, contracts, order.price);
+    }
+    let verdict = RiskVerdict { outcome, reason };
+    if outcome == "allowed" {
+        return (contracts, verdict);
+    }
+
+    tracing::info!(
+        model = state.name,
+        action = order.action,
+        requested = order.contracts,
+        allowed = contracts,
+        reason,
+        "order {outcome} by risk limits"
+    );
+    state.last_risk_decision = Some(RiskDecision {
+        action: order.action,
+        outcome,
+        reason,
+        requested_contracts: order.contracts,
+        allowed_contracts: contracts,
+        timestamp: timestamp.to_string(),
+    });
+    (contracts, verdict)
+}
+
+/// Operator flatten or flattening shutdown: close every open position,
+/// regardless of hold time or exit rules.
+///
+/// A position on one of `markets` (each horizon's active market) exits at
+/// its current bid. Any other -- its horizon has rolled to a new market, or
+/// lost it -- has no quote to exit at, so it exits at its last mark: the bid
+/// as of the last tick its market was quoted, or the entry price if it never
+/// was. Nothing is left open.
+pub fn flatten_positions(
+    model_states: &mut [ModelState],
+    markets: &[&ActiveMarket],
+    reason: &'static str,
+    fee_rate: f64,
+    timestamp: &str,
+) -> SmallVec<[EngineAction; 16]> {
+    let mut actions: SmallVec<[EngineAction; 16]> = SmallVec::new();
+
+    let quote = |s: &Option<String>| s.as_ref().and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+
+    for state in model_states.iter_mut() {
+        for pos in std::mem::take(&mut state.open_positions) {
+            let market = markets.iter().find(|m| m.ticker == pos.market_ticker);
+            let exit_price = match market {
+                Some(m) if pos.side == "yes" => quote(&m.yes_bid).max(0.01),
+                Some(m) => (1.0 - quote(&m.yes_ask)).max(0.01),
+                None if pos.contracts > 0.0 => (pos.entry_price + pos.unrealized / pos.contracts).clamp(0.01, 0.99),
+                None => pos.entry_price,
+            };
+
+            if market.is_some() {
+                tracing::info!(
+                    model = state.name,
+                    side = %pos.side,
+                    entry = pos.entry_price,
+                    exit = exit_price,
+                    contracts = pos.contracts,
+                    reason = reason,
+                    "flattening position"
+                );
+            } else {
+                tracing::warn!(
+                    model = state.name,
+                    ticker = %pos.market_ticker,
+                    side = %pos.side,
+                    entry = pos.entry_price,
+                    exit = exit_price,
+                    contracts = pos.contracts,
+                    reason = reason,
+                    "flattening position off its market at its last mark"
+                );
+            }
+
+            close_position(state, pos, exit_price, reason, fee_rate, timestamp, &mut actions);
+        }
+        state.unrealized_pnl = 0.0;
+    }
+
+    actions
+}
+
 /// Settle all pending trades for a market that has resolved.
 pub fn settle_trades(
     model_states: &mut [ModelState],
@@ -763,27 +1214,31 @@ pub fn settle_trades(
         let won = (trade.side == "yes" && result == "yes")
             || (trade.side == "no" && result == "no");
 
-        let pnl = if won {
-            (1.0 - trade.entry_price) * trade.contracts - trade.fees_estimate
+        // Only what is left after any partial exits settles; those exits
+        // already booked their share of the entry fee
+        let contracts = trade.remaining_contracts;
+        let fees = if trade.contracts > 0.0 {
+            trade.fees_estimate * contracts / trade.contracts
         } else {
-            -trade.entry_price * trade.contracts - trade.fees_estimate
+            0.0
         };
+        let settlement_price = if won { 1.0 } else { 0.0 };
+        let pnl = (settlement_price - trade.entry_price) * contracts - fees;
 
         let outcome: &'static str = if won { "win" } else { "loss" };
 
         if let Some(state) = model_states.iter_mut().find(|s| s.name == trade.model_name) {
-            state.cumulative_pnl += pnl;
-            state.daily_pnl += pnl;
+            state.realize(&trade.horizon, pnl);
             if won {
                 state.winning_trades += 1;
                 state.beta_alpha += 1.0;
             } else {
                 state.beta_beta += 1.0;
             }
-            state.current_exposure -= trade.entry_price * trade.contracts;
+            state.current_exposure -= trade.entry_price * contracts;
             state.current_exposure = state.current_exposure.max(0.0);
 
-            let ret = pnl / (trade.entry_price * trade.contracts).max(0.01);
+            let ret = pnl / (trade.entry_price * contracts).max(0.01);
             state.record_return(ret);
             state.update_drawdown();
             state.compute_sharpe();
@@ -795,7 +1250,7 @@ pub fn settle_trades(
             state.compute_brier();
 
             state.open_positions.retain(|p| p.trade_id != trade.id);
-            state.unrealized_pnl = 0.0;
+            state.unrealized_pnl = state.open_positions.iter().map(|p| p.unrealized).sum();
         }
 
         let cal_idx = model_states.iter().position(|s| s.name == trade.model_name);
@@ -815,12 +1270,16 @@ pub fn settle_trades(
         actions.push(EngineAction::DbWrite(DbCommand::SettleTrade {
             trade_id: trade.id.clone(),
             outcome: outcome.to_string(),
+            settlement_price,
+            contracts,
+            fees,
             pnl,
             settle_time: timestamp.to_string(),
         }));
 
         actions.push(EngineAction::BroadcastUpdate(WsMessage::TradeSettled {
             model: trade.model_name.clone(),
+            asset: trade.asset,
             trade_id: trade.id.clone(),
             outcome: outcome.to_string(),
             pnl,
@@ -831,6 +1290,7 @@ pub fn settle_trades(
     for state in model_states.iter() {
         actions.push(EngineAction::BroadcastUpdate(WsMessage::MetricsUpdate {
             model: state.name.to_string(),
+            asset: state.asset,
             sharpe: state.sharpe,
             max_drawdown: state.max_drawdown,
             win_rate: state.win_rate(),
@@ -841,6 +1301,7 @@ pub fn settle_trades(
 
         actions.push(EngineAction::DbWrite(DbCommand::UpdateRiskState {
             model_name: state.name.to_string(),
+            asset: state.asset,
             exposure: state.current_exposure,
             daily_pnl: state.daily_pnl,
             max_drawdown: state.max_drawdown,
@@ -853,19 +1314,23 @@ pub fn settle_trades(
     actions
 }
 
-fn compute_ttl(close_time: &str) -> f64 {
-    let now = chrono::Utc::now();
-    let close = chrono::DateTime::parse_from_rfc3339(close_time)
+/// Seconds from `now` until `close_time` (negative once closed, -1 if the
+/// time can't be parsed).
+pub fn compute_ttl(close_time: &str, now: chrono::DateTime<chrono::Utc>) -> f64 {
+    match parse_time(close_time) {
+        Some(c) => (c - now).num_seconds() as f64,
+        None => -1.0,
+    }
+}
+
+/// RFC 3339, or Kalshi's `YYYY-MM-DDTHH:MM:SSZ`.
+pub fn parse_time(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s)
         .ok()
         .map(|dt| dt.with_timezone(&chrono::Utc))
         .or_else(|| {
-            chrono::NaiveDateTime::parse_from_str(close_time, "%Y-%m-%dT%H:%M:%SZ")
+            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%SZ")
                 .ok()
                 .map(|dt| dt.and_utc())
-        });
-
-    match close {
-        Some(c) => (c - now).num_seconds() as f64,
-        None => -1.0,
-    }
+        })
 }
@@ -1,7 +1,10 @@
+#![allow(clippy::empty_line_after_doc_comments)]
 /// Performance metrics computation.
 /// All functions are pure -- they take state and return computed values.
 
-use crate::state::ModelState;
+use crate::asset::Asset;
+use crate::state::{ModelState, Signal};
+use std::collections::BTreeMap;
 
 /// Aggregate metrics for all models. Used by REST endpoints.
 #[derive(Debug, Clone, serde::Serialize)]
@@ -12,6 +15,7 @@ pub struct AggregateMetrics {
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ModelMetrics {
     pub name: String,
+    pub asset: Asset,
     pub cumulative_pnl: f64,
     pub daily_pnl: f64,
     pub total_trades: i64,
@@ -21,17 +25,17 @@ pub struct ModelMetrics {
     pub max_drawdown: f64,
     pub brier_score: f64,
     pub current_exposure: f64,
-    pub kelly_size: f64,
-    pub probability: f64,
-    pub ev: f64,
+    /// Latest signal per expiry horizon
+    pub signals: BTreeMap<String, Signal>,
 }
 
 /// Compute aggregate metrics from model states. Pure function.
-pub fn compute_aggregate(models: &[ModelState]) -> AggregateMetrics {
+pub fn compute_aggregate<'a>(models: impl IntoIterator<Item = &'a ModelState>) -> AggregateMetrics {
     let metrics = models
-        .iter()
+        .into_iter()
         .map(|m| ModelMetrics {
             name: m.name.to_string(),
+            asset: m.asset,
             cumulative_pnl: m.cumulative_pnl,
             daily_pnl: m.daily_pnl,
             total_trades: m.total_trades,
@@ -41,9 +45,7 @@ pub fn compute_aggregate(models: &[ModelState]) -> AggregateMetrics {
             max_drawdown: m.max_drawdown,
             brier_score: m.brier_score,
             current_exposure: m.current_exposure,
-            kelly_size: m.kelly_size,
-            probability: m.probability,
-            ev: m.ev,
+            signals: m.signals.clone(),
         })
         .collect();
 
@@ -14,6 +14,7 @@
 /// All inputs/outputs are f64. Pure function.
 
 /// Kelly sizing parameters. Stack-allocated.
+#[allow(clippy::empty_line_after_doc_comments)]
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct KellyParams {
@@ -1,7 +1,7 @@
 use crate::state::{ModelState, VolRegime, VolatilityState};
 
 /// Risk limit check result
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RiskCheck {
     /// Trading allowed
     Allowed,
@@ -9,13 +9,6 @@ pub enum RiskCheck {
     Blocked(&'static str),
 }
 
-impl RiskCheck {
-    #[inline]
-    pub fn is_allowed(&self) -> bool {
-        matches!(self, RiskCheck::Allowed)
-    }
-}
-
 /// Check all risk limits before placing a trade.
 /// Pure function, no side effects.
 #[inline]
@@ -59,21 +52,22 @@ pub fn check_risk_limits(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::asset::Asset;
 
     #[test]
     fn test_normal_conditions_allowed() {
-        let model = ModelState::new("test");
+        let model = ModelState::new("test", Asset::Btc);
         let vol = VolatilityState::default();
         let check = check_risk_limits(&model, &vol, 10.0, 0.5, 100.0, 50.0);
-        assert!(check.is_allowed());
+        assert_eq!(check, RiskCheck::Allowed);
     }
 
     #[test]
     fn test_drawdown_blocks() {
-        let mut model = ModelState::new("test");
+        let mut model = ModelState::new("test", Asset::Btc);
         model.daily_pnl = -150.0;
         let vol = VolatilityState::default();
         let check = check_risk_limits(&model, &vol, 10.0, 0.5, 100.0, 50.0);
-        assert!(!check.is_allowed());
+        assert_eq!(check, RiskCheck::Blocked("daily drawdown limit breached"));
     }
 }
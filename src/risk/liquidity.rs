@@ -0,0 +1,234 @@
+//! Market-quality gates, checked before every entry and scale-in. A model
+//! can see edge in a market that is too wide or too thin to trade at the
+//! quoted price; a market failing a gate takes no new positions (exits
+//! still run) and the decision journal records the gate as the reason.
+//!
+//! Every gate is off at 0. The spread comes with each quote and open
+//! interest with the market listing. Top-of-book size and recent trades
+//! cost the scanner an order book and a trades request per market and
+//! poll, so it only makes them for the gates enabled when it starts. A gate
+//! whose data is missing fails, so a hot reload cannot switch one on (see
+//! `LiquidityGates::fetched_by`).
+
+use crate::state::ActiveMarket;
+
+/// Quotes are whole cents; a spread this close to the cap is at the cap
+const SPREAD_EPS: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LiquidityGates {
+    /// Widest YES bid/ask spread to enter at, dollars
+    pub max_spread: f64,
+    /// Fewest contracts at the best bid on each side (each side's ask is
+    /// the other side's bid)
+    pub min_top_of_book: f64,
+    /// Fewest trades in the last `recent_window_secs`
+    pub min_recent_trades: u32,
+    /// Fewest contracts traded in the last `recent_window_secs`
+    pub min_recent_volume: f64,
+    pub recent_window_secs: f64,
+    /// Fewest contracts of open interest
+    pub min_open_interest: f64,
+}
+
+impl Default for LiquidityGates {
+    fn default() -> Self {
+        Self {
+            max_spread: 0.0,
+            min_top_of_book: 0.0,
+            min_recent_trades: 0,
+            min_recent_volume: 0.0,
+            recent_window_secs: 600.0,
+            min_open_interest: 0.0,
+        }
+    }
+}
+
+impl LiquidityGates {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        let mut check = |ok: bool, field: &str, want: &str, got: f64| {
+            if !ok {
+                errors.push(format!("liquidity.{field} must be {want}, got {got}"));
+            }
+        };
+        check((0.0..1.0).contains(&self.max_spread), "max_spread", "in [0, 1)", self.max_spread);
+        check(self.min_top_of_book >= 0.0, "min_top_of_book", ">= 0", self.min_top_of_book);
+        check(self.min_recent_volume >= 0.0, "min_recent_volume", ">= 0", self.min_recent_volume);
+        check(self.recent_window_secs > 0.0, "recent_window_secs", "> 0", self.recent_window_secs);
+        check(self.min_open_interest >= 0.0, "min_open_interest", ">= 0", self.min_open_interest);
+    }
+
+    /// Whether the scanner has to fetch each market's order book
+    pub fn needs_book(&self) -> bool {
+        self.min_top_of_book > 0.0
+    }
+
+    /// Whether the scanner has to fetch each market's recent trades
+    pub fn needs_trades(&self) -> bool {
+        self.min_recent_trades > 0 || self.min_recent_volume > 0.0
+    }
+
+    /// These gates limited to what scanners started under `running` fetch,
+    /// for a hot reload: a book or trades gate switched on keeps `running`'s
+    /// (off) threshold, and the trades window stays `running`'s, since the
+    /// data would never arrive and the gate would block every entry. True
+    /// if anything was held back until a restart.
+    pub fn fetched_by(self, running: &Self) -> (Self, bool) {
+        let mut gates = self;
+        let mut held = false;
+        if gates.needs_book() && !running.needs_book() {
+            gates.min_top_of_book = running.min_top_of_book;
+            held = true;
+        }
+        if gates.needs_trades() && !running.needs_trades() {
+            gates.min_recent_trades = running.min_recent_trades;
+            gates.min_recent_volume = running.min_recent_volume;
+            held = true;
+        }
+        if gates.recent_window_secs != running.recent_window_secs {
+            gates.recent_window_secs = running.recent_window_secs;
+            held = true;
+        }
+        (gates, held)
+    }
+
+    /// The gates a replay can apply: recorded quotes carry no depth.
+    pub fn quotes_only(&self) -> Self {
+        Self { max_spread: self.max_spread, ..Self::default() }
+    }
+
+    /// The first gate `market` fails, as a journal reason, or None if it
+    /// may be traded.
+    pub fn check(&self, market: &ActiveMarket) -> Option<&'static str> {
+        let quote = |q: &Option<String>| q.as_deref().and_then(|v| v.parse::<f64>().ok());
+        let depth = &market.depth;
+        let short = |min: f64, have: Option<f64>| min > 0.0 && have.is_none_or(|have| have < min);
+
+        if self.max_spread > 0.0 {
+            let spread = quote(&market.yes_ask).zip(quote(&market.yes_bid)).map(|(ask, bid)| ask - bid);
+            if spread.is_none_or(|s| s > self.max_spread + SPREAD_EPS) {
+                return Some("wide_spread");
+            }
+        }
+        if short(self.min_top_of_book, depth.top_of_book()) {
+            return Some("thin_book");
+        }
+        if short(f64::from(self.min_recent_trades), depth.recent_trades.map(f64::from)) {
+            return Some("few_recent_trades");
+        }
+        if short(self.min_recent_volume, depth.recent_volume) {
+            return Some("low_recent_volume");
+        }
+        if short(self.min_open_interest, depth.open_interest) {
+            return Some("low_open_interest");
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::MarketDepth;
+    use crate::Asset;
+
+    fn market(yes_bid: &str, yes_ask: &str, depth: MarketDepth) -> ActiveMarket {
+        ActiveMarket {
+            asset: Asset::Btc,
+            horizon: "hourly".into(),
+            ticker: "KXBTCD-26MAR02H15-T100000".into(),
+            event_ticker: "KXBTCD-26MAR02H15".into(),
+            series_ticker: "KXBTCD".into(),
+            strike: Some(100_000.0),
+            yes_bid: Some(yes_bid.into()),
+            yes_ask: Some(yes_ask.into()),
+            no_bid: None,
+            no_ask: None,
+            last_price: None,
+            close_time: "2026-03-02T15:00:00Z".into(),
+            expiration_time: "2026-03-02T15:00:00Z".into(),
+            status: "active".into(),
+            result: None,
+            depth,
+        }
+    }
+
+    fn deep() -> MarketDepth {
+        MarketDepth {
+            open_interest: Some(500.0),
+            yes_bid_size: Some(100.0),
+            no_bid_size: Some(40.0),
+            recent_trades: Some(3),
+            recent_volume: Some(30.0),
+        }
+    }
+
+    #[test]
+    fn test_gates_off_by_default() {
+        let gates = LiquidityGates::default();
+        assert_eq!(gates.check(&market("0.05", "0.95", MarketDepth::default())), None);
+        assert!(!gates.needs_book() && !gates.needs_trades());
+    }
+
+    #[test]
+    fn test_each_gate_names_itself() {
+        let gates = LiquidityGates {
+            max_spread: 0.02,
+            min_top_of_book: 25.0,
+            min_recent_trades: 2,
+            min_recent_volume: 20.0,
+            min_open_interest: 100.0,
+            ..LiquidityGates::default()
+        };
+        // 57 - 55 is at the cap, not over it
+        assert_eq!(gates.check(&market("0.55", "0.57", deep())), None);
+        assert_eq!(gates.check(&market("0.40", "0.70", deep())), Some("wide_spread"));
+
+        let cases = [
+            (MarketDepth { no_bid_size: Some(10.0), ..deep() }, "thin_book"),
+            (MarketDepth { recent_trades: Some(1), ..deep() }, "few_recent_trades"),
+            (MarketDepth { recent_volume: Some(5.0), ..deep() }, "low_recent_volume"),
+            (MarketDepth { open_interest: Some(50.0), ..deep() }, "low_open_interest"),
+            // Not fetched counts as failing
+            (MarketDepth { yes_bid_size: None, ..deep() }, "thin_book"),
+        ];
+        for (depth, reason) in cases {
+            assert_eq!(gates.check(&market("0.55", "0.57", depth)), Some(reason));
+        }
+        assert!(gates.needs_book() && gates.needs_trades());
+        assert_eq!(gates.quotes_only(), LiquidityGates { max_spread: 0.02, ..LiquidityGates::default() });
+    }
+
+    #[test]
+    fn test_reload_cannot_switch_on_unfetched_gates() {
+        let running = LiquidityGates { min_recent_trades: 2, ..LiquidityGates::default() };
+        let reloaded = LiquidityGates {
+            max_spread: 0.05,
+            min_top_of_book: 10.0,
+            min_recent_volume: 20.0,
+            recent_window_secs: 300.0,
+            ..LiquidityGates::default()
+        };
+        let (gates, held) = reloaded.fetched_by(&running);
+        assert!(held);
+        // Quote gates and trades thresholds (trades are already fetched)
+        // apply; the book gate and the new window wait for a restart
+        assert_eq!(
+            gates,
+            LiquidityGates { max_spread: 0.05, min_recent_volume: 20.0, ..LiquidityGates::default() }
+        );
+
+        // Switching gates off is always safe
+        assert_eq!(LiquidityGates::default().fetched_by(&running), (LiquidityGates::default(), false));
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut errors = Vec::new();
+        LiquidityGates::default().validate(&mut errors);
+        assert!(errors.is_empty(), "{errors:?}");
+        LiquidityGates { max_spread: 1.5, recent_window_secs: 0.0, ..LiquidityGates::default() }.validate(&mut errors);
+        assert_eq!(errors.len(), 2, "{errors:?}");
+    }
+}
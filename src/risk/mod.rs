@@ -1,2 +1,5 @@
 pub mod kelly;
 pub mod limits;
+pub mod liquidity;
+pub mod portfolio;
+pub mod var;
@@ -0,0 +1,326 @@
+//! Portfolio-level risk limits.
+//!
+//! `limits::check_risk_limits` guards one model in isolation. The checks here
+//! look at the book of every model on every asset together: total notional
+//! at cost, notional concentrated in a single market, and the combined daily
+//! loss. Net delta is capped per underlying, since BTC and ETH deltas do not
+//! offset. An order that would breach a cap is scaled down to whole
+//! contracts that fit, or vetoed if none do. A breached daily loss vetoes
+//! every new order.
+//!
+//! Net delta is quoted in dollars of P/L per $100 move in the underlying.
+//! Every position is priced off its own strike and close time, with the
+//! volatility of the horizon it was opened under.
+
+use super::var::HorizonVols;
+use crate::asset::Asset;
+use crate::models::{PricingModel, VolContext};
+use crate::paper::simulator::compute_ttl;
+use crate::state::{ModelParams, ModelState, OpenPosition};
+use smallvec::SmallVec;
+
+/// Underlying bump for the finite-difference delta, as a fraction of spot
+const DELTA_BUMP: f64 = 1e-4;
+
+/// Orders are placed in whole contracts; a scale-down below this is a veto
+const MIN_CONTRACTS: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PortfolioLimits {
+    /// Total cost of open positions across every model and asset
+    pub max_notional: f64,
+    /// Absolute net delta on any one underlying, $ per $100 move
+    pub max_net_delta: f64,
+    /// Cost of open positions in any one market
+    pub max_market_notional: f64,
+    /// Combined daily P/L (realized + unrealized) below which entries stop
+    pub max_daily_loss: f64,
+}
+
+impl Default for PortfolioLimits {
+    fn default() -> Self {
+        Self {
+            max_notional: 150.0,
+            max_net_delta: 10.0,
+            max_market_notional: 100.0,
+            max_daily_loss: 200.0,
+        }
+    }
+}
+
+/// Aggregate exposure across all models and assets, built once per tick and
+/// updated as orders are accepted so later models, horizons and assets see
+/// earlier fills.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct PortfolioBook {
+    pub notional: f64,
+    /// Net delta per underlying
+    pub net_delta: SmallVec<[(Asset, f64); 3]>,
+    pub daily_pnl: f64,
+    pub markets: SmallVec<[(String, f64); 4]>,
+}
+
+impl PortfolioBook {
+    /// Add one asset's models: their daily P/L and every open position.
+    /// Deltas come from `model` (the reference model) at `spot`, with each
+    /// position's horizon volatility from `vols`.
+    pub fn add_states(
+        &mut self,
+        model: &dyn PricingModel,
+        states: &[ModelState],
+        spot: f64,
+        vols: &HorizonVols<'_>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) {
+        for state in states {
+            self.daily_pnl += state.daily_pnl + state.unrealized_pnl;
+            for pos in &state.open_positions {
+                let delta = position_yes_delta(model, spot, pos, vols, now);
+                self.add(state.asset, &pos.market_ticker, &pos.side, pos.contracts, pos.entry_price, delta);
+            }
+        }
+    }
+
+    /// Record an accepted order.
+    pub fn add(&mut self, asset: Asset, ticker: &str, side: &str, contracts: f64, price: f64, yes_delta: f64) {
+        let cost = contracts * price;
+        self.notional += cost;
+        let delta = contracts * side_delta(side, yes_delta);
+        match self.net_delta.iter_mut().find(|(a, _)| *a == asset) {
+            Some((_, d)) => *d += delta,
+            None => self.net_delta.push((asset, delta)),
+        }
+        match self.markets.iter_mut().find(|(t, _)| t == ticker) {
+            Some((_, n)) => *n += cost,
+            None => self.markets.push((ticker.to_string(), cost)),
+        }
+    }
+
+    pub fn market_notional(&self, ticker: &str) -> f64 {
+        self.markets.iter().find(|(t, _)| t == ticker).map_or(0.0, |(_, n)| *n)
+    }
+
+    pub fn asset_delta(&self, asset: Asset) -> f64 {
+        self.net_delta.iter().find(|(a, _)| *a == asset).map_or(0.0, |(_, d)| *d)
+    }
+}
+
+/// Portfolio check result
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PortfolioCheck {
+    Allowed,
+    /// Allowed at a smaller size; the reason names the binding cap
+    ScaledDown { contracts: f64, reason: &'static str },
+    Blocked(&'static str),
+}
+
+/// Check a proposed order against the portfolio caps.
+/// Pure function, no side effects.
+#[allow(clippy::too_many_arguments)]
+pub fn check_order(
+    limits: &PortfolioLimits,
+    book: &PortfolioBook,
+    asset: Asset,
+    ticker: &str,
+    side: &str,
+    contracts: f64,
+    price: f64,
+    yes_delta: f64,
+) -> PortfolioCheck {
+    if book.daily_pnl < -limits.max_daily_loss {
+        return PortfolioCheck::Blocked("portfolio daily loss limit breached");
+    }
+
+    let mut allowed = contracts;
+    let mut binding = None;
+
+    let mut cap = |max: f64, reason: &'static str| {
+        if max < allowed {
+            allowed = max;
+            binding = Some(reason);
+        }
+    };
+
+    if price > 0.0 {
+        cap((limits.max_notional - book.notional) / price, "portfolio notional cap");
+        cap(
+            (limits.max_market_notional - book.market_notional(ticker)) / price,
+            "market concentration cap",
+        );
+    }
+
+    // Only orders that push |net delta| past the cap are limited; an order
+    // that offsets existing delta can go up to the cap on the other side.
+    let delta = side_delta(side, yes_delta);
+    if delta != 0.0 {
+        cap(
+            (limits.max_net_delta - book.asset_delta(asset) * delta.signum()) / delta.abs(),
+            "portfolio net delta cap",
+        );
+    }
+
+    match binding {
+        None => PortfolioCheck::Allowed,
+        Some(reason) => {
+            let contracts = allowed.floor();
+            if contracts >= MIN_CONTRACTS {
+                PortfolioCheck::ScaledDown { contracts, reason }
+            } else {
+                PortfolioCheck::Blocked(reason)
+            }
+        }
+    }
+}
+
+/// Delta of one YES contract in $ per $100 move in the underlying, by
+/// central difference on the (uncalibrated) model probability.
+pub fn yes_delta(
+    model: &dyn PricingModel,
+    spot: f64,
+    strike: f64,
+    ttl_seconds: f64,
+    sigma: f64,
+    vol_ctx: &VolContext,
+) -> f64 {
+    let bump = spot * DELTA_BUMP;
+    let bumped = |s: f64| model.probability(&ModelParams::new(s, strike, ttl_seconds, sigma), vol_ctx);
+    let up = bumped(spot + bump);
+    let down = bumped(spot - bump);
+    (up - down) / (2.0 * bump) * 100.0
+}
+
+/// `yes_delta` for an open position: its strike, the time left to its close
+/// and its horizon's volatility. 0 once it has closed or without a strike.
+pub fn position_yes_delta(
+    model: &dyn PricingModel,
+    spot: f64,
+    pos: &OpenPosition,
+    vols: &HorizonVols<'_>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> f64 {
+    let ttl_seconds = compute_ttl(&pos.close_time, now);
+    match vols.get(&pos.horizon) {
+        Some((sigma, vol_ctx)) if pos.strike > 0.0 && ttl_seconds > 0.0 && spot > 0.0 => {
+            yes_delta(model, spot, pos.strike, ttl_seconds, sigma, vol_ctx)
+        }
+        _ => 0.0,
+    }
+}
+
+#[inline]
+fn side_delta(side: &str, yes_delta: f64) -> f64 {
+    if side == "yes" { yes_delta } else { -yes_delta }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::black_scholes::BlackScholesDigital;
+    use crate::state::VolatilityState;
+
+    const BTC: Asset = Asset::Btc;
+
+    fn limits() -> PortfolioLimits {
+        PortfolioLimits {
+            max_notional: 20.0,
+            max_net_delta: 5.0,
+            max_market_notional: 15.0,
+            max_daily_loss: 50.0,
+        }
+    }
+
+    #[test]
+    fn test_within_caps_allowed() {
+        let book = PortfolioBook::default();
+        assert_eq!(check_order(&limits(), &book, BTC, "M", "yes", 10.0, 0.5, 0.1), PortfolioCheck::Allowed);
+    }
+
+    #[test]
+    fn test_scales_down_to_binding_cap() {
+        let mut book = PortfolioBook::default();
+        book.add(Asset::Eth, "A", "yes", 20.0, 0.5, 0.0); // $10 notional on another asset
+        // Notional headroom $10 -> 20 contracts; market cap $15 -> 30
+        assert_eq!(
+            check_order(&limits(), &book, BTC, "M", "no", 40.0, 0.5, 0.0),
+            PortfolioCheck::ScaledDown { contracts: 20.0, reason: "portfolio notional cap" }
+        );
+
+        let mut book = PortfolioBook::default();
+        book.add(BTC, "M", "yes", 29.0, 0.5, 0.0); // $14.50 in M
+        assert_eq!(
+            check_order(&limits(), &book, BTC, "M", "yes", 1.0, 0.6, 0.0),
+            PortfolioCheck::Blocked("market concentration cap")
+        );
+    }
+
+    #[test]
+    fn test_delta_cap_is_directional_and_per_asset() {
+        let mut book = PortfolioBook::default();
+        book.add(BTC, "M", "yes", 10.0, 0.1, 0.4); // net delta +4
+        // Another YES can only add 1.0 of delta
+        assert_eq!(
+            check_order(&limits(), &book, BTC, "M", "yes", 10.0, 0.1, 0.4),
+            PortfolioCheck::ScaledDown { contracts: 2.0, reason: "portfolio net delta cap" }
+        );
+        // A NO offsets: up to (5 + 4) / 0.4 = 22 contracts
+        assert_eq!(check_order(&limits(), &book, BTC, "M", "no", 20.0, 0.1, 0.4), PortfolioCheck::Allowed);
+        // BTC delta leaves ETH's headroom alone
+        assert_eq!(check_order(&limits(), &book, Asset::Eth, "E", "yes", 10.0, 0.1, 0.4), PortfolioCheck::Allowed);
+    }
+
+    #[test]
+    fn test_daily_loss_vetoes_across_assets() {
+        let model = BlackScholesDigital::new();
+        let now = chrono::Utc::now();
+        let mut btc = vec![ModelState::new("a", BTC)];
+        let mut eth = vec![ModelState::new("a", Asset::Eth)];
+        btc[0].daily_pnl = -30.0;
+        eth[0].unrealized_pnl = -25.0;
+
+        let mut book = PortfolioBook::default();
+        book.add_states(&model, &btc, 100_000.0, &HorizonVols::default(), now);
+        assert_eq!(check_order(&limits(), &book, BTC, "M", "yes", 1.0, 0.5, 0.1), PortfolioCheck::Allowed);
+        book.add_states(&model, &eth, 4_000.0, &HorizonVols::default(), now);
+        assert_eq!(
+            check_order(&limits(), &book, BTC, "M", "yes", 1.0, 0.5, 0.1),
+            PortfolioCheck::Blocked("portfolio daily loss limit breached")
+        );
+    }
+
+    #[test]
+    fn test_positions_priced_with_their_horizon_vol() {
+        let model = BlackScholesDigital::new();
+        let now = chrono::Utc::now();
+        let close = (now + chrono::Duration::minutes(30)).to_rfc3339();
+        let pos = |horizon: &str| OpenPosition {
+            trade_id: "t".into(),
+            market_ticker: "M".into(),
+            horizon: horizon.into(),
+            strike: 100_200.0,
+            close_time: close.clone(),
+            side: "yes".into(),
+            entry_price: 0.4,
+            contracts: 1.0,
+            model_probability: 0.5,
+            entry_tick: 0,
+            entry_btc_price: 100_000.0,
+            peak_unrealized: 0.0,
+            leg: 0,
+            unrealized: 0.0,
+        };
+        let calm = VolatilityState { ewma_vol: 0.0001, ..VolatilityState::default() };
+        let wild = VolatilityState { ewma_vol: 0.001, ..VolatilityState::default() };
+        let mut vols = HorizonVols::default();
+        vols.push("hourly", &calm);
+        vols.push("daily", &wild);
+
+        let hourly = position_yes_delta(&model, 100_000.0, &pos("hourly"), &vols, now);
+        let daily = position_yes_delta(&model, 100_000.0, &pos("daily"), &vols, now);
+        assert!(hourly > 0.0 && daily > 0.0);
+        assert!((hourly - daily).abs() > 1e-3, "hourly {hourly} daily {daily}");
+        // A horizon no longer configured falls back to the first estimate
+        assert_eq!(position_yes_delta(&model, 100_000.0, &pos("weekly"), &vols, now), hourly);
+        assert_eq!(position_yes_delta(&model, 100_000.0, &pos("hourly"), &HorizonVols::default(), now), 0.0);
+    }
+}
@@ -0,0 +1,519 @@
+//! Value-at-Risk and expected shortfall for open binary positions.
+//!
+//! BTC is simulated forward as a jump diffusion driven by the current
+//! `VolatilityState`s: the EWMA vol plus the estimated jump intensity and
+//! jump size distribution. Every open position with a known strike and close
+//! time is revalued on each path -- at short horizons with its own model's
+//! probability for the time left, and at its expiry with the $0/$1 payoff.
+//! Positions on different expiry horizons share the Brownian and jump draws
+//! of each path but diffuse at their own horizon's vol, so each is valued
+//! and simulated as its horizon's estimate sees it.
+//! Losses are measured from the position's current model value, so a VaR of
+//! $12 means "$12 below what the book is worth now".
+//!
+//! Everything here is per asset: each asset's positions are simulated on its
+//! own paths, with no model of how the assets move together.
+//!
+//! The full report runs periodically off the tick path. Entries are gated on
+//! the asset's 99% ES at expiry through `EsGate`, which simulates one set of
+//! paths per tick (lazily, only when an order is proposed) and then prices
+//! each order with a single pass over the scenario losses. The limit is per
+//! asset, so with several assets enabled the combined tail can reach the
+//! limit times the number of assets; size `max_asset_es99` with that in mind.
+
+use crate::models::{PricingModel, VolContext};
+use crate::paper::simulator::{compute_ttl, parse_time};
+use crate::state::{ModelParams, ModelState, VolatilityState};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Poisson, StandardNormal};
+use smallvec::SmallVec;
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0;
+
+/// Short revaluation horizons (label, seconds), alongside "expiry"
+pub const SHORT_HORIZONS: [(&str, f64); 2] = [("1m", 60.0), ("5m", 300.0)];
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TailRiskConfig {
+    /// Monte Carlo paths per run
+    pub scenarios: usize,
+    /// Entries that would push one asset's 99% ES at expiry (every model and
+    /// horizon on that asset) above this ($) are blocked. Per asset, not
+    /// across assets. 0 disables the gate.
+    #[serde(alias = "max_es99")]
+    pub max_asset_es99: f64,
+}
+
+impl Default for TailRiskConfig {
+    fn default() -> Self {
+        Self { scenarios: 2000, max_asset_es99: 100.0 }
+    }
+}
+
+/// One open position as the simulation sees it.
+#[derive(Debug, Clone, Copy)]
+pub struct RiskPosition {
+    /// Index into the model list
+    pub model: usize,
+    pub yes: bool,
+    pub contracts: f64,
+    pub strike: f64,
+    pub ttl_seconds: f64,
+    /// Current value per contract
+    pub value: f64,
+    /// Annualized vol and jump context of the horizon it was opened under
+    pub sigma: f64,
+    pub vol_ctx: VolContext,
+}
+
+impl RiskPosition {
+    #[inline]
+    fn payoff(&self, terminal: f64) -> f64 {
+        let yes_pays = if terminal >= self.strike { 1.0 } else { 0.0 };
+        if self.yes { yes_pays } else { 1.0 - yes_pays }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
+pub struct TailRisk {
+    pub var95: f64,
+    pub var99: f64,
+    pub es95: f64,
+    pub es99: f64,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ModelTail {
+    pub model: &'static str,
+    #[serde(flatten)]
+    pub risk: TailRisk,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HorizonRisk {
+    /// "1m", "5m" or "expiry"
+    pub horizon: &'static str,
+    pub portfolio: TailRisk,
+    pub models: Vec<ModelTail>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TailReport {
+    pub timestamp: String,
+    pub scenarios: usize,
+    /// Positions included (those with a known strike and close time)
+    pub positions: usize,
+    pub horizons: Vec<HorizonRisk>,
+}
+
+/// Simulated BTC paths at a fixed set of times (seconds from now), kept as
+/// their Brownian and jump parts so each position can read the spot at its
+/// own vol.
+struct Paths {
+    spot: f64,
+    times: Vec<f64>,
+    /// Jump compensator per year, keeping the price a martingale
+    jump_comp: f64,
+    /// Row-major: `brownian[scenario * times.len() + j]`, in sqrt(years)
+    brownian: Vec<f64>,
+    /// Summed log jumps, laid out as `brownian`
+    jumps: Vec<f64>,
+}
+
+impl Paths {
+    fn simulate(spot: f64, vol_ctx: &VolContext, mut times: Vec<f64>, n: usize, seed: u64) -> Self {
+        times.retain(|t| *t > 0.0);
+        times.sort_by(f64::total_cmp);
+        times.dedup();
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let jump_sd = vol_ctx.jump_var.max(0.0).sqrt();
+        // Compensate the jumps so the price stays a martingale
+        let jump_comp = vol_ctx.jump_intensity * ((vol_ctx.jump_mean + 0.5 * vol_ctx.jump_var).exp() - 1.0);
+
+        let mut brownian = Vec::with_capacity(n * times.len());
+        let mut jumps = Vec::with_capacity(n * times.len());
+        for _ in 0..n {
+            let (mut w, mut log_j) = (0.0, 0.0);
+            let mut prev = 0.0;
+            for &t in &times {
+                let dt = (t - prev) / SECONDS_PER_YEAR;
+                prev = t;
+                let z: f64 = StandardNormal.sample(&mut rng);
+                w += dt.sqrt() * z;
+
+                let lambda_dt = vol_ctx.jump_intensity * dt;
+                if lambda_dt > 0.0 {
+                    if let Ok(poisson) = Poisson::new(lambda_dt) {
+                        let count = poisson.sample(&mut rng) as u64;
+                        for _ in 0..count {
+                            let zj: f64 = StandardNormal.sample(&mut rng);
+                            log_j += vol_ctx.jump_mean + jump_sd * zj;
+                        }
+                    }
+                }
+                brownian.push(w);
+                jumps.push(log_j);
+            }
+        }
+        Self { spot, times, jump_comp, brownian, jumps }
+    }
+
+    /// Column index for time `t` (the first simulated time at or after it).
+    fn index(&self, t: f64) -> usize {
+        self.times.partition_point(|x| *x < t).min(self.times.len().saturating_sub(1))
+    }
+
+    /// Spot on `scenario` at column `j`, diffusing at `sigma`
+    #[inline]
+    fn spot(&self, scenario: usize, j: usize, sigma: f64) -> f64 {
+        let k = scenario * self.times.len() + j;
+        let t = self.times[j] / SECONDS_PER_YEAR;
+        let drift = -(0.5 * sigma * sigma + self.jump_comp) * t;
+        self.spot * (drift + sigma * self.brownian[k] + self.jumps[k]).exp()
+    }
+}
+
+/// Collect every open position that can be priced, valued by its own model
+/// at its own horizon's vol.
+pub fn open_positions(
+    models: &[&dyn PricingModel],
+    states: &[ModelState],
+    spot: f64,
+    vols: &HorizonVols<'_>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Vec<RiskPosition> {
+    let mut out = Vec::new();
+    for (i, (model, state)) in models.iter().zip(states).enumerate() {
+        for pos in &state.open_positions {
+            let ttl_seconds = compute_ttl(&pos.close_time, now);
+            let Some((sigma, vol_ctx)) = vols.get(&pos.horizon) else {
+                continue;
+            };
+            if pos.strike <= 0.0 || ttl_seconds <= 0.0 {
+                continue;
+            }
+            let p = model.probability(&ModelParams::new(spot, pos.strike, ttl_seconds, sigma), vol_ctx);
+            let yes = pos.side == "yes";
+            out.push(RiskPosition {
+                model: i,
+                yes,
+                contracts: pos.contracts,
+                strike: pos.strike,
+                ttl_seconds,
+                value: if yes { p } else { 1.0 - p },
+                sigma,
+                vol_ctx: *vol_ctx,
+            });
+        }
+    }
+    out
+}
+
+/// Run the Monte Carlo and report VaR/ES per model and for the portfolio at
+/// each horizon. Jumps are drawn from the first horizon's estimate in
+/// `vols`; each position diffuses at its own. Empty when `vols` is.
+pub fn tail_report(
+    models: &[&dyn PricingModel],
+    states: &[ModelState],
+    spot: f64,
+    vols: &HorizonVols<'_>,
+    scenarios: usize,
+    seed: u64,
+    timestamp: &str,
+) -> TailReport {
+    let now = parse_time(timestamp).unwrap_or_else(chrono::Utc::now);
+    let positions = open_positions(models, states, spot, vols, now);
+    let Some((_, _, jump_ctx)) = vols.horizons.first() else {
+        return TailReport { timestamp: timestamp.to_string(), scenarios, positions: 0, horizons: Vec::new() };
+    };
+
+    let mut times: Vec<f64> = SHORT_HORIZONS.iter().map(|(_, h)| *h).collect();
+    times.extend(positions.iter().map(|p| p.ttl_seconds));
+    let paths = Paths::simulate(spot, jump_ctx, times, scenarios, seed);
+    let expiry_idx: Vec<usize> = positions.iter().map(|p| paths.index(p.ttl_seconds)).collect();
+
+    let mut horizons = Vec::with_capacity(SHORT_HORIZONS.len() + 1);
+    let labels = SHORT_HORIZONS.iter().map(|(l, h)| (*l, Some(*h))).chain([("expiry", None)]);
+
+    for (label, horizon) in labels {
+        let mut by_model = vec![vec![0.0_f64; scenarios]; models.len()];
+        let mut total = vec![0.0_f64; scenarios];
+        let h_idx = horizon.map(|h| paths.index(h));
+
+        for s in 0..scenarios {
+            for (k, pos) in positions.iter().enumerate() {
+                let future = match (horizon, h_idx) {
+                    (Some(h), Some(j)) if h < pos.ttl_seconds => {
+                        let params = ModelParams::new(paths.spot(s, j, pos.sigma), pos.strike, pos.ttl_seconds - h, pos.sigma);
+                        let p = models[pos.model].probability(&params, &pos.vol_ctx);
+                        if pos.yes { p } else { 1.0 - p }
+                    }
+                    _ => pos.payoff(paths.spot(s, expiry_idx[k], pos.sigma)),
+                };
+                let loss = pos.contracts * (pos.value - future);
+                by_model[pos.model][s] += loss;
+                total[s] += loss;
+            }
+        }
+
+        horizons.push(HorizonRisk {
+            horizon: label,
+            portfolio: tail_risk(&mut total),
+            models: states
+                .iter()
+                .zip(by_model.iter_mut())
+                .map(|(state, losses)| ModelTail { model: state.name, risk: tail_risk(losses) })
+                .collect(),
+        });
+    }
+
+    TailReport {
+        timestamp: timestamp.to_string(),
+        scenarios,
+        positions: positions.len(),
+        horizons,
+    }
+}
+
+/// VaR and ES at 95% and 99% of a loss sample (positive = loss). Sorts in place.
+pub fn tail_risk(losses: &mut [f64]) -> TailRisk {
+    losses.sort_by(f64::total_cmp);
+    let (var95, es95) = var_es(losses, 0.95);
+    let (var99, es99) = var_es(losses, 0.99);
+    TailRisk { var95, var99, es95, es99 }
+}
+
+fn var_es(sorted: &[f64], confidence: f64) -> (f64, f64) {
+    let n = sorted.len();
+    if n == 0 {
+        return (0.0, 0.0);
+    }
+    let idx = ((confidence * n as f64).ceil() as usize).clamp(1, n) - 1;
+    let tail = &sorted[idx..];
+    (sorted[idx], tail.iter().sum::<f64>() / tail.len() as f64)
+}
+
+/// One asset's volatility estimate per expiry horizon. Positions are priced
+/// with the estimate of the horizon they were opened under.
+#[derive(Debug, Clone, Default)]
+pub struct HorizonVols<'a> {
+    /// (horizon, annualized sigma, jump context)
+    horizons: SmallVec<[(&'a str, f64, VolContext); 4]>,
+}
+
+impl<'a> HorizonVols<'a> {
+    pub fn push(&mut self, horizon: &'a str, vol_state: &VolatilityState) {
+        self.horizons.push((horizon, vol_state.annualized(), vol_context(vol_state)));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.horizons.is_empty()
+    }
+
+    /// Sigma and jump context for `horizon`. A horizon with no estimate here
+    /// (not yet warm, or no longer configured for a restored position) gets
+    /// the first one; None only when there is none at all.
+    pub fn get(&self, horizon: &str) -> Option<(f64, &VolContext)> {
+        let (_, sigma, ctx) = self.horizons.iter().find(|(h, ..)| *h == horizon).or(self.horizons.first())?;
+        Some((*sigma, ctx))
+    }
+}
+
+pub fn vol_context(vol_state: &VolatilityState) -> VolContext {
+    VolContext {
+        jump_intensity: vol_state.jump_intensity,
+        jump_mean: vol_state.jump_mean,
+        jump_var: vol_state.jump_var,
+        student_t_nu: vol_state.student_t_nu,
+    }
+}
+
+/// One asset's 99% ES at expiry, across every model and horizon trading it,
+/// for entry gating on the active market.
+/// `sigma` and `vol_ctx` are the active market's horizon's: orders diffuse at
+/// that vol and the paths draw their jumps from it, while open positions
+/// diffuse at their own.
+pub struct EsGate {
+    positions: Vec<RiskPosition>,
+    spot: f64,
+    sigma: f64,
+    vol_ctx: VolContext,
+    strike: f64,
+    ttl_seconds: f64,
+    scenarios: usize,
+    seed: u64,
+    /// Paths plus the current book's expiry loss per scenario, built on first use
+    sim: Option<(Paths, Vec<f64>)>,
+}
+
+impl EsGate {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        positions: Vec<RiskPosition>,
+        spot: f64,
+        sigma: f64,
+        vol_ctx: VolContext,
+        strike: f64,
+        ttl_seconds: f64,
+        scenarios: usize,
+        seed: u64,
+    ) -> Self {
+        Self { positions, spot, sigma, vol_ctx, strike, ttl_seconds, scenarios, seed, sim: None }
+    }
+
+    fn sim(&mut self) -> &mut (Paths, Vec<f64>) {
+        if self.sim.is_none() {
+            let mut times: Vec<f64> = self.positions.iter().map(|p| p.ttl_seconds).collect();
+            times.push(self.ttl_seconds);
+            let paths = Paths::simulate(self.spot, &self.vol_ctx, times, self.scenarios, self.seed);
+            let mut losses = vec![0.0; self.scenarios];
+            for pos in &self.positions {
+                let j = paths.index(pos.ttl_seconds);
+                for (s, loss) in losses.iter_mut().enumerate() {
+                    *loss += pos.contracts * (pos.value - pos.payoff(paths.spot(s, j, pos.sigma)));
+                }
+            }
+            self.sim = Some((paths, losses));
+        }
+        self.sim.as_mut().expect("simulated above")
+    }
+
+    fn order(&self, yes: bool, contracts: f64, price: f64) -> RiskPosition {
+        RiskPosition {
+            model: 0,
+            yes,
+            contracts,
+            strike: self.strike,
+            ttl_seconds: self.ttl_seconds,
+            value: price,
+            sigma: self.sigma,
+            vol_ctx: self.vol_ctx,
+        }
+    }
+
+    /// The asset's 99% ES at expiry before and after buying `contracts` at
+    /// `price` on the active market.
+    pub fn es99_with(&mut self, yes: bool, contracts: f64, price: f64) -> (f64, f64) {
+        let order = self.order(yes, contracts, price);
+        let (paths, losses) = self.sim();
+        let j = paths.index(order.ttl_seconds);
+        let mut before = losses.clone();
+        let mut after: Vec<f64> = losses
+            .iter()
+            .enumerate()
+            .map(|(s, l)| l + order.contracts * (order.value - order.payoff(paths.spot(s, j, order.sigma))))
+            .collect();
+        (tail_risk(&mut before).es99, tail_risk(&mut after).es99)
+    }
+
+    /// Record an accepted order so later checks this tick include it.
+    pub fn add(&mut self, yes: bool, contracts: f64, price: f64) {
+        let order = self.order(yes, contracts, price);
+        match self.sim.as_mut() {
+            Some((paths, losses)) => {
+                let j = paths.index(order.ttl_seconds);
+                for (s, loss) in losses.iter_mut().enumerate() {
+                    *loss += order.contracts * (order.value - order.payoff(paths.spot(s, j, order.sigma)));
+                }
+            }
+            None => self.positions.push(order),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_var_es_on_known_sample() {
+        // Losses 1..=100: 95% VaR is the 95th value, ES the mean of 95..=100
+        let mut losses: Vec<f64> = (1..=100).rev().map(f64::from).collect();
+        let risk = tail_risk(&mut losses);
+        assert_eq!(risk.var95, 95.0);
+        assert!((risk.es95 - 97.5).abs() < 1e-12);
+        assert_eq!(risk.var99, 99.0);
+        assert!((risk.es99 - 99.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_binary_expiry_loss_is_bounded_by_value() {
+        // ATM YES worth ~0.5: the worst case at expiry is losing the value
+        let vol_ctx = VolContext { jump_intensity: 0.0, jump_mean: 0.0, jump_var: 0.0, student_t_nu: 5.0 };
+        let pos = RiskPosition {
+            model: 0,
+            yes: true,
+            contracts: 10.0,
+            strike: 100_000.0,
+            ttl_seconds: 600.0,
+            value: 0.5,
+            sigma: 0.6,
+            vol_ctx,
+        };
+        let mut gate = EsGate::new(vec![pos], 100_000.0, 0.6, vol_ctx, 100_000.0, 600.0, 4000, 7);
+
+        let (before, with_same_side) = gate.es99_with(true, 10.0, 0.5);
+        assert!((before - 5.0).abs() < 1e-9, "es99 {before}");
+        assert!((with_same_side - 10.0).abs() < 1e-9);
+
+        // The opposite side hedges the same strike and expiry completely
+        let (_, hedged) = gate.es99_with(false, 10.0, 0.5);
+        assert!(hedged.abs() < 1e-9);
+
+        gate.add(true, 10.0, 0.5);
+        assert!((gate.es99_with(true, 0.0, 0.5).0 - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_es_limit_is_per_asset_and_reads_the_old_key() {
+        let config: TailRiskConfig = toml::from_str("max_es99 = 40.0").unwrap();
+        assert_eq!(config.max_asset_es99, 40.0);
+        let config: TailRiskConfig = toml::from_str("max_asset_es99 = 25.0").unwrap();
+        assert_eq!(config.max_asset_es99, 25.0);
+    }
+
+    #[test]
+    fn test_simulation_is_seeded_and_centered() {
+        let vol_ctx = VolContext { jump_intensity: 100.0, jump_mean: 0.0, jump_var: 1e-5, student_t_nu: 5.0 };
+        let a = Paths::simulate(100_000.0, &vol_ctx, vec![300.0, 60.0, 300.0], 5000, 1);
+        let b = Paths::simulate(100_000.0, &vol_ctx, vec![60.0, 300.0], 5000, 1);
+        assert_eq!(a.times, vec![60.0, 300.0]);
+        assert_eq!((&a.brownian, &a.jumps), (&b.brownian, &b.jumps));
+
+        for sigma in [0.3, 0.6, 1.2] {
+            let mean = (0..5000).map(|s| a.spot(s, 1, sigma)).sum::<f64>() / 5000.0;
+            assert!((mean / 100_000.0 - 1.0).abs() < 0.001, "sigma {sigma} mean {mean}");
+        }
+    }
+
+    #[test]
+    fn test_positions_diffuse_at_their_own_vol() {
+        // Two OTM YES legs on the same strike and expiry, one opened under a
+        // calm horizon: only the volatile one can finish in the money
+        let vol_ctx = VolContext { jump_intensity: 0.0, jump_mean: 0.0, jump_var: 0.0, student_t_nu: 5.0 };
+        let leg = |sigma| RiskPosition {
+            model: 0,
+            yes: true,
+            contracts: 10.0,
+            strike: 101_000.0,
+            ttl_seconds: 600.0,
+            value: 0.1,
+            sigma,
+            vol_ctx,
+        };
+        let paths = Paths::simulate(100_000.0, &vol_ctx, vec![600.0], 4000, 3);
+        let in_the_money =
+            |pos: &RiskPosition| (0..4000).filter(|&s| pos.payoff(paths.spot(s, 0, pos.sigma)) > 0.0).count();
+        assert_eq!(in_the_money(&leg(0.01)), 0);
+        assert!(in_the_money(&leg(2.0)) > 0);
+
+        // The gate prices an order at its own (calm) vol beside a volatile leg
+        let mut gate = EsGate::new(vec![leg(2.0)], 100_000.0, 0.01, vol_ctx, 101_000.0, 600.0, 4000, 3);
+        let (before, after) = gate.es99_with(true, 10.0, 0.1);
+        assert!((before - 1.0).abs() < 1e-9, "es99 {before}");
+        assert!((after - 2.0).abs() < 1e-9, "es99 {after}");
+    }
+}
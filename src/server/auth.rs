@@ -0,0 +1,332 @@
+//! API key authentication for the REST and WebSocket endpoints.
+//!
+//! Keys are never stored in plaintext: config holds `role:label:sha256hex`
+//! lines and requests are authenticated by hashing the presented key.
+//! Several keys may share a role, so rotation is "add new line, switch
+//! clients, remove old line, reload".
+//!
+//! A key is presented as `Authorization: Bearer <key>` or `X-API-Key: <key>`.
+//! The `/ws` upgrade also takes `?api_key=<key>`, since browsers cannot set
+//! headers on it; other routes ignore the query string so keys stay out of
+//! proxy and access logs.
+//!
+//! With no keys configured every route is closed. `AUTH_DISABLED=1` opens
+//! the read routes to anonymous callers (local dev); operator routes always
+//! need a key.
+
+use crate::errors::{EngineError, EngineResult};
+use crate::state::AppState;
+use axum::extract::{Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Json, Response};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// GET endpoints and the WebSocket feed
+    Read,
+    /// Everything `Read` can do plus /api/control/*
+    Operator,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Operator => write!(f, "operator"),
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = EngineError;
+
+    fn from_str(s: &str) -> EngineResult<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "read" | "readonly" | "read-only" => Ok(Self::Read),
+            "operator" => Ok(Self::Operator),
+            other => Err(EngineError::Config(format!("unknown API key role: {other}"))),
+        }
+    }
+}
+
+/// A configured API key (hash only).
+#[derive(Debug, Clone)]
+pub struct ApiKey {
+    pub role: Role,
+    pub label: String,
+    hash: [u8; 32],
+}
+
+/// The authenticated caller, attached to the request extensions by the
+/// middleware so handlers can record who did what.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub label: String,
+    pub role: Role,
+}
+
+impl ApiKey {
+    /// Parse one config line: `role:label:sha256hex` (or `role:sha256hex`,
+    /// in which case the label defaults to the role).
+    pub fn parse(line: &str) -> EngineResult<Self> {
+        let parts: Vec<&str> = line.trim().split(':').collect();
+        let (role, label, hex) = match parts.as_slice() {
+            [role, hex] => (*role, *role, *hex),
+            [role, label, hex] => (*role, *label, *hex),
+            _ => {
+                return Err(EngineError::Config(format!(
+                    "API key entry must be role:label:sha256hex, got {line:?}"
+                )))
+            }
+        };
+        Ok(Self {
+            role: role.parse()?,
+            label: label.trim().to_string(),
+            hash: decode_hash(hex.trim())?,
+        })
+    }
+
+    /// The config line for this key (safe to store: contains no secret).
+    pub fn to_config_line(&self) -> String {
+        format!("{}:{}:{}", self.role, self.label, encode_hex(&self.hash))
+    }
+}
+
+/// Parse a list of keys: entries separated by commas or newlines.
+/// Blank lines and `#` comments are ignored.
+pub fn parse_api_keys(src: &str) -> EngineResult<Vec<ApiKey>> {
+    src.split([',', '\n'])
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(ApiKey::parse)
+        .collect()
+}
+
+/// Hex SHA-256 of a raw API key.
+pub fn hash_key(raw: &str) -> String {
+    encode_hex(&Sha256::digest(raw.as_bytes()))
+}
+
+/// Generate a new random key. Returns (raw key, config entry).
+/// The raw key is shown once and must be handed to the client; only the
+/// config entry is stored.
+pub fn generate_key(role: Role, label: &str) -> (String, ApiKey) {
+    let raw = format!(
+        "prk_{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    let key = ApiKey {
+        role,
+        label: label.to_string(),
+        hash: Sha256::digest(raw.as_bytes()).into(),
+    };
+    (raw, key)
+}
+
+/// Look up the key matching `raw`. Every configured hash is compared in
+/// constant time so timing does not reveal which entry matched.
+pub fn authenticate<'a>(keys: &'a [ApiKey], raw: &str) -> Option<&'a ApiKey> {
+    let presented: [u8; 32] = Sha256::digest(raw.as_bytes()).into();
+    let mut found = None;
+    for key in keys {
+        if constant_time_eq(&key.hash, &presented) {
+            found = Some(key);
+        }
+    }
+    found
+}
+
+/// Middleware for read-only routes.
+pub async fn require_read(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
+    require_role(&state, Role::Read, req, next).await
+}
+
+/// Middleware for /api/control/*.
+pub async fn require_operator(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    require_role(&state, Role::Operator, req, next).await
+}
+
+async fn require_role(state: &AppState, required: Role, mut req: Request, next: Next) -> Response {
+    let presented = presented_key(&req);
+    let principal = {
+        let keys = state.api_keys.read().unwrap_or_else(|e| e.into_inner());
+        match resolve(&keys, state.config.auth_disabled, required, presented.as_deref()) {
+            Ok(principal) => principal,
+            Err((status, msg)) => {
+                if presented.is_some() {
+                    tracing::warn!(path = %req.uri().path(), reason = msg, "rejected API request");
+                }
+                return reject(status, msg);
+            }
+        }
+    };
+
+    req.extensions_mut().insert(principal);
+    next.run(req).await
+}
+
+/// Who the caller is, or the status and message to reject them with.
+fn resolve(
+    keys: &[ApiKey],
+    auth_disabled: bool,
+    required: Role,
+    presented: Option<&str>,
+) -> Result<Principal, (StatusCode, &'static str)> {
+    let anonymous = auth_disabled && required == Role::Read;
+    let Some(raw) = presented else {
+        if anonymous {
+            return Ok(Principal { label: "anonymous".into(), role: Role::Read });
+        }
+        if keys.is_empty() {
+            return Err((StatusCode::FORBIDDEN, "access disabled: no API keys configured"));
+        }
+        return Err((StatusCode::UNAUTHORIZED, "missing API key"));
+    };
+    match authenticate(keys, raw) {
+        Some(key) if key.role >= required => Ok(Principal { label: key.label.clone(), role: key.role }),
+        Some(_) => Err((StatusCode::FORBIDDEN, "insufficient role")),
+        None => Err((StatusCode::UNAUTHORIZED, "invalid API key")),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct KeyQuery {
+    api_key: Option<String>,
+}
+
+fn presented_key(req: &Request) -> Option<String> {
+    let headers = req.headers();
+    if let Some(bearer) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return Some(bearer.trim().to_string());
+    }
+    if let Some(key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) {
+        return Some(key.trim().to_string());
+    }
+    if req.uri().path() != "/ws" {
+        return None;
+    }
+    Query::<KeyQuery>::try_from_uri(req.uri()).ok()?.0.api_key
+}
+
+fn reject(status: StatusCode, msg: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": msg }))).into_response()
+}
+
+#[inline]
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_hash(hex: &str) -> EngineResult<[u8; 32]> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(EngineError::Config(format!(
+            "API key hash must be 64 hex chars (sha256), got {} chars",
+            hex.len()
+        )));
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|e| EngineError::Config(format!("API key hash: {e}")))?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_and_authenticate() {
+        let (raw, key) = generate_key(Role::Operator, "alice");
+        let parsed = ApiKey::parse(&key.to_config_line()).unwrap();
+        assert_eq!(parsed.role, Role::Operator);
+        assert_eq!(parsed.label, "alice");
+
+        let keys = vec![parsed];
+        assert!(authenticate(&keys, &raw).is_some());
+        assert!(authenticate(&keys, "prk_wrong").is_none());
+    }
+
+    #[test]
+    fn test_parse_list_and_default_label() {
+        let h = hash_key("secret");
+        let src = format!("# dashboard\nread:{h}\n\noperator:ops:{h}, read:ci:{h}");
+        let keys = parse_api_keys(&src).unwrap();
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[0].label, "read");
+        assert_eq!(keys[1].role, Role::Operator);
+        assert_eq!(keys[2].label, "ci");
+    }
+
+    #[test]
+    fn test_rejects_bad_entries() {
+        assert!(ApiKey::parse("admin:deadbeef").is_err());
+        assert!(ApiKey::parse(&format!("read:{}", "z".repeat(64))).is_err());
+        assert!(ApiKey::parse("read").is_err());
+    }
+
+    #[test]
+    fn test_no_keys_fails_closed() {
+        assert_eq!(resolve(&[], false, Role::Read, None).unwrap_err().0, StatusCode::FORBIDDEN);
+        assert_eq!(resolve(&[], false, Role::Read, Some("prk_x")).unwrap_err().0, StatusCode::UNAUTHORIZED);
+
+        // The opt-out opens read routes only
+        assert_eq!(resolve(&[], true, Role::Read, None).unwrap().label, "anonymous");
+        assert_eq!(resolve(&[], true, Role::Operator, None).unwrap_err().0, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_resolve_with_keys() {
+        let (read_raw, read) = generate_key(Role::Read, "dash");
+        let (op_raw, op) = generate_key(Role::Operator, "ops");
+        let keys = [read, op];
+
+        assert_eq!(resolve(&keys, false, Role::Read, None).unwrap_err().0, StatusCode::UNAUTHORIZED);
+        assert_eq!(resolve(&keys, false, Role::Read, Some(&read_raw)).unwrap().label, "dash");
+        assert_eq!(resolve(&keys, false, Role::Operator, Some(&read_raw)).unwrap_err().0, StatusCode::FORBIDDEN);
+        assert_eq!(resolve(&keys, false, Role::Operator, Some(&op_raw)).unwrap().role, Role::Operator);
+        // A bad key is refused even where anonymous reads are allowed
+        assert_eq!(resolve(&keys, true, Role::Read, Some("prk_x")).unwrap_err().0, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_query_key_only_on_ws() {
+        let req = |uri: &str| Request::builder().uri(uri).body(axum::body::Body::empty()).unwrap();
+        assert_eq!(presented_key(&req("/ws?api_key=prk%5Fabc")).as_deref(), Some("prk_abc"));
+        assert_eq!(presented_key(&req("/api/state?api_key=prk_abc")), None);
+
+        let with_header = Request::builder()
+            .uri("/api/state")
+            .header("x-api-key", "prk_hdr")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        assert_eq!(presented_key(&with_header).as_deref(), Some("prk_hdr"));
+    }
+
+    #[test]
+    fn test_operator_outranks_read() {
+        assert!(Role::Operator >= Role::Read);
+        assert!(Role::Read < Role::Operator);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::config;
+use crate::db;
+use crate::errors::{EngineError, EngineResult};
+use crate::server::auth::{Principal, Role};
+use crate::state::{AppState, DbCommand, EngineEvent};
+use axum::extract::{Extension, Query, State};
+use axum::http::StatusCode;
+use axum::response::Json;
+use std::sync::Arc;
+
+// All handlers here sit behind `auth::require_operator`, which attaches the
+// authenticated `Principal` used as the audit actor.
+
+/// Body for every POST /api/control/* endpoint. All fields optional;
+/// `model` is only meaningful for pause/resume.
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ControlRequest {
+    pub model: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct AuditQuery {
+    pub limit: Option<usize>,
+}
+
+type ControlResponse = (StatusCode, Json<serde_json::Value>);
+
+/// Static description of a control action: its audit name and what the
+/// request body must / may contain.
+struct Action {
+    name: &'static str,
+    model_scoped: bool,
+    reason_required: bool,
+}
+
+const PAUSE: Action = Action { name: "pause", model_scoped: true, reason_required: false };
+const RESUME: Action = Action { name: "resume", model_scoped: true, reason_required: false };
+const FLATTEN: Action = Action { name: "flatten", model_scoped: false, reason_required: false };
+const HALT: Action = Action { name: "halt", model_scoped: false, reason_required: true };
+const UNHALT: Action = Action { name: "unhalt", model_scoped: false, reason_required: false };
+
+/// POST /api/control/pause -- stop new entries (globally or for `model`)
+pub async fn pause(
+    State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
+    body: Option<Json<ControlRequest>>,
+) -> ControlResponse {
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    dispatch(&state, &principal, PAUSE, req, |model, reason| {
+        EngineEvent::Pause { model, reason }
+    })
+    .await
+}
+
+/// POST /api/control/resume -- re-enable entries (globally or for `model`)
+pub async fn resume(
+    State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
+    body: Option<Json<ControlRequest>>,
+) -> ControlResponse {
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    dispatch(&state, &principal, RESUME, req, |model, reason| {
+        EngineEvent::Resume { model, reason }
+    })
+    .await
+}
+
+/// POST /api/control/flatten -- close all open positions and pause entries
+pub async fn flatten(
+    State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
+    body: Option<Json<ControlRequest>>,
+) -> ControlResponse {
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    dispatch(&state, &principal, FLATTEN, req, |_, reason| {
+        EngineEvent::Flatten { reason }
+    })
+    .await
+}
+
+/// POST /api/control/halt -- halt the engine; `reason` is required
+pub async fn halt(
+    State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
+    body: Option<Json<ControlRequest>>,
+) -> ControlResponse {
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    dispatch(&state, &principal, HALT, req, |_, reason| {
+        EngineEvent::Halt { reason }
+    })
+    .await
+}
+
+/// POST /api/control/unhalt -- leave Halted; the engine re-syncs before trading
+pub async fn unhalt(
+    State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
+    body: Option<Json<ControlRequest>>,
+) -> ControlResponse {
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    dispatch(&state, &principal, UNHALT, req, |_, reason| {
+        EngineEvent::Unhalt { reason }
+    })
+    .await
+}
+
+/// POST /api/control/reload-keys -- re-read API_KEYS + API_KEYS_FILE.
+/// Used to rotate keys without a restart.
+pub async fn reload_keys(
+    State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
+) -> ControlResponse {
+    let keys = match config::load_api_keys(state.config.api_keys_file.as_deref()) {
+        Ok(keys) => keys,
+        Err(e) => return error(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+    if !keys.iter().any(|k| k.role == Role::Operator) {
+        // Refuse a reload that would lock every operator out
+        return error(StatusCode::BAD_REQUEST, "refusing to load a key set with no operator key");
+    }
+
+    let count = keys.len();
+    *state.api_keys.write().unwrap_or_else(|e| e.into_inner()) = keys;
+
+    let reason = format!("{count} keys loaded");
+    tracing::warn!(actor = %principal.label, reason = %reason, "API keys reloaded");
+    let _ = state
+        .db_tx
+        .send(DbCommand::InsertOperatorAudit {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            actor: principal.label,
+            action: "reload_keys".to_string(),
+            model_name: None,
+            reason: reason.clone(),
+        })
+        .await;
+
+    (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "keys": count })))
+}
+
+/// POST /api/control/reload-config -- re-read the active profile from the
+/// config file and swap its strategy/risk/volatility params into the engine.
+pub async fn reload_config(
+    State(state): State<Arc<AppState>>,
+    Extension(principal): Extension<Principal>,
+) -> ControlResponse {
+    match apply_config_reload(&state, &principal.label).await {
+        Ok(summary) => (StatusCode::OK, Json(summary)),
+        Err(EngineError::ChannelClosed(msg)) => error(StatusCode::SERVICE_UNAVAILABLE, &msg),
+        Err(e) => error(StatusCode::BAD_REQUEST, &e.to_string()),
+    }
+}
+
+/// Shared by the reload-config endpoint and the SIGHUP handler. A file that
+/// fails to parse or validate changes nothing.
+pub async fn apply_config_reload(state: &AppState, actor: &str) -> EngineResult<serde_json::Value> {
+    let reloaded = state.config.reload()?;
+    let changed = state
+        .trading
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .changed_fields(&reloaded.trading);
+
+    if !changed.is_empty() {
+        state
+            .engine_tx
+            .send(EngineEvent::ConfigUpdate(Box::new(reloaded.trading.clone())))
+            .await
+            .map_err(|_| EngineError::ChannelClosed("engine channel closed".into()))?;
+        *state.trading.write().unwrap_or_else(|e| e.into_inner()) = reloaded.trading;
+    }
+
+    let reason = if changed.is_empty() {
+        "no changes".to_string()
+    } else {
+        format!("changed: {}", changed.join(", "))
+    };
+    tracing::warn!(actor = actor, profile = %state.config.profile, reason = %reason, restart_required = ?reloaded.restart_required, "config reloaded");
+    let _ = state
+        .db_tx
+        .send(DbCommand::InsertOperatorAudit {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            actor: actor.to_string(),
+            action: "reload_config".to_string(),
+            model_name: None,
+            reason,
+        })
+        .await;
+
+    Ok(serde_json::json!({
+        "status": "ok",
+        "profile": state.config.profile,
+        "changed": changed,
+        "restart_required": reloaded.restart_required,
+    }))
+}
+
+/// GET /api/control/audit -- most recent operator actions
+pub async fn get_audit(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AuditQuery>,
+) -> ControlResponse {
+    let limit = params.limit.unwrap_or(100).min(1000);
+    match state.db.read(move |conn| db::get_operator_audit(conn, limit)).await {
+        Ok(rows) => (StatusCode::OK, Json(serde_json::json!({ "audit": rows }))),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+/// Shared path for all control actions: validate, forward to the engine,
+/// then record the action in the audit table.
+async fn dispatch(
+    state: &Arc<AppState>,
+    principal: &Principal,
+    action: Action,
+    req: ControlRequest,
+    make_event: impl FnOnce(Option<String>, String) -> EngineEvent,
+) -> ControlResponse {
+    let actor = principal.label.as_str();
+    let name = action.name;
+
+    if let Some(model) = req.model.as_deref() {
+        if !action.model_scoped {
+            return error(StatusCode::BAD_REQUEST, &format!("{name} is global and does not take a model"));
+        }
+        let known = state.snapshot_rx.borrow().models().any(|m| m.name == model);
+        if !known {
+            return error(StatusCode::NOT_FOUND, &format!("unknown model: {model}"));
+        }
+    }
+
+    let reason = match req.reason.filter(|r| !r.trim().is_empty()) {
+        Some(r) => r,
+        None if action.reason_required => {
+            return error(StatusCode::BAD_REQUEST, &format!("{name} requires a reason"));
+        }
+        None => format!("operator {name}"),
+    };
+
+    if state
+        .engine_tx
+        .send(make_event(req.model.clone(), reason.clone()))
+        .await
+        .is_err()
+    {
+        return error(StatusCode::SERVICE_UNAVAILABLE, "engine channel closed");
+    }
+
+    tracing::warn!(actor = actor, role = %principal.role, action = name, model = ?req.model, reason = %reason, "operator action");
+
+    let _ = state
+        .db_tx
+        .send(DbCommand::InsertOperatorAudit {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            actor: actor.to_string(),
+            action: name.to_string(),
+            model_name: req.model.clone(),
+            reason: reason.clone(),
+        })
+        .await;
+
+    (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({
+            "status": "accepted",
+            "action": name,
+            "model": req.model,
+            "reason": reason,
+        })),
+    )
+}
+
+fn error(status: StatusCode, msg: &str) -> ControlResponse {
+    (status, Json(serde_json::json!({ "error": msg })))
+}
@@ -1,2 +1,85 @@
+//! HTTP + WebSocket API and the dashboard's static files.
+
+pub mod auth;
+pub mod control;
 pub mod routes;
 pub mod ws;
+
+use crate::state::AppState;
+use std::sync::Arc;
+
+/// Every route, with read/operator auth applied, serving the dashboard
+/// from `dashboard/dist` for everything else.
+pub fn router(state: Arc<AppState>, cors_allowed_origins: &[String]) -> axum::Router {
+    // Read role: dashboard data + WebSocket feed
+    let read_routes = axum::Router::new()
+        .route("/api/state", axum::routing::get(routes::get_state))
+        .route("/api/trades", axum::routing::get(routes::get_trades))
+        .route("/api/trades/export", axum::routing::get(routes::export_trades))
+        .route("/api/markets", axum::routing::get(routes::get_markets))
+        .route("/api/analytics", axum::routing::get(routes::get_analytics))
+        .route("/api/daily", axum::routing::get(routes::get_daily))
+        .route("/api/decisions", axum::routing::get(routes::get_decisions))
+        .route("/api/pnl", axum::routing::get(routes::get_pnl))
+        .route("/api/metrics", axum::routing::get(routes::get_metrics))
+        .route("/api/risk", axum::routing::get(routes::get_risk))
+        .route("/api/counters", axum::routing::get(routes::get_counters))
+        .route("/metrics", axum::routing::get(routes::get_prometheus))
+        .route("/ws", axum::routing::get(ws::ws_handler))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_read,
+        ));
+
+    // Operator role: engine control
+    let operator_routes = axum::Router::new()
+        .route("/api/control/pause", axum::routing::post(control::pause))
+        .route("/api/control/resume", axum::routing::post(control::resume))
+        .route("/api/control/flatten", axum::routing::post(control::flatten))
+        .route("/api/control/halt", axum::routing::post(control::halt))
+        .route("/api/control/unhalt", axum::routing::post(control::unhalt))
+        .route("/api/control/reload-keys", axum::routing::post(control::reload_keys))
+        .route("/api/control/reload-config", axum::routing::post(control::reload_config))
+        .route("/api/control/audit", axum::routing::get(control::get_audit))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_operator,
+        ));
+
+    axum::Router::new()
+        .route("/api/health", axum::routing::get(routes::get_health))
+        .merge(read_routes)
+        .merge(operator_routes)
+        .fallback_service(
+            tower_http::services::ServeDir::new("dashboard/dist")
+                .fallback(tower_http::services::ServeFile::new("dashboard/dist/index.html")),
+        )
+        .layer(cors_layer(cors_allowed_origins))
+        .with_state(state)
+}
+
+/// CORS from config: no origins = same-origin only (no CORS headers),
+/// "*" = any origin, otherwise the explicit allow-list.
+fn cors_layer(origins: &[String]) -> tower_http::cors::CorsLayer {
+    use axum::http::{header, HeaderName, HeaderValue, Method};
+    use tower_http::cors::{AllowOrigin, CorsLayer};
+
+    let layer = CorsLayer::new()
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE, HeaderName::from_static("x-api-key")]);
+
+    if origins.iter().any(|o| o == "*") {
+        return layer.allow_origin(AllowOrigin::any());
+    }
+    let list: Vec<HeaderValue> = origins
+        .iter()
+        .filter_map(|o| match HeaderValue::from_str(o) {
+            Ok(v) => Some(v),
+            Err(_) => {
+                tracing::warn!(origin = %o, "ignoring invalid CORS origin");
+                None
+            }
+        })
+        .collect();
+    layer.allow_origin(AllowOrigin::list(list))
+}
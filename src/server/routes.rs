@@ -1,22 +1,102 @@
+use crate::asset::Asset;
 use crate::db;
-use crate::paper::tracker;
+use crate::db::decisions::{self, DecisionFilter};
+use crate::db::trades::{self as trade_history, TradeFilter};
+use crate::errors::EngineError;
+use crate::kalshi::limits::BreakerState;
+use crate::metrics;
+use crate::paper::{analytics, tracker};
 use crate::state::{AppState, EngineSnapshot};
+use crate::supervisor;
+use axum::body::{Body, Bytes};
 use axum::extract::{Query, State};
-use axum::response::Json;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
 use std::sync::Arc;
 
+/// Rows per DB round trip while streaming an export.
+const EXPORT_PAGE_SIZE: usize = 500;
+
+/// Paging for GET /api/trades; filters are a separate `TradeFilter`.
 #[derive(serde::Deserialize)]
 pub struct TradesQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ExportQuery {
+    /// `csv` (default) or `ndjson`
+    pub format: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct AnalyticsQuery {
     pub model: Option<String>,
+    /// `btc`, `eth` or `sol`; all assets when unset
+    pub asset: Option<Asset>,
+    /// Close-time lower bound, inclusive (RFC 3339 or `YYYY-MM-DD`)
+    pub since: Option<String>,
+    /// Close-time upper bound, exclusive
+    pub until: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct DecisionsQuery {
+    pub limit: Option<usize>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct DailyQuery {
+    pub model: Option<String>,
+    /// `btc`, `eth` or `sol`; all assets when unset
+    pub asset: Option<Asset>,
+    /// Rows, not days (one row per model per day)
+    pub limit: Option<usize>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct MarketsQuery {
+    pub settled: Option<bool>,
     pub limit: Option<usize>,
 }
 
 #[derive(serde::Deserialize)]
 pub struct PnlQuery {
     pub model: String,
+    /// `btc` (default), `eth` or `sol`
+    #[serde(default)]
+    pub asset: Asset,
     pub limit: Option<usize>,
 }
 
+/// GET /api/health -- unauthenticated liveness probe (Railway healthcheck)
+pub async fn get_health(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let (engine_state, kalshi_api) = {
+        let snapshot = state.snapshot_rx.borrow();
+        (snapshot.engine_state, snapshot.kalshi_api.clone())
+    };
+    let tasks = state.tasks.read().unwrap_or_else(|e| e.into_inner()).clone();
+    let mut status = supervisor::overall_status(&tasks);
+    if status == "ok" && kalshi_api.breaker != BreakerState::Closed {
+        status = "degraded";
+    }
+    // Only a dead critical task fails the check; a restarting feed or a
+    // Kalshi outage is not worth a redeploy
+    let code = if status == "failed" { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK };
+    (
+        code,
+        Json(serde_json::json!({
+            "status": status,
+            "engine_state": engine_state,
+            "kalshi_api": kalshi_api,
+            "tasks": tasks,
+        })),
+    )
+}
+
 /// GET /api/state -- current engine snapshot (from watch channel, no lock)
 pub async fn get_state(
     State(state): State<Arc<AppState>>,
@@ -25,27 +105,204 @@ pub async fn get_state(
     Json(snapshot)
 }
 
-/// GET /api/trades -- recent trades from DB (cold path)
+/// GET /api/trades -- trade history with fills, newest first (cold path).
+///
+/// Filters: `model`, `market`, `side`, `outcome` (win/loss/exit/open),
+/// `exit_reason`, `since`/`until` (entry time) and `min_abs_pnl`. Pass the
+/// returned `next_cursor` back as `cursor` for the next page.
 pub async fn get_trades(
     State(state): State<Arc<AppState>>,
+    Query(filter): Query<TradeFilter>,
     Query(params): Query<TradesQuery>,
-) -> Json<serde_json::Value> {
-    let limit = params.limit.unwrap_or(50).min(200);
-    match db::get_recent_trades(&state.db, params.model.as_deref(), limit) {
-        Ok(trades) => Json(serde_json::json!({ "trades": trades })),
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
+) -> (StatusCode, Json<serde_json::Value>) {
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let cursor = params.cursor;
+    match state.db.read(move |conn| trade_history::query_trades(conn, &filter, cursor.as_deref(), limit, true)).await {
+        Ok(page) => (StatusCode::OK, Json(serde_json::json!(page))),
+        Err(e) => query_error(e),
     }
 }
 
+/// GET /api/trades/export -- the full filtered history as CSV (default) or
+/// NDJSON with fills (`format=ndjson`). Streamed page by page, so neither
+/// the response nor a pooled connection ever holds the whole history.
+pub async fn export_trades(
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<TradeFilter>,
+    Query(params): Query<ExportQuery>,
+) -> Response {
+    let ndjson = match params.format.as_deref() {
+        None | Some("csv") => false,
+        Some("ndjson") => true,
+        Some(other) => {
+            return query_error(EngineError::Parse(format!("format must be csv or ndjson, got {other:?}")))
+                .into_response()
+        }
+    };
+    // Fail bad filters with a 400 before any of the body is sent
+    if let Err(e) = filter.validate() {
+        return query_error(e).into_response();
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<Bytes, std::io::Error>>(4);
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        if !ndjson && tx.blocking_send(Ok(Bytes::from_static(trade_history::CSV_HEADER.as_bytes()))).is_err() {
+            return;
+        }
+        let mut cursor: Option<String> = None;
+        loop {
+            // A connection per page, so a slow client never pins one
+            let page = match db
+                .get()
+                .and_then(|conn| trade_history::query_trades(&conn, &filter, cursor.as_deref(), EXPORT_PAGE_SIZE, ndjson))
+            {
+                Ok(page) => page,
+                Err(e) => {
+                    // Headers are already out; abort the body so the client
+                    // sees a truncated download rather than a short file
+                    tracing::error!("trade export failed: {e}");
+                    let _ = tx.blocking_send(Err(std::io::Error::other(e.to_string())));
+                    return;
+                }
+            };
+            let mut chunk = String::new();
+            for trade in &page.trades {
+                if ndjson {
+                    chunk.push_str(&serde_json::to_string(trade).unwrap_or_default());
+                    chunk.push('\n');
+                } else {
+                    chunk.push_str(&trade_history::csv_row(trade));
+                }
+            }
+            // A send error means the client went away
+            if tx.blocking_send(Ok(Bytes::from(chunk))).is_err() {
+                return;
+            }
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return,
+            }
+        }
+    });
+
+    let body = Body::from_stream(futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }));
+    let (content_type, filename) = if ndjson {
+        ("application/x-ndjson", "trades.ndjson")
+    } else {
+        ("text/csv; charset=utf-8", "trades.csv")
+    };
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{filename}\"")),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// GET /api/markets -- tracked markets with results and per-market trade
+/// totals (`settled=true|false` to filter)
+pub async fn get_markets(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<MarketsQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+    let settled = params.settled;
+    match state.db.read(move |conn| db::get_markets(conn, settled, limit)).await {
+        Ok(markets) => (StatusCode::OK, Json(serde_json::json!({ "markets": markets }))),
+        Err(e) => query_error(e),
+    }
+}
+
+/// GET /api/analytics -- performance over closed trades in a date range:
+/// per-model ratios and equity/drawdown curves, P/L attribution by exit
+/// reason, and hit rate by entry TTL, entry EV and vol regime. `model` and
+/// `asset` narrow it to one model or one asset's pipeline.
+pub async fn get_analytics(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AnalyticsQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let (model, asset, since, until) = (params.model.clone(), params.asset, params.since.clone(), params.until.clone());
+    let trades = match state
+        .db
+        .read(move |conn| trade_history::closed_trades(conn, model.as_deref(), asset, since.as_deref(), until.as_deref()))
+        .await
+    {
+        Ok(trades) => trades,
+        Err(e) => return query_error(e),
+    };
+    let period = analytics::period_days(params.since.as_deref(), params.until.as_deref());
+    let report = analytics::compute_report(&trades, period);
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "asset": params.asset,
+            "since": params.since,
+            "until": params.until,
+            "report": report,
+        })),
+    )
+}
+
+/// GET /api/daily -- end-of-day summaries per model and asset, most recent
+/// first (`model` / `asset` to filter)
+pub async fn get_daily(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DailyQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let limit = params.limit.unwrap_or(90).clamp(1, 3000);
+    let (model, asset) = (params.model, params.asset);
+    match state.db.read(move |conn| db::get_daily_summaries(conn, model.as_deref(), asset, limit)).await {
+        Ok(days) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "trading_day": state.config.trading_calendar.day_of(state.clock.now()).to_string(),
+                "calendar": state.config.trading_calendar.to_string(),
+                "days": days,
+            })),
+        ),
+        Err(e) => query_error(e),
+    }
+}
+
+/// GET /api/decisions -- decision journal rows, newest first, plus a count
+/// per reason code (e.g. `?model=Student-t&at=2026-10-18T14:03:00Z`)
+pub async fn get_decisions(
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<DecisionFilter>,
+    Query(params): Query<DecisionsQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let limit = params.limit.unwrap_or(200).clamp(1, 2000);
+    match state.db.read(move |conn| decisions::query_decisions(conn, &filter, limit)).await {
+        Ok(page) => (StatusCode::OK, Json(serde_json::json!(page))),
+        Err(e) => query_error(e),
+    }
+}
+
+/// Bad filters / cursors are the caller's fault; anything else is ours.
+fn query_error(e: EngineError) -> (StatusCode, Json<serde_json::Value>) {
+    let status = match e {
+        EngineError::Parse(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(serde_json::json!({ "error": e.to_string() })))
+}
+
 /// GET /api/pnl -- P/L time series from DB (cold path)
 pub async fn get_pnl(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PnlQuery>,
 ) -> Json<serde_json::Value> {
     let limit = params.limit.unwrap_or(500).min(5000);
-    match db::get_model_pnl_series(&state.db, &params.model, limit) {
+    let (model, asset) = (params.model.clone(), params.asset);
+    match state.db.read(move |conn| db::get_model_pnl_series(conn, &model, asset, limit)).await {
         Ok(series) => Json(serde_json::json!({
             "model": params.model,
+            "asset": params.asset,
             "series": series.iter().map(|(t, v)| serde_json::json!({"t": t, "pnl": v})).collect::<Vec<_>>()
         })),
         Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
@@ -57,20 +314,29 @@ pub async fn get_metrics(
     State(state): State<Arc<AppState>>,
 ) -> Json<serde_json::Value> {
     let snapshot = state.snapshot_rx.borrow().clone();
-    let metrics = tracker::compute_aggregate(&snapshot.models);
+    let metrics = tracker::compute_aggregate(snapshot.models());
     Json(serde_json::json!(metrics))
 }
 
-/// GET /api/risk -- risk states from DB
+/// GET /api/risk -- risk states from DB plus the latest VaR/ES report
 pub async fn get_risk(
     State(state): State<Arc<AppState>>,
 ) -> Json<serde_json::Value> {
-    match db::get_risk_states(&state.db) {
-        Ok(states) => Json(serde_json::json!({ "risk": states })),
+    let tail = state.tail_risk.read().unwrap_or_else(|e| e.into_inner()).clone();
+    match state.db.read(db::get_risk_states).await {
+        Ok(states) => Json(serde_json::json!({ "risk": states, "tail": tail })),
         Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
     }
 }
 
+/// GET /metrics -- Prometheus text exposition format
+pub async fn get_prometheus(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        metrics::render(&state),
+    )
+}
+
 /// GET /api/counters -- performance counters (lock-free reads)
 pub async fn get_counters(
     State(state): State<Arc<AppState>>,
@@ -78,6 +344,7 @@ pub async fn get_counters(
     use portable_atomic::Ordering::Relaxed;
     Json(serde_json::json!({
         "ticks_processed": state.counters.ticks_processed.load(Relaxed),
+        "events_processed": state.counters.events_processed.load(Relaxed),
         "prices_received": state.counters.prices_received.load(Relaxed),
         "decisions_made": state.counters.decisions_made.load(Relaxed),
         "trades_placed": state.counters.trades_placed.load(Relaxed),
@@ -1,5 +1,5 @@
-use crate::state::{AppState, WsMessage};
-use axum::extract::ws::{Message, WebSocket};
+use crate::state::AppState;
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket};
 use axum::extract::{State, WebSocketUpgrade};
 use axum::response::Response;
 use futures_util::{SinkExt, StreamExt};
@@ -16,6 +16,7 @@ pub async fn ws_handler(
 async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
     let (mut sender, mut receiver) = socket.split();
     let mut rx = state.ws_tx.subscribe();
+    let mut shutdown = state.shutdown_tx.subscribe();
 
     // Send initial snapshot
     {
@@ -28,16 +29,32 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
         }
     }
 
-    // Forward broadcast messages to this client
+    // Forward broadcast messages to this client until it goes away or the
+    // server shuts down (then send a proper close frame so the dashboard
+    // reconnects instead of seeing an abrupt reset)
     let send_task = tokio::spawn(async move {
-        while let Ok(ws_msg) = rx.recv().await {
-            match serde_json::to_string(&ws_msg) {
-                Ok(json) => {
-                    if sender.send(Message::Text(json.into())).await.is_err() {
-                        break;
+        loop {
+            tokio::select! {
+                msg = rx.recv() => {
+                    let Ok(ws_msg) = msg else { break };
+                    match serde_json::to_string(&ws_msg) {
+                        Ok(json) => {
+                            if sender.send(Message::Text(json.into())).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(_) => continue,
                     }
                 }
-                Err(_) => continue,
+                _ = async { let _ = shutdown.wait_for(|s| *s).await; } => {
+                    let _ = sender
+                        .send(Message::Close(Some(CloseFrame {
+                            code: close_code::AWAY,
+                            reason: "server shutting down".into(),
+                        })))
+                        .await;
+                    break;
+                }
             }
         }
     });
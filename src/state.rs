@@ -1,7 +1,15 @@
-use crate::db::DbPool;
-use crate::config::AppConfig;
+use crate::asset::Asset;
+use crate::clock::Clock;
+use crate::db::{DbPool, DbSender};
+use crate::kalshi::exchange::ExchangeInfo;
+use crate::kalshi::limits::ApiHealth;
+use crate::config::{AppConfig, TradingParams};
+use crate::server::auth::ApiKey;
+use crate::supervisor::TaskHealth;
+use crate::metrics::Metrics;
+use crate::risk::var::TailReport;
 use smallvec::SmallVec;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::{broadcast, mpsc, watch};
 use portable_atomic::{AtomicU64, Ordering};
@@ -9,11 +17,18 @@ use portable_atomic::{AtomicU64, Ordering};
 // ── Engine State Machine ──
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
 pub enum EngineState {
     Connecting,
     Syncing,
     Trading,
+    /// Operator pause: open positions are still managed, no new entries
+    Paused,
+    /// Kalshi is outside trading hours or has paused trading; managed like
+    /// `Paused` until it reopens
+    ExchangeClosed,
+    /// Kalshi is down for maintenance; managed like `Paused`
+    Maintenance,
     Halted,
 }
 
@@ -23,6 +38,9 @@ impl std::fmt::Display for EngineState {
             Self::Connecting => write!(f, "connecting"),
             Self::Syncing => write!(f, "syncing"),
             Self::Trading => write!(f, "trading"),
+            Self::Paused => write!(f, "paused"),
+            Self::ExchangeClosed => write!(f, "exchange_closed"),
+            Self::Maintenance => write!(f, "maintenance"),
             Self::Halted => write!(f, "halted"),
         }
     }
@@ -46,18 +64,92 @@ pub struct PaperOrder {
 #[derive(Debug, Clone)]
 pub enum Decision {
     PlacePaperTrade(PaperOrder),
+    /// `reason` is a short code, e.g. `no_edge`, `holding`, `risk_blocked`
     NoAction { reason: &'static str },
 }
 
+/// What the risk checks said about an order. `reason` is empty when allowed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RiskVerdict {
+    /// "allowed", "scaled" or "blocked"
+    pub outcome: &'static str,
+    pub reason: &'static str,
+}
+
+/// One model's decision on one tick, as written to the decision journal.
+#[derive(Debug, Clone)]
+pub struct DecisionRecord {
+    pub timestamp: String,
+    pub model_name: &'static str,
+    pub asset: Asset,
+    pub market_ticker: String,
+    /// Price of the underlying (the column predates other assets)
+    pub btc_price: f64,
+    pub ttl_seconds: f64,
+    /// Model output before calibration
+    pub raw_probability: f64,
+    pub probability: f64,
+    pub ev_yes: f64,
+    pub ev_no: f64,
+    pub kelly_contracts: f64,
+    /// `None` when no order reached the risk checks
+    pub risk: Option<RiskVerdict>,
+    /// First position exit this tick, if any
+    pub exit_reason: Option<&'static str>,
+    pub decision: Decision,
+}
+
 // ── Messages INTO the engine (bounded channels) ──
 
 #[derive(Debug, Clone)]
 pub enum EngineEvent {
-    BtcPrice { price: f64, timestamp_ms: i64 },
+    Price { asset: Asset, price: f64, timestamp_ms: i64 },
     MarketUpdate(Box<ActiveMarket>),
-    MarketSettled { ticker: String, result: String },
+    MarketSettled { asset: Asset, ticker: String, result: String },
+    /// The Kalshi client's circuit breaker changed state
+    KalshiApiHealth(Box<ApiHealth>),
+    /// Exchange phase, schedule or announcements changed
+    Exchange(Box<ExchangeInfo>),
     Tick,
+    /// Apply the shutdown position policy, publish a final snapshot and
+    /// stop the engine loop
     Shutdown,
+
+    // ── Operator control (sent by the authenticated /api/control endpoints) ──
+    /// Stop new entries globally (`model: None`) or for a single model
+    Pause { model: Option<String>, reason: String },
+    /// Undo a `Pause` for the same scope
+    Resume { model: Option<String>, reason: String },
+    /// Close every open position at the current bid and pause entries
+    Flatten { reason: String },
+    /// Stop all trading activity until an explicit `Unhalt`
+    Halt { reason: String },
+    /// Leave `Halted` and re-sync before trading again
+    Unhalt { reason: String },
+    /// Swap in strategy, risk and volatility params re-read from the config
+    /// file (SIGHUP or POST /api/control/reload-config)
+    ConfigUpdate(Box<TradingParams>),
+}
+
+impl EngineEvent {
+    /// Short name used as a metrics label.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Price { .. } => "price",
+            Self::MarketUpdate(_) => "market_update",
+            Self::MarketSettled { .. } => "market_settled",
+            Self::KalshiApiHealth(_) => "kalshi_api_health",
+            Self::Exchange(_) => "exchange",
+            Self::Tick => "tick",
+            Self::Shutdown => "shutdown",
+            Self::Pause { .. } => "pause",
+            Self::Resume { .. } => "resume",
+            Self::Flatten { .. } => "flatten",
+            Self::Halt { .. } => "halt",
+            Self::Unhalt { .. } => "unhalt",
+            Self::ConfigUpdate(_) => "config_update",
+        }
+    }
 }
 
 // ── Messages OUT of the engine ──
@@ -65,25 +157,30 @@ pub enum EngineEvent {
 #[derive(Debug, Clone, serde::Serialize)]
 #[serde(tag = "type")]
 pub enum WsMessage {
-    #[serde(rename = "btc_price")]
-    BtcPrice { price: f64, timestamp: String },
+    #[serde(rename = "price")]
+    Price { asset: Asset, price: f64, timestamp: String },
 
     #[serde(rename = "market_state")]
     MarketState {
+        asset: Asset,
+        horizon: String,
         ticker: String,
         strike: Option<f64>,
         ttl_seconds: f64,
         yes_bid: Option<String>,
         yes_ask: Option<String>,
         status: String,
+        depth: MarketDepth,
+        /// Liquidity gate the market fails, if any (see `risk::liquidity`)
+        entry_gate: Option<&'static str>,
     },
 
     #[serde(rename = "model_update")]
     ModelUpdate {
         model: String,
-        probability: f64,
-        ev: f64,
-        kelly_size: f64,
+        asset: Asset,
+        /// Latest signal per expiry horizon
+        signals: BTreeMap<String, Signal>,
         cumulative_pnl: f64,
         unrealized_pnl: f64,
         total_pnl: f64,
@@ -95,11 +192,16 @@ pub enum WsMessage {
         daily_pnl: f64,
         current_exposure: f64,
         open_position_count: usize,
+        paused: bool,
+        last_risk_decision: Option<RiskDecision>,
+        /// Realized plus unrealized P/L per expiry horizon
+        pnl_by_horizon: BTreeMap<String, f64>,
     },
 
     #[serde(rename = "new_trade")]
     NewTrade {
         model: String,
+        asset: Asset,
         side: String,
         action: String,
         price: f64,
@@ -111,6 +213,7 @@ pub enum WsMessage {
     #[serde(rename = "trade_exited")]
     TradeExited {
         model: String,
+        asset: Asset,
         trade_id: String,
         side: String,
         entry_price: f64,
@@ -124,6 +227,7 @@ pub enum WsMessage {
     #[serde(rename = "trade_settled")]
     TradeSettled {
         model: String,
+        asset: Asset,
         trade_id: String,
         outcome: String,
         pnl: f64,
@@ -133,6 +237,7 @@ pub enum WsMessage {
     #[serde(rename = "metrics_update")]
     MetricsUpdate {
         model: String,
+        asset: Asset,
         sharpe: f64,
         max_drawdown: f64,
         win_rate: f64,
@@ -146,13 +251,48 @@ pub enum WsMessage {
         state: String,
         reason: String,
     },
+
+    /// Kalshi API circuit breaker state change
+    #[serde(rename = "kalshi_api")]
+    KalshiApi {
+        breaker: String,
+        consecutive_failures: u32,
+        last_error: Option<String>,
+    },
+
+    /// Exchange phase change (open / closed / maintenance)
+    #[serde(rename = "exchange_status")]
+    ExchangeStatus {
+        phase: String,
+        reason: String,
+        estimated_resume_time: Option<String>,
+    },
+
+    /// A new exchange announcement
+    #[serde(rename = "announcement")]
+    Announcement {
+        /// "info", "warning" or "error"
+        level: String,
+        message: String,
+        delivery_time: String,
+    },
+
+    /// A trading day ended; daily counters have been reset
+    #[serde(rename = "day_rollover")]
+    DayRollover {
+        previous_day: String,
+        trading_day: String,
+        /// (model, asset, realized P/L for `previous_day`)
+        daily_pnl: Vec<(String, Asset, f64)>,
+        timestamp: String,
+    },
 }
 
 // ── DB Commands (sent to writer task via bounded channel) ──
 
 #[derive(Debug)]
 pub enum DbCommand {
-    InsertBtcPrice { timestamp: String, price: f64 },
+    InsertPrice { asset: Asset, timestamp: String, price: f64 },
     InsertMarket {
         ticker: String,
         event_ticker: String,
@@ -162,9 +302,23 @@ pub enum DbCommand {
         close_time: String,
         expiration_time: String,
     },
+    /// Active market quote, recorded for backtest replay
+    InsertMarketQuote {
+        timestamp: String,
+        ticker: String,
+        yes_bid: Option<f64>,
+        yes_ask: Option<f64>,
+        no_bid: Option<f64>,
+        no_ask: Option<f64>,
+        last_price: Option<f64>,
+        status: String,
+    },
     InsertTrade {
         id: String,
         model_name: String,
+        asset: Asset,
+        /// Expiry horizon of the market
+        horizon: String,
         market_ticker: String,
         side: String,
         action: String,
@@ -175,22 +329,41 @@ pub enum DbCommand {
         kelly_fraction: f64,
         fees_estimate: f64,
         entry_time: String,
+        /// Seconds to market close at entry
+        entry_ttl_seconds: f64,
+        /// Volatility regime at entry
+        entry_regime: String,
     },
+    /// Close the rest of a trade at market settlement
     SettleTrade {
         trade_id: String,
         outcome: String,
+        /// 1.0 if the contract paid out, else 0.0
+        settlement_price: f64,
+        contracts: f64,
+        /// Fees booked by this fill (the entry fee on the settled contracts)
+        fees: f64,
         pnl: f64,
         settle_time: String,
     },
+    /// Sell `contracts` of a trade; `closed` when nothing remains
     ExitTrade {
         trade_id: String,
         exit_price: f64,
+        contracts: f64,
+        /// Fee charged on this sale
+        exit_fee: f64,
+        /// Fees booked by this fill: `exit_fee` plus the entry fee on the
+        /// contracts sold
+        fees: f64,
         pnl: f64,
         reason: String,
         exit_time: String,
+        closed: bool,
     },
     InsertSnapshot {
         model_name: String,
+        asset: Asset,
         timestamp: String,
         btc_price: f64,
         market_ticker: Option<String>,
@@ -203,6 +376,7 @@ pub enum DbCommand {
     },
     UpdateRiskState {
         model_name: String,
+        asset: Asset,
         exposure: f64,
         daily_pnl: f64,
         max_drawdown: f64,
@@ -219,12 +393,73 @@ pub enum DbCommand {
         market_ticker: String,
         reply: tokio::sync::oneshot::Sender<Vec<crate::db::TradeRow>>,
     },
+    InsertOperatorAudit {
+        timestamp: String,
+        actor: String,
+        action: String,
+        model_name: Option<String>,
+        reason: String,
+    },
+    /// Replace the saved open positions (shutdown with the `persist` policy)
+    PersistPositions {
+        positions: Vec<(String, OpenPosition)>,
+        timestamp: String,
+    },
+    /// End-of-day row for one model. Trade counts and fees are computed by
+    /// the writer from the trades table over `[day_start, day_end)`.
+    InsertDailySummary {
+        trading_day: String,
+        model_name: String,
+        asset: Asset,
+        day_start: String,
+        day_end: String,
+        realized_pnl: f64,
+        cumulative_pnl: f64,
+        max_drawdown: f64,
+        exposure: f64,
+        open_positions: i64,
+    },
+    /// A batch of decision journal rows, written in one transaction
+    InsertDecisions { records: Vec<DecisionRecord> },
+    /// Drop journal rows older than `before`
+    PruneDecisions { before: String },
+    /// Replies once every command queued before it has been executed
+    Flush {
+        reply: tokio::sync::oneshot::Sender<()>,
+    },
+}
+
+impl DbCommand {
+    /// Short name used as a metrics label.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::InsertPrice { .. } => "insert_price",
+            Self::InsertMarket { .. } => "insert_market",
+            Self::InsertMarketQuote { .. } => "insert_market_quote",
+            Self::InsertTrade { .. } => "insert_trade",
+            Self::SettleTrade { .. } => "settle_trade",
+            Self::ExitTrade { .. } => "exit_trade",
+            Self::InsertSnapshot { .. } => "insert_snapshot",
+            Self::UpdateRiskState { .. } => "update_risk_state",
+            Self::UpdateMarketResult { .. } => "update_market_result",
+            Self::GetPendingTrades { .. } => "get_pending_trades",
+            Self::InsertOperatorAudit { .. } => "insert_operator_audit",
+            Self::PersistPositions { .. } => "persist_positions",
+            Self::InsertDailySummary { .. } => "insert_daily_summary",
+            Self::InsertDecisions { .. } => "insert_decisions",
+            Self::PruneDecisions { .. } => "prune_decisions",
+            Self::Flush { .. } => "flush",
+        }
+    }
 }
 
 // ── Active Market (stack-friendly) ──
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ActiveMarket {
+    pub asset: Asset,
+    /// The expiry horizon the scanner picked this market for
+    pub horizon: String,
     pub ticker: String,
     pub event_ticker: String,
     pub series_ticker: String,
@@ -238,6 +473,27 @@ pub struct ActiveMarket {
     pub expiration_time: String,
     pub status: String,
     pub result: Option<String>,
+    pub depth: MarketDepth,
+}
+
+/// What stands behind a market's quotes, for the liquidity gates. Unset
+/// fields were not fetched (or the request failed).
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct MarketDepth {
+    pub open_interest: Option<f64>,
+    /// Contracts at the best YES bid and at the best NO bid (the YES ask)
+    pub yes_bid_size: Option<f64>,
+    pub no_bid_size: Option<f64>,
+    /// Trades, and contracts traded, in the gates' recent window
+    pub recent_trades: Option<u32>,
+    pub recent_volume: Option<f64>,
+}
+
+impl MarketDepth {
+    /// The thinner side of the top of the book
+    pub fn top_of_book(&self) -> Option<f64> {
+        Some(self.yes_bid_size?.min(self.no_bid_size?))
+    }
 }
 
 // ── Per-Model State ──
@@ -245,9 +501,10 @@ pub struct ActiveMarket {
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ModelState {
     pub name: &'static str,
-    pub probability: f64,
-    pub ev: f64,
-    pub kelly_size: f64,
+    /// Each model runs once per asset
+    pub asset: Asset,
+    /// Latest signal per expiry horizon, from its active market
+    pub signals: BTreeMap<String, Signal>,
     pub cumulative_pnl: f64,
     pub total_trades: i64,
     pub winning_trades: i64,
@@ -270,8 +527,35 @@ pub struct ModelState {
     pub brier_count: i64,
     /// Live unrealized P/L from open positions (mark-to-market)
     pub unrealized_pnl: f64,
+    /// Realized P/L per expiry horizon (`cumulative_pnl` split up)
+    pub realized_by_horizon: BTreeMap<String, f64>,
     /// Open positions for this model (replaces simple trade ID list)
     pub open_positions: SmallVec<[OpenPosition; 4]>,
+    /// Operator pause for this model only (exits still run, no new entries)
+    pub paused: bool,
+    /// Most recent order that a risk limit blocked or scaled down
+    pub last_risk_decision: Option<RiskDecision>,
+}
+
+/// What a model made of one horizon's market on its last tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
+pub struct Signal {
+    pub probability: f64,
+    pub ev: f64,
+    pub kelly_size: f64,
+}
+
+/// An entry or scale-in that did not go through at the size the model asked for.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RiskDecision {
+    /// "buy" or "scale_in"
+    pub action: &'static str,
+    /// "blocked" or "scaled"
+    pub outcome: &'static str,
+    pub reason: &'static str,
+    pub requested_contracts: f64,
+    pub allowed_contracts: f64,
+    pub timestamp: String,
 }
 
 /// A live open paper trade position with full details for MTM + adaptive management.
@@ -279,27 +563,33 @@ pub struct ModelState {
 pub struct OpenPosition {
     pub trade_id: String,
     pub market_ticker: String,
+    /// Expiry horizon the market was traded under
+    pub horizon: String,
+    /// Market strike and close time (for revaluing positions off the active market)
+    pub strike: f64,
+    pub close_time: String,
     pub side: String,
     pub entry_price: f64,
     pub contracts: f64,
     pub model_probability: f64,
     /// Tick counter at entry (for hold-time tracking)
     pub entry_tick: u64,
-    /// BTC price at time of entry (for strike-relative tracking)
+    /// Underlying price at time of entry (for strike-relative tracking)
     pub entry_btc_price: f64,
     /// Highest unrealized P/L seen (for trailing stop)
     pub peak_unrealized: f64,
     /// Which "leg" this is (0 = initial, 1+ = scale-ins)
     pub leg: u32,
+    /// Mark-to-market P/L as of the last tick its market was quoted
+    pub unrealized: f64,
 }
 
 impl ModelState {
-    pub fn new(name: &'static str) -> Self {
+    pub fn new(name: &'static str, asset: Asset) -> Self {
         Self {
             name,
-            probability: 0.0,
-            ev: 0.0,
-            kelly_size: 0.0,
+            asset,
+            signals: BTreeMap::new(),
             cumulative_pnl: 0.0,
             total_trades: 0,
             winning_trades: 0,
@@ -315,7 +605,10 @@ impl ModelState {
             brier_sum: 0.0,
             brier_count: 0,
             unrealized_pnl: 0.0,
+            realized_by_horizon: BTreeMap::new(),
             open_positions: SmallVec::new(),
+            paused: false,
+            last_risk_decision: None,
         }
     }
 
@@ -362,6 +655,22 @@ impl ModelState {
         self.trade_returns.push_back(ret);
     }
 
+    /// Book realized P/L from a position or trade in `horizon`.
+    pub fn realize(&mut self, horizon: &str, pnl: f64) {
+        self.cumulative_pnl += pnl;
+        self.daily_pnl += pnl;
+        *self.realized_by_horizon.entry(horizon.to_string()).or_default() += pnl;
+    }
+
+    /// Realized plus unrealized P/L per horizon.
+    pub fn pnl_by_horizon(&self) -> BTreeMap<String, f64> {
+        let mut pnl = self.realized_by_horizon.clone();
+        for pos in &self.open_positions {
+            *pnl.entry(pos.horizon.clone()).or_default() += pos.unrealized;
+        }
+        pnl
+    }
+
     pub fn update_drawdown(&mut self) {
         if self.cumulative_pnl > self.peak_equity {
             self.peak_equity = self.cumulative_pnl;
@@ -403,6 +712,15 @@ impl std::fmt::Display for VolRegime {
     }
 }
 
+impl VolatilityState {
+    /// `ewma_vol` (per ~2s observation) scaled to annual terms
+    #[inline]
+    pub fn annualized(&self) -> f64 {
+        let obs_per_year: f64 = 365.25 * 24.0 * 3600.0 / 2.0;
+        self.ewma_vol * obs_per_year.sqrt()
+    }
+}
+
 impl Default for VolatilityState {
     fn default() -> Self {
         Self {
@@ -459,26 +777,72 @@ impl ModelParams {
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct EngineSnapshot {
     pub engine_state: EngineState,
-    pub btc_price: f64,
-    pub btc_timestamp: String,
+    /// One entry per configured asset, in config order
+    pub assets: Vec<AssetSnapshot>,
+    pub kalshi_api: ApiHealth,
+    pub exchange: ExchangeInfo,
+}
+
+/// One asset's pipeline: its latest price, a market and volatility
+/// estimate per horizon, and its models.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AssetSnapshot {
+    pub asset: Asset,
+    pub price: f64,
+    pub price_timestamp: String,
+    /// One entry per configured horizon, in config order
+    pub horizons: Vec<HorizonSnapshot>,
+    pub models: Vec<ModelState>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HorizonSnapshot {
+    pub name: String,
     pub active_market: Option<ActiveMarket>,
     pub volatility: VolatilityState,
-    pub models: Vec<ModelState>,
+    /// Liquidity gate the active market fails, if any
+    pub entry_gate: Option<&'static str>,
+}
+
+impl AssetSnapshot {
+    pub fn horizon(&self, name: &str) -> Option<&HorizonSnapshot> {
+        self.horizons.iter().find(|h| h.name == name)
+    }
+}
+
+impl EngineSnapshot {
+    pub fn asset(&self, asset: Asset) -> Option<&AssetSnapshot> {
+        self.assets.iter().find(|a| a.asset == asset)
+    }
+
+    /// Every model on every asset
+    pub fn models(&self) -> impl Iterator<Item = &ModelState> {
+        self.assets.iter().flat_map(|a| a.models.iter())
+    }
 }
 
 impl Default for EngineSnapshot {
     fn default() -> Self {
         Self {
             engine_state: EngineState::Connecting,
-            btc_price: 0.0,
-            btc_timestamp: String::new(),
-            active_market: None,
-            volatility: VolatilityState::default(),
-            models: vec![
-                ModelState::new("Black-Scholes"),
-                ModelState::new("Jump-Diffusion"),
-                ModelState::new("Student-t"),
-            ],
+            assets: vec![AssetSnapshot {
+                asset: Asset::Btc,
+                price: 0.0,
+                price_timestamp: String::new(),
+                horizons: vec![HorizonSnapshot {
+                    name: crate::horizon::DEFAULT_HORIZON.to_string(),
+                    active_market: None,
+                    volatility: VolatilityState::default(),
+                    entry_gate: None,
+                }],
+                models: vec![
+                    ModelState::new("Black-Scholes", Asset::Btc),
+                    ModelState::new("Jump-Diffusion", Asset::Btc),
+                    ModelState::new("Student-t", Asset::Btc),
+                ],
+            }],
+            kalshi_api: ApiHealth::default(),
+            exchange: ExchangeInfo::default(),
         }
     }
 }
@@ -487,6 +851,8 @@ impl Default for EngineSnapshot {
 
 pub struct PerfCounters {
     pub ticks_processed: AtomicU64,
+    /// Engine events fully handled, of any kind
+    pub events_processed: AtomicU64,
     pub prices_received: AtomicU64,
     pub decisions_made: AtomicU64,
     pub trades_placed: AtomicU64,
@@ -498,6 +864,7 @@ impl PerfCounters {
     pub fn new() -> Self {
         Self {
             ticks_processed: AtomicU64::new(0),
+            events_processed: AtomicU64::new(0),
             prices_received: AtomicU64::new(0),
             decisions_made: AtomicU64::new(0),
             trades_placed: AtomicU64::new(0),
@@ -507,6 +874,12 @@ impl PerfCounters {
     }
 }
 
+impl Default for PerfCounters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // ── Application shared state (channels, not locks) ──
 
 pub struct AppState {
@@ -524,10 +897,31 @@ pub struct AppState {
     pub engine_tx: mpsc::Sender<EngineEvent>,
 
     // Engine -> DB Writer: bounded command channel
-    pub db_tx: mpsc::Sender<DbCommand>,
+    pub db_tx: DbSender,
 
     // Lock-free performance counters
     pub counters: PerfCounters,
+
+    // Hashed API keys (cold path: read per request, written on key reload)
+    pub api_keys: std::sync::RwLock<Vec<ApiKey>>,
+
+    // Latency histograms and API counters for /metrics
+    pub metrics: Arc<Metrics>,
+
+    // Flipped to true once shutdown starts; WebSocket clients are closed on it
+    pub shutdown_tx: watch::Sender<bool>,
+
+    // Background task health, written by the supervisor (cold path)
+    pub tasks: std::sync::RwLock<Vec<TaskHealth>>,
+
+    // Latest Monte Carlo VaR/ES report per asset, written by the engine (cold path)
+    pub tail_risk: std::sync::RwLock<BTreeMap<Asset, TailReport>>,
+
+    // Trading params last sent to the engine, for diffing config reloads
+    pub trading: std::sync::RwLock<TradingParams>,
+
+    // Engine and scanner time; simulated in integration tests
+    pub clock: Clock,
 }
 
 impl AppState {
@@ -535,10 +929,22 @@ impl AppState {
         config: AppConfig,
         db: DbPool,
         engine_tx: mpsc::Sender<EngineEvent>,
-        db_tx: mpsc::Sender<DbCommand>,
+        db_tx: DbSender,
+    ) -> Arc<Self> {
+        Self::with_clock(config, db, engine_tx, db_tx, Clock::System)
+    }
+
+    pub fn with_clock(
+        config: AppConfig,
+        db: DbPool,
+        engine_tx: mpsc::Sender<EngineEvent>,
+        db_tx: DbSender,
+        clock: Clock,
     ) -> Arc<Self> {
         let (ws_tx, _) = broadcast::channel(2048);
         let (snapshot_tx, snapshot_rx) = watch::channel(EngineSnapshot::default());
+        let api_keys = std::sync::RwLock::new(config.api_keys.clone());
+        let trading = std::sync::RwLock::new(config.trading.clone());
 
         Arc::new(Self {
             config,
@@ -549,6 +955,13 @@ impl AppState {
             engine_tx,
             db_tx,
             counters: PerfCounters::new(),
+            api_keys,
+            metrics: Arc::new(Metrics::new()),
+            shutdown_tx: watch::Sender::new(false),
+            tasks: std::sync::RwLock::new(Vec::new()),
+            tail_risk: std::sync::RwLock::new(BTreeMap::new()),
+            trading,
+            clock,
         })
     }
 
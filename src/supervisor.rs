@@ -0,0 +1,385 @@
+//! Owns every background task handle.
+//!
+//! Tasks come in two kinds:
+//! - **critical** (engine, DB writer): hold state that cannot be rebuilt, so
+//!   they are never restarted. If one dies the engine is halted and
+//!   `/api/health` reports `failed`.
+//! - **restartable** (BTC feed, market scanner, tick generator): stateless
+//!   producers, rebuilt from a factory with exponential backoff.
+//!
+//! Status is mirrored into `AppState::tasks` for `/api/health`.
+
+use crate::state::{AppState, EngineEvent, EngineState, WsMessage};
+use futures_util::future::BoxFuture;
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::{JoinError, JoinHandle};
+use tokio::time::Instant;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A task that ran this long before dying gets its backoff reset
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Running,
+    /// Died; waiting for the backoff to elapse before restarting
+    Restarting,
+    /// Critical task died, or restarts disabled after a critical failure
+    Failed,
+    /// Stopped on purpose (shutdown)
+    Stopped,
+}
+
+/// Per-task health, as served by `/api/health`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TaskHealth {
+    pub name: Cow<'static, str>,
+    pub critical: bool,
+    pub status: TaskStatus,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    /// RFC 3339 time of the last status change
+    pub since: String,
+}
+
+type Factory = Box<dyn Fn() -> BoxFuture<'static, ()> + Send>;
+
+struct Task {
+    name: Cow<'static, str>,
+    /// `None` for critical tasks
+    factory: Option<Factory>,
+    handle: Option<JoinHandle<()>>,
+    started: Instant,
+    restart_at: Option<Instant>,
+    backoff: Duration,
+    health: TaskHealth,
+}
+
+pub struct Supervisor {
+    state: Arc<AppState>,
+    tasks: Vec<Task>,
+    /// Set once a critical task dies: producers are stopped, not restarted
+    escalated: bool,
+}
+
+impl Supervisor {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state, tasks: Vec::new(), escalated: false }
+    }
+
+    /// Spawn a task that is never restarted. Its death halts the engine.
+    pub fn spawn_critical<F>(&mut self, name: impl Into<Cow<'static, str>>, fut: F)
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(fut);
+        self.push(name.into(), None, handle);
+    }
+
+    /// Spawn a task built by `factory`; if it exits or panics a fresh one is
+    /// built and spawned after a backoff.
+    pub fn spawn_restartable<F>(&mut self, name: impl Into<Cow<'static, str>>, factory: F)
+    where
+        F: Fn() -> BoxFuture<'static, ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(factory());
+        self.push(name.into(), Some(Box::new(factory)), handle);
+    }
+
+    fn push(&mut self, name: Cow<'static, str>, factory: Option<Factory>, handle: JoinHandle<()>) {
+        let health = TaskHealth {
+            name: name.clone(),
+            critical: factory.is_none(),
+            status: TaskStatus::Running,
+            restarts: 0,
+            last_error: None,
+            since: now(),
+        };
+        self.tasks.push(Task {
+            name,
+            factory,
+            handle: Some(handle),
+            started: Instant::now(),
+            restart_at: None,
+            backoff: INITIAL_BACKOFF,
+            health,
+        });
+        self.publish();
+    }
+
+    /// Watch tasks until cancelled. Cancel-safe: dropping the future between
+    /// polls leaves every handle in place for `shutdown`.
+    pub async fn watch(&mut self) {
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            self.check().await;
+        }
+    }
+
+    async fn check(&mut self) {
+        let now_i = Instant::now();
+        let mut changed = false;
+        let mut critical_failure: Option<(Cow<'static, str>, String)> = None;
+
+        for task in &mut self.tasks {
+            // Reap a finished task
+            if task.handle.as_ref().is_some_and(|h| h.is_finished()) {
+                let handle = task.handle.take().expect("checked above");
+                let error = describe_exit(handle.await);
+                let ran_for = task.started.elapsed();
+                changed = true;
+
+                if task.factory.is_none() {
+                    tracing::error!(task = %task.name, error = %error, "critical task died");
+                    set_status(&mut task.health, TaskStatus::Failed, Some(error.clone()));
+                    critical_failure.get_or_insert((task.name.clone(), error));
+                    continue;
+                }
+
+                if ran_for >= STABLE_AFTER {
+                    task.backoff = INITIAL_BACKOFF;
+                }
+                tracing::warn!(
+                    task = %task.name,
+                    error = %error,
+                    restart_in_secs = task.backoff.as_secs(),
+                    "task died, scheduling restart"
+                );
+                task.restart_at = Some(now_i + task.backoff);
+                task.backoff = (task.backoff * 2).min(MAX_BACKOFF);
+                set_status(&mut task.health, TaskStatus::Restarting, Some(error));
+            }
+
+            // Restart once its backoff has elapsed
+            if let (Some(at), Some(factory)) = (task.restart_at, task.factory.as_ref()) {
+                if now_i >= at && !self.escalated {
+                    task.restart_at = None;
+                    task.handle = Some(tokio::spawn(factory()));
+                    task.started = now_i;
+                    task.health.restarts += 1;
+                    let last_error = task.health.last_error.take();
+                    set_status(&mut task.health, TaskStatus::Running, last_error);
+                    tracing::info!(task = %task.name, restarts = task.health.restarts, "task restarted");
+                    changed = true;
+                }
+            }
+        }
+
+        if let Some((name, error)) = critical_failure {
+            self.escalate(&name, &error);
+            changed = true;
+        }
+        if changed {
+            self.publish();
+        }
+    }
+
+    /// A critical task died: halt trading and stop the producers.
+    fn escalate(&mut self, name: &str, error: &str) {
+        let reason = format!("{name} task died: {error}");
+        self.escalated = true;
+
+        let engine_alive = self
+            .tasks
+            .iter()
+            .any(|t| t.name == "engine" && t.handle.is_some());
+
+        if engine_alive {
+            // Engine can still apply the halt itself (e.g. the DB writer died)
+            let _ = self.state.engine_tx.try_send(EngineEvent::Halt { reason: reason.clone() });
+        } else {
+            // Nobody left to publish state: do it here so the dashboard and
+            // health check stop reporting a live engine
+            self.state.snapshot_tx.send_modify(|s| s.engine_state = EngineState::Halted);
+            self.state.broadcast(WsMessage::EngineStateMsg {
+                state: EngineState::Halted.to_string(),
+                reason: reason.clone(),
+            });
+        }
+
+        for task in self.tasks.iter_mut().filter(|t| t.factory.is_some()) {
+            if let Some(handle) = task.handle.take() {
+                handle.abort();
+            }
+            task.restart_at = None;
+            set_status(&mut task.health, TaskStatus::Failed, Some(format!("stopped: {reason}")));
+        }
+        tracing::error!(reason = %reason, "ENGINE HALTED by supervisor");
+    }
+
+    /// Abort every restartable task (first step of shutdown).
+    pub fn stop_restartable(&mut self) {
+        self.escalated = true;
+        for task in self.tasks.iter_mut().filter(|t| t.factory.is_some()) {
+            if let Some(handle) = task.handle.take() {
+                handle.abort();
+            }
+            task.restart_at = None;
+            set_status(&mut task.health, TaskStatus::Stopped, None);
+        }
+        self.publish();
+    }
+
+    /// Hand a task's handle to the caller (to await it during shutdown).
+    pub fn take(&mut self, name: &str) -> Option<JoinHandle<()>> {
+        let task = self.tasks.iter_mut().find(|t| t.name == name)?;
+        let handle = task.handle.take()?;
+        set_status(&mut task.health, TaskStatus::Stopped, None);
+        self.publish();
+        Some(handle)
+    }
+
+    fn publish(&self) {
+        let health = self.tasks.iter().map(|t| t.health.clone()).collect();
+        *self.state.tasks.write().unwrap_or_else(|e| e.into_inner()) = health;
+    }
+}
+
+/// Overall status for `/api/health`: `failed` if a critical task is down,
+/// `degraded` if a restartable one is, else `ok`.
+pub fn overall_status(tasks: &[TaskHealth]) -> &'static str {
+    if tasks.iter().any(|t| t.critical && t.status == TaskStatus::Failed) {
+        "failed"
+    } else if tasks
+        .iter()
+        .any(|t| matches!(t.status, TaskStatus::Restarting | TaskStatus::Failed))
+    {
+        "degraded"
+    } else {
+        "ok"
+    }
+}
+
+fn set_status(health: &mut TaskHealth, status: TaskStatus, last_error: Option<String>) {
+    health.status = status;
+    health.last_error = last_error;
+    health.since = now();
+}
+
+fn describe_exit(result: Result<(), JoinError>) -> String {
+    match result {
+        Ok(()) => "exited".to_string(),
+        Err(e) if e.is_panic() => {
+            let payload = e.into_panic();
+            let msg = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            format!("panicked: {msg}")
+        }
+        Err(_) => "cancelled".to_string(),
+    }
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health(critical: bool, status: TaskStatus) -> TaskHealth {
+        TaskHealth {
+            name: "t".into(),
+            critical,
+            status,
+            restarts: 0,
+            last_error: None,
+            since: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_overall_status() {
+        assert_eq!(overall_status(&[]), "ok");
+        assert_eq!(overall_status(&[health(true, TaskStatus::Running)]), "ok");
+        assert_eq!(
+            overall_status(&[health(true, TaskStatus::Running), health(false, TaskStatus::Restarting)]),
+            "degraded"
+        );
+        assert_eq!(
+            overall_status(&[health(true, TaskStatus::Failed), health(false, TaskStatus::Restarting)]),
+            "failed"
+        );
+    }
+
+    fn test_state() -> Arc<AppState> {
+        let db = crate::db::DbPool::from_connection(rusqlite::Connection::open_in_memory().unwrap());
+        let (engine_tx, _) = tokio::sync::mpsc::channel(8);
+        let (db_tx, _) = crate::db::channel(8);
+        AppState::new(crate::config::AppConfig::for_tests(), db, engine_tx, db_tx)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_restarts_with_backoff() {
+        let state = test_state();
+        let mut sup = Supervisor::new(state.clone());
+        let runs = Arc::new(portable_atomic::AtomicU32::new(0));
+
+        let r = runs.clone();
+        sup.spawn_restartable("flaky", move || {
+            let n = r.fetch_add(1, portable_atomic::Ordering::SeqCst);
+            Box::pin(async move {
+                if n < 2 {
+                    panic!("run {n} failed");
+                }
+                std::future::pending::<()>().await
+            })
+        });
+
+        tokio::task::yield_now().await;
+        sup.check().await;
+        assert_eq!(state.tasks.read().unwrap()[0].status, TaskStatus::Restarting);
+
+        // First restart after 1s, second failure waits 2s
+        tokio::time::advance(INITIAL_BACKOFF).await;
+        sup.check().await;
+        tokio::task::yield_now().await;
+        sup.check().await;
+        tokio::time::advance(INITIAL_BACKOFF * 2).await;
+        sup.check().await;
+        tokio::task::yield_now().await;
+        sup.check().await;
+
+        let health = state.tasks.read().unwrap()[0].clone();
+        assert_eq!(health.status, TaskStatus::Running);
+        assert_eq!(health.restarts, 2);
+        assert_eq!(runs.load(portable_atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_critical_death_escalates() {
+        let state = test_state();
+        let mut sup = Supervisor::new(state.clone());
+        sup.spawn_restartable("feed", || Box::pin(std::future::pending::<()>()));
+        sup.spawn_critical("engine", async {});
+
+        tokio::task::yield_now().await;
+        sup.check().await;
+
+        let tasks = state.tasks.read().unwrap().clone();
+        assert_eq!(overall_status(&tasks), "failed");
+        assert!(tasks.iter().all(|t| t.status == TaskStatus::Failed));
+        assert_eq!(state.snapshot_rx.borrow().engine_state, EngineState::Halted);
+
+        // Nothing comes back after escalation
+        tokio::time::advance(MAX_BACKOFF).await;
+        sup.check().await;
+        assert!(sup.tasks.iter().all(|t| t.handle.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_describe_panic() {
+        let handle = tokio::spawn(async { panic!("boom") });
+        assert_eq!(describe_exit(handle.await), "panicked: boom");
+        let handle = tokio::spawn(async {});
+        assert_eq!(describe_exit(handle.await), "exited");
+    }
+}
@@ -0,0 +1,146 @@
+//! Trading-day boundaries.
+//!
+//! A trading day starts at a configured local time (`TRADING_DAY_ROLLOVER`)
+//! in a configured timezone (`TRADING_DAY_TZ`) and is named by the local
+//! date on which it starts. With the defaults (UTC, 00:00) a trading day is
+//! a UTC calendar day.
+//!
+//! The engine checks the boundary on every tick; crossing it resets the
+//! daily counters and writes a `daily_summary` row per model.
+
+use crate::errors::{EngineError, EngineResult};
+use crate::state::{DbCommand, ModelState};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradingCalendar {
+    pub tz: Tz,
+    pub rollover: NaiveTime,
+}
+
+impl Default for TradingCalendar {
+    fn default() -> Self {
+        Self { tz: Tz::UTC, rollover: NaiveTime::MIN }
+    }
+}
+
+impl TradingCalendar {
+    /// Parse an IANA timezone name (e.g. `America/New_York`) and an `HH:MM`
+    /// rollover time.
+    pub fn parse(tz: &str, rollover: &str) -> EngineResult<Self> {
+        let tz: Tz = tz
+            .trim()
+            .parse()
+            .map_err(|e| EngineError::Config(format!("TRADING_DAY_TZ: {e}")))?;
+        let rollover = NaiveTime::parse_from_str(rollover.trim(), "%H:%M")
+            .map_err(|e| EngineError::Config(format!("TRADING_DAY_ROLLOVER must be HH:MM: {e}")))?;
+        Ok(Self { tz, rollover })
+    }
+
+    /// The trading day containing `t`.
+    pub fn day_of(&self, t: DateTime<Utc>) -> NaiveDate {
+        let local = t.with_timezone(&self.tz).naive_local();
+        (local - self.rollover.signed_duration_since(NaiveTime::MIN)).date()
+    }
+
+    /// When `day` starts. If the rollover time falls in a DST gap that day,
+    /// the day starts at the first valid instant after it.
+    pub fn start_of(&self, day: NaiveDate) -> DateTime<Utc> {
+        let mut local = day.and_time(self.rollover);
+        // Gaps are at most an hour in practice; step in 15 minute increments
+        for _ in 0..8 {
+            if let Some(t) = self.tz.from_local_datetime(&local).earliest() {
+                return t.with_timezone(&Utc);
+            }
+            local += Duration::minutes(15);
+        }
+        Utc.from_utc_datetime(&day.and_time(self.rollover))
+    }
+}
+
+impl std::fmt::Display for TradingCalendar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.rollover.format("%H:%M"), self.tz)
+    }
+}
+
+/// End `previous` for every model: reset daily P/L and return the
+/// `daily_summary` and risk-state writes that record it.
+pub fn close_day(
+    calendar: &TradingCalendar,
+    previous: NaiveDate,
+    today: NaiveDate,
+    model_states: &mut [ModelState],
+) -> Vec<DbCommand> {
+    let day_start = calendar.start_of(previous).to_rfc3339();
+    let day_end = calendar.start_of(today).to_rfc3339();
+
+    let mut commands = Vec::with_capacity(model_states.len() * 2);
+    for ms in model_states.iter_mut() {
+        commands.push(DbCommand::InsertDailySummary {
+            trading_day: previous.to_string(),
+            model_name: ms.name.to_string(),
+            asset: ms.asset,
+            day_start: day_start.clone(),
+            day_end: day_end.clone(),
+            realized_pnl: ms.daily_pnl,
+            cumulative_pnl: ms.cumulative_pnl,
+            max_drawdown: ms.max_drawdown,
+            exposure: ms.current_exposure,
+            open_positions: ms.open_positions.len() as i64,
+        });
+
+        ms.daily_pnl = 0.0;
+        commands.push(DbCommand::UpdateRiskState {
+            model_name: ms.name.to_string(),
+            asset: ms.asset,
+            exposure: ms.current_exposure,
+            daily_pnl: ms.daily_pnl,
+            max_drawdown: ms.max_drawdown,
+            peak_equity: ms.peak_equity,
+            total_trades: ms.total_trades,
+            winning_trades: ms.winning_trades,
+        });
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_default_is_utc_calendar_day() {
+        let cal = TradingCalendar::default();
+        assert_eq!(cal.day_of(utc("2026-10-18T23:59:59Z")), NaiveDate::from_ymd_opt(2026, 10, 18).unwrap());
+        assert_eq!(cal.day_of(utc("2026-10-19T00:00:00Z")), NaiveDate::from_ymd_opt(2026, 10, 19).unwrap());
+    }
+
+    #[test]
+    fn test_rollover_in_timezone() {
+        // 17:00 New York (EDT, UTC-4 in October)
+        let cal = TradingCalendar::parse("America/New_York", "17:00").unwrap();
+        let oct18 = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        assert_eq!(cal.day_of(utc("2026-10-18T20:59:00Z")), oct18.pred_opt().unwrap());
+        assert_eq!(cal.day_of(utc("2026-10-18T21:00:00Z")), oct18);
+        assert_eq!(cal.start_of(oct18), utc("2026-10-18T21:00:00Z"));
+        // After the November DST change the same local time is an hour later in UTC
+        assert_eq!(cal.start_of(NaiveDate::from_ymd_opt(2026, 11, 2).unwrap()), utc("2026-11-02T22:00:00Z"));
+    }
+
+    #[test]
+    fn test_dst_gap_and_bad_input() {
+        // 02:30 does not exist in New York on 2026-03-08
+        let cal = TradingCalendar::parse("America/New_York", "02:30").unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 3, 8).unwrap();
+        assert_eq!(cal.start_of(day), utc("2026-03-08T07:00:00Z"));
+
+        assert!(TradingCalendar::parse("Mars/Olympus", "00:00").is_err());
+        assert!(TradingCalendar::parse("UTC", "25:00").is_err());
+    }
+}
@@ -0,0 +1,278 @@
+//! Runs the real engine, db writer and market scanner against the mock
+//! exchange on a simulated clock.
+//!
+//! Nothing sleeps: each simulated second the harness advances the clock,
+//! applies the scenario steps that are due, feeds one price per asset and
+//! a tick (polling each asset's scanner every 5 seconds and the exchange
+//! monitor every 30, as the binary does), then waits
+//! until the engine has processed every event sent so far. An hour of
+//! market activity runs in well under a second.
+
+use super::mock_kalshi::{MockKalshi, Scenario, Step, KEY_ID, TEST_KEY};
+use pretty_rusty::clock::Clock;
+use pretty_rusty::config::AppConfig;
+use pretty_rusty::db::trades::{query_trades, TradeFilter};
+use pretty_rusty::db::{self, TradeRow};
+use pretty_rusty::kalshi::auth::KalshiAuth;
+use pretty_rusty::kalshi::exchange::{self, ExchangeMonitor};
+use pretty_rusty::kalshi::limits::{BreakerConfig, RetryPolicy};
+use pretty_rusty::kalshi::scanner::MarketScanner;
+use pretty_rusty::state::{DbCommand, EngineEvent, EngineSnapshot};
+use pretty_rusty::asset::AssetConfig;
+use pretty_rusty::horizon::Horizon;
+use pretty_rusty::{engine, AppState, Asset, KalshiClient};
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// The scanner's cadence in the binary
+const SCAN_EVERY_SECS: i64 = 5;
+
+/// An asset's price at each elapsed second
+pub type PricePath = Box<dyn FnMut(i64) -> f64 + Send>;
+
+pub struct Harness {
+    pub mock: MockKalshi,
+    pub state: Arc<AppState>,
+    pub clock: Clock,
+    /// Simulated seconds since the scenario start
+    pub elapsed: i64,
+    engine_tx: mpsc::Sender<EngineEvent>,
+    /// One per asset, in config order
+    scanners: Vec<MarketScanner>,
+    scanner_rx: mpsc::Receiver<EngineEvent>,
+    exchange: ExchangeMonitor,
+    steps: VecDeque<(i64, Step)>,
+    price_paths: Vec<(Asset, PricePath)>,
+    sent: u64,
+    engine: JoinHandle<()>,
+    writer: JoinHandle<()>,
+    _data_dir: tempfile::TempDir,
+}
+
+impl Harness {
+    /// Start everything at `scenario.start`, trading BTC only. `price_path`
+    /// gives the BTC price at each elapsed second. The scanner polls once
+    /// before the first price, so the engine knows its market from the start.
+    pub async fn start(scenario: Scenario, price_path: impl FnMut(i64) -> f64 + Send + 'static) -> Self {
+        Self::start_assets(scenario, vec![(Asset::Btc, Box::new(price_path))]).await
+    }
+
+    /// Start everything trading each listed asset on its default series,
+    /// fed by its own price path.
+    pub async fn start_assets(scenario: Scenario, price_paths: Vec<(Asset, PricePath)>) -> Self {
+        Self::launch(scenario, price_paths, |_| {}).await
+    }
+
+    /// Start everything trading BTC on each of `horizons`.
+    pub async fn start_horizons(
+        scenario: Scenario,
+        price_path: impl FnMut(i64) -> f64 + Send + 'static,
+        horizons: Vec<Horizon>,
+    ) -> Self {
+        Self::start_configured(scenario, price_path, |config| config.horizons = horizons).await
+    }
+
+    /// Start everything trading BTC, on the default config as changed by
+    /// `configure`.
+    pub async fn start_configured(
+        scenario: Scenario,
+        price_path: impl FnMut(i64) -> f64 + Send + 'static,
+        configure: impl FnOnce(&mut AppConfig),
+    ) -> Self {
+        Self::launch(scenario, vec![(Asset::Btc, Box::new(price_path))], configure).await
+    }
+
+    async fn launch(
+        scenario: Scenario,
+        price_paths: Vec<(Asset, PricePath)>,
+        configure: impl FnOnce(&mut AppConfig),
+    ) -> Self {
+        let clock = Clock::simulated(scenario.start);
+        let mock = MockKalshi::start(&scenario, clock.clone()).await;
+
+        let mut config = AppConfig::load(None, None).expect("default config");
+        configure(&mut config);
+        config.assets = price_paths
+            .iter()
+            .map(|(asset, _)| AssetConfig { asset: *asset, series: vec![asset.default_series().to_string()] })
+            .collect();
+        let data_dir = tempfile::tempdir().expect("temp data dir");
+        let (writer_conn, pool) = db::init_db(data_dir.path()).expect("init db");
+        let (engine_tx, engine_rx) = mpsc::channel(512);
+        let (db_tx, db_rx) = db::channel(1024);
+        let state = AppState::with_clock(config.clone(), pool, engine_tx.clone(), db_tx, clock.clone());
+
+        let writer = tokio::spawn(db::run_db_writer(writer_conn, db_rx, state.metrics.clone()));
+        let engine = tokio::spawn(engine::run_engine(state.clone(), config.clone(), engine_rx, Vec::new()));
+
+        let auth = KalshiAuth::new(KEY_ID, std::path::Path::new(TEST_KEY)).expect("load test key");
+        // Breaker cooldowns on the simulated clock; retries and the rate
+        // limit on real time, so keep them out of the way
+        let client = KalshiClient::new(&mock.base_url, auth, state.metrics.clone())
+            .with_breaker(BreakerConfig::default(), clock.clone())
+            .with_retry_policy(RetryPolicy { base_delay: Duration::from_millis(1), ..RetryPolicy::default() })
+            .with_rate_limit(10_000.0);
+        let (scanner_tx, scanner_rx) = mpsc::channel(64);
+        let exchange = ExchangeMonitor::new(client.clone(), scanner_tx.clone(), clock.clone());
+        let scanners = config
+            .assets
+            .iter()
+            .map(|a| {
+                let snapshots = state.snapshot_rx.clone();
+                MarketScanner::new(config.clone(), a.asset, client.clone(), scanner_tx.clone(), Vec::new(), snapshots, clock.clone())
+            })
+            .collect();
+
+        let mut steps: Vec<(i64, Step)> = scenario.steps;
+        steps.sort_by_key(|(secs, _)| *secs);
+
+        let mut harness = Self {
+            mock,
+            state,
+            clock,
+            elapsed: 0,
+            engine_tx,
+            scanners,
+            scanner_rx,
+            exchange,
+            steps: steps.into(),
+            price_paths,
+            sent: 0,
+            engine,
+            writer,
+            _data_dir: data_dir,
+        };
+        harness.apply_due_steps();
+        harness.poll_scanner().await;
+        harness.poll_exchange().await;
+        harness.settle().await;
+        harness
+    }
+
+    /// Advance `secs` simulated seconds, one price and tick per second.
+    pub async fn run_for(&mut self, secs: i64) {
+        for _ in 0..secs {
+            self.elapsed += 1;
+            self.clock.advance(chrono::Duration::seconds(1));
+            self.apply_due_steps();
+            if self.elapsed % SCAN_EVERY_SECS == 0 {
+                self.poll_scanner().await;
+            }
+            if self.elapsed % exchange::POLL_SECS as i64 == 0 {
+                self.poll_exchange().await;
+            }
+            let timestamp_ms = self.clock.now().timestamp_millis();
+            let elapsed = self.elapsed;
+            let prices: Vec<_> = self.price_paths.iter_mut().map(|(asset, path)| (*asset, path(elapsed))).collect();
+            for (asset, price) in prices {
+                self.send(EngineEvent::Price { asset, price, timestamp_ms }).await;
+            }
+            self.send(EngineEvent::Tick).await;
+            self.settle().await;
+        }
+    }
+
+    /// Run until `elapsed` reaches `secs`
+    pub async fn run_until(&mut self, secs: i64) {
+        self.run_for(secs - self.elapsed).await;
+    }
+
+    pub fn snapshot(&self) -> EngineSnapshot {
+        self.state.snapshot_rx.borrow().clone()
+    }
+
+    /// The first asset's active market on its first horizon
+    pub fn active_ticker(&self) -> Option<String> {
+        self.snapshot().assets[0].horizons[0].active_market.as_ref().map(|m| m.ticker.clone())
+    }
+
+    /// The first asset's active market on `horizon`
+    pub fn horizon_ticker(&self, horizon: &str) -> Option<String> {
+        let snapshot = self.snapshot();
+        snapshot.assets[0].horizon(horizon)?.active_market.as_ref().map(|m| m.ticker.clone())
+    }
+
+    /// Markets the first asset's scanner is still waiting on to settle
+    pub fn pending_settlement(&self) -> Vec<String> {
+        self.scanners[0].pending_settlement().to_vec()
+    }
+
+    /// Every trade written so far, oldest first
+    pub async fn trades(&self) -> Vec<TradeRow> {
+        self.flush().await;
+        let mut trades = self
+            .state
+            .db
+            .read(|conn| Ok(query_trades(conn, &TradeFilter::default(), None, 10_000, false)?.trades))
+            .await
+            .expect("query trades");
+        trades.reverse();
+        trades
+    }
+
+    /// Same order as the binary: stop the engine, then drain the writer
+    pub async fn shutdown(mut self) {
+        let _ = self.engine_tx.send(EngineEvent::Shutdown).await;
+        let _ = (&mut self.engine).await;
+        self.flush().await;
+        self.writer.abort();
+    }
+
+    async fn flush(&self) {
+        let (reply, done) = oneshot::channel();
+        let _ = self.state.db_tx.send(DbCommand::Flush { reply }).await;
+        let _ = done.await;
+    }
+
+    fn apply_due_steps(&mut self) {
+        while self.steps.front().is_some_and(|(secs, _)| *secs <= self.elapsed) {
+            let (_, step) = self.steps.pop_front().unwrap();
+            self.mock.apply(step);
+        }
+    }
+
+    /// One round of every scanner; their events reach the engine through
+    /// the harness so the barrier can count them.
+    async fn poll_scanner(&mut self) {
+        for scanner in &mut self.scanners {
+            assert!(scanner.poll().await, "engine channel closed");
+        }
+        while let Ok(event) = self.scanner_rx.try_recv() {
+            self.send(event).await;
+        }
+    }
+
+    async fn poll_exchange(&mut self) {
+        assert!(self.exchange.poll().await, "engine channel closed");
+        while let Ok(event) = self.scanner_rx.try_recv() {
+            self.send(event).await;
+        }
+    }
+
+    async fn send(&mut self, event: EngineEvent) {
+        self.engine_tx.send(event).await.expect("engine stopped");
+        self.sent += 1;
+    }
+
+    /// Wait for the engine to finish every event sent so far.
+    async fn settle(&self) {
+        let caught_up = async {
+            while self.state.counters.events_processed.load(Ordering::Acquire) < self.sent {
+                tokio::task::yield_now().await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(10), caught_up)
+            .await
+            .expect("engine did not catch up within 10s");
+    }
+}
+
+/// A path that drifts from `base` with a small wobble, enough for the
+/// volatility estimate to warm up without crossing `base`.
+pub fn wobble(base: f64) -> impl FnMut(i64) -> f64 + Send + 'static {
+    move |t| base + 15.0 * (t as f64 * 0.9).sin() + 0.2 * t as f64
+}
@@ -0,0 +1,642 @@
+//! An in-process Kalshi exchange for integration tests.
+//!
+//! Serves the REST endpoints the client and scanner use under
+//! `/trade-api/v2`, plus the WebSocket feed at `/trade-api/ws/v2`, from an
+//! in-memory order book that tests script step by step. Market status
+//! follows the shared simulated clock the way the real exchange does:
+//! `active` until close, `closed` until a result is set, then `determined`.
+//!
+//! Authenticated routes verify the three `KALSHI-ACCESS-*` headers against
+//! the fixture key's public half, over the same payload the real exchange
+//! signs (timestamp + METHOD + full path).
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{OriginalUri, Path, Query, Request, State};
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use pretty_rusty::clock::Clock;
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::signature::Verifier;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+pub const SERIES: &str = "KXBTCD";
+pub const KEY_ID: &str = "test-key-id";
+/// The key the mock trusts
+pub const TEST_KEY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/test_key.pem");
+/// A valid key the mock does not know about
+pub const OTHER_KEY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/other_key.pem");
+
+/// Signatures older than this are rejected, as on the real exchange
+const MAX_CLOCK_SKEW_MS: i64 = 30_000;
+
+#[derive(Debug, Clone)]
+pub struct MockMarket {
+    pub ticker: String,
+    pub event_ticker: String,
+    pub strike: f64,
+    pub close_time: DateTime<Utc>,
+    /// Quotes in cents
+    pub yes_bid: i64,
+    pub yes_ask: i64,
+    pub volume: i64,
+    pub open_interest: i64,
+    pub result: Option<String>,
+}
+
+impl MockMarket {
+    /// A binary market in the test series, quoted 55/57.
+    pub fn new(ticker: &str, strike: f64, close_time: DateTime<Utc>) -> Self {
+        let event_ticker = ticker.rsplit_once('-').map_or(ticker, |(event, _)| event).to_string();
+        Self {
+            ticker: ticker.into(),
+            event_ticker,
+            strike,
+            close_time,
+            yes_bid: 55,
+            yes_ask: 57,
+            volume: 1_000,
+            open_interest: 500,
+            result: None,
+        }
+    }
+
+    fn status(&self, now: DateTime<Utc>) -> &'static str {
+        match (&self.result, now < self.close_time) {
+            (Some(_), _) => "determined",
+            (None, true) => "active",
+            (None, false) => "closed",
+        }
+    }
+
+    fn to_json(&self, now: DateTime<Utc>) -> Value {
+        let dollars = |cents: i64| format!("{:.4}", cents as f64 / 100.0);
+        let close = self.close_time.to_rfc3339();
+        json!({
+            "ticker": self.ticker,
+            "event_ticker": self.event_ticker,
+            "market_type": "binary",
+            "status": self.status(now),
+            "yes_bid": self.yes_bid,
+            "yes_ask": self.yes_ask,
+            "no_bid": 100 - self.yes_ask,
+            "no_ask": 100 - self.yes_bid,
+            "yes_bid_dollars": dollars(self.yes_bid),
+            "yes_ask_dollars": dollars(self.yes_ask),
+            "no_bid_dollars": dollars(100 - self.yes_ask),
+            "no_ask_dollars": dollars(100 - self.yes_bid),
+            "last_price": self.yes_bid,
+            "last_price_dollars": dollars(self.yes_bid),
+            "volume": self.volume,
+            "open_interest": self.open_interest,
+            "open_time": (self.close_time - chrono::Duration::hours(1)).to_rfc3339(),
+            "close_time": close,
+            "expiration_time": close,
+            "result": self.result.clone().unwrap_or_default(),
+            "floor_strike": self.strike,
+            "strike_type": "greater",
+            "can_close_early": false,
+        })
+    }
+}
+
+/// A canned error for requests whose path (below `/trade-api/v2`) starts
+/// with `path`, returned for the next `remaining` matching requests.
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub path: String,
+    pub status: u16,
+    pub remaining: u32,
+}
+
+impl Failure {
+    pub fn new(path: &str, status: u16, remaining: u32) -> Self {
+        Self { path: path.into(), status, remaining }
+    }
+}
+
+/// One scripted change to the exchange.
+#[derive(Debug, Clone)]
+pub enum Step {
+    Quote { ticker: String, yes_bid: i64, yes_ask: i64 },
+    Settle { ticker: String, result: String },
+    Fail(Failure),
+    AddMarket(MockMarket),
+    /// What `/exchange/status` reports from now on
+    ExchangeStatus { exchange_active: bool, trading_active: bool },
+    /// Deliver an active announcement of `kind` ("info", "warning", "error")
+    Announce { kind: String, message: String },
+}
+
+/// The exchange's starting markets and what happens to them, as offsets in
+/// seconds from `start` on the simulated clock.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub start: DateTime<Utc>,
+    pub markets: Vec<MockMarket>,
+    pub steps: Vec<(i64, Step)>,
+}
+
+impl Scenario {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self { start, markets: Vec::new(), steps: Vec::new() }
+    }
+
+    pub fn market(mut self, market: MockMarket) -> Self {
+        self.markets.push(market);
+        self
+    }
+
+    pub fn at(mut self, secs: i64, step: Step) -> Self {
+        self.steps.push((secs, step));
+        self
+    }
+}
+
+#[derive(Default)]
+struct Exchange {
+    markets: Vec<MockMarket>,
+    /// Executed trades per market, newest last
+    trades: HashMap<String, Vec<Value>>,
+    balance_cents: i64,
+    positions: Vec<Value>,
+    fills: Vec<Value>,
+    orders: Vec<Value>,
+    failures: Vec<Failure>,
+    exchange_active: bool,
+    trading_active: bool,
+    announcements: Vec<Value>,
+    /// (method, path below `/trade-api/v2`) of every REST request
+    requests: Vec<(String, String)>,
+}
+
+#[derive(Clone)]
+struct Shared {
+    exchange: Arc<Mutex<Exchange>>,
+    clock: Clock,
+    verifier: VerifyingKey<Sha256>,
+    ws_tx: broadcast::Sender<Value>,
+}
+
+pub struct MockKalshi {
+    /// REST base URL, as `KalshiClient::new` expects it
+    pub base_url: String,
+    pub ws_url: String,
+    shared: Shared,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl MockKalshi {
+    /// Bind an ephemeral port and serve `scenario.markets`. The caller owns
+    /// `scenario.steps` and applies them as the clock moves.
+    pub async fn start(scenario: &Scenario, clock: Clock) -> Self {
+        let pem = std::fs::read_to_string(TEST_KEY).expect("read test key");
+        let public = RsaPublicKey::from(&RsaPrivateKey::from_pkcs1_pem(&pem).expect("parse test key"));
+        let (ws_tx, _) = broadcast::channel(256);
+        let shared = Shared {
+            exchange: Arc::new(Mutex::new(Exchange {
+                markets: scenario.markets.clone(),
+                balance_cents: 100_000,
+                exchange_active: true,
+                trading_active: true,
+                ..Exchange::default()
+            })),
+            clock,
+            verifier: VerifyingKey::new(public),
+            ws_tx,
+        };
+
+        let rest = Router::new()
+            .route("/markets", get(list_markets))
+            .route("/markets/trades", get(market_trades))
+            .route("/markets/{ticker}", get(get_market))
+            .route("/markets/{ticker}/orderbook", get(orderbook))
+            .route("/events", get(list_events))
+            .route("/events/{event_ticker}", get(get_event))
+            .route("/exchange/status", get(exchange_status))
+            .route("/exchange/schedule", get(exchange_schedule))
+            .route("/exchange/announcements", get(exchange_announcements))
+            .route("/account/limits", get(account_limits))
+            .route("/portfolio/balance", get(balance))
+            .route("/portfolio/positions", get(positions))
+            .route("/portfolio/fills", get(fills))
+            .route("/portfolio/orders", get(orders))
+            .layer(axum::middleware::from_fn_with_state(shared.clone(), record_and_fail));
+        let app = Router::new()
+            .nest("/trade-api/v2", rest)
+            .route("/trade-api/ws/v2", get(ws_upgrade))
+            .with_state(shared.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind mock");
+        let addr = listener.local_addr().expect("mock addr");
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        Self {
+            base_url: format!("http://{addr}/trade-api/v2"),
+            ws_url: format!("ws://{addr}/trade-api/ws/v2"),
+            shared,
+            server,
+        }
+    }
+
+    /// Apply one scripted change, pushing the matching WebSocket messages.
+    pub fn apply(&self, step: Step) {
+        let mut ex = self.shared.exchange.lock().unwrap();
+        let ts = self.shared.clock.now().timestamp();
+        match step {
+            Step::Quote { ticker, yes_bid, yes_ask } => {
+                if let Some(m) = ex.markets.iter_mut().find(|m| m.ticker == ticker) {
+                    m.yes_bid = yes_bid;
+                    m.yes_ask = yes_ask;
+                    m.volume += 10;
+                }
+                let trade = json!({
+                    "trade_id": format!("{ticker}-{ts}"),
+                    "ticker": ticker,
+                    "yes_price_dollars": format!("{:.4}", yes_bid as f64 / 100.0),
+                    "no_price_dollars": format!("{:.4}", (100 - yes_bid) as f64 / 100.0),
+                    "count_fp": "10.00",
+                    "taker_side": "yes",
+                    "created_time": self.shared.clock.now().to_rfc3339(),
+                });
+                ex.trades.entry(ticker.clone()).or_default().push(trade);
+                let _ = self.shared.ws_tx.send(json!({
+                    "type": "ticker",
+                    "msg": {
+                        "market_ticker": ticker,
+                        "price": yes_bid,
+                        "yes_bid": yes_bid,
+                        "yes_ask": yes_ask,
+                        "ts": ts,
+                    },
+                }));
+            }
+            Step::Settle { ticker, result } => {
+                if let Some(m) = ex.markets.iter_mut().find(|m| m.ticker == ticker) {
+                    m.result = Some(result.clone());
+                }
+                let _ = self.shared.ws_tx.send(json!({
+                    "type": "market_lifecycle_v2",
+                    "msg": {
+                        "market_ticker": ticker,
+                        "event_type": "determined",
+                        "result": result,
+                        "determination_ts": ts,
+                    },
+                }));
+            }
+            Step::Fail(failure) => ex.failures.push(failure),
+            Step::AddMarket(market) => {
+                let _ = self.shared.ws_tx.send(json!({
+                    "type": "market_lifecycle_v2",
+                    "msg": {
+                        "market_ticker": market.ticker,
+                        "event_type": "created",
+                        "close_ts": market.close_time.timestamp(),
+                    },
+                }));
+                ex.markets.push(market);
+            }
+            Step::ExchangeStatus { exchange_active, trading_active } => {
+                ex.exchange_active = exchange_active;
+                ex.trading_active = trading_active;
+            }
+            Step::Announce { kind, message } => {
+                let announcement = json!({
+                    "type": kind,
+                    "message": message,
+                    "delivery_time": self.shared.clock.now().to_rfc3339(),
+                    "status": "active",
+                });
+                ex.announcements.push(announcement);
+            }
+        }
+    }
+
+    /// Paths (below `/trade-api/v2`, without the query) requested so far
+    pub fn requests(&self) -> Vec<String> {
+        let ex = self.shared.exchange.lock().unwrap();
+        ex.requests.iter().map(|(_, path)| path.clone()).collect()
+    }
+
+    pub fn set_positions(&self, positions: Vec<Value>) {
+        self.shared.exchange.lock().unwrap().positions = positions;
+    }
+}
+
+impl Drop for MockKalshi {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+// ── Middleware ──
+
+async fn record_and_fail(State(shared): State<Shared>, req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    let injected = {
+        let mut ex = shared.exchange.lock().unwrap();
+        ex.requests.push((req.method().to_string(), path.clone()));
+        let failure = ex.failures.iter_mut().find(|f| f.remaining > 0 && path.starts_with(&f.path));
+        failure.map(|f| {
+            f.remaining -= 1;
+            f.status
+        })
+    };
+    match injected {
+        Some(status) => {
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            let mut resp = (status, Json(json!({"error": {"code": "injected", "message": "scripted failure"}})))
+                .into_response();
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                resp.headers_mut().insert("retry-after", "1".parse().unwrap());
+            }
+            resp
+        }
+        None => next.run(req).await,
+    }
+}
+
+fn unauthorized(why: &str) -> Response {
+    (StatusCode::UNAUTHORIZED, Json(json!({"error": {"code": "unauthorized", "message": why}}))).into_response()
+}
+
+/// Check the signed headers for `method` on the full request path,
+/// returning why they were rejected.
+fn verify(shared: &Shared, headers: &HeaderMap, method: &Method, full_path: &str) -> Result<(), &'static str> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let (Some(key), Some(timestamp), Some(signature)) = (
+        header("KALSHI-ACCESS-KEY"),
+        header("KALSHI-ACCESS-TIMESTAMP"),
+        header("KALSHI-ACCESS-SIGNATURE"),
+    ) else {
+        return Err("missing auth headers");
+    };
+    if key != KEY_ID {
+        return Err("unknown key id");
+    }
+    // Freshness is judged on wall-clock time: the client signs with it
+    let stamped: i64 = timestamp.parse().map_err(|_| "bad timestamp")?;
+    if (Utc::now().timestamp_millis() - stamped).abs() > MAX_CLOCK_SKEW_MS {
+        return Err("stale timestamp");
+    }
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(signature)
+        .map_err(|_| "bad signature encoding")?;
+    let signature = Signature::try_from(bytes.as_slice()).map_err(|_| "bad signature")?;
+    let message = format!("{timestamp}{}{full_path}", method.as_str());
+    shared.verifier.verify(message.as_bytes(), &signature).map_err(|_| "signature mismatch")
+}
+
+// ── REST handlers ──
+
+#[derive(serde::Deserialize)]
+struct ListQuery {
+    series_ticker: Option<String>,
+    status: Option<String>,
+    limit: Option<usize>,
+    cursor: Option<String>,
+    ticker: Option<String>,
+}
+
+fn not_found(what: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(json!({"error": {"code": "not_found", "message": what}}))).into_response()
+}
+
+/// `status=open` matches active markets, as on the real exchange
+fn status_matches(filter: Option<&str>, status: &str) -> bool {
+    match filter {
+        None => true,
+        Some("open") => status == "active",
+        Some(wanted) => wanted == status,
+    }
+}
+
+async fn list_markets(State(shared): State<Shared>, Query(q): Query<ListQuery>) -> Response {
+    let now = shared.clock.now();
+    let ex = shared.exchange.lock().unwrap();
+    let matching: Vec<Value> = ex
+        .markets
+        .iter()
+        .filter(|m| q.series_ticker.as_deref().is_none_or(|s| m.ticker.starts_with(s)))
+        .filter(|m| status_matches(q.status.as_deref(), m.status(now)))
+        .map(|m| m.to_json(now))
+        .collect();
+
+    // Cursor is the offset of the next page
+    let offset: usize = q.cursor.as_deref().and_then(|c| c.parse().ok()).unwrap_or(0);
+    let limit = q.limit.unwrap_or(100).clamp(1, 1000);
+    let page: Vec<Value> = matching.iter().skip(offset).take(limit).cloned().collect();
+    let cursor = if offset + limit < matching.len() { (offset + limit).to_string() } else { String::new() };
+    Json(json!({"markets": page, "cursor": cursor})).into_response()
+}
+
+async fn get_market(State(shared): State<Shared>, Path(ticker): Path<String>) -> Response {
+    let now = shared.clock.now();
+    let ex = shared.exchange.lock().unwrap();
+    match ex.markets.iter().find(|m| m.ticker == ticker) {
+        Some(m) => Json(json!({"market": m.to_json(now)})).into_response(),
+        None => not_found("market not found"),
+    }
+}
+
+async fn orderbook(
+    State(shared): State<Shared>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Path(ticker): Path<String>,
+) -> Response {
+    if let Err(why) = verify(&shared, &headers, &Method::GET, uri.path()) {
+        return unauthorized(why);
+    }
+    let ex = shared.exchange.lock().unwrap();
+    let Some(m) = ex.markets.iter().find(|m| m.ticker == ticker) else {
+        return not_found("market not found");
+    };
+    // Bids on both sides; a NO bid at p is a YES ask at 1 - p
+    let dollars = |cents: i64| format!("{:.4}", cents as f64 / 100.0);
+    Json(json!({
+        "orderbook": {
+            "yes": [[m.yes_bid, 100]],
+            "no": [[100 - m.yes_ask, 100]],
+        },
+        "orderbook_fp": {
+            "yes_dollars": [[dollars(m.yes_bid), "100.00"]],
+            "no_dollars": [[dollars(100 - m.yes_ask), "100.00"]],
+        },
+    }))
+    .into_response()
+}
+
+async fn market_trades(State(shared): State<Shared>, Query(q): Query<ListQuery>) -> Response {
+    let ex = shared.exchange.lock().unwrap();
+    let limit = q.limit.unwrap_or(100);
+    let mut trades: Vec<Value> = match &q.ticker {
+        Some(t) => ex.trades.get(t).cloned().unwrap_or_default(),
+        None => ex.trades.values().flatten().cloned().collect(),
+    };
+    trades.reverse();
+    trades.truncate(limit);
+    Json(json!({"trades": trades, "cursor": ""})).into_response()
+}
+
+fn event_json(shared: &Shared, ex: &Exchange, event_ticker: &str) -> Value {
+    let now = shared.clock.now();
+    let markets: Vec<Value> =
+        ex.markets.iter().filter(|m| m.event_ticker == event_ticker).map(|m| m.to_json(now)).collect();
+    json!({
+        "event_ticker": event_ticker,
+        "series_ticker": event_ticker.split('-').next().unwrap_or(SERIES),
+        "title": "Bitcoin price",
+        "mutually_exclusive": false,
+        "category": "Crypto",
+        "markets": markets,
+    })
+}
+
+async fn list_events(State(shared): State<Shared>, Query(q): Query<ListQuery>) -> Response {
+    let now = shared.clock.now();
+    let ex = shared.exchange.lock().unwrap();
+    let mut tickers: Vec<&str> = ex
+        .markets
+        .iter()
+        .filter(|m| q.series_ticker.as_deref().is_none_or(|s| m.event_ticker.starts_with(s)))
+        .filter(|m| status_matches(q.status.as_deref(), m.status(now)))
+        .map(|m| m.event_ticker.as_str())
+        .collect();
+    tickers.dedup();
+    tickers.truncate(q.limit.unwrap_or(100));
+    let events: Vec<Value> = tickers.iter().map(|t| event_json(&shared, &ex, t)).collect();
+    Json(json!({"events": events, "cursor": ""})).into_response()
+}
+
+async fn get_event(State(shared): State<Shared>, Path(event_ticker): Path<String>) -> Response {
+    let ex = shared.exchange.lock().unwrap();
+    if !ex.markets.iter().any(|m| m.event_ticker == event_ticker) {
+        return not_found("event not found");
+    }
+    Json(json!({"event": event_json(&shared, &ex, &event_ticker)})).into_response()
+}
+
+async fn exchange_status(State(shared): State<Shared>) -> Response {
+    let ex = shared.exchange.lock().unwrap();
+    Json(json!({"exchange_active": ex.exchange_active, "trading_active": ex.trading_active})).into_response()
+}
+
+/// No weekly hours or maintenance windows: status alone decides
+async fn exchange_schedule() -> Response {
+    Json(json!({"schedule": {"standard_hours": [], "maintenance_windows": []}})).into_response()
+}
+
+async fn exchange_announcements(State(shared): State<Shared>) -> Response {
+    let ex = shared.exchange.lock().unwrap();
+    Json(json!({"announcements": ex.announcements})).into_response()
+}
+
+/// Shared body of the authenticated portfolio routes
+fn portfolio(shared: &Shared, uri: &axum::http::Uri, headers: &HeaderMap, body: impl FnOnce(&Exchange) -> Value) -> Response {
+    if let Err(why) = verify(shared, headers, &Method::GET, uri.path()) {
+        return unauthorized(why);
+    }
+    let ex = shared.exchange.lock().unwrap();
+    Json(body(&ex)).into_response()
+}
+
+/// Kalshi's Advanced tier
+async fn account_limits(State(shared): State<Shared>, OriginalUri(uri): OriginalUri, headers: HeaderMap) -> Response {
+    portfolio(&shared, &uri, &headers, |_| json!({"usage_tier": "advanced", "read_limit": 30, "write_limit": 30}))
+}
+
+async fn balance(State(shared): State<Shared>, OriginalUri(uri): OriginalUri, headers: HeaderMap) -> Response {
+    portfolio(&shared, &uri, &headers, |ex| {
+        json!({"balance": ex.balance_cents, "portfolio_value": ex.balance_cents})
+    })
+}
+
+async fn positions(State(shared): State<Shared>, OriginalUri(uri): OriginalUri, headers: HeaderMap) -> Response {
+    portfolio(&shared, &uri, &headers, |ex| json!({"market_positions": ex.positions, "cursor": ""}))
+}
+
+async fn fills(State(shared): State<Shared>, OriginalUri(uri): OriginalUri, headers: HeaderMap) -> Response {
+    portfolio(&shared, &uri, &headers, |ex| json!({"fills": ex.fills, "cursor": ""}))
+}
+
+async fn orders(State(shared): State<Shared>, OriginalUri(uri): OriginalUri, headers: HeaderMap) -> Response {
+    portfolio(&shared, &uri, &headers, |ex| json!({"orders": ex.orders, "cursor": ""}))
+}
+
+// ── WebSocket ──
+
+async fn ws_upgrade(
+    State(shared): State<Shared>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if let Err(why) = verify(&shared, &headers, &Method::GET, uri.path()) {
+        return unauthorized(why);
+    }
+    ws.on_upgrade(move |socket| ws_session(socket, shared))
+}
+
+/// Kalshi's subscribe protocol, reduced to what tests need: one `sid` per
+/// channel, optional market filter, `seq` counting pushes per session.
+async fn ws_session(mut socket: WebSocket, shared: Shared) {
+    let mut feed = shared.ws_tx.subscribe();
+    let mut channels: HashMap<String, u64> = HashMap::new();
+    let mut markets: Vec<String> = Vec::new();
+    let mut seq = 0u64;
+
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => {
+                let Some(Ok(Message::Text(text))) = incoming else { return };
+                let Ok(cmd) = serde_json::from_str::<Value>(&text) else { continue };
+                let id = cmd["id"].clone();
+                if cmd["cmd"] != "subscribe" {
+                    let reply = json!({"id": id, "type": "error", "msg": {"code": 5, "msg": "unknown command"}});
+                    let _ = socket.send(Message::Text(reply.to_string().into())).await;
+                    continue;
+                }
+                if let Some(tickers) = cmd["params"]["market_tickers"].as_array() {
+                    markets.extend(tickers.iter().filter_map(|t| t.as_str().map(String::from)));
+                }
+                for channel in cmd["params"]["channels"].as_array().into_iter().flatten() {
+                    let Some(channel) = channel.as_str() else { continue };
+                    let sid = channels.len() as u64 + 1;
+                    channels.insert(channel.to_string(), sid);
+                    let reply = json!({"id": id, "type": "subscribed", "msg": {"channel": channel, "sid": sid}});
+                    if socket.send(Message::Text(reply.to_string().into())).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            pushed = feed.recv() => {
+                let Ok(mut msg) = pushed else { return };
+                let Some(&sid) = msg["type"].as_str().and_then(|t| channels.get(t)) else { continue };
+                let ticker = msg["msg"]["market_ticker"].as_str().unwrap_or_default();
+                if !markets.is_empty() && !markets.iter().any(|m| m == ticker) {
+                    continue;
+                }
+                seq += 1;
+                msg["sid"] = json!(sid);
+                msg["seq"] = json!(seq);
+                if socket.send(Message::Text(msg.to_string().into())).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
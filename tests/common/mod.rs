@@ -0,0 +1,7 @@
+//! Shared fixtures for the integration tests. Each test binary uses a
+//! different subset, hence the allow.
+#![allow(dead_code)]
+
+pub mod harness;
+pub mod mock_kalshi;
+pub mod sim;
@@ -0,0 +1,246 @@
+//! Drives `simulator::run_tick` and `settle_trades` directly, one scripted
+//! tick at a time, on a single market. Every DB write goes through
+//! `db::execute_command` into an in-memory database, the same way the
+//! backtest does, so trades and fills can be read back afterwards.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use pretty_rusty::config::TradingParams;
+use pretty_rusty::db::trades::{query_trades, TradeFilter};
+use pretty_rusty::db::{self, migrations, TradeRow};
+use pretty_rusty::models::volatility::VolatilityEngine;
+use pretty_rusty::models::{self, PricingModel, MODEL_NAMES};
+use pretty_rusty::paper::simulator::{self, EngineAction};
+use pretty_rusty::risk::portfolio::PortfolioBook;
+use pretty_rusty::risk::var::HorizonVols;
+use pretty_rusty::state::{ActiveMarket, DbCommand, MarketDepth, ModelState};
+use pretty_rusty::{Asset, Calibrator};
+use rusqlite::Connection;
+
+pub const TICKER: &str = "KXBTCD-26MAR02H15-T100000";
+pub const STRIKE: f64 = 100_000.0;
+
+/// One second of the script: BTC price and the market's YES quotes.
+#[derive(Debug, Clone, Copy)]
+pub struct Tick {
+    pub price: f64,
+    pub yes_bid: f64,
+    pub yes_ask: f64,
+}
+
+/// A trade-level DB write, as the tests see it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Entry { trade_id: String, model: String, side: String, action: String, price: f64, contracts: f64 },
+    /// A partial (`closed: false`) or full exit
+    Exit { trade_id: String, model: String, price: f64, contracts: f64, pnl: f64, reason: String, closed: bool },
+    Settle { trade_id: String, model: String, outcome: String, contracts: f64, pnl: f64 },
+}
+
+/// A few dollars of tick-to-tick noise, roughly BTC's 1-second variance
+pub fn wobble(t: f64) -> f64 {
+    12.0 * (t * 0.9).sin() + 5.0 * (t * 2.3).cos()
+}
+
+pub struct SimDriver {
+    pub conn: Connection,
+    models: Vec<Box<dyn PricingModel>>,
+    pub states: Vec<ModelState>,
+    calibrators: Vec<Calibrator>,
+    vol: VolatilityEngine,
+    pub market: ActiveMarket,
+    pub trading: TradingParams,
+    start: DateTime<Utc>,
+    /// Ticks run so far; also the `tick_counter` passed to `run_tick`
+    pub tick: u64,
+    /// Trade events in order, with the tick they happened on (settlement
+    /// is logged under the last tick)
+    pub log: Vec<(u64, Event)>,
+}
+
+impl SimDriver {
+    /// All models on a fresh database, with the market closing
+    /// `ttl_secs` after the first tick. The volatility estimate is warmed
+    /// on a quiet path around `warmup_price`.
+    pub fn new(trading: TradingParams, ttl_secs: i64, warmup_price: f64) -> Self {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrations::migrate(&mut conn).unwrap();
+
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 14, 30, 0).unwrap();
+        let close = (start + Duration::seconds(ttl_secs)).to_rfc3339();
+        db::execute_command(
+            &conn,
+            DbCommand::InsertMarket {
+                ticker: TICKER.into(),
+                event_ticker: "KXBTCD-26MAR02H15".into(),
+                series_ticker: "KXBTCD".into(),
+                strike_price: Some(STRIKE),
+                open_time: String::new(),
+                close_time: close.clone(),
+                expiration_time: close.clone(),
+            },
+        )
+        .unwrap();
+
+        let mut vol = VolatilityEngine::new(trading.volatility);
+        for i in 0..120 {
+            vol.update(warmup_price + wobble(i as f64));
+        }
+
+        let models = models::enabled(&MODEL_NAMES.map(String::from));
+        let states = models.iter().map(|m| ModelState::new(m.name(), Asset::Btc)).collect();
+        let calibrators = models.iter().map(|_| Calibrator::new()).collect();
+        Self {
+            conn,
+            models,
+            states,
+            calibrators,
+            vol,
+            market: ActiveMarket {
+                asset: Asset::Btc,
+                horizon: "hourly".into(),
+                ticker: TICKER.into(),
+                event_ticker: "KXBTCD-26MAR02H15".into(),
+                series_ticker: "KXBTCD".into(),
+                strike: Some(STRIKE),
+                yes_bid: None,
+                yes_ask: None,
+                no_bid: None,
+                no_ask: None,
+                last_price: None,
+                close_time: close.clone(),
+                expiration_time: close,
+                status: "active".into(),
+                result: None,
+                depth: MarketDepth::default(),
+            },
+            trading,
+            start,
+            tick: 0,
+            log: Vec::new(),
+        }
+    }
+
+    fn timestamp(&self) -> String {
+        (self.start + Duration::seconds(self.tick as i64)).to_rfc3339()
+    }
+
+    /// Run one tick at the scripted price and quotes.
+    pub fn step(&mut self, t: Tick) {
+        self.tick += 1;
+        self.vol.update(t.price);
+        let quote = |q: f64| Some(format!("{q:.2}"));
+        self.market.yes_bid = quote(t.yes_bid);
+        self.market.yes_ask = quote(t.yes_ask);
+        self.market.no_bid = quote(1.0 - t.yes_ask);
+        self.market.no_ask = quote(1.0 - t.yes_bid);
+
+        let models: Vec<&dyn PricingModel> = self.models.iter().map(|m| m.as_ref()).collect();
+        let timestamp = self.timestamp();
+        let mut journal = Vec::new();
+        let horizon = self.market.horizon.clone();
+        let mut vols = HorizonVols::default();
+        vols.push(&horizon, &self.vol.state);
+        let mut book = PortfolioBook::default();
+        let now = simulator::parse_time(&timestamp).unwrap();
+        book.add_states(models[0], &self.states, t.price, &vols, now);
+        let actions = simulator::run_tick(
+            &models,
+            &mut self.states,
+            &mut self.calibrators,
+            &self.vol.state,
+            &vols,
+            &Some(self.market.clone()),
+            t.price,
+            &self.trading,
+            &mut book,
+            &timestamp,
+            self.tick,
+            None,
+            &mut journal,
+        );
+        self.apply(actions);
+    }
+
+    /// Settle the market with `result` ("yes" or "no"), as the engine does
+    /// once the scanner sees it determined.
+    pub fn settle(&mut self, result: &str) {
+        let pending = db::get_pending_trades(&self.conn, TICKER).unwrap();
+        let timestamp = self.timestamp();
+        let actions = simulator::settle_trades(
+            &mut self.states,
+            &mut self.calibrators,
+            TICKER,
+            result,
+            &pending,
+            &timestamp,
+        );
+        self.apply(actions);
+    }
+
+    /// Flatten every model, as the operator or a flattening shutdown does.
+    /// Unless `quoted`, the market has rolled away and positions exit at
+    /// their last mark.
+    pub fn flatten(&mut self, quoted: bool) {
+        let markets = if quoted { vec![&self.market] } else { Vec::new() };
+        let fee_rate = self.trading.strategy.fee_rate;
+        let timestamp = self.timestamp();
+        let actions = simulator::flatten_positions(&mut self.states, &markets, "operator_flatten", fee_rate, &timestamp);
+        self.apply(actions);
+    }
+
+    fn apply(&mut self, actions: impl IntoIterator<Item = EngineAction>) {
+        for action in actions {
+            let EngineAction::DbWrite(cmd) = action else { continue };
+            if let Some(event) = self.event(&cmd) {
+                self.log.push((self.tick, event));
+            }
+            db::execute_command(&self.conn, cmd).unwrap();
+        }
+    }
+
+    fn event(&self, cmd: &DbCommand) -> Option<Event> {
+        let model_of = |trade_id: &str| {
+            self.log
+                .iter()
+                .find_map(|(_, e)| match e {
+                    Event::Entry { trade_id: id, model, .. } if id == trade_id => Some(model.clone()),
+                    _ => None,
+                })
+                .unwrap_or_default()
+        };
+        Some(match cmd {
+            DbCommand::InsertTrade { id, model_name, side, action, entry_price, contracts, .. } => Event::Entry {
+                trade_id: id.clone(),
+                model: model_name.clone(),
+                side: side.clone(),
+                action: action.clone(),
+                price: *entry_price,
+                contracts: *contracts,
+            },
+            DbCommand::ExitTrade { trade_id, exit_price, contracts, pnl, reason, closed, .. } => Event::Exit {
+                trade_id: trade_id.clone(),
+                model: model_of(trade_id),
+                price: *exit_price,
+                contracts: *contracts,
+                pnl: *pnl,
+                reason: reason.clone(),
+                closed: *closed,
+            },
+            DbCommand::SettleTrade { trade_id, outcome, contracts, pnl, .. } => Event::Settle {
+                trade_id: trade_id.clone(),
+                model: model_of(trade_id),
+                outcome: outcome.clone(),
+                contracts: *contracts,
+                pnl: *pnl,
+            },
+            _ => return None,
+        })
+    }
+
+    /// Every trade with its fills, oldest first
+    pub fn trades(&self) -> Vec<TradeRow> {
+        let mut trades = query_trades(&self.conn, &TradeFilter::default(), None, 10_000, true).unwrap().trades;
+        trades.reverse();
+        trades
+    }
+}
@@ -0,0 +1,307 @@
+//! The full engine against the mock exchange on a simulated clock: market
+//! roll, settlement, recovery from API errors and the circuit breaker,
+//! exchange maintenance, liquidity gates, several assets and expiry horizons at once, minutes of trading per test without any real waiting.
+
+mod common;
+
+use chrono::{Duration, TimeZone, Utc};
+use common::harness::{wobble, Harness, PricePath};
+use common::mock_kalshi::{Failure, MockMarket, Scenario, Step};
+use pretty_rusty::db::TradeRow;
+use pretty_rusty::horizon::Horizon;
+use pretty_rusty::kalshi::exchange::ExchangePhase;
+use pretty_rusty::kalshi::limits::BreakerState;
+use pretty_rusty::state::EngineState;
+use pretty_rusty::Asset;
+
+const FIRST: &str = "KXBTCD-26MAR02H15-T100000";
+const SECOND: &str = "KXBTCD-26MAR02H16-T100250";
+
+/// Two hourly markets, the first closing 10 minutes in
+fn two_markets() -> Scenario {
+    let start = Utc.with_ymd_and_hms(2026, 3, 2, 14, 50, 0).unwrap();
+    Scenario::new(start)
+        .market(MockMarket::new(FIRST, 100_000.0, start + Duration::minutes(10)))
+        .market(MockMarket::new(SECOND, 100_250.0, start + Duration::minutes(70)))
+}
+
+#[tokio::test]
+async fn test_scanner_rolls_to_the_next_market_at_close() {
+    let mut h = Harness::start(two_markets(), wobble(100_300.0)).await;
+    // Snapshots start once the volatility estimate is warm (20 prices)
+    h.run_until(30).await;
+    assert_eq!(h.active_ticker().as_deref(), Some(FIRST));
+
+    h.run_until(599).await;
+    assert_eq!(h.active_ticker().as_deref(), Some(FIRST));
+    assert!(h.pending_settlement().is_empty());
+
+    // The scanner's next poll after close picks the later market
+    h.run_until(600).await;
+    assert_eq!(h.active_ticker().as_deref(), Some(SECOND));
+    assert_eq!(h.pending_settlement(), vec![FIRST.to_string()]);
+    h.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_trades_settle_only_once_the_result_is_known() {
+    // BTC sits ~$300 above the first strike while YES is offered at 57c
+    let scenario = two_markets().at(660, Step::Settle { ticker: FIRST.into(), result: "yes".into() });
+    let mut h = Harness::start(scenario, wobble(100_300.0)).await;
+
+    h.run_until(300).await;
+    let entered: Vec<_> = h.trades().await.into_iter().filter(|t| t.market_ticker == FIRST).collect();
+    assert!(!entered.is_empty(), "no entries on a deep in-the-money market");
+    assert!(entered.iter().all(|t| t.side == "yes"));
+
+    // Closed but not yet determined: nothing may settle
+    h.run_until(655).await;
+    assert_eq!(h.pending_settlement(), vec![FIRST.to_string()]);
+    let held = h.trades().await.into_iter().filter(|t| t.market_ticker == FIRST && t.outcome.is_none()).count();
+
+    h.run_until(665).await;
+    assert!(h.pending_settlement().is_empty());
+    let first: Vec<_> = h.trades().await.into_iter().filter(|t| t.market_ticker == FIRST).collect();
+    assert!(first.iter().all(|t| t.outcome.is_some()), "trades left open after settlement");
+    let settled: Vec<_> = first.iter().filter(|t| t.exit_reason.as_deref() == Some("settlement")).collect();
+    assert_eq!(settled.len(), held);
+    assert!(settled.iter().all(|t| t.outcome.as_deref() == Some("win") && t.pnl.unwrap() > 0.0));
+
+    let snapshot = h.snapshot();
+    let winners: i64 = snapshot.models().map(|m| m.winning_trades).sum();
+    assert!(winners as usize >= settled.len());
+    h.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_scanner_rides_out_api_errors() {
+    let scenario = two_markets()
+        // Every market list fails for the first 20 seconds after close:
+        // four polls, each tried three times by the client
+        .at(599, Step::Fail(Failure::new("/markets", 500, 12)))
+        .at(619, Step::Fail(Failure::new(&format!("/markets/{FIRST}"), 503, 9)))
+        .at(620, Step::Settle { ticker: FIRST.into(), result: "no".into() });
+    let mut h = Harness::start(scenario, wobble(100_300.0)).await;
+
+    // The engine keeps its last market while the scanner can't see new ones
+    h.run_until(615).await;
+    assert_eq!(h.active_ticker().as_deref(), Some(FIRST));
+    assert!(h.pending_settlement().is_empty());
+
+    h.run_until(620).await;
+    assert_eq!(h.active_ticker().as_deref(), Some(SECOND));
+
+    // Settlement checks fail three polls running (625, 630, 635), then
+    // succeed; too few failed calls to open the breaker
+    h.run_until(635).await;
+    assert_eq!(h.pending_settlement(), vec![FIRST.to_string()]);
+    h.run_until(640).await;
+    assert!(h.pending_settlement().is_empty());
+
+    let first: Vec<_> = h.trades().await.into_iter().filter(|t| t.market_ticker == FIRST).collect();
+    assert!(first.iter().all(|t| t.outcome.is_some()));
+    assert!(first
+        .iter()
+        .filter(|t| t.exit_reason.as_deref() == Some("settlement"))
+        .all(|t| t.outcome.as_deref() == Some("loss")));
+
+    let failed = h.mock.requests().iter().filter(|p| *p == "/markets").count();
+    assert!(failed >= 12);
+    assert_eq!(h.snapshot().kalshi_api.breaker, BreakerState::Closed);
+    h.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_state_reaches_the_engine() {
+    // Five failed polls in a row (100..=120) open the breaker
+    let scenario = two_markets().at(99, Step::Fail(Failure::new("/markets", 500, 15)));
+    let mut h = Harness::start(scenario, wobble(100_300.0)).await;
+
+    h.run_until(115).await;
+    assert_eq!(h.snapshot().kalshi_api.breaker, BreakerState::Closed);
+    h.run_until(120).await;
+    let api = h.snapshot().kalshi_api;
+    assert_eq!(api.breaker, BreakerState::Open);
+    assert_eq!(api.consecutive_failures, 5);
+    assert!(api.last_error.unwrap().contains("500"));
+
+    // Open for 30 seconds: polls fail fast without reaching the exchange
+    let before = h.mock.requests().len();
+    h.run_until(145).await;
+    assert_eq!(h.mock.requests().len(), before);
+    assert_eq!(h.active_ticker().as_deref(), Some(FIRST));
+
+    // The first poll after the cooldown probes, succeeds and closes it
+    h.run_until(150).await;
+    assert_eq!(h.snapshot().kalshi_api.breaker, BreakerState::Closed);
+    h.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_exchange_maintenance_blocks_entries() {
+    let start = two_markets().start;
+    let scenario = two_markets()
+        .at(200, Step::ExchangeStatus { exchange_active: false, trading_active: false })
+        .at(250, Step::Announce { kind: "warning".into(), message: "Scheduled maintenance".into() })
+        .at(400, Step::ExchangeStatus { exchange_active: true, trading_active: true });
+    let mut h = Harness::start(scenario, wobble(100_300.0)).await;
+
+    h.run_until(205).await;
+    assert_eq!(h.snapshot().engine_state, EngineState::Trading);
+
+    // The monitor's next poll (210) sees the exchange go down
+    h.run_until(210).await;
+    let snapshot = h.snapshot();
+    assert_eq!(snapshot.engine_state, EngineState::Maintenance);
+    assert_eq!(snapshot.exchange.phase, ExchangePhase::Maintenance);
+
+    h.run_until(270).await;
+    let announcements = h.snapshot().exchange.announcements;
+    assert_eq!(announcements.len(), 1);
+    assert_eq!(announcements[0].message, "Scheduled maintenance");
+
+    h.run_until(419).await;
+    assert_eq!(h.snapshot().engine_state, EngineState::Maintenance);
+    let (down, up) = ((start + Duration::seconds(210)).to_rfc3339(), (start + Duration::seconds(420)).to_rfc3339());
+    let entered = h.trades().await.into_iter().filter(|t| t.entry_time >= down && t.entry_time < up).count();
+    assert_eq!(entered, 0, "entries while the exchange was down");
+
+    h.run_until(420).await;
+    let snapshot = h.snapshot();
+    assert_eq!(snapshot.engine_state, EngineState::Trading);
+    assert_eq!(snapshot.exchange.phase, ExchangePhase::Open);
+    h.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_assets_trade_independently() {
+    const ETH: &str = "KXETHD-26MAR02H15-T3000";
+    // ETH settles NO while its price sits 1% above the strike: only ETH's
+    // YES trades lose
+    let scenario = two_markets()
+        .market(MockMarket::new(ETH, 3_000.0, two_markets().start + Duration::minutes(10)))
+        .at(620, Step::Settle { ticker: FIRST.into(), result: "yes".into() })
+        .at(620, Step::Settle { ticker: ETH.into(), result: "no".into() });
+    let eth_path: PricePath = Box::new(|t| 3_030.0 + 0.45 * (t as f64 * 0.9).sin());
+    let paths: Vec<(Asset, PricePath)> = vec![(Asset::Btc, Box::new(wobble(100_300.0))), (Asset::Eth, eth_path)];
+    let mut h = Harness::start_assets(scenario, paths).await;
+
+    h.run_until(300).await;
+    let snapshot = h.snapshot();
+    assert_eq!(snapshot.assets.len(), 2);
+    let eth = snapshot.asset(Asset::Eth).unwrap();
+    assert_eq!(eth.horizons[0].active_market.as_ref().map(|m| m.ticker.as_str()), Some(ETH));
+    assert!((eth.price - 3_030.0).abs() < 1.0);
+    assert!(eth.models.iter().all(|m| m.asset == Asset::Eth));
+    assert_eq!(snapshot.asset(Asset::Btc).unwrap().horizons[0].active_market.as_ref().map(|m| m.ticker.as_str()), Some(FIRST));
+
+    let trades = h.trades().await;
+    for (asset, ticker) in [(Asset::Btc, FIRST), (Asset::Eth, ETH)] {
+        let entered: Vec<_> = trades.iter().filter(|t| t.market_ticker == ticker).collect();
+        assert!(!entered.is_empty(), "no {asset} entries");
+        assert!(entered.iter().all(|t| t.asset == asset));
+    }
+
+    h.run_until(640).await;
+    let settled: Vec<_> =
+        h.trades().await.into_iter().filter(|t| t.exit_reason.as_deref() == Some("settlement")).collect();
+    assert!(settled.iter().filter(|t| t.asset == Asset::Btc).all(|t| t.outcome.as_deref() == Some("win")));
+    assert!(settled.iter().filter(|t| t.asset == Asset::Eth).all(|t| t.outcome.as_deref() == Some("loss")));
+
+    // Each asset's models carry only their own results
+    let snapshot = h.snapshot();
+    let pnl = |asset| snapshot.asset(asset).unwrap().models.iter().map(|m| m.cumulative_pnl).sum::<f64>();
+    assert!(pnl(Asset::Btc) > 0.0);
+    assert!(pnl(Asset::Eth) < 0.0);
+    h.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_horizons_trade_side_by_side() {
+    const DAILY: &str = "KXBTCD-26MAR02H17-T99000";
+    let band = |name: &str, min: f64, max: Option<f64>| Horizon {
+        name: name.into(),
+        min_ttl_secs: min,
+        max_ttl_secs: max,
+        ..Horizon::default()
+    };
+    let start = two_markets().start;
+    // Far enough below the price to be worth buying two hours out
+    let scenario = Scenario::new(start)
+        .market(MockMarket::new(FIRST, 100_000.0, start + Duration::minutes(10)))
+        .market(MockMarket::new(DAILY, 99_000.0, start + Duration::minutes(130)))
+        .at(620, Step::Settle { ticker: FIRST.into(), result: "yes".into() });
+    let horizons = vec![band("hourly", 0.0, Some(3600.0)), band("daily", 3600.0, None)];
+    let mut h = Harness::start_horizons(scenario, wobble(100_300.0), horizons).await;
+
+    h.run_until(300).await;
+    assert_eq!(h.horizon_ticker("hourly").as_deref(), Some(FIRST));
+    assert_eq!(h.horizon_ticker("daily").as_deref(), Some(DAILY));
+    let trades = h.trades().await;
+    for (horizon, ticker) in [("hourly", FIRST), ("daily", DAILY)] {
+        let entered: Vec<_> = trades.iter().filter(|t| t.market_ticker == ticker).collect();
+        assert!(!entered.is_empty(), "no {horizon} entries");
+        assert!(entered.iter().all(|t| t.horizon == horizon));
+    }
+    // One model can hold both markets at once
+    let open = |t: &&TradeRow| t.outcome.is_none();
+    assert!(trades.iter().filter(open).any(|a| {
+        trades.iter().filter(open).any(|b| a.model_name == b.model_name && a.market_ticker != b.market_ticker)
+    }));
+    // Each horizon keeps its own signal rather than the last one priced
+    for m in h.snapshot().models() {
+        let (hourly, daily) = (&m.signals["hourly"], &m.signals["daily"]);
+        assert_ne!(hourly.probability, daily.probability, "{}", m.name);
+    }
+
+    // Settling the hourly market leaves the daily one and its positions alone
+    h.run_until(640).await;
+    assert_eq!(h.horizon_ticker("daily").as_deref(), Some(DAILY));
+    let trades = h.trades().await;
+    assert!(trades.iter().filter(|t| t.market_ticker == FIRST).all(|t| t.outcome.is_some()));
+    assert!(trades.iter().any(|t| t.market_ticker == DAILY && t.outcome.is_none()));
+
+    let snapshot = h.snapshot();
+    let models: Vec<_> = snapshot.models().collect();
+    let hourly: f64 = models.iter().filter_map(|m| m.pnl_by_horizon().get("hourly").copied()).sum();
+    assert!(hourly > 0.0);
+    h.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_liquidity_gates_hold_back_entries() {
+    let start = two_markets().start;
+    // Opens 30c wide; the first trade prints at 200s and nothing after
+    let scenario = two_markets()
+        .at(0, Step::Quote { ticker: FIRST.into(), yes_bid: 40, yes_ask: 70 })
+        .at(200, Step::Quote { ticker: FIRST.into(), yes_bid: 55, yes_ask: 57 });
+    let mut h = Harness::start_configured(scenario, wobble(100_300.0), |config| {
+        let gates = &mut config.trading.liquidity;
+        gates.max_spread = 0.05;
+        gates.min_top_of_book = 50.0;
+        gates.min_recent_trades = 1;
+        gates.recent_window_secs = 120.0;
+        gates.min_open_interest = 100.0;
+    })
+    .await;
+    let gate = |h: &Harness| h.snapshot().assets[0].horizons[0].entry_gate;
+
+    h.run_until(150).await;
+    assert_eq!(gate(&h), Some("wide_spread"));
+    assert!(h.trades().await.is_empty(), "entered a 30c-wide market");
+
+    h.run_until(300).await;
+    assert_eq!(gate(&h), None);
+    let snapshot = h.snapshot();
+    let depth = &snapshot.assets[0].horizons[0].active_market.as_ref().unwrap().depth;
+    assert_eq!((depth.top_of_book(), depth.open_interest), (Some(100.0), Some(500.0)));
+    let opened = (start + Duration::seconds(200)).to_rfc3339();
+    let trades = h.trades().await;
+    assert!(!trades.is_empty(), "no entries once the market tightened");
+    assert!(trades.iter().all(|t| t.entry_time >= opened));
+
+    // The last print ages out of the window
+    h.run_until(340).await;
+    assert_eq!(gate(&h), Some("few_recent_trades"));
+    h.shutdown().await;
+}
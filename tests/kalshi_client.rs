@@ -0,0 +1,331 @@
+//! `KalshiClient` against the mock exchange: response parsing, pagination,
+//! error mapping, retries and the circuit breaker, request signing, plus the
+//! WebSocket handshake.
+
+mod common;
+
+use chrono::{Duration, TimeZone, Utc};
+use common::mock_kalshi::{Failure, MockKalshi, MockMarket, Scenario, Step, KEY_ID, OTHER_KEY, TEST_KEY};
+use futures_util::{SinkExt, StreamExt};
+use pretty_rusty::clock::Clock;
+use pretty_rusty::kalshi::auth::KalshiAuth;
+use pretty_rusty::kalshi::limits::{BreakerConfig, BreakerState, RetryPolicy};
+use pretty_rusty::metrics::Metrics;
+use pretty_rusty::{EngineError, KalshiClient};
+use std::path::Path;
+use std::sync::Arc;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+
+fn start_time() -> chrono::DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 3, 2, 15, 0, 0).unwrap()
+}
+
+/// Ten hourly markets, the first closing in 10 minutes
+async fn exchange() -> MockKalshi {
+    let start = start_time();
+    let mut scenario = Scenario::new(start);
+    for i in 0..10 {
+        let close = start + Duration::minutes(10) + Duration::hours(i);
+        scenario = scenario.market(MockMarket::new(&format!("KXBTCD-26MAR02H{i:02}-T100000"), 100_000.0, close));
+    }
+    MockKalshi::start(&scenario, Clock::simulated(start)).await
+}
+
+fn client(mock: &MockKalshi, key: &str) -> KalshiClient {
+    let auth = KalshiAuth::new(KEY_ID, Path::new(key)).unwrap();
+    KalshiClient::new(&mock.base_url, auth, Arc::new(Metrics::new()))
+}
+
+/// Quick retries, so tests don't sit through real backoff
+fn fast_retries(attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: attempts,
+        base_delay: std::time::Duration::from_millis(5),
+        max_delay: std::time::Duration::from_secs(2),
+    }
+}
+
+fn api_status(err: EngineError) -> u16 {
+    match err {
+        EngineError::KalshiApi { status, .. } => status,
+        other => panic!("expected an API error, got {other}"),
+    }
+}
+
+#[tokio::test]
+async fn test_markets_parse_and_paginate() {
+    let mock = exchange().await;
+    let client = client(&mock, TEST_KEY);
+
+    let first = client.get_markets(Some("KXBTCD"), Some("open"), Some(4), None).await.unwrap();
+    let markets = first.markets.unwrap();
+    assert_eq!(markets.len(), 4);
+    assert!(markets.iter().all(|m| m.is_active() && m.strike_price() == Some(100_000.0)));
+    assert_eq!(markets[0].yes_ask_f64(), Some(0.57));
+
+    let mut seen = markets.len();
+    let mut cursor = first.cursor.filter(|c| !c.is_empty());
+    while let Some(c) = cursor {
+        let page = client.get_markets(Some("KXBTCD"), Some("open"), Some(4), Some(&c)).await.unwrap();
+        seen += page.markets.unwrap().len();
+        cursor = page.cursor.filter(|c| !c.is_empty());
+    }
+    assert_eq!(seen, 10);
+
+    let none = client.get_markets(Some("KXETHD"), None, None, None).await.unwrap();
+    assert!(none.markets.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_single_market_event_and_trades() {
+    let mock = exchange().await;
+    let client = client(&mock, TEST_KEY);
+    let ticker = "KXBTCD-26MAR02H00-T100000";
+
+    let market = client.get_market(ticker).await.unwrap().market.unwrap();
+    assert_eq!(market.ticker_str(), ticker);
+    assert!(!market.is_settled());
+
+    mock.apply(Step::Quote { ticker: ticker.into(), yes_bid: 61, yes_ask: 63 });
+    let trades = client.get_market_trades(Some(ticker), Some(10)).await.unwrap().trades.unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].yes_price_dollars.as_deref(), Some("0.6100"));
+
+    let event = client.get_event("KXBTCD-26MAR02H00").await.unwrap().event.unwrap();
+    assert_eq!(event.markets.unwrap().len(), 1);
+    let events = client.get_events(Some("KXBTCD"), Some("open"), Some(3)).await.unwrap();
+    assert_eq!(events.events.unwrap().len(), 3);
+
+    mock.apply(Step::Settle { ticker: ticker.into(), result: "no".into() });
+    let settled = client.get_market(ticker).await.unwrap().market.unwrap();
+    assert!(settled.is_settled());
+    assert_eq!(settled.result.as_deref(), Some("no"));
+}
+
+#[tokio::test]
+async fn test_missing_market_is_a_404() {
+    let mock = exchange().await;
+    let err = client(&mock, TEST_KEY).get_market("KXBTCD-NOPE").await.unwrap_err();
+    assert_eq!(api_status(err), 404);
+}
+
+#[tokio::test]
+async fn test_injected_errors_surface_then_clear() {
+    let mock = exchange().await;
+    let client = client(&mock, TEST_KEY).with_retry_policy(RetryPolicy::none());
+    mock.apply(Step::Fail(Failure::new("/markets", 500, 1)));
+    mock.apply(Step::Fail(Failure::new("/portfolio", 429, 1)));
+
+    assert_eq!(api_status(client.get_markets(None, None, None, None).await.unwrap_err()), 500);
+    assert!(client.get_markets(None, None, None, None).await.is_ok());
+
+    assert_eq!(api_status(client.get_balance().await.unwrap_err()), 429);
+    assert!(client.get_balance().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_transient_errors_are_retried() {
+    let mock = exchange().await;
+    let client = client(&mock, TEST_KEY).with_retry_policy(fast_retries(3));
+    mock.apply(Step::Fail(Failure::new("/markets", 503, 2)));
+
+    assert!(client.get_markets(None, None, None, None).await.is_ok());
+    assert_eq!(mock.requests().iter().filter(|p| *p == "/markets").count(), 3);
+
+    // Not worth retrying: the same request would 404 again
+    client.get_market("KXBTCD-NOPE").await.unwrap_err();
+    assert_eq!(mock.requests().iter().filter(|p| p.ends_with("NOPE")).count(), 1);
+}
+
+#[tokio::test]
+async fn test_retries_give_up_after_max_attempts() {
+    let mock = exchange().await;
+    let client = client(&mock, TEST_KEY).with_retry_policy(fast_retries(3));
+    mock.apply(Step::Fail(Failure::new("/markets", 500, 5)));
+
+    assert_eq!(api_status(client.get_markets(None, None, None, None).await.unwrap_err()), 500);
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_429_waits_for_retry_after() {
+    let mock = exchange().await;
+    let client = client(&mock, TEST_KEY).with_retry_policy(fast_retries(2));
+    // The mock sends `Retry-After: 1`
+    mock.apply(Step::Fail(Failure::new("/portfolio", 429, 1)));
+
+    let started = std::time::Instant::now();
+    assert!(client.get_balance().await.is_ok());
+    assert!(started.elapsed() >= std::time::Duration::from_secs(1), "retried after {:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_fails_fast_then_recovers() {
+    let mock = exchange().await;
+    let clock = Clock::simulated(start_time());
+    let config = BreakerConfig { failure_threshold: 2, cooldown: Duration::seconds(30) };
+    let client = client(&mock, TEST_KEY).with_retry_policy(RetryPolicy::none()).with_breaker(config, clock.clone());
+    mock.apply(Step::Fail(Failure::new("/markets", 502, 2)));
+
+    for _ in 0..2 {
+        assert_eq!(api_status(client.get_markets(None, None, None, None).await.unwrap_err()), 502);
+    }
+    let health = client.health();
+    assert_eq!(health.breaker, BreakerState::Open);
+    assert_eq!(health.consecutive_failures, 2);
+
+    // Open: fails without reaching the exchange, whatever the endpoint
+    assert_eq!(api_status(client.get_market("KXBTCD-26MAR02H00-T100000").await.unwrap_err()), 503);
+    assert_eq!(mock.requests().len(), 2);
+
+    clock.advance(Duration::seconds(30));
+    assert!(client.get_markets(None, None, None, None).await.is_ok());
+    assert_eq!(client.health().breaker, BreakerState::Closed);
+}
+
+#[tokio::test]
+async fn test_account_limits_size_the_rate_limiter() {
+    let mock = exchange().await;
+    let client = client(&mock, TEST_KEY);
+    assert_eq!(client.health().reads_per_sec, 20.0);
+
+    let limits = client.apply_account_limits().await.unwrap();
+    assert_eq!(limits.usage_tier.as_deref(), Some("advanced"));
+    assert_eq!(client.health().reads_per_sec, 30.0);
+}
+
+#[tokio::test]
+async fn test_signed_requests_are_accepted() {
+    let mock = exchange().await;
+    let client = client(&mock, TEST_KEY);
+
+    let balance = client.get_balance().await.unwrap();
+    assert_eq!(balance.balance, Some(100_000));
+
+    // The query string is not part of the signed path
+    let book = client.get_orderbook("KXBTCD-26MAR02H00-T100000", Some(5)).await.unwrap();
+    let yes = book.orderbook_fp.unwrap().yes_dollars.unwrap();
+    assert_eq!(yes[0][0], "0.5500");
+}
+
+#[tokio::test]
+async fn test_wrong_key_is_rejected() {
+    let mock = exchange().await;
+    let err = client(&mock, OTHER_KEY).get_balance().await.unwrap_err();
+    assert_eq!(api_status(err), 401);
+}
+
+#[tokio::test]
+async fn test_missing_or_tampered_headers_are_rejected() {
+    let mock = exchange().await;
+    let http = reqwest::Client::new();
+    let url = format!("{}/portfolio/balance", mock.base_url);
+
+    let resp = http.get(&url).send().await.unwrap();
+    assert_eq!(resp.status(), 401);
+
+    // A valid signature for a different path
+    let auth = KalshiAuth::new(KEY_ID, Path::new(TEST_KEY)).unwrap();
+    let (key, ts, sig) = auth.sign_request("GET", "/portfolio/balance", "").unwrap();
+    let resp = http
+        .get(&url)
+        .header("KALSHI-ACCESS-KEY", &key)
+        .header("KALSHI-ACCESS-TIMESTAMP", &ts)
+        .header("KALSHI-ACCESS-SIGNATURE", &sig)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 401, "signature must cover the /trade-api/v2 prefix");
+
+    // The right signature under a timestamp it was not made for
+    let (key, ts, sig) = auth.sign_request("GET", "/trade-api/v2/portfolio/balance", "").unwrap();
+    let shifted = (ts.parse::<i64>().unwrap() + 1).to_string();
+    let resp = http
+        .get(&url)
+        .header("KALSHI-ACCESS-KEY", &key)
+        .header("KALSHI-ACCESS-TIMESTAMP", &shifted)
+        .header("KALSHI-ACCESS-SIGNATURE", &sig)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 401);
+
+    let resp = http
+        .get(&url)
+        .header("KALSHI-ACCESS-KEY", "someone-else")
+        .header("KALSHI-ACCESS-TIMESTAMP", &ts)
+        .header("KALSHI-ACCESS-SIGNATURE", &sig)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 401);
+
+    // Public endpoints need no headers
+    let resp = http.get(format!("{}/markets", mock.base_url)).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+}
+
+async fn next_json<S>(ws: &mut S) -> serde_json::Value
+where
+    S: StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    loop {
+        if let Message::Text(text) = ws.next().await.unwrap().unwrap() {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_websocket_subscribe_and_push() {
+    let mock = exchange().await;
+    let auth = KalshiAuth::new(KEY_ID, Path::new(TEST_KEY)).unwrap();
+    let (key, ts, sig) = auth.sign_request("GET", "/trade-api/ws/v2", "").unwrap();
+    let mut request = mock.ws_url.as_str().into_client_request().unwrap();
+    let headers = request.headers_mut();
+    headers.insert("KALSHI-ACCESS-KEY", key.parse().unwrap());
+    headers.insert("KALSHI-ACCESS-TIMESTAMP", ts.parse().unwrap());
+    headers.insert("KALSHI-ACCESS-SIGNATURE", sig.parse().unwrap());
+    let (mut ws, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+
+    let ticker = "KXBTCD-26MAR02H00-T100000";
+    let subscribe = serde_json::json!({
+        "id": 1,
+        "cmd": "subscribe",
+        "params": {"channels": ["ticker", "market_lifecycle_v2"], "market_tickers": [ticker]},
+    });
+    ws.send(Message::Text(subscribe.to_string().into())).await.unwrap();
+
+    for channel in ["ticker", "market_lifecycle_v2"] {
+        let ack = next_json(&mut ws).await;
+        assert_eq!(ack["type"], "subscribed");
+        assert_eq!(ack["msg"]["channel"], channel);
+    }
+
+    // Quotes on other markets are filtered out
+    mock.apply(Step::Quote { ticker: "KXBTCD-26MAR02H01-T100000".into(), yes_bid: 40, yes_ask: 42 });
+    mock.apply(Step::Quote { ticker: ticker.into(), yes_bid: 70, yes_ask: 72 });
+    mock.apply(Step::Settle { ticker: ticker.into(), result: "yes".into() });
+
+    let quote = next_json(&mut ws).await;
+    assert_eq!(quote["type"], "ticker");
+    assert_eq!(quote["msg"]["market_ticker"], ticker);
+    assert_eq!(quote["msg"]["yes_ask"], 72);
+    assert_eq!(quote["seq"], 1);
+
+    let settled = next_json(&mut ws).await;
+    assert_eq!(settled["type"], "market_lifecycle_v2");
+    assert_eq!(settled["msg"]["result"], "yes");
+    assert_eq!(settled["seq"], 2);
+}
+
+#[tokio::test]
+async fn test_websocket_requires_auth() {
+    let mock = exchange().await;
+    let err = tokio_tungstenite::connect_async(mock.ws_url.as_str()).await.unwrap_err();
+    match err {
+        tokio_tungstenite::tungstenite::Error::Http(resp) => assert_eq!(resp.status(), 401),
+        other => panic!("expected an HTTP 401, got {other}"),
+    }
+}
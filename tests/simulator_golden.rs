@@ -0,0 +1,179 @@
+//! Golden-file scenarios for the paper simulator.
+//!
+//! Each scenario feeds a scripted price and quote path through `run_tick`,
+//! settles the market and renders the trade events plus each model's final
+//! books as text, compared line for line with `tests/golden/<name>.txt`.
+//! Trade ids are numbered in order of entry so the output is stable.
+//!
+//! After an intended strategy change, regenerate and review the diff:
+//!
+//!     UPDATE_GOLDEN=1 cargo test --test simulator_golden
+
+mod common;
+
+use common::sim::{wobble, Event, SimDriver, Tick, STRIKE};
+use pretty_rusty::config::TradingParams;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+struct Scenario {
+    name: &'static str,
+    ttl_secs: i64,
+    ticks: usize,
+    /// Price and YES bid/ask at tick `t` (1-based)
+    path: fn(f64) -> (f64, f64, f64),
+    result: &'static str,
+}
+
+fn run(scenario: &Scenario) -> String {
+    let (start_price, ..) = (scenario.path)(1.0);
+    let mut sim = SimDriver::new(TradingParams::default(), scenario.ttl_secs, start_price);
+    for t in 1..=scenario.ticks {
+        let (price, yes_bid, yes_ask) = (scenario.path)(t as f64);
+        sim.step(Tick { price, yes_bid, yes_ask });
+    }
+    sim.settle(scenario.result);
+    render(&sim)
+}
+
+fn render(sim: &SimDriver) -> String {
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    let mut out = String::new();
+    for (tick, event) in &sim.log {
+        match event {
+            Event::Entry { trade_id, model, side, action, price, contracts } => {
+                let n = ids.len() + 1;
+                ids.insert(trade_id, n);
+                writeln!(out, "{tick:04} {model:<15} #{n} {action} {side} {contracts:.4} @ {price:.2}").unwrap();
+            }
+            Event::Exit { trade_id, model, price, contracts, pnl, reason, closed } => {
+                let kind = if *closed { "exit" } else { "partial" };
+                let n = ids[trade_id.as_str()];
+                writeln!(out, "{tick:04} {model:<15} #{n} {kind} {reason} {contracts:.4} @ {price:.2} pnl {pnl:+.4}").unwrap();
+            }
+            Event::Settle { trade_id, model, outcome, contracts, pnl } => {
+                let n = ids[trade_id.as_str()];
+                writeln!(out, "{tick:04} {model:<15} #{n} settle {outcome} {contracts:.4} pnl {pnl:+.4}").unwrap();
+            }
+        }
+    }
+    out.push_str("--\n");
+    for s in &sim.states {
+        writeln!(
+            out,
+            "{:<15} pnl {:+.4} trades {} won {} exposure {:.4} open {}",
+            s.name,
+            s.cumulative_pnl,
+            s.total_trades,
+            s.winning_trades,
+            s.current_exposure,
+            s.open_positions.len()
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn check(scenario: &Scenario) {
+    let actual = run(scenario);
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", &format!("{}.txt", scenario.name)]
+        .iter()
+        .collect();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{}: {e} (run with UPDATE_GOLDEN=1 to create it)", path.display()));
+    if actual != expected {
+        let first = actual
+            .lines()
+            .zip(expected.lines())
+            .position(|(a, e)| a != e)
+            .unwrap_or(actual.lines().count().min(expected.lines().count()));
+        panic!(
+            "{} differs from {} at line {}:\n  expected: {}\n  actual:   {}\n\nfull output:\n{actual}",
+            scenario.name,
+            path.display(),
+            first + 1,
+            expected.lines().nth(first).unwrap_or("<end>"),
+            actual.lines().nth(first).unwrap_or("<end>"),
+        );
+    }
+}
+
+/// BTC at `trend` plus the usual noise, YES quoted two cents wide
+/// around `mid`
+fn quotes(t: f64, trend: f64, mid: f64) -> (f64, f64, f64) {
+    let mid = (mid * 100.0).round() / 100.0;
+    (trend + wobble(t), mid - 0.01, mid + 0.01)
+}
+
+#[test]
+fn golden_rally_takes_profit_in_stages() {
+    // BTC grinds higher while YES reprices from 40c toward 95c
+    check(&Scenario {
+        name: "rally_takes_profit_in_stages",
+        ttl_secs: 900,
+        ticks: 150,
+        path: |t| quotes(t, STRIKE + 120.0 + 1.5 * t, (0.40 + 0.005 * t).min(0.95)),
+        result: "yes",
+    });
+}
+
+#[test]
+fn golden_strike_cross_exits_inside_min_hold() {
+    // Entered just above the strike, then BTC drops through it on tick 3
+    check(&Scenario {
+        name: "strike_cross_exits_inside_min_hold",
+        ttl_secs: 900,
+        ticks: 40,
+        path: |t| {
+            if t < 3.0 {
+                quotes(t, STRIKE + 60.0, 0.46)
+            } else {
+                quotes(t, STRIKE - 80.0 - t, 0.30)
+            }
+        },
+        result: "no",
+    });
+}
+
+#[test]
+fn golden_scale_in_and_hold_to_resolution() {
+    // A steady climb adds legs, then the models hold through the last
+    // minutes because BTC is far above the strike
+    check(&Scenario {
+        name: "scale_in_and_hold_to_resolution",
+        ttl_secs: 420,
+        ticks: 410,
+        path: |t| quotes(t, STRIKE + 150.0 + 1.2 * t, (0.60 + 0.0006 * t).min(0.85)),
+        result: "yes",
+    });
+}
+
+#[test]
+fn golden_time_exit_near_close() {
+    // BTC hovers just above the strike: not a clear enough win to hold
+    // into the last four minutes
+    check(&Scenario {
+        name: "time_exit_near_close",
+        ttl_secs: 330,
+        ticks: 120,
+        path: |t| quotes(t, STRIKE + 80.0 + 5.0 * (t * 0.3).sin(), 0.55),
+        result: "yes",
+    });
+}
+
+#[test]
+fn golden_held_position_settles_a_loss() {
+    // Flat market above the strike that resolves NO
+    check(&Scenario {
+        name: "held_position_settles_a_loss",
+        ttl_secs: 900,
+        ticks: 90,
+        path: |t| quotes(t, STRIKE + 100.0 + 3.0 * (t * 0.5).sin(), 0.56),
+        result: "no",
+    });
+}
@@ -0,0 +1,174 @@
+//! Invariants of the paper simulator over random price and quote paths.
+//!
+//! Each case runs every model through a few minutes of a single market,
+//! maybe flattens, then settles it, and checks the books: exposure, contract counts and
+//! P/L must reconcile exactly with the trades and fills written to the DB.
+
+mod common;
+
+use common::sim::{Event, SimDriver, Tick, STRIKE, TICKER};
+use pretty_rusty::config::TradingParams;
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+const EPS: f64 = 1e-9;
+
+#[derive(Debug, Clone)]
+struct Script {
+    ttl_secs: i64,
+    min_hold_ticks: u64,
+    start_offset: f64,
+    /// Largest BTC move per tick, dollars, and largest noise on the YES mid
+    step: f64,
+    quote_noise: f64,
+    /// How far the market prices YES below (or above) fair; a big gap means
+    /// big Kelly sizes
+    bias: f64,
+    /// Per-tick BTC moves and YES mid noise, in [-1, 1], and the spread
+    moves: Vec<(f64, f64, f64)>,
+    /// Flatten before settling: `Some(true)` at the market's quote,
+    /// `Some(false)` once it has rolled away
+    flatten: Option<bool>,
+    result_yes: bool,
+}
+
+fn script() -> impl Strategy<Value = Script> {
+    (
+        // Some paths run into the last minutes before close, some don't
+        320i64..900,
+        0u64..12,
+        -400.0..400.0f64,
+        // Calm paths reach take-profits and time exits; wild ones trip stops
+        1.0..30.0f64,
+        0.0..0.04f64,
+        -0.25..0.25f64,
+        prop::collection::vec((-1.0..1.0f64, -1.0..1.0f64, 0.01..0.05f64), 30..320),
+        prop::option::of(any::<bool>()),
+        any::<bool>(),
+    )
+        .prop_map(|(ttl_secs, min_hold_ticks, start_offset, step, quote_noise, bias, moves, flatten, result_yes)| Script {
+            ttl_secs,
+            min_hold_ticks,
+            start_offset,
+            step,
+            quote_noise,
+            bias,
+            moves,
+            flatten,
+            result_yes,
+        })
+}
+
+/// The YES quotes loosely follow BTC's distance to the strike, with noise
+/// so the models see both cheap and rich contracts.
+fn ticks(s: &Script) -> Vec<Tick> {
+    let mut price = STRIKE + s.start_offset;
+    s.moves
+        .iter()
+        .map(|(dx, noise, spread)| {
+            price += dx * s.step;
+            let fair = 1.0 / (1.0 + (-(price - STRIKE) / 150.0).exp());
+            let mid = (fair + s.bias + noise * s.quote_noise).clamp(0.03, 0.97);
+            Tick {
+                price,
+                yes_bid: ((mid - spread / 2.0) * 100.0).round() / 100.0,
+                yes_ask: ((mid + spread / 2.0) * 100.0).round() / 100.0,
+            }
+        })
+        .collect()
+}
+
+fn trading(s: &Script) -> TradingParams {
+    let mut trading = TradingParams::default();
+    trading.strategy.min_hold_ticks = s.min_hold_ticks;
+    // Fewer tail scenarios keep each case fast; the gate still runs
+    trading.risk.tail.scenarios = 200;
+    trading
+}
+
+fn open_cost(sim: &SimDriver, i: usize) -> f64 {
+    sim.states[i].open_positions.iter().map(|p| p.entry_price * p.contracts).sum()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn prop_books_reconcile(s in script()) {
+        let mut sim = SimDriver::new(trading(&s), s.ttl_secs, STRIKE + s.start_offset);
+
+        for tick in ticks(&s) {
+            sim.step(tick);
+            for (i, state) in sim.states.iter().enumerate() {
+                // Exposure never negative, and always the cost of what is held
+                prop_assert!(state.current_exposure >= 0.0, "{} exposure {}", state.name, state.current_exposure);
+                prop_assert!(
+                    (state.current_exposure - open_cost(&sim, i)).abs() < 1e-6,
+                    "{} exposure {} vs open cost {}", state.name, state.current_exposure, open_cost(&sim, i)
+                );
+                prop_assert!(state.open_positions.iter().all(|p| p.contracts > 0.0));
+            }
+        }
+        if let Some(quoted) = s.flatten {
+            sim.flatten(quoted);
+            // Nothing left open, whether or not there was a quote to exit at
+            prop_assert!(sim.states.iter().all(|state| state.open_positions.is_empty()));
+        }
+        sim.settle(if s.result_yes { "yes" } else { "no" });
+
+        // No position left open after settlement
+        for (i, state) in sim.states.iter().enumerate() {
+            prop_assert!(state.open_positions.iter().all(|p| p.market_ticker != TICKER));
+            prop_assert!(state.current_exposure.abs() < 1e-6, "{} exposure {}", state.name, open_cost(&sim, i));
+        }
+        prop_assert!(pretty_rusty::db::get_pending_trades(&sim.conn, TICKER).unwrap().is_empty());
+
+        let fee_rate = sim.trading.strategy.fee_rate;
+        let mut model_pnl: HashMap<String, f64> = HashMap::new();
+        for trade in sim.trades() {
+            prop_assert!(trade.outcome.is_some(), "trade {} still open", trade.id);
+            prop_assert!(trade.remaining_contracts.abs() < EPS);
+
+            // Contracts conserved: everything bought was sold or settled
+            let entered: f64 = trade.fills.iter().filter(|f| f.kind == "entry").map(|f| f.contracts).sum();
+            let left: f64 = trade.fills.iter().filter(|f| f.kind != "entry").map(|f| f.contracts).sum();
+            prop_assert!((entered - trade.contracts).abs() < EPS);
+            prop_assert!((entered - left).abs() < EPS, "trade {} bought {entered}, sold/settled {left}", trade.id);
+
+            // P/L is the fills' proceeds minus their fees
+            let proceeds: f64 = trade
+                .fills
+                .iter()
+                .map(|f| match f.kind.as_str() {
+                    "entry" => -f.price * f.contracts,
+                    _ => f.price * f.contracts,
+                })
+                .sum();
+            let fees: f64 = trade.fills.iter().map(|f| f.fee).sum();
+            let pnl = trade.pnl.unwrap_or(0.0);
+            prop_assert!((pnl - (proceeds - fees)).abs() < 1e-6, "trade {} pnl {pnl} vs fills {}", trade.id, proceeds - fees);
+            prop_assert!((trade.realized_fees - fees).abs() < 1e-6);
+            prop_assert!((trade.fees_estimate - trade.entry_price * trade.contracts * fee_rate).abs() < 1e-9);
+            *model_pnl.entry(trade.model_name.clone()).or_default() += pnl;
+        }
+        for state in &sim.states {
+            let booked = model_pnl.get(state.name).copied().unwrap_or(0.0);
+            prop_assert!((state.cumulative_pnl - booked).abs() < 1e-6, "{} P/L {} vs trades {booked}", state.name, state.cumulative_pnl);
+        }
+
+        // No exit inside the minimum hold except on a strike cross or flatten
+        let mut entry_tick: HashMap<&str, u64> = HashMap::new();
+        for (tick, event) in &sim.log {
+            match event {
+                Event::Entry { trade_id, .. } => {
+                    entry_tick.insert(trade_id, *tick);
+                }
+                Event::Exit { trade_id, reason, .. } if reason != "strike_cross" && reason != "operator_flatten" => {
+                    let held = tick - entry_tick[trade_id.as_str()];
+                    prop_assert!(held >= s.min_hold_ticks, "{reason} after {held} ticks (min {})", s.min_hold_ticks);
+                }
+                _ => {}
+            }
+        }
+    }
+}